@@ -2,10 +2,12 @@ use std::any::Any;
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use std::fmt;
-use crate::event::delegate::Delegate;
+
+/// 事件监听器
+pub type Listener = Box<dyn FnMut(Arc<dyn Any + Send + Sync>) + Send + Sync>;
 
 pub struct Bus{
-    listeners: Mutex<HashMap<String, Vec<Box<dyn FnMut(Arc<dyn Any + Send + Sync>)->() + Send + Sync>>>>
+    listeners: Mutex<HashMap<String, Vec<Listener>>>
 }
 
 impl Bus {
@@ -14,11 +16,11 @@ impl Bus {
     }
 
     /// 注册一个特定事件类型 T 的监听器
-    pub fn subscribe(& mut self, event: &str, listener: Box<dyn FnMut(Arc<dyn Any + Send + Sync>)->() + Send + Sync>) -> ()
+    pub fn subscribe(& mut self, event: &str, listener: Listener)
     {
         let mut listeners_guard = self.listeners.lock().unwrap();
         listeners_guard.entry(event.to_string())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(listener);
     }
 
@@ -34,6 +36,12 @@ impl Bus {
     }
 }
 
+impl Default for Bus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // 修正：为 Bus 结构体实现 Debug Trait
 impl fmt::Debug for Bus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -3,8 +3,11 @@ use std::fmt;
 use std::ops::AddAssign;
 use std::sync::{Arc, Mutex};
 
+/// 可在多个线程间共享的事件监听器
+pub type SharedListener = Arc<Mutex<dyn FnMut(Arc<dyn Any + Send + Sync>) + Send + Sync>>;
+
 pub struct Delegate{
-    listeners: Vec<Arc<Mutex<dyn FnMut(Arc<dyn Any + Send + Sync>)->() + Send + Sync>>>
+    listeners: Vec<SharedListener>
 }
 
 impl Delegate{
@@ -13,11 +16,11 @@ impl Delegate{
     }
 
     // 接受一个实现了 EventListener Trait 的对象，并将其安全地存储起来。
-    fn add_listener(&mut self, listener: Arc<Mutex<dyn FnMut(Arc<dyn Any + Send + Sync>)->() + Send + Sync>>) ->() {
+    pub fn add_listener(&mut self, listener: SharedListener) {
         self.listeners.push(listener);
     }
 
-    pub fn trigger_event(&mut self, data: Arc<dyn Any + Send + Sync>) ->() {
+    pub fn trigger_event(&mut self, data: Arc<dyn Any + Send + Sync>) {
         for listener_arc_mutex in &mut self.listeners {
             if let Ok(mut listener) = listener_arc_mutex.lock() {
                 // 克隆 Arc，将克隆的所有权副本传递给闭包
@@ -27,6 +30,12 @@ impl Delegate{
     }
 }
 
+impl Default for Delegate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // 手动实现 Debug Trait
 impl fmt::Debug for Delegate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -37,9 +46,9 @@ impl fmt::Debug for Delegate {
 }
 
 // 实现 add_assign 方法
-impl AddAssign<Arc<Mutex<dyn FnMut(Arc<dyn Any + Send + Sync>)->() + Send + Sync>>> for Delegate {
+impl AddAssign<SharedListener> for Delegate {
 
-    fn add_assign(&mut self, rhs: Arc<Mutex<dyn FnMut(Arc<dyn Any + Send + Sync>)->() + Send + Sync>>) {
+    fn add_assign(&mut self, rhs: SharedListener) {
         self.listeners.push(rhs);
     }
 }
\ No newline at end of file
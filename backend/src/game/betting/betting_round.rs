@@ -0,0 +1,425 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// 下注结构
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BettingStructure {
+    NoLimit,  // 无限注
+    PotLimit, // 底池限注
+    FixedLimit {
+        small_bet: u32, // 前两轮的固定注额
+        big_bet: u32,   // 后两轮的固定注额
+        cap: u8,        // 每轮最多的下注加注次数(含首次下注)
+    },
+}
+
+impl BettingStructure {
+    /// 本轮的最小下注额：限注为固定注额，其余为大盲
+    pub fn bet_unit(&self, big_blind: u32, is_big_street: bool) -> u32 {
+        match self {
+            BettingStructure::FixedLimit{small_bet, big_bet, ..} => {
+                if is_big_street { *big_bet } else { *small_bet }
+            }
+            _ => big_blind,
+        }
+    }
+}
+
+/// 玩家行动，Bet 和 Raise 的金额均为本轮下注到的总额，
+/// 面对低于 bet_unit 的引入注(bring-in)时，补足到 bet_unit 用 Raise(bet_unit)
+///
+/// AllIn 投入规则允许的最大数额：不能再加注时按跟注处理，底池限注和限注下超出上限的筹码留在手中
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BettingAction {
+    Fold,
+    Check,
+    Call,
+    Bet(u32),
+    Raise(u32),
+    AllIn,
+}
+
+/// 行动校验失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BettingError {
+    RoundClosed,
+    NotYourTurn,
+    CannotCheck,
+    NothingToCall,
+    CannotBet,
+    CannotRaise,
+    AmountTooSmall { min: u32 },
+    AmountTooLarge { max: u32 },
+    RaiseCapReached,
+    RaiseNotReopened,
+    InsufficientChips,
+}
+
+impl fmt::Display for BettingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BettingError::RoundClosed => write!(f, "betting round is closed"),
+            BettingError::NotYourTurn => write!(f, "it is not this player's turn"),
+            BettingError::CannotCheck => write!(f, "cannot check facing a bet"),
+            BettingError::NothingToCall => write!(f, "there is nothing to call"),
+            BettingError::CannotBet => write!(f, "cannot bet after a bet has been made"),
+            BettingError::CannotRaise => write!(f, "cannot raise before a bet has been made"),
+            BettingError::AmountTooSmall{min} => write!(f, "amount is below the minimum of {}", min),
+            BettingError::AmountTooLarge{max} => write!(f, "amount is above the maximum of {}", max),
+            BettingError::RaiseCapReached => write!(f, "raise cap reached"),
+            BettingError::RaiseNotReopened => write!(f, "action was not reopened by an incomplete raise"),
+            BettingError::InsufficientChips => write!(f, "not enough chips"),
+        }
+    }
+}
+
+/// 参与本轮下注的座位
+#[derive(Debug, Clone, Copy)]
+pub struct BettingSeat {
+    pub user_id: u32,
+    pub stack: u32,     // 尚未投入的筹码
+    pub street_bet: u32, // 本轮已下注的筹码(如盲注)
+}
+
+/// 一条街的下注轮
+#[derive(Debug, Clone)]
+pub struct BettingRound {
+    structure: BettingStructure,
+    bet_unit: u32,
+    pot: u32, // 之前各轮累积的底池
+    seats: Vec<u32>, // 按行动顺序排列
+    stacks: HashMap<u32, u32>,
+    street_bets: HashMap<u32, u32>,
+    folded: HashSet<u32>,
+    current_bet: u32,
+    last_raise: u32,
+    raise_count: u8,
+    acted_since_full_raise: HashSet<u32>,
+    pending: VecDeque<u32>,
+}
+
+impl BettingRound {
+    /// seats 按本轮的行动顺序排列，pot 为之前各轮累积的底池(不含 seats 中的 street_bet)
+    pub fn new(structure: BettingStructure, bet_unit: u32, pot: u32, seats: Vec<BettingSeat>) -> Self {
+        let current_bet = seats.iter().map(|seat| seat.street_bet).max().unwrap_or(0);
+        let mut round = BettingRound {
+            structure,
+            bet_unit,
+            pot,
+            seats: seats.iter().map(|seat| seat.user_id).collect(),
+            stacks: seats.iter().map(|seat| (seat.user_id, seat.stack)).collect(),
+            street_bets: seats.iter().map(|seat| (seat.user_id, seat.street_bet)).collect(),
+            folded: HashSet::new(),
+            current_bet,
+            last_raise: bet_unit,
+            // 盲注视为本轮的首次下注
+            raise_count: if current_bet > 0 { 1 } else { 0 },
+            acted_since_full_raise: HashSet::new(),
+            pending: VecDeque::new(),
+        };
+
+        let active: Vec<u32> = round.seats.iter().copied().filter(|user_id| round.stacks[user_id] > 0).collect();
+        round.pending = active.iter()
+            .copied()
+            // 只剩一位可行动玩家时，仅在需要跟注时才行动
+            .filter(|user_id| active.len() > 1 || round.to_call(*user_id) > 0)
+            .collect();
+        round
+    }
+
+    /// 以引入注(bring-in)开局的下注轮，例如七张梭哈的第三街
+    ///
+    /// 引入注不算作本轮的首次下注，其他玩家可以跟引入注，也可以补足(complete)到 bet_unit，补足视为首次下注
+    pub fn with_bring_in(structure: BettingStructure, bet_unit: u32, pot: u32, seats: Vec<BettingSeat>) -> Self {
+        let mut round = BettingRound::new(structure, bet_unit, pot, seats);
+        if round.current_bet < bet_unit {
+            round.raise_count = 0;
+            round.last_raise = bet_unit - round.current_bet;
+        }
+        round
+    }
+
+    /// 本轮是否已结束
+    pub fn is_complete(&self) -> bool {
+        self.pending.is_empty() || self.remaining_players().len() <= 1
+    }
+
+    /// 当前应行动的玩家
+    pub fn next_to_act(&self) -> Option<u32> {
+        if self.is_complete() { None } else { self.pending.front().copied() }
+    }
+
+    /// 未弃牌的玩家
+    pub fn remaining_players(&self) -> Vec<u32> {
+        self.seats.iter().copied().filter(|user_id| !self.folded.contains(user_id)).collect()
+    }
+
+    /// 未弃牌且仍有筹码、之后还能继续下注的玩家
+    pub fn active_players(&self) -> Vec<u32> {
+        self.remaining_players().into_iter().filter(|user_id| self.stacks[user_id] > 0).collect()
+    }
+
+    pub fn get_current_bet(&self) -> u32 {
+        self.current_bet
+    }
+
+    pub fn get_street_bet(&self, user_id: u32) -> u32 {
+        self.street_bets.get(&user_id).copied().unwrap_or(0)
+    }
+
+    /// 玩家需要跟注的数额
+    pub fn to_call(&self, user_id: u32) -> u32 {
+        self.current_bet.saturating_sub(self.get_street_bet(user_id))
+    }
+
+    /// 玩家可以加注到的最小总额
+    pub fn min_raise_to(&self) -> u32 {
+        if self.current_bet == 0 { self.bet_unit } else { self.current_bet + self.last_raise }
+    }
+
+    /// 玩家可以下注/加注到的最大总额
+    pub fn max_raise_to(&self, user_id: u32) -> u32 {
+        let all_in_to = self.get_street_bet(user_id) + self.stacks.get(&user_id).copied().unwrap_or(0);
+        match self.structure {
+            BettingStructure::NoLimit => all_in_to,
+            BettingStructure::PotLimit => {
+                // 先跟注，再加注整个底池
+                let street_total: u32 = self.street_bets.values().sum();
+                let pot_after_call = self.pot + street_total + self.to_call(user_id);
+                all_in_to.min(self.current_bet + pot_after_call)
+            }
+            // 面对引入注时只能补足到 bet_unit
+            BettingStructure::FixedLimit{..} if self.current_bet < self.bet_unit => all_in_to.min(self.bet_unit),
+            BettingStructure::FixedLimit{..} => all_in_to.min(self.current_bet + self.bet_unit),
+        }
+    }
+
+    /// 让不在牌桌上的玩家(如离桌)弃牌
+    pub fn fold_out(&mut self, user_id: u32) {
+        self.folded.insert(user_id);
+        self.pending.retain(|pending| *pending != user_id);
+    }
+
+    /// 校验并执行一个行动，返回该玩家需要新投入底池的筹码
+    pub fn apply(&mut self, user_id: u32, action: BettingAction) -> Result<u32, BettingError> {
+        if self.is_complete() {
+            return Err(BettingError::RoundClosed);
+        }
+        if self.pending.front() != Some(&user_id) {
+            return Err(BettingError::NotYourTurn);
+        }
+
+        let stack = self.stacks[&user_id];
+        let street_bet = self.get_street_bet(user_id);
+        let to_call = self.to_call(user_id);
+
+        let raise_to = match action {
+            BettingAction::Fold => {
+                self.folded.insert(user_id);
+                self.finish_turn(user_id);
+                return Ok(0);
+            }
+            BettingAction::Check => {
+                if to_call > 0 {
+                    return Err(BettingError::CannotCheck);
+                }
+                self.finish_turn(user_id);
+                return Ok(0);
+            }
+            BettingAction::Call => {
+                if to_call == 0 {
+                    return Err(BettingError::NothingToCall);
+                }
+                let committed = to_call.min(stack);
+                self.put_in(user_id, committed);
+                self.finish_turn(user_id);
+                return Ok(committed);
+            }
+            BettingAction::Bet(amount) => {
+                if self.current_bet > 0 {
+                    return Err(BettingError::CannotBet);
+                }
+                amount
+            }
+            BettingAction::Raise(amount) => {
+                if self.current_bet == 0 {
+                    return Err(BettingError::CannotRaise);
+                }
+                amount
+            }
+            BettingAction::AllIn => {
+                let raise_to = (street_bet + stack).min(self.max_raise_to(user_id));
+                if raise_to <= self.current_bet || !self.can_raise(user_id) {
+                    // 筹码不足以超过当前注额、加注未重新开放或已达加注上限，按跟注处理
+                    let committed = to_call.min(stack);
+                    self.put_in(user_id, committed);
+                    self.finish_turn(user_id);
+                    return Ok(committed);
+                }
+                raise_to
+            }
+        };
+
+        self.validate_raise(user_id, raise_to)?;
+
+        let committed = raise_to - street_bet;
+        let increment = raise_to - self.current_bet;
+        let is_full_raise = increment >= self.last_raise;
+        self.put_in(user_id, committed);
+        self.current_bet = raise_to;
+        self.raise_count += 1;
+        if is_full_raise {
+            // 补足引入注之后，下一次加注至少为 bet_unit
+            self.last_raise = if raise_to - increment < self.bet_unit { increment.max(self.bet_unit) } else { increment };
+            self.acted_since_full_raise.clear();
+        }
+        self.acted_since_full_raise.insert(user_id);
+
+        // 加注后其他仍可行动的玩家需要重新表态
+        let position = self.seats.iter().position(|seat| *seat == user_id).unwrap_or(0);
+        self.pending = (1..self.seats.len())
+            .map(|offset| self.seats[(position + offset) % self.seats.len()])
+            .filter(|seat| !self.folded.contains(seat) && self.stacks[seat] > 0)
+            .collect();
+        Ok(committed)
+    }
+
+    fn validate_raise(&self, user_id: u32, raise_to: u32) -> Result<(), BettingError> {
+        let stack = self.stacks[&user_id];
+        let all_in_to = self.get_street_bet(user_id) + stack;
+
+        if raise_to <= self.current_bet {
+            return Err(BettingError::AmountTooSmall{min: self.min_raise_to().min(all_in_to)});
+        }
+        if raise_to > all_in_to {
+            return Err(BettingError::InsufficientChips);
+        }
+        if self.acted_since_full_raise.contains(&user_id) {
+            return Err(BettingError::RaiseNotReopened);
+        }
+        if self.raise_capped() {
+            return Err(BettingError::RaiseCapReached);
+        }
+
+        let max = self.max_raise_to(user_id);
+        if raise_to > max {
+            return Err(BettingError::AmountTooLarge{max});
+        }
+        // 全下时允许低于最小加注额
+        let min = self.min_raise_to();
+        if raise_to < min && raise_to != all_in_to {
+            return Err(BettingError::AmountTooSmall{min: min.min(all_in_to)});
+        }
+        // 限注只能按固定注额加注
+        if let BettingStructure::FixedLimit{..} = self.structure {
+            if raise_to != max && raise_to != all_in_to {
+                return Err(BettingError::AmountTooSmall{min: max});
+            }
+        }
+        Ok(())
+    }
+
+    // 玩家此时能否加注：之前的不完整加注不会重新开放行动，限注有加注次数上限
+    fn can_raise(&self, user_id: u32) -> bool {
+        !self.acted_since_full_raise.contains(&user_id) && !self.raise_capped()
+    }
+
+    fn raise_capped(&self) -> bool {
+        matches!(self.structure, BettingStructure::FixedLimit{cap, ..} if self.raise_count >= cap)
+    }
+
+    fn put_in(&mut self, user_id: u32, amount: u32) {
+        if let Some(stack) = self.stacks.get_mut(&user_id) {
+            *stack -= amount;
+        }
+        *self.street_bets.entry(user_id).or_insert(0) += amount;
+    }
+
+    fn finish_turn(&mut self, user_id: u32) {
+        self.acted_since_full_raise.insert(user_id);
+        self.pending.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seat(user_id: u32, stack: u32, street_bet: u32) -> BettingSeat {
+        BettingSeat{user_id, stack, street_bet}
+    }
+
+    const FIXED_LIMIT: BettingStructure = BettingStructure::FixedLimit{small_bet: 10, big_bet: 20, cap: 4};
+
+    #[test]
+    fn no_limit_min_raise_follows_the_last_full_raise() {
+        let mut round = BettingRound::new(BettingStructure::NoLimit, 10, 0, vec![seat(1, 1000, 0), seat(2, 1000, 0), seat(3, 1000, 0)]);
+        assert_eq!(round.apply(1, BettingAction::Bet(5)), Err(BettingError::AmountTooSmall{min: 10}));
+        assert_eq!(round.apply(1, BettingAction::Bet(20)), Ok(20));
+        assert_eq!(round.min_raise_to(), 40);
+        assert_eq!(round.apply(2, BettingAction::Raise(70)), Ok(70));
+        // 上一次完整加注为 50，下一次至少加注到 120
+        assert_eq!(round.min_raise_to(), 120);
+        assert_eq!(round.apply(3, BettingAction::Raise(100)), Err(BettingError::AmountTooSmall{min: 120}));
+        assert_eq!(round.apply(3, BettingAction::Raise(120)), Ok(120));
+        assert_eq!(round.next_to_act(), Some(1));
+    }
+
+    #[test]
+    fn pot_limit_max_counts_the_pending_call() {
+        let mut round = BettingRound::new(BettingStructure::PotLimit, 10, 100, vec![seat(1, 1000, 0), seat(2, 1000, 0)]);
+        assert_eq!(round.max_raise_to(1), 100);
+        assert_eq!(round.apply(1, BettingAction::Bet(50)), Ok(50));
+        // 先跟注 50，底池变为 200，再加注整个底池
+        assert_eq!(round.max_raise_to(2), 250);
+        assert_eq!(round.apply(2, BettingAction::Raise(251)), Err(BettingError::AmountTooLarge{max: 250}));
+        // 全下只投入到底池限额
+        assert_eq!(round.apply(2, BettingAction::AllIn), Ok(250));
+        assert_eq!(round.get_current_bet(), 250);
+    }
+
+    #[test]
+    fn fixed_limit_raises_by_the_unit_up_to_the_cap() {
+        let mut round = BettingRound::new(FIXED_LIMIT, 10, 0, vec![seat(1, 1000, 0), seat(2, 1000, 0)]);
+        assert_eq!(round.apply(1, BettingAction::Bet(10)), Ok(10));
+        assert_eq!(round.apply(2, BettingAction::Raise(15)), Err(BettingError::AmountTooSmall{min: 20}));
+        // 全下只加注一个固定注额
+        assert_eq!(round.apply(2, BettingAction::AllIn), Ok(20));
+        assert_eq!(round.apply(1, BettingAction::Raise(30)), Ok(20));
+        assert_eq!(round.apply(2, BettingAction::Raise(40)), Ok(20));
+        assert_eq!(round.apply(1, BettingAction::Raise(50)), Err(BettingError::RaiseCapReached));
+        // 达到上限后全下按跟注处理
+        assert_eq!(round.apply(1, BettingAction::AllIn), Ok(10));
+        assert!(round.is_complete());
+    }
+
+    #[test]
+    fn incomplete_all_in_does_not_reopen_action() {
+        let mut round = BettingRound::new(BettingStructure::NoLimit, 10, 0, vec![seat(1, 1000, 0), seat(2, 1000, 0), seat(3, 30, 0)]);
+        assert_eq!(round.apply(1, BettingAction::Bet(20)), Ok(20));
+        assert_eq!(round.apply(2, BettingAction::Call), Ok(20));
+        // 3 号全下只多出 10，不足一次完整加注
+        assert_eq!(round.apply(3, BettingAction::AllIn), Ok(30));
+        assert_eq!(round.next_to_act(), Some(1));
+        assert_eq!(round.apply(1, BettingAction::Raise(60)), Err(BettingError::RaiseNotReopened));
+        assert_eq!(round.apply(1, BettingAction::AllIn), Ok(10));
+        assert_eq!(round.apply(2, BettingAction::Call), Ok(10));
+        assert!(round.is_complete());
+    }
+
+    #[test]
+    fn bring_in_can_be_called_or_completed() {
+        // 1 号下了 3 的引入注，最后行动
+        let mut round = BettingRound::with_bring_in(FIXED_LIMIT, 10, 0, vec![seat(2, 100, 0), seat(3, 100, 0), seat(1, 97, 3)]);
+        assert_eq!(round.min_raise_to(), 10);
+        assert_eq!(round.apply(2, BettingAction::Call), Ok(3));
+        assert_eq!(round.apply(3, BettingAction::Raise(20)), Err(BettingError::AmountTooLarge{max: 10}));
+        assert_eq!(round.apply(3, BettingAction::Raise(10)), Ok(10));
+        // 补足视为首次下注，之后按固定注额加注
+        assert_eq!(round.min_raise_to(), 20);
+        assert_eq!(round.apply(1, BettingAction::Raise(15)), Err(BettingError::AmountTooSmall{min: 20}));
+        assert_eq!(round.apply(1, BettingAction::Raise(20)), Ok(17));
+        assert_eq!(round.next_to_act(), Some(2));
+        assert_eq!(round.to_call(2), 17);
+    }
+}
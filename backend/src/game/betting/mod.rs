@@ -0,0 +1,2 @@
+pub mod betting_round;
+pub mod pot;
\ No newline at end of file
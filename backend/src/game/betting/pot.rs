@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet};
+
+/// 底池，第一个为主池，其余为边池
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: u32,
+    pub eligible: Vec<u32>, // 有资格赢取该池的玩家，保持 contributions 中的顺序
+}
+
+/// 按每位玩家本手牌的总投入构建主池和边池，弃牌玩家的筹码留在池中但无资格赢取
+pub fn build_pots(contributions: &[(u32, u32)], folded: &HashSet<u32>) -> Vec<Pot> {
+    // 每个未弃牌玩家的投入额都是一个分池的层级
+    let mut levels: Vec<u32> = contributions.iter()
+        .filter(|(user_id, amount)| !folded.contains(user_id) && *amount > 0)
+        .map(|(_, amount)| *amount)
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut previous_level = 0;
+    for level in levels {
+        let amount: u32 = contributions.iter()
+            .map(|(_, contributed)| (*contributed).min(level) - (*contributed).min(previous_level))
+            .sum();
+        let eligible: Vec<u32> = contributions.iter()
+            .filter(|(user_id, contributed)| !folded.contains(user_id) && *contributed >= level)
+            .map(|(user_id, _)| *user_id)
+            .collect();
+        previous_level = level;
+
+        // 资格相同的相邻分池合并
+        match pots.last_mut() {
+            Some(last) if last.eligible == eligible => last.amount += amount,
+            _ => pots.push(Pot{amount, eligible}),
+        }
+    }
+
+    // 弃牌玩家超出最高层级的投入并入最后一个池
+    let leftover: u32 = contributions.iter()
+        .map(|(_, contributed)| contributed.saturating_sub(previous_level))
+        .sum();
+    if leftover > 0 {
+        if let Some(last) = pots.last_mut() {
+            last.amount += leftover;
+        }
+    }
+    pots
+}
+
+/// 把每个池分给有资格玩家中牌力最大的一方，平局平分，
+/// 除不尽的筹码按 seat_order(从庄位左手边开始的座位顺序)逐个分给赢家，返回每位玩家赢得的筹码
+pub fn distribute_pots<K: Ord>(pots: &[Pot], hand_strength: &HashMap<u32, K>, seat_order: &[u32]) -> HashMap<u32, u32> {
+    let mut payouts: HashMap<u32, u32> = HashMap::new();
+    for pot in pots {
+        let best = pot.eligible.iter().map(|user_id| hand_strength.get(user_id)).max().flatten();
+        let winners: Vec<u32> = pot.eligible.iter()
+            .copied()
+            .filter(|user_id| hand_strength.get(user_id) == best)
+            .collect();
+        split_among(pot.amount, &winners, seat_order, &mut payouts);
+    }
+    payouts
+}
+
+/// 高低牌平分底池：每个池一半给有资格玩家中高牌最大的一方，一半给合格低牌最好的一方，
+/// 除不尽时多出的一个筹码归高牌一半；没有人有合格低牌时整个池归高牌
+///
+/// low_strength 中只放有合格低牌的玩家，Ord 越大表示低牌越好
+pub fn distribute_hi_lo_pots<H: Ord, L: Ord>(
+    pots: &[Pot],
+    high_strength: &HashMap<u32, H>,
+    low_strength: &HashMap<u32, L>,
+    seat_order: &[u32],
+) -> HashMap<u32, u32> {
+    let mut payouts: HashMap<u32, u32> = HashMap::new();
+    for pot in pots {
+        let best_high = pot.eligible.iter().map(|user_id| high_strength.get(user_id)).max().flatten();
+        let high_winners: Vec<u32> = pot.eligible.iter()
+            .copied()
+            .filter(|user_id| high_strength.get(user_id) == best_high)
+            .collect();
+        let best_low = pot.eligible.iter().filter_map(|user_id| low_strength.get(user_id)).max();
+        let low_winners: Vec<u32> = pot.eligible.iter()
+            .copied()
+            .filter(|user_id| best_low.is_some() && low_strength.get(user_id) == best_low)
+            .collect();
+
+        if low_winners.is_empty() {
+            split_among(pot.amount, &high_winners, seat_order, &mut payouts);
+        } else {
+            let low_amount = pot.amount / 2;
+            split_among(pot.amount - low_amount, &high_winners, seat_order, &mut payouts);
+            split_among(low_amount, &low_winners, seat_order, &mut payouts);
+        }
+    }
+    payouts
+}
+
+/// 把 amount 平分给 winners，除不尽的筹码按 seat_order 逐个分配
+pub fn split_among(amount: u32, winners: &[u32], seat_order: &[u32], payouts: &mut HashMap<u32, u32>) {
+    if winners.is_empty() {
+        return;
+    }
+    let mut ordered_winners: Vec<u32> = winners.to_vec();
+    ordered_winners.sort_by_key(|user_id| seat_order.iter().position(|seat| seat == user_id).unwrap_or(usize::MAX));
+
+    let share = amount / ordered_winners.len() as u32;
+    let odd_chips = amount % ordered_winners.len() as u32;
+    for (index, user_id) in ordered_winners.iter().enumerate() {
+        let won = share + if (index as u32) < odd_chips { 1 } else { 0 };
+        *payouts.entry(*user_id).or_insert(0) += won;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_in_levels_build_main_and_side_pots() {
+        let contributions = [(1, 30), (2, 100), (3, 200), (4, 200)];
+        let pots = build_pots(&contributions, &HashSet::new());
+        assert_eq!(pots, vec![
+            Pot{amount: 120, eligible: vec![1, 2, 3, 4]},
+            Pot{amount: 210, eligible: vec![2, 3, 4]},
+            Pot{amount: 200, eligible: vec![3, 4]},
+        ]);
+    }
+
+    #[test]
+    fn folded_chips_stay_in_the_pot_without_eligibility() {
+        // 4 号弃牌前投入最多，超出最高层级的部分并入最后一个池
+        let contributions = [(1, 50), (2, 100), (3, 100), (4, 150)];
+        let folded: HashSet<u32> = [4].into_iter().collect();
+        let pots = build_pots(&contributions, &folded);
+        assert_eq!(pots, vec![
+            Pot{amount: 200, eligible: vec![1, 2, 3]},
+            Pot{amount: 200, eligible: vec![2, 3]},
+        ]);
+        assert_eq!(pots.iter().map(|pot| pot.amount).sum::<u32>(), 400);
+    }
+
+    #[test]
+    fn each_pot_goes_to_its_best_eligible_hand() {
+        let pots = build_pots(&[(1, 30), (2, 100), (3, 100)], &HashSet::new());
+        // 短码玩家牌最大只赢主池，边池归其余玩家中牌最大的一方
+        let strength: HashMap<u32, u8> = [(1, 9), (2, 5), (3, 7)].into_iter().collect();
+        let payouts = distribute_pots(&pots, &strength, &[1, 2, 3]);
+        assert_eq!(payouts.get(&1), Some(&90));
+        assert_eq!(payouts.get(&2), None);
+        assert_eq!(payouts.get(&3), Some(&140));
+    }
+
+    #[test]
+    fn odd_chips_go_to_winners_in_seat_order() {
+        let mut payouts = HashMap::new();
+        split_among(101, &[3, 1, 2], &[2, 3, 1], &mut payouts);
+        assert_eq!(payouts[&2], 34);
+        assert_eq!(payouts[&3], 34);
+        assert_eq!(payouts[&1], 33);
+
+        let pots = vec![Pot{amount: 25, eligible: vec![1, 2, 3]}];
+        let strength: HashMap<u32, u8> = [(1, 4), (2, 1), (3, 4)].into_iter().collect();
+        let payouts = distribute_pots(&pots, &strength, &[3, 2, 1]);
+        assert_eq!(payouts[&3], 13);
+        assert_eq!(payouts[&1], 12);
+    }
+
+    #[test]
+    fn hi_lo_splits_each_pot_and_odd_chip_goes_high() {
+        let pots = vec![Pot{amount: 101, eligible: vec![1, 2, 3]}];
+        let high: HashMap<u32, u8> = [(1, 9), (2, 3), (3, 1)].into_iter().collect();
+        let low: HashMap<u32, u8> = [(2, 5), (3, 6)].into_iter().collect();
+        let payouts = distribute_hi_lo_pots(&pots, &high, &low, &[1, 2, 3]);
+        assert_eq!(payouts[&1], 51);
+        assert_eq!(payouts[&3], 50);
+
+        // 没有合格低牌时整个池归高牌
+        let payouts = distribute_hi_lo_pots(&pots, &high, &HashMap::<u32, u8>::new(), &[1, 2, 3]);
+        assert_eq!(payouts[&1], 101);
+    }
+
+    #[test]
+    fn hi_lo_scoops_and_quarters() {
+        let pots = vec![Pot{amount: 120, eligible: vec![1, 2, 3]}];
+        // 1 号高牌和低牌都最好，独得整个池
+        let high: HashMap<u32, u8> = [(1, 9), (2, 3), (3, 1)].into_iter().collect();
+        let low: HashMap<u32, u8> = [(1, 7), (2, 5)].into_iter().collect();
+        let payouts = distribute_hi_lo_pots(&pots, &high, &low, &[1, 2, 3]);
+        assert_eq!(payouts[&1], 120);
+        assert_eq!(payouts.get(&2), None);
+
+        // 1、2 号低牌相同，各得四分之一，高牌一半归 3 号
+        let high: HashMap<u32, u8> = [(1, 1), (2, 3), (3, 9)].into_iter().collect();
+        let low: HashMap<u32, u8> = [(1, 7), (2, 7)].into_iter().collect();
+        let payouts = distribute_hi_lo_pots(&pots, &high, &low, &[1, 2, 3]);
+        assert_eq!((payouts[&1], payouts[&2], payouts[&3]), (30, 30, 60));
+    }
+}
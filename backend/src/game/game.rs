@@ -7,7 +7,9 @@ use std::collections::HashMap;
 use std::fmt;
 use std::sync::{Arc, Mutex, MutexGuard};
 use crate::game::player::Player;
-use crate::game::game_rule::{GameCB, GameRule, PlayersCB};
+use crate::game::game_context::{get_or_insert_state, lock_or_recover, CURRENT_ACTION_PLAYERS_KEY};
+use crate::game::game_rule::{ActionOutcome, GameRule, PlayersCB, TimeoutCB};
+use crate::game::provably_fair::commit_reveal::{ClientSeed, FairnessState, PROVABLY_FAIR_KEY};
 use crate::timer::timer::Timer;
 
 ///游戏状态
@@ -36,8 +38,9 @@ struct Tuple(
     pub Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
     pub Arc<Mutex<GameState>>,
     pub Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
-    pub GameCB,
+    pub TimeoutCB,
     pub PlayersCB,
+    pub Arc<Mutex<Vec<ActionOutcome>>>,
 );
 
 impl fmt::Debug for Tuple{
@@ -50,6 +53,7 @@ impl fmt::Debug for Tuple{
         .field("4", &self.3)
         .field("5", &"Arc[Fn]")
         .field("6", &"Arc[Fn]")
+        .field("7", &self.7)
         .finish() // 结束构建并返回 Result
     }
 }
@@ -65,6 +69,7 @@ pub struct Game {
     game_context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
     game_timer_for_whole: Mutex<Option<Timer<Tuple>>>,
     game_timer_for_players: Mutex<Option<Timer<Tuple>>>,
+    timer_outcomes: Arc<Mutex<Vec<ActionOutcome>>>, // 定时器回调返回的结果，由 update_timers 统一处理
 }
 
 impl Game {
@@ -72,41 +77,54 @@ impl Game {
         game_item: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
         game_rule: &'static mut GameRule,
     ) -> Self {
-        Game {
+        let current_action_players: Arc<Mutex<Vec<Arc<Player>>>> = Arc::new(Mutex::new(Vec::new()));
+        // 规则回调通过上下文读写当前行动玩家
+        let mut game_context: HashMap<String, Arc<dyn Any + Send + Sync>> = HashMap::new();
+        game_context.insert(CURRENT_ACTION_PLAYERS_KEY.to_string(), current_action_players.clone());
+
+        let mut game = Game {
             current_players:Arc::new(Mutex::new(Vec::new())),
-            current_action_players:Arc::new(Mutex::new(Vec::new())),
+            current_action_players,
             game_item,
             game_rule,
             game_state: Arc::new(Mutex::new(GameState::NotStarted)),
-            game_context:Arc::new(Mutex::new(HashMap::new())),
+            game_context:Arc::new(Mutex::new(game_context)),
             game_timer_for_whole: Mutex::new(None),
             game_timer_for_players: Mutex::new(None),
-        }
+            timer_outcomes: Arc::new(Mutex::new(Vec::new())),
+        };
+        // 按规则中的定时器配置创建定时器，开局时才开始计时
+        game.init();
+        game
     }
 
     fn init(&mut self) {
+        // 定时器只在对局所在的线程上推进，回调不需要跨线程
+        #[allow(clippy::arc_with_non_send_sync)]
         let tuple:Arc<Tuple> = Arc::new(
             Tuple(
                 self.current_players.clone(),
-                self.current_players.clone(),
+                self.current_action_players.clone(),
                 self.game_item.clone(),
                 self.game_state.clone(),
                 self.game_context.clone(),
                 self.game_rule.game_timeout.clone(),
-                self.game_rule.players_timeout.clone()
+                self.game_rule.players_timeout.clone(),
+                self.timer_outcomes.clone()
             )
         );
         let tuple_clone = tuple.clone();
 
         if let Some(cb_times_method) = self.game_rule.game_timer_times_method{
-            let game_timeout:Box<dyn FnMut(Option<Arc<Tuple>>)->()>
+            let game_timeout:Box<dyn FnMut(Option<Arc<Tuple>>)>
                 = Box::new(|option_tuple: Option<Arc<Tuple>>| {
                 if let Some(tuple) = option_tuple {
                     let players_clone = tuple.0.clone();
                     let game_items_clone = tuple.2.clone();
                     let context_clone = tuple.4.clone();
                     let game_timeout = tuple.5.clone();
-                    (game_timeout)(players_clone, game_items_clone, context_clone)
+                    let outcome = (game_timeout)(players_clone, game_items_clone, context_clone);
+                    lock_or_recover(&tuple.7).push(outcome);
                 }
             });
 
@@ -122,7 +140,7 @@ impl Game {
 
         if let Some(cb_times_method) = self.game_rule.players_timer_times_method{
 
-            let player_timeout:Box<dyn FnMut(Option<Arc<Tuple>>)->()>
+            let player_timeout:Box<dyn FnMut(Option<Arc<Tuple>>)>
                 = Box::new(|option_tuple: Option<Arc<Tuple>>| {
                 if let Some(tuple) = option_tuple {
                     let players_clone = tuple.0.clone();
@@ -131,14 +149,15 @@ impl Game {
                     let game_state = tuple.3.clone();
                     let context_clone = tuple.4.clone();
                     let player_timeout = tuple.6.clone();
-                    (player_timeout)(players_clone, action_players_clone, game_items_clone, game_state, context_clone)
+                    let outcome = (player_timeout)(players_clone, action_players_clone, game_items_clone, game_state, context_clone);
+                    lock_or_recover(&tuple.7).push(outcome);
                 }
             });
 
             self.set_game_timer_for_players(
                 Mutex::new(
                     Some(Timer::new(
-                        self.game_rule.game_timer_duration,
+                        self.game_rule.players_timer_duration,
                         Some(tuple_clone),
                         Box::new(player_timeout),
                         cb_times_method,
@@ -148,24 +167,40 @@ impl Game {
         }
     }
 
-    fn set_game_timer_for_whole(&mut self, option_timer:Mutex<Option<Timer<Tuple>>>)->(){
+    pub fn get_current_players(&self) -> Arc<Mutex<Vec<Arc<Player>>>> {
+        self.current_players.clone()
+    }
+
+    pub fn get_current_action_players(&self) -> Arc<Mutex<Vec<Arc<Player>>>> {
+        self.current_action_players.clone()
+    }
+
+    pub fn get_game_state(&self) -> GameState {
+        *lock_or_recover(&self.game_state)
+    }
+
+    pub fn get_game_context(&self) -> Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>> {
+        self.game_context.clone()
+    }
+
+    fn set_game_timer_for_whole(&mut self, option_timer:Mutex<Option<Timer<Tuple>>>) {
         self.game_timer_for_whole = option_timer;
     }
 
-    fn set_game_timer_for_players(&mut self, option_timer:Mutex<Option<Timer<Tuple>>>)->(){
+    fn set_game_timer_for_players(&mut self, option_timer:Mutex<Option<Timer<Tuple>>>) {
         self.game_timer_for_players = option_timer;
     }
 
-    fn set_all_timer_is_running(&mut self, is_running: bool) -> () {
+    fn set_all_timer_is_running(&mut self, is_running: bool) {
         match self.game_timer_for_whole.lock() {
             Ok(mut guard) => {
-                if let Some(mut item) = guard.take() {
+                if let Some(item) = guard.as_mut() {
                     item.set_is_running(is_running);
                 };
             },
             Err(poisoned) => {
                 print!("The lock is poisoned! Attempting to unpoison (or recover) the data and resume operations.");
-                if let Some(mut item) = poisoned.into_inner().take(){
+                if let Some(item) = poisoned.into_inner().as_mut(){
                     item.set_is_running(is_running);
                 };
             }
@@ -173,20 +208,40 @@ impl Game {
 
         match self.game_timer_for_players.lock() {
             Ok(mut guard) => {
-                if let Some(mut item) = guard.take() {
+                if let Some(item) = guard.as_mut() {
                     item.set_is_running(is_running);
                 };
             },
             Err(poisoned) => {
                 print!("The lock is poisoned! Attempting to unpoison (or recover) the data and resume operations.");
-                if let Some(mut item) = poisoned.into_inner().take(){
+                if let Some(item) = poisoned.into_inner().as_mut(){
                     item.set_is_running(is_running);
                 };
             }
         }
     }
 
-    fn  translate_game_state(&self, ori_game_state:GameState, tar_game_state: Option<GameState>) -> () {
+    /// 推进对局和玩家的定时器，由调度方或外部请求周期性调用，只在对局进行中生效
+    ///
+    /// 定时器回调返回 RoundComplete 或 GameComplete 时在这里进入下一轮或结算
+    pub fn update_timers(&mut self) {
+        if self.get_game_state() != GameState::InProgress {
+            return;
+        }
+        if let Some(timer) = lock_or_recover(&self.game_timer_for_whole).as_mut() {
+            timer.update_timer();
+        }
+        if let Some(timer) = lock_or_recover(&self.game_timer_for_players).as_mut() {
+            timer.update_timer();
+        }
+
+        let outcomes: Vec<ActionOutcome> = lock_or_recover(&self.timer_outcomes).drain(..).collect();
+        for outcome in outcomes {
+            self.apply_outcome(&outcome);
+        }
+    }
+
+    fn  translate_game_state(&self, ori_game_state:GameState, tar_game_state: Option<GameState>) {
         let error_message:String = "game state isn't ".to_owned() + ori_game_state.to_string().as_str();
 
         match self.game_state.lock() {
@@ -207,18 +262,23 @@ impl Game {
         }
     }
 
-    pub fn player_join(&mut self, join_players: Vec<Arc<Player>>) -> () {
+    pub fn player_join(&mut self, join_players: Vec<Arc<Player>>) -> ActionOutcome {
         self.current_players.lock().unwrap().extend(join_players.clone());
-        (self.game_rule.players_join) (
+        let outcome = (self.game_rule.players_join) (
             Arc::new(Mutex::new(join_players)),
             self.current_players.clone(),
             self.game_item.clone(),
             self.game_state.clone(),
             self.game_context.clone()
         );
+        self.apply_outcome(&outcome);
+        outcome
     }
 
-    pub fn player_leave(&mut self, leave_players: Vec<Arc<Player>>) -> (){
+    /// 玩家离桌，离桌导致本轮结束或只剩一位玩家时由规则通知 Game 推进或结算
+    pub fn player_leave(&mut self, leave_players: Vec<Arc<Player>>) -> ActionOutcome {
+        // Player 只按用户和物品地址哈希，筹码和角色的变化不影响哈希
+        #[allow(clippy::mutable_key_type)]
         let leave_players_set: HashSet<_> = leave_players.clone().into_iter().collect();
 
         match self.current_players.lock() {
@@ -231,16 +291,72 @@ impl Game {
             }
         }
 
-        (self.game_rule.players_leave) (
+        let outcome = (self.game_rule.players_leave) (
             Arc::new(Mutex::new(leave_players)),
             self.current_players.clone(),
             self.game_item.clone(),
             self.game_state.clone(),
             self.game_context.clone()
         );
+        self.apply_outcome(&outcome);
+        outcome
+    }
+
+    /// 玩家行动，由规则校验后决定是否推进到下一轮或结算
+    pub fn player_action(&mut self, player: Arc<Player>, action: Arc<dyn Any + Send + Sync>) -> ActionOutcome {
+        // 客户端种子与具体玩法无关，开局前也可以提交
+        if let Some(ClientSeed(client_seed)) = action.downcast_ref::<ClientSeed>() {
+            return self.set_client_seed(&player, client_seed.clone());
+        }
+        // 行动来自客户端消息，对局不在进行中时拒绝而不是断言失败
+        if self.get_game_state() != GameState::InProgress {
+            return ActionOutcome::Rejected("game is not in progress".to_string());
+        }
+
+        let outcome = (self.game_rule.players_action) (
+            player,
+            action,
+            self.current_players.clone(),
+            self.game_item.clone(),
+            self.game_state.clone(),
+            self.game_context.clone()
+        );
+
+        // 玩家定时器按最近一次有效行动计时
+        if matches!(outcome, ActionOutcome::Continue | ActionOutcome::RoundComplete) {
+            if let Some(timer) = lock_or_recover(&self.game_timer_for_players).as_mut() {
+                timer.restart();
+            }
+        }
+
+        self.apply_outcome(&outcome);
+        outcome
     }
 
-    pub fn game_start(&mut self) -> () {
+    // 记录座位上玩家的客户端种子，下一次洗牌时按座位顺序参与计算
+    fn set_client_seed(&self, player: &Player, client_seed: String) -> ActionOutcome {
+        let user_id = player.get_user().get_id();
+        if !lock_or_recover(&self.current_players).iter().any(|seated| seated.get_user().get_id() == user_id) {
+            return ActionOutcome::Rejected("player is not seated".to_string());
+        }
+        let fairness = get_or_insert_state::<FairnessState>(&self.game_context, PROVABLY_FAIR_KEY);
+        lock_or_recover(&fairness).set_client_seed(user_id, client_seed);
+        ActionOutcome::Continue
+    }
+
+    // 按规则回调的结果进入下一轮或结算，对局不在进行中时忽略
+    fn apply_outcome(&mut self, outcome: &ActionOutcome) {
+        if self.get_game_state() != GameState::InProgress {
+            return;
+        }
+        match outcome {
+            ActionOutcome::RoundComplete => self.game_progress(),
+            ActionOutcome::GameComplete => self.game_finish(),
+            _ => {}
+        }
+    }
+
+    pub fn game_start(&mut self) {
         self.translate_game_state(GameState::NotStarted, Some(GameState::InProgress));
 
         self.set_all_timer_is_running(true);
@@ -250,9 +366,16 @@ impl Game {
             self.game_item.clone(),
             self.game_context.clone(),
         );
+
+        // 开局后分发游戏物品
+        (self.game_rule.allocate) (
+            self.current_players.clone(),
+            self.game_item.clone(),
+            self.game_context.clone(),
+        );
     }
 
-    pub fn game_pause(&mut self) -> () {
+    pub fn game_pause(&mut self) {
         self.translate_game_state(GameState::InProgress, Some(GameState::Paused));
 
         self.set_all_timer_is_running(false);
@@ -264,7 +387,7 @@ impl Game {
         );
     }
 
-    pub fn game_resume(&mut self) -> () {
+    pub fn game_resume(&mut self) {
         self.translate_game_state(GameState::Paused, Some(GameState::InProgress));
 
         self.set_all_timer_is_running(true);
@@ -276,7 +399,7 @@ impl Game {
         );
     }
 
-    pub fn game_progress(&mut self) -> () {
+    pub fn game_progress(&mut self) {
         self.translate_game_state(GameState::InProgress, None);
 
         (self.game_rule.game_progress) (
@@ -286,7 +409,7 @@ impl Game {
         );
     }
 
-    pub fn game_finish(&mut self) -> () {
+    pub fn game_finish(&mut self) {
         self.translate_game_state(GameState::InProgress, Some(GameState::Finished));
 
         self.set_all_timer_is_running(false);
@@ -298,7 +421,7 @@ impl Game {
         );
     }
 
-    pub fn game_wait_start(&mut self) -> () {
+    pub fn game_wait_start(&mut self) {
         self.translate_game_state(GameState::Finished, Some(GameState::NotStarted));
 
         (self.game_rule.game_wait_start) (
@@ -330,27 +453,8 @@ impl Hash for Game {
 impl PartialEq for Game {
     fn eq(&self, other: &Self) -> bool {
         // 比较参与者列表
-        let self_current_players: MutexGuard<Vec<Arc<Player>>>;
-        match self.current_players.lock() {
-            Ok(guard) => {
-                self_current_players = guard;
-            }
-            Err(poisoned) => {
-                print!("The lock is poisoned! Attempting to unpoison (or recover) the data and resume operations.");
-                self_current_players = poisoned.into_inner();
-            }
-        }
-
-        let other_current_players: MutexGuard<Vec<Arc<Player>>>;
-        match other.current_players.lock() {
-            Ok(guard) => {
-                other_current_players = guard;
-            }
-            Err(poisoned) => {
-                print!("The lock is poisoned! Attempting to unpoison (or recover) the data and resume operations.");
-                other_current_players = poisoned.into_inner();
-            }
-        }
+        let self_current_players: MutexGuard<Vec<Arc<Player>>> = lock_or_recover(&self.current_players);
+        let other_current_players: MutexGuard<Vec<Arc<Player>>> = lock_or_recover(&other.current_players);
         *self_current_players == *other_current_players &&
             // 比较 game_rule 指针的地址，以判断是否是同一个实例
             std::ptr::eq(self.game_rule as *const _, other.game_rule as *const _)
@@ -358,3 +462,61 @@ impl PartialEq for Game {
 }
 
 impl Eq for Game {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::betting::betting_round::{BettingAction, BettingStructure};
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+    use crate::game::game_context::get_state;
+    use crate::game::game_items::poker::poker::get_shuffled_cards;
+    use crate::game::game_projects::texas_holdem_poker::TexasHoldemPokerGameRules;
+    use crate::game::player::PlayerRole;
+    use crate::game::provably_fair::commit_reveal::{FairRng, ServerSeed};
+
+    #[test]
+    fn actions_outside_a_running_game_are_rejected() {
+        let rule = TexasHoldemPokerGameRules::new(5, 10, BettingStructure::NoLimit).build().unwrap();
+        let mut game = game(rule);
+        let players: Vec<Arc<Player>> = (1..=2)
+            .map(|id| player(id, PlayerRole::Player, GameProject::TexasHoldemPoker, 100))
+            .collect();
+        game.player_join(players.clone());
+        let rejected = ActionOutcome::Rejected("game is not in progress".to_string());
+        assert_eq!(game.player_action(players[0].clone(), Arc::new(BettingAction::Call)), rejected);
+
+        game.game_start();
+        game.game_pause();
+        assert_eq!(game.player_action(players[0].clone(), Arc::new(BettingAction::Call)), rejected);
+        game.game_resume();
+        game.game_finish();
+        assert_eq!(game.player_action(players[0].clone(), Arc::new(BettingAction::Call)), rejected);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+    }
+
+    #[test]
+    fn client_seed_message_changes_the_shuffle() {
+        let rule = TexasHoldemPokerGameRules::new(5, 10, BettingStructure::NoLimit).build().unwrap();
+        let mut game = game(rule);
+        let players: Vec<Arc<Player>> = (1..=2)
+            .map(|id| player(id, PlayerRole::Player, GameProject::TexasHoldemPoker, 100))
+            .collect();
+        game.player_join(players.clone());
+        let outsider = player(3, PlayerRole::Player, GameProject::TexasHoldemPoker, 100);
+        let seed = |text: &str| Arc::new(ClientSeed(text.to_string())) as Arc<dyn Any + Send + Sync>;
+        assert_eq!(game.player_action(outsider, seed("outsider")), ActionOutcome::Rejected("player is not seated".to_string()));
+        assert_eq!(game.player_action(players[1].clone(), seed("alice")), ActionOutcome::Continue);
+
+        let fairness = get_state::<FairnessState>(&game.get_game_context(), PROVABLY_FAIR_KEY).unwrap();
+        lock_or_recover(&fairness).commit(ServerSeed::new("server".to_string()));
+        game.game_start();
+        game.game_finish();
+
+        // 庄位为 1 号，座位顺序从 2 号开始
+        let reveal = lock_or_recover(&fairness).get_last_reveal().unwrap().clone();
+        assert_eq!(reveal.client_seeds, vec!["alice".to_string(), String::new()]);
+        let mut without_client_seed = FairRng::new("server", &[String::new(), String::new()], reveal.nonce);
+        assert_ne!(get_shuffled_cards(&mut reveal.rng()), get_shuffled_cards(&mut without_client_seed));
+    }
+}
@@ -0,0 +1,43 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// 当前行动玩家列表在 game_context 中的 key，由 Game 创建时写入
+pub const CURRENT_ACTION_PLAYERS_KEY: &str = "current_action_players";
+
+/// 加锁，锁中毒时恢复数据继续使用
+pub fn lock_or_recover<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            print!("The lock is poisoned! Attempting to unpoison (or recover) the data and resume operations.");
+            poisoned.into_inner()
+        }
+    }
+}
+
+/// 从游戏上下文中取出 key 对应的状态，不存在时插入默认值
+pub fn get_or_insert_state<T: Default + Send + 'static>(
+    context: &Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>,
+    key: &str,
+) -> Arc<Mutex<T>> {
+    let mut context_guard = lock_or_recover(context);
+    if let Some(state) = context_guard.get(key) {
+        if let Ok(state) = Arc::clone(state).downcast::<Mutex<T>>() {
+            return state;
+        }
+    }
+
+    let state: Arc<Mutex<T>> = Arc::new(Mutex::new(T::default()));
+    context_guard.insert(key.to_string(), state.clone());
+    state
+}
+
+/// 从游戏上下文中取出 key 对应的状态，不存在或类型不符时返回 None
+pub fn get_state<T: Send + 'static>(
+    context: &Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>,
+    key: &str,
+) -> Option<Arc<Mutex<T>>> {
+    let context_guard = lock_or_recover(context);
+    context_guard.get(key).and_then(|state| Arc::clone(state).downcast::<Mutex<T>>().ok())
+}
@@ -0,0 +1,104 @@
+use std::any::Any;
+use std::fmt;
+use crate::game::game_item::GameItem;
+use crate::game::provably_fair::commit_reveal::{FairRng, SeedReveal};
+
+/// 标准骰子的面数
+pub const STANDARD_DICE_SIDES: u8 = 6;
+
+/// 掷出的一颗骰子，作为 GameItem 放在对局的物品中
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Dice {
+    sides: u8,
+    value: u8, // 1~sides
+}
+
+impl GameItem for Dice {}
+
+impl Dice {
+    pub fn new(sides: u8, value: u8) -> Option<Self> {
+        (sides > 0 && (1..=sides).contains(&value)).then_some(Dice{sides, value})
+    }
+
+    /// 用可验证的随机数掷一颗骰子
+    pub fn roll(rng: &mut FairRng, sides: u8) -> Self {
+        Dice{sides, value: rng.next_below(sides as u32) as u8 + 1}
+    }
+
+    pub fn get_sides(&self) -> u8 {
+        self.sides
+    }
+
+    pub fn get_value(&self) -> u8 {
+        self.value
+    }
+
+    /// 从 GameItem 还原出骰子
+    pub fn from_item(item: &dyn GameItem) -> Option<Dice> {
+        let any: &dyn Any = item;
+        any.downcast_ref::<Dice>().copied()
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+/// 依次掷 count 颗骰子
+pub fn roll_dice(rng: &mut FairRng, count: usize, sides: u8) -> Vec<Dice> {
+    (0..count).map(|_| Dice::roll(rng, sides)).collect()
+}
+
+/// 按公开的种子重掷一遍，校验与实际掷出的点数是否一致
+pub fn verify_roll(reveal: &SeedReveal, sides: u8, values: &[u8]) -> bool {
+    if !reveal.verify_commitment() {
+        return false;
+    }
+    let mut rng = reveal.rng();
+    roll_dice(&mut rng, values.len(), sides).iter().map(|dice| dice.get_value()).eq(values.iter().copied())
+}
+
+/// 骰子的点数之和
+pub fn dice_total(dice: &[Dice]) -> u8 {
+    dice.iter().map(|dice| dice.get_value()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::provably_fair::commit_reveal::{FairnessState, ServerSeed};
+
+    #[test]
+    fn dice_values_stay_on_the_faces() {
+        assert_eq!(Dice::new(6, 0), None);
+        assert_eq!(Dice::new(6, 7), None);
+        assert_eq!(Dice::new(0, 1), None);
+        assert_eq!(Dice::new(20, 20).map(|dice| dice.get_value()), Some(20));
+
+        let mut rng = FairRng::new("server", &[], 0);
+        let dice = roll_dice(&mut rng, 600, STANDARD_DICE_SIDES);
+        assert!(dice.iter().all(|dice| (1..=6).contains(&dice.get_value()) && dice.get_sides() == 6));
+        assert!((1..=6).all(|face| dice.iter().any(|dice| dice.get_value() == face)));
+        assert_eq!(dice_total(&dice[..2]), dice[0].get_value() + dice[1].get_value());
+    }
+
+    #[test]
+    fn roll_verifies_against_the_revealed_seed() {
+        let mut fairness = FairnessState::default();
+        fairness.commit(ServerSeed::new("server seed".to_string()));
+        fairness.set_client_seed(1, "lucky".to_string());
+        let mut rng = fairness.begin_round(&[1, 2]);
+        let values: Vec<u8> = roll_dice(&mut rng, 3, STANDARD_DICE_SIDES).iter().map(|dice| dice.get_value()).collect();
+        let reveal = fairness.reveal().unwrap();
+
+        assert!(verify_roll(&reveal, STANDARD_DICE_SIDES, &values));
+        let mut tampered = values.clone();
+        tampered[2] = tampered[2] % 6 + 1;
+        assert!(!verify_roll(&reveal, STANDARD_DICE_SIDES, &tampered));
+        let mut forged = reveal.clone();
+        forged.server_seed = "another seed".to_string();
+        assert!(!verify_roll(&forged, STANDARD_DICE_SIDES, &values));
+    }
+}
@@ -0,0 +1 @@
+pub mod dice;
\ No newline at end of file
@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use crate::game::game_items::dou_dizhu::dou_dizhu_card::DouDizhuCard;
+
+/// 能组成顺子、连对、飞机的最大点数，2 和大小王不能连
+pub const MAX_SEQUENCE_RANK: u8 = 14;
+
+/// 斗地主牌型
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum ComboKind {
+    Single,              // 单张
+    Pair,                // 对子
+    Trio,                // 三张
+    TrioWithSingle,      // 三带一
+    TrioWithPair,        // 三带二(一对)
+    Straight,            // 顺子，至少 5 张
+    PairStraight,        // 连对，至少 3 对
+    Airplane,            // 飞机，至少 2 个连续的三张
+    AirplaneWithSingles, // 飞机带单张，每个三张带一张
+    AirplaneWithPairs,   // 飞机带对子，每个三张带一对
+    FourWithTwoSingles,  // 四带二
+    FourWithTwoPairs,    // 四带两对
+    Bomb,                // 炸弹
+    Rocket,              // 王炸
+}
+
+impl fmt::Display for ComboKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ComboKind::Single => "单张",
+            ComboKind::Pair => "对子",
+            ComboKind::Trio => "三张",
+            ComboKind::TrioWithSingle => "三带一",
+            ComboKind::TrioWithPair => "三带二",
+            ComboKind::Straight => "顺子",
+            ComboKind::PairStraight => "连对",
+            ComboKind::Airplane => "飞机",
+            ComboKind::AirplaneWithSingles => "飞机带单",
+            ComboKind::AirplaneWithPairs => "飞机带对",
+            ComboKind::FourWithTwoSingles => "四带二",
+            ComboKind::FourWithTwoPairs => "四带两对",
+            ComboKind::Bomb => "炸弹",
+            ComboKind::Rocket => "王炸",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 一手出牌的牌型
+///
+/// 同牌型且 length 相同时比较 main_rank，带牌不参与比较
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Combination {
+    kind: ComboKind,
+    main_rank: u8, // 主体部分的点数，连续牌型为最小的一组
+    length: u8,    // 连续牌型的组数，其余为 1
+}
+
+impl Combination {
+    pub fn get_kind(&self) -> ComboKind {
+        self.kind
+    }
+
+    pub fn get_main_rank(&self) -> u8 {
+        self.main_rank
+    }
+
+    pub fn get_length(&self) -> u8 {
+        self.length
+    }
+
+    /// 炸弹或王炸，出牌时倍数翻倍
+    pub fn is_bomb(&self) -> bool {
+        matches!(self.kind, ComboKind::Bomb | ComboKind::Rocket)
+    }
+
+    /// 能否压过上一手牌：王炸最大，炸弹压一切非炸弹，其余需同牌型、同组数且主体点数更大
+    pub fn beats(&self, previous: &Combination) -> bool {
+        match (self.kind, previous.kind) {
+            (_, ComboKind::Rocket) => false,
+            (ComboKind::Rocket, _) => true,
+            (ComboKind::Bomb, ComboKind::Bomb) => self.main_rank > previous.main_rank,
+            (ComboKind::Bomb, _) => true,
+            (kind, previous_kind) => kind == previous_kind && self.length == previous.length && self.main_rank > previous.main_rank,
+        }
+    }
+}
+
+impl fmt::Display for Combination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+/// 识别一手牌的牌型，不是合法牌型时返回 None
+pub fn parse_combination(cards: &[DouDizhuCard]) -> Option<Combination> {
+    let total = cards.len();
+    if total == 0 {
+        return None;
+    }
+    if total == 2 && cards.iter().all(|card| card.is_joker()) {
+        return Some(Combination{kind: ComboKind::Rocket, main_rank: 17, length: 1});
+    }
+
+    // 点数 -> 张数
+    let mut counts: BTreeMap<u8, usize> = BTreeMap::new();
+    for card in cards {
+        *counts.entry(card.rank_value()).or_insert(0) += 1;
+    }
+    let single = |kind: ComboKind, main_rank: u8| Some(Combination{kind, main_rank, length: 1});
+    let rank_with = |count: usize| counts.iter().find(|(_, c)| **c == count).map(|(rank, _)| *rank);
+
+    match total {
+        1 => return single(ComboKind::Single, cards[0].rank_value()),
+        2 if counts.len() == 1 => return single(ComboKind::Pair, cards[0].rank_value()),
+        3 if counts.len() == 1 => return single(ComboKind::Trio, cards[0].rank_value()),
+        4 if counts.len() == 1 => return single(ComboKind::Bomb, cards[0].rank_value()),
+        4 if counts.len() == 2 => return rank_with(3).and_then(|rank| single(ComboKind::TrioWithSingle, rank)),
+        5 if counts.len() == 2 && rank_with(3).is_some() && rank_with(2).is_some() => {
+            return rank_with(3).and_then(|rank| single(ComboKind::TrioWithPair, rank));
+        }
+        _ => {}
+    }
+
+    // 顺子、连对、纯飞机：每个点数张数相同且连续
+    let ranks: Vec<u8> = counts.keys().copied().collect();
+    let uniform_count = counts.values().all(|count| *count == counts[&ranks[0]]).then(|| counts[&ranks[0]]);
+    if let Some(count) = uniform_count {
+        let (kind, min_length) = match count {
+            1 => (ComboKind::Straight, 5),
+            2 => (ComboKind::PairStraight, 3),
+            3 => (ComboKind::Airplane, 2),
+            _ => (ComboKind::Bomb, usize::MAX),
+        };
+        if ranks.len() >= min_length && is_sequence(&ranks) {
+            return Some(Combination{kind, main_rank: ranks[0], length: ranks.len() as u8});
+        }
+    }
+
+    // 四带二、四带两对
+    if let Some(four_rank) = rank_with(4) {
+        let rest_are_pairs = counts.iter().filter(|(rank, _)| **rank != four_rank).all(|(_, count)| count % 2 == 0);
+        if total == 6 {
+            return single(ComboKind::FourWithTwoSingles, four_rank);
+        }
+        if total == 8 && rest_are_pairs {
+            // 四张的点数可能不止一个，两个炸弹拆开时按飞机带单识别
+            if airplane_with_wings(&counts, total).is_none() {
+                return single(ComboKind::FourWithTwoPairs, four_rank);
+            }
+        }
+    }
+
+    airplane_with_wings(&counts, total)
+}
+
+// 飞机带翅膀：找连续的 k 个三张，剩余 k 张单牌或 k 对，主体取最大的可行组合
+fn airplane_with_wings(counts: &BTreeMap<u8, usize>, total: usize) -> Option<Combination> {
+    let trio_ranks: Vec<u8> = counts.iter()
+        .filter(|(rank, count)| **count >= 3 && **rank <= MAX_SEQUENCE_RANK)
+        .map(|(rank, _)| *rank)
+        .collect();
+
+    for length in (2..=trio_ranks.len()).rev() {
+        for window in trio_ranks.windows(length).rev() {
+            if !is_sequence(window) {
+                continue;
+            }
+            if total == length * 4 {
+                return Some(Combination{kind: ComboKind::AirplaneWithSingles, main_rank: window[0], length: length as u8});
+            }
+            let rest_are_pairs = counts.iter().all(|(rank, count)| {
+                let rest = if window.contains(rank) { count - 3 } else { *count };
+                rest % 2 == 0
+            });
+            if total == length * 5 && rest_are_pairs {
+                return Some(Combination{kind: ComboKind::AirplaneWithPairs, main_rank: window[0], length: length as u8});
+            }
+        }
+    }
+    None
+}
+
+// 点数严格连续且都不超过 A
+fn is_sequence(ranks: &[u8]) -> bool {
+    ranks.windows(2).all(|pair| pair[1] == pair[0] + 1) && ranks.last().is_some_and(|rank| *rank <= MAX_SEQUENCE_RANK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(text: &str) -> Vec<DouDizhuCard> {
+        text.split_whitespace()
+            .map(|card| match card {
+                "SJ" => DouDizhuCard::SmallJoker,
+                "BJ" => DouDizhuCard::BigJoker,
+                card => DouDizhuCard::Standard(card.parse().unwrap()),
+            })
+            .collect()
+    }
+
+    fn combo(text: &str) -> Combination {
+        parse_combination(&cards(text)).unwrap()
+    }
+
+    #[test]
+    fn recognises_every_kind() {
+        let cases = [
+            ("2s", ComboKind::Single, 15, 1),
+            ("SJ", ComboKind::Single, 16, 1),
+            ("9s 9d", ComboKind::Pair, 9, 1),
+            ("9s 9d 9c", ComboKind::Trio, 9, 1),
+            ("9s 9d 9c 3h", ComboKind::TrioWithSingle, 9, 1),
+            ("9s 9d 9c 3h 3s", ComboKind::TrioWithPair, 9, 1),
+            ("3s 4d 5c 6h 7s", ComboKind::Straight, 3, 5),
+            ("Ts Jd Qc Kh As", ComboKind::Straight, 10, 5),
+            ("3s 3d 4c 4h 5s 5d", ComboKind::PairStraight, 3, 3),
+            ("3s 3d 3c 4h 4s 4d", ComboKind::Airplane, 3, 2),
+            ("3s 3d 3c 4h 4s 4d 8c 9h", ComboKind::AirplaneWithSingles, 3, 2),
+            ("3s 3d 3c 4h 4s 4d 8c 8h 9c 9h", ComboKind::AirplaneWithPairs, 3, 2),
+            ("9s 9d 9c 9h 3s 5d", ComboKind::FourWithTwoSingles, 9, 1),
+            ("9s 9d 9c 9h 3s 3d 5c 5d", ComboKind::FourWithTwoPairs, 9, 1),
+            ("9s 9d 9c 9h", ComboKind::Bomb, 9, 1),
+            ("SJ BJ", ComboKind::Rocket, 17, 1),
+        ];
+        for (text, kind, main_rank, length) in cases {
+            let combination = combo(text);
+            assert_eq!((combination.get_kind(), combination.get_main_rank(), combination.get_length()), (kind, main_rank, length), "{}", text);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_hands() {
+        for text in ["", "9s 8d", "3s 4d 5c 6h", "Js Qd Kc As 2h", "3s 3d 4c 4h", "9s 9d 9c 3h 4s", "3s 3d 3c 5h 5s 5d", "2s 2d 2c As Ad Ac"] {
+            assert_eq!(parse_combination(&cards(text)), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn two_bombs_split_into_an_airplane_with_singles() {
+        let combination = combo("5s 5d 5c 5h 6s 6d 6c 6h");
+        assert_eq!((combination.get_kind(), combination.get_main_rank()), (ComboKind::AirplaneWithSingles, 5));
+    }
+
+    #[test]
+    fn beats_follows_kind_length_and_rank() {
+        assert!(combo("Ts Jd Qc Kh As").beats(&combo("3s 4d 5c 6h 7s")));
+        // 组数不同的顺子不能互压
+        assert!(!combo("4s 5d 6c 7h 8s 9d").beats(&combo("3s 4d 5c 6h 7s")));
+        assert!(!combo("Ks Kd").beats(&combo("As")));
+        assert!(!combo("9s 9d").beats(&combo("9h 9c")));
+        // 带牌不参与比较
+        assert!(combo("Ts Td Tc 3h").beats(&combo("9s 9d 9c Ah")));
+
+        let bomb = combo("3s 3d 3c 3h");
+        assert!(bomb.is_bomb());
+        assert!(bomb.beats(&combo("2s 2d")));
+        assert!(combo("4s 4d 4c 4h").beats(&bomb));
+        assert!(!bomb.beats(&combo("4s 4d 4c 4h")));
+        let rocket = combo("SJ BJ");
+        assert!(rocket.beats(&combo("2s 2d 2c 2h")));
+        assert!(!combo("2s 2d 2c 2h").beats(&rocket));
+    }
+}
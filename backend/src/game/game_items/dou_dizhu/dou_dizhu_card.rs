@@ -0,0 +1,62 @@
+use std::any::Any;
+use std::fmt;
+use crate::game::game_item::GameItem;
+use crate::game::game_items::poker::poker::{get_all_cards, Card, Rank};
+
+/// 斗地主一副牌的张数，含大小王
+pub const DOU_DIZHU_DECK_SIZE: usize = 54;
+
+/// 斗地主用牌，在 52 张扑克牌之外加上大小王
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum DouDizhuCard {
+    Standard(Card),
+    SmallJoker, // 小王
+    BigJoker,   // 大王
+}
+
+impl GameItem for DouDizhuCard {}
+
+impl DouDizhuCard {
+    /// 斗地主中的点数大小：3~K 为 3~13，A 为 14，2 为 15，小王 16，大王 17
+    pub fn rank_value(&self) -> u8 {
+        match self {
+            DouDizhuCard::Standard(card) => match card.rank {
+                Rank::Two => 15,
+                rank => rank.value(),
+            },
+            DouDizhuCard::SmallJoker => 16,
+            DouDizhuCard::BigJoker => 17,
+        }
+    }
+
+    pub fn is_joker(&self) -> bool {
+        !matches!(self, DouDizhuCard::Standard(_))
+    }
+
+    /// 从 GameItem 还原出斗地主用牌，普通扑克牌也可以还原
+    pub fn from_item(item: &dyn GameItem) -> Option<DouDizhuCard> {
+        let any: &dyn Any = item;
+        any.downcast_ref::<DouDizhuCard>()
+            .copied()
+            .or_else(|| Card::from_item(item).map(DouDizhuCard::Standard))
+    }
+}
+
+// 普通牌沿用两字符记法，小王记为 SJ，大王记为 BJ
+impl fmt::Display for DouDizhuCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DouDizhuCard::Standard(card) => write!(f, "{}", card),
+            DouDizhuCard::SmallJoker => write!(f, "SJ"),
+            DouDizhuCard::BigJoker => write!(f, "BJ"),
+        }
+    }
+}
+
+// 获取完整的54张斗地主牌组
+pub fn get_dou_dizhu_cards() -> Vec<DouDizhuCard> {
+    let mut deck: Vec<DouDizhuCard> = get_all_cards().into_iter().map(DouDizhuCard::Standard).collect();
+    deck.push(DouDizhuCard::SmallJoker);
+    deck.push(DouDizhuCard::BigJoker);
+    deck
+}
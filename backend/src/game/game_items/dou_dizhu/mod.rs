@@ -0,0 +1,2 @@
+pub mod dou_dizhu_card;
+pub mod combination;
\ No newline at end of file
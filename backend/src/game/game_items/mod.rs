@@ -1 +1,5 @@
-pub mod poker;
\ No newline at end of file
+pub mod poker;
+pub mod dou_dizhu;
+pub mod roulette;
+pub mod dice;
+pub mod slots;
\ No newline at end of file
@@ -0,0 +1,353 @@
+use std::fmt;
+use std::ops::{BitAnd, BitOr, Not, Sub};
+use std::str::FromStr;
+use crate::game::game_item::GameItem;
+use crate::game::game_items::poker::poker::{Card, Rank, Suit};
+
+/// 一副牌的张数
+pub const DECK_SIZE: u8 = 52;
+
+// 低 52 位有效
+const FULL_DECK_BITS: u64 = (1u64 << DECK_SIZE) - 1;
+
+/// 牌在位图中的下标：花色按 Suit::ALL_SUITS 的顺序每 13 位一组，组内 2 在最低位、A 在最高位
+pub fn card_index(card: &Card) -> u8 {
+    let suit_index = Suit::ALL_SUITS.iter().position(|suit| *suit == card.suit).unwrap_or(0) as u8;
+    suit_index * 13 + card.rank.value() - 2
+}
+
+/// card_index 的逆运算，下标越界返回 None
+pub fn card_from_index(index: u8) -> Option<Card> {
+    if index >= DECK_SIZE {
+        return None;
+    }
+    let suit = Suit::ALL_SUITS[(index / 13) as usize];
+    // ALL_RANKS 以 A 开头，点数下标 0 对应 Two
+    let rank = Rank::ALL_RANKS[((index % 13 + 1) % 13) as usize];
+    Some(Card::new(suit, rank))
+}
+
+/// 解析牌面字符串失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardParseError {
+    InvalidRank(char),
+    InvalidSuit(char),
+    MissingSuit(char), // 点数后面缺少花色
+    DuplicateCard(Card),
+    CardCount(u32), // 需要单张牌时给出的张数
+}
+
+impl fmt::Display for CardParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CardParseError::InvalidRank(symbol) => write!(f, "invalid rank '{}'", symbol),
+            CardParseError::InvalidSuit(symbol) => write!(f, "invalid suit '{}'", symbol),
+            CardParseError::MissingSuit(symbol) => write!(f, "rank '{}' has no suit", symbol),
+            CardParseError::DuplicateCard(card) => write!(f, "card {} appears more than once", card),
+            CardParseError::CardCount(count) => write!(f, "expected exactly one card, found {}", count),
+        }
+    }
+}
+
+impl FromStr for Card {
+    type Err = CardParseError;
+
+    /// 解析单张牌，例如 "As"、"td"
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let set: CardSet = text.parse()?;
+        match set.iter().next() {
+            Some(card) if set.count() == 1 => Ok(card),
+            _ => Err(CardParseError::CardCount(set.count())),
+        }
+    }
+}
+
+/// 用 u64 位图表示的一组牌，集合运算都是常数时间
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub struct CardSet {
+    bits: u64,
+}
+
+impl CardSet {
+    pub fn new() -> Self {
+        CardSet{bits: 0}
+    }
+
+    /// 完整的 52 张牌
+    pub fn full() -> Self {
+        CardSet{bits: FULL_DECK_BITS}
+    }
+
+    pub fn from_bits(bits: u64) -> Self {
+        CardSet{bits: bits & FULL_DECK_BITS}
+    }
+
+    pub fn get_bits(&self) -> u64 {
+        self.bits
+    }
+
+    pub fn from_cards(cards: &[Card]) -> Self {
+        cards.iter().copied().collect()
+    }
+
+    /// 从 GameItem 列表中收集扑克牌，非扑克牌忽略
+    pub fn from_items<T: AsRef<dyn GameItem>>(items: &[T]) -> Self {
+        items.iter().filter_map(|item| Card::from_item(item.as_ref())).collect()
+    }
+
+    pub fn to_cards(self) -> Vec<Card> {
+        self.iter().collect()
+    }
+
+    /// 加入一张牌，已存在时返回 false
+    pub fn insert(&mut self, card: Card) -> bool {
+        let bit = 1u64 << card_index(&card);
+        let is_new = self.bits & bit == 0;
+        self.bits |= bit;
+        is_new
+    }
+
+    /// 移除一张牌，不存在时返回 false
+    pub fn remove(&mut self, card: Card) -> bool {
+        let bit = 1u64 << card_index(&card);
+        let existed = self.bits & bit != 0;
+        self.bits &= !bit;
+        existed
+    }
+
+    pub fn contains(&self, card: Card) -> bool {
+        self.bits & (1u64 << card_index(&card)) != 0
+    }
+
+    pub fn union(&self, other: CardSet) -> CardSet {
+        CardSet{bits: self.bits | other.bits}
+    }
+
+    pub fn intersection(&self, other: CardSet) -> CardSet {
+        CardSet{bits: self.bits & other.bits}
+    }
+
+    pub fn difference(&self, other: CardSet) -> CardSet {
+        CardSet{bits: self.bits & !other.bits}
+    }
+
+    /// 补集，即一副牌中不在本集合里的牌
+    pub fn complement(&self) -> CardSet {
+        CardSet{bits: !self.bits & FULL_DECK_BITS}
+    }
+
+    pub fn is_disjoint(&self, other: CardSet) -> bool {
+        self.bits & other.bits == 0
+    }
+
+    pub fn is_subset(&self, other: CardSet) -> bool {
+        self.bits & !other.bits == 0
+    }
+
+    pub fn count(&self) -> u32 {
+        self.bits.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// 按下标从小到大遍历
+    pub fn iter(&self) -> CardSetIter {
+        CardSetIter{bits: self.bits}
+    }
+
+    /// 某一花色的 13 位点数掩码，2 在最低位
+    pub fn suit_mask(&self, suit: Suit) -> u16 {
+        let suit_index = Suit::ALL_SUITS.iter().position(|candidate| *candidate == suit).unwrap_or(0);
+        ((self.bits >> (suit_index * 13)) & 0x1fff) as u16
+    }
+
+    /// 不分花色出现过的点数掩码
+    pub fn rank_mask(&self) -> u16 {
+        Suit::ALL_SUITS.iter().fold(0, |mask, suit| mask | self.suit_mask(*suit))
+    }
+
+    /// 某一点数的张数
+    pub fn rank_count(&self, rank: Rank) -> u32 {
+        let rank_bit = 1u16 << (rank.value() - 2);
+        Suit::ALL_SUITS.iter().filter(|suit| self.suit_mask(**suit) & rank_bit != 0).count() as u32
+    }
+}
+
+impl FromStr for CardSet {
+    type Err = CardParseError;
+
+    /// 解析连续的两字符牌面，例如 "AsKd"，允许用空白或逗号分隔
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut set = CardSet::new();
+        let mut symbols = text.chars().filter(|symbol| !symbol.is_whitespace() && *symbol != ',');
+        while let Some(rank_symbol) = symbols.next() {
+            let rank = Rank::from_char(rank_symbol).ok_or(CardParseError::InvalidRank(rank_symbol))?;
+            let suit_symbol = symbols.next().ok_or(CardParseError::MissingSuit(rank_symbol))?;
+            let suit = Suit::from_char(suit_symbol).ok_or(CardParseError::InvalidSuit(suit_symbol))?;
+            let card = Card::new(suit, rank);
+            if !set.insert(card) {
+                return Err(CardParseError::DuplicateCard(card));
+            }
+        }
+        Ok(set)
+    }
+}
+
+// 按下标顺序连续输出，例如 "2sAsKd"，可以被 parse 还原
+impl fmt::Display for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for card in self.iter() {
+            write!(f, "{}", card)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Card> for CardSet {
+    fn from(card: Card) -> Self {
+        CardSet{bits: 1u64 << card_index(&card)}
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(cards: I) -> Self {
+        let mut set = CardSet::new();
+        for card in cards {
+            set.insert(card);
+        }
+        set
+    }
+}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = CardSetIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+
+    fn bitor(self, other: CardSet) -> CardSet {
+        self.union(other)
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+
+    fn bitand(self, other: CardSet) -> CardSet {
+        self.intersection(other)
+    }
+}
+
+impl Sub for CardSet {
+    type Output = CardSet;
+
+    fn sub(self, other: CardSet) -> CardSet {
+        self.difference(other)
+    }
+}
+
+impl Not for CardSet {
+    type Output = CardSet;
+
+    fn not(self) -> CardSet {
+        self.complement()
+    }
+}
+
+/// CardSet 的迭代器，每次取出最低位的牌
+#[derive(Debug, Clone)]
+pub struct CardSetIter {
+    bits: u64,
+}
+
+impl Iterator for CardSetIter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.bits == 0 {
+            return None;
+        }
+        let index = self.bits.trailing_zeros() as u8;
+        self.bits &= self.bits - 1;
+        card_from_index(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.bits.count_ones() as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for CardSetIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(text: &str) -> CardSet {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        let cards = set("As Kd, 2s th");
+        assert_eq!(cards.count(), 4);
+        assert!(cards.contains(Card::new(Suit::Hearts, Rank::Ten)));
+        assert_eq!(cards.to_string(), "2sAsThKd");
+        assert_eq!(set(&cards.to_string()), cards);
+        assert_eq!("Qc".parse::<Card>(), Ok(Card::new(Suit::Clubs, Rank::Queen)));
+        assert_eq!(CardSet::full().to_string().parse::<CardSet>(), Ok(CardSet::full()));
+        for index in 0..DECK_SIZE {
+            assert_eq!(card_from_index(index).map(|card| card_index(&card)), Some(index));
+        }
+        assert_eq!(card_from_index(DECK_SIZE), None);
+    }
+
+    #[test]
+    fn set_operations() {
+        let left = set("AsKsQs");
+        let right = set("KsQsJs");
+        assert_eq!(left | right, set("AsKsQsJs"));
+        assert_eq!(left & right, set("KsQs"));
+        assert_eq!(left - right, set("As"));
+        assert_eq!((!left).count(), 49);
+        assert!((left & right).is_subset(left));
+        assert!((left - right).is_disjoint(right));
+        assert!(CardSet::new().is_empty());
+    }
+
+    #[test]
+    fn counts_and_masks() {
+        let cards = set("AsAhAd2s7c");
+        assert_eq!(cards.count(), 5);
+        assert_eq!(cards.iter().len(), 5);
+        assert_eq!(CardSet::full().count(), DECK_SIZE as u32);
+        assert_eq!(cards.rank_count(Rank::Ace), 3);
+        assert_eq!(cards.rank_count(Rank::King), 0);
+        assert_eq!(cards.suit_mask(Suit::Spades), (1 << 12) | 1);
+        assert_eq!(cards.rank_mask(), (1 << 12) | (1 << 5) | 1);
+    }
+
+    #[test]
+    fn invalid_or_duplicate_cards_are_rejected() {
+        let ace_of_spades = Card::new(Suit::Spades, Rank::Ace);
+        assert_eq!("AsAs".parse::<CardSet>(), Err(CardParseError::DuplicateCard(ace_of_spades)));
+        assert_eq!("Xs".parse::<CardSet>(), Err(CardParseError::InvalidRank('X')));
+        assert_eq!("Ax".parse::<CardSet>(), Err(CardParseError::InvalidSuit('x')));
+        assert_eq!("AsK".parse::<CardSet>(), Err(CardParseError::MissingSuit('K')));
+        assert_eq!("AsKd".parse::<Card>(), Err(CardParseError::CardCount(2)));
+        assert_eq!("".parse::<Card>(), Err(CardParseError::CardCount(0)));
+        let mut cards = CardSet::from(ace_of_spades);
+        assert!(!cards.insert(ace_of_spades));
+        assert!(cards.remove(ace_of_spades));
+        assert!(!cards.remove(ace_of_spades));
+    }
+}
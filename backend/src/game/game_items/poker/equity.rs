@@ -0,0 +1,423 @@
+use std::fmt;
+use crate::game::game_items::poker::card_set::CardSet;
+use crate::game::game_items::poker::hand_range::HandRange;
+use crate::game::game_items::poker::lookup_evaluator::{self, HandRank};
+use crate::game::game_items::poker::poker::Card;
+
+/// 支持的玩家人数
+pub const MIN_EQUITY_PLAYERS: usize = 2;
+pub const MAX_EQUITY_PLAYERS: usize = 10;
+
+// 德州扑克每位玩家的手牌数和公共牌数
+const HOLE_CARDS: u32 = 2;
+const BOARD_CARDS: u32 = 5;
+
+// 蒙特卡洛为范围发牌时，连续冲突超过该次数视为范围之间没有可同时成立的手牌
+const MAX_DEAL_ATTEMPTS: u32 = 10_000;
+
+/// 胜率计算参数
+#[derive(Debug, Clone, Copy)]
+pub struct EquityOptions {
+    pub exhaustive_limit: u64, // 剩余公共牌组合数不超过该值时穷举，否则蒙特卡洛
+    pub iterations: u64,       // 蒙特卡洛的抽样次数
+    pub seed: u64,             // 蒙特卡洛的随机种子，相同输入和种子得到相同结果
+}
+
+impl Default for EquityOptions {
+    fn default() -> Self {
+        EquityOptions {
+            exhaustive_limit: 2_000_000,
+            iterations: 100_000,
+            seed: 0,
+        }
+    }
+}
+
+/// 参数校验失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EquityError {
+    PlayerCount(usize),
+    HoleCardCount { player: usize, count: u32 },
+    BoardCardCount(u32),
+    DuplicateCards(CardSet), // 在手牌、公共牌、死牌之间重复出现的牌
+    EmptyRange(usize),       // 去掉已知牌后范围中没有手牌的玩家
+    NoCompatibleHands,       // 各玩家的范围之间找不到互不冲突的手牌组合
+}
+
+impl fmt::Display for EquityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EquityError::PlayerCount(count) => write!(f, "equity needs {} to {} players, got {}", MIN_EQUITY_PLAYERS, MAX_EQUITY_PLAYERS, count),
+            EquityError::HoleCardCount{player, count} => write!(f, "player {} has {} hole cards, expected {}", player, count, HOLE_CARDS),
+            EquityError::BoardCardCount(count) => write!(f, "board has {} cards, expected at most {}", count, BOARD_CARDS),
+            EquityError::DuplicateCards(cards) => write!(f, "cards {} are used more than once", cards),
+            EquityError::EmptyRange(player) => write!(f, "range of player {} is empty after card removal", player),
+            EquityError::NoCompatibleHands => write!(f, "ranges have no combination of hands without shared cards"),
+        }
+    }
+}
+
+/// 单个玩家的统计，每个发牌结果按其权重计数，确定手牌时权重恒为 1
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlayerEquity {
+    pub wins: f64,   // 独赢的次数
+    pub ties: f64,   // 平分的次数
+    pub equity: f64, // 按平分比例累计的赢得份额
+}
+
+/// 胜率计算结果，players 与输入的手牌顺序一致
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityResult {
+    pub players: Vec<PlayerEquity>,
+    pub boards: u64,       // 参与统计的发牌结果数
+    pub total_weight: f64, // 所有发牌结果的权重之和
+    pub exhaustive: bool, // true 为精确穷举，false 为蒙特卡洛估算
+}
+
+impl EquityResult {
+    pub fn win_percent(&self, player: usize) -> f64 {
+        self.percent(self.players[player].wins)
+    }
+
+    pub fn tie_percent(&self, player: usize) -> f64 {
+        self.percent(self.players[player].ties)
+    }
+
+    pub fn equity_percent(&self, player: usize) -> f64 {
+        self.percent(self.players[player].equity)
+    }
+
+    fn percent(&self, value: f64) -> f64 {
+        if self.total_weight == 0.0 { 0.0 } else { value * 100.0 / self.total_weight }
+    }
+}
+
+/// 计算各玩家的胜率、平局率和权益
+///
+/// hands 为每位玩家的两张手牌，board 为已知的公共牌(0~5 张)，dead 为已知不会再发出的牌
+pub fn calculate_equity(hands: &[CardSet], board: CardSet, dead: CardSet, options: EquityOptions) -> Result<EquityResult, EquityError> {
+    if !(MIN_EQUITY_PLAYERS..=MAX_EQUITY_PLAYERS).contains(&hands.len()) {
+        return Err(EquityError::PlayerCount(hands.len()));
+    }
+    if let Some(player) = hands.iter().position(|hand| hand.count() != HOLE_CARDS) {
+        return Err(EquityError::HoleCardCount{player, count: hands[player].count()});
+    }
+    if board.count() > BOARD_CARDS {
+        return Err(EquityError::BoardCardCount(board.count()));
+    }
+
+    let mut used = CardSet::new();
+    let mut duplicates = CardSet::new();
+    for cards in hands.iter().chain([board, dead].iter()) {
+        duplicates = duplicates | (used & *cards);
+        used = used | *cards;
+    }
+    if !duplicates.is_empty() {
+        return Err(EquityError::DuplicateCards(duplicates));
+    }
+
+    let missing = (BOARD_CARDS - board.count()) as usize;
+    let mut tally = Tally::new(hands.len(), board);
+    let mut deck: Vec<Card> = used.complement().to_cards();
+
+    if combination_count(deck.len() as u64, missing as u64) <= options.exhaustive_limit {
+        for_each_runout(&deck, missing, |runout| tally.record(hands, runout, 1.0));
+        return Ok(tally.finish(true));
+    }
+
+    let mut rng = SplitMix64::new(options.seed);
+    for _ in 0..options.iterations {
+        let runout = sample_runout(&mut deck, missing, &mut rng);
+        tally.record(hands, runout, 1.0);
+    }
+    Ok(tally.finish(false))
+}
+
+/// 范围对范围的胜率
+///
+/// 每位玩家给出一个范围，先按 board 和 dead 去掉冲突的手牌，
+/// 再按权重在互不冲突的手牌组合上统计；组合数较少时穷举，否则按权重抽样
+pub fn calculate_range_equity(ranges: &[HandRange], board: CardSet, dead: CardSet, options: EquityOptions) -> Result<EquityResult, EquityError> {
+    if !(MIN_EQUITY_PLAYERS..=MAX_EQUITY_PLAYERS).contains(&ranges.len()) {
+        return Err(EquityError::PlayerCount(ranges.len()));
+    }
+    if board.count() > BOARD_CARDS {
+        return Err(EquityError::BoardCardCount(board.count()));
+    }
+    if !board.is_disjoint(dead) {
+        return Err(EquityError::DuplicateCards(board & dead));
+    }
+
+    let known = board | dead;
+    let ranges: Vec<HandRange> = ranges.iter().map(|range| range.without(known)).collect();
+    if let Some(player) = ranges.iter().position(|range| range.is_empty()) {
+        return Err(EquityError::EmptyRange(player));
+    }
+    if let Some((player, combo)) = ranges.iter().enumerate()
+        .flat_map(|(player, range)| range.get_combos().iter().map(move |combo| (player, combo)))
+        .find(|(_, combo)| combo.cards.count() != HOLE_CARDS) {
+        return Err(EquityError::HoleCardCount{player, count: combo.cards.count()});
+    }
+
+    let missing = (BOARD_CARDS - board.count()) as usize;
+    let mut tally = Tally::new(ranges.len(), board);
+    let deck_size = (CardSet::full() - known).count() - HOLE_CARDS * ranges.len() as u32;
+    let deals = ranges.iter()
+        .fold(combination_count(deck_size as u64, missing as u64), |deals, range| deals.saturating_mul(range.len() as u64));
+
+    if deals <= options.exhaustive_limit {
+        let mut hands: Vec<CardSet> = Vec::with_capacity(ranges.len());
+        for_each_deal(&ranges, known, 1.0, &mut hands, &mut |hands, used, weight| {
+            let deck: Vec<Card> = used.complement().to_cards();
+            for_each_runout(&deck, missing, |runout| tally.record(hands, runout, weight));
+        });
+        if tally.boards == 0 {
+            return Err(EquityError::NoCompatibleHands);
+        }
+        return Ok(tally.finish(true));
+    }
+
+    let cumulative_weights: Vec<Vec<f64>> = ranges.iter()
+        .map(|range| {
+            range.get_combos().iter()
+                .scan(0.0, |total, combo| {
+                    *total += combo.weight;
+                    Some(*total)
+                })
+                .collect()
+        })
+        .collect();
+    let mut rng = SplitMix64::new(options.seed);
+    let mut hands: Vec<CardSet> = Vec::with_capacity(ranges.len());
+    for _ in 0..options.iterations {
+        // 整组重抽直到各玩家的手牌互不冲突，保证按权重的联合分布无偏
+        let mut attempts = 0;
+        let used = loop {
+            attempts += 1;
+            if attempts > MAX_DEAL_ATTEMPTS {
+                return Err(EquityError::NoCompatibleHands);
+            }
+            hands.clear();
+            let mut used = known;
+            for (range, cumulative) in ranges.iter().zip(cumulative_weights.iter()) {
+                let target = rng.next_f64() * cumulative.last().copied().unwrap_or(0.0);
+                let index = cumulative.partition_point(|total| *total <= target).min(range.len() - 1);
+                let cards = range.get_combos()[index].cards;
+                if !cards.is_disjoint(used) {
+                    break;
+                }
+                used = used | cards;
+                hands.push(cards);
+            }
+            if hands.len() == ranges.len() {
+                break used;
+            }
+        };
+        let mut deck: Vec<Card> = used.complement().to_cards();
+        let runout = sample_runout(&mut deck, missing, &mut rng);
+        tally.record(&hands, runout, 1.0);
+    }
+    Ok(tally.finish(false))
+}
+
+// 枚举各玩家范围中互不冲突的手牌组合，weight 为组合中各手牌权重之积
+fn for_each_deal(ranges: &[HandRange], used: CardSet, weight: f64, hands: &mut Vec<CardSet>, visit: &mut dyn FnMut(&[CardSet], CardSet, f64)) {
+    let Some((range, rest)) = ranges.split_first() else {
+        visit(hands, used, weight);
+        return;
+    };
+    for combo in range.get_combos() {
+        if combo.cards.is_disjoint(used) {
+            hands.push(combo.cards);
+            for_each_deal(rest, used | combo.cards, weight * combo.weight, hands, visit);
+            hands.pop();
+        }
+    }
+}
+
+// 枚举从 deck 中补齐 missing 张公共牌的全部组合
+fn for_each_runout(deck: &[Card], missing: usize, mut visit: impl FnMut(CardSet)) {
+    let mut positions: Vec<usize> = (0..missing).collect();
+    loop {
+        visit(positions.iter().map(|position| deck[*position]).collect());
+        if !next_combination(&mut positions, deck.len()) {
+            break;
+        }
+    }
+}
+
+// 部分 Fisher-Yates：只打乱前 missing 张作为补齐的公共牌
+fn sample_runout(deck: &mut [Card], missing: usize, rng: &mut SplitMix64) -> CardSet {
+    for index in 0..missing {
+        let swap = index + rng.next_below((deck.len() - index) as u64) as usize;
+        deck.swap(index, swap);
+    }
+    deck[..missing].iter().copied().collect()
+}
+
+// 按发牌结果逐个累计胜负
+struct Tally {
+    board: CardSet,
+    players: Vec<PlayerEquity>,
+    boards: u64,
+    total_weight: f64,
+    ranks: Vec<HandRank>,
+}
+
+impl Tally {
+    fn new(player_count: usize, board: CardSet) -> Self {
+        Tally {
+            board,
+            players: vec![PlayerEquity::default(); player_count],
+            boards: 0,
+            total_weight: 0.0,
+            ranks: Vec::with_capacity(player_count),
+        }
+    }
+
+    fn record(&mut self, hands: &[CardSet], runout: CardSet, weight: f64) {
+        let board = self.board | runout;
+        self.ranks.clear();
+        for hand in hands {
+            // 7 张牌一定可以求值
+            if let Some(rank) = lookup_evaluator::evaluate(*hand | board) {
+                self.ranks.push(rank);
+            }
+        }
+        let best = self.ranks.iter().max().copied();
+        let winners = self.ranks.iter().filter(|rank| Some(**rank) == best).count();
+        for (player, rank) in self.ranks.iter().enumerate() {
+            if Some(*rank) != best {
+                continue;
+            }
+            if winners == 1 {
+                self.players[player].wins += weight;
+            } else {
+                self.players[player].ties += weight;
+            }
+            self.players[player].equity += weight / winners as f64;
+        }
+        self.boards += 1;
+        self.total_weight += weight;
+    }
+
+    fn finish(self, exhaustive: bool) -> EquityResult {
+        EquityResult{players: self.players, boards: self.boards, total_weight: self.total_weight, exhaustive}
+    }
+}
+
+// C(n, k)，超过 u64 时饱和
+fn combination_count(n: u64, k: u64) -> u64 {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1u64, |count, i| count.saturating_mul(n - i) / (i + 1))
+}
+
+// 按字典序推进到下一个组合，已是最后一个时返回 false
+fn next_combination(positions: &mut [usize], n: usize) -> bool {
+    let k = positions.len();
+    for i in (0..k).rev() {
+        if positions[i] < n - k + i {
+            positions[i] += 1;
+            for j in i + 1..k {
+                positions[j] = positions[j - 1] + 1;
+            }
+            return true;
+        }
+    }
+    false
+}
+
+/// 模拟用的快速伪随机数(SplitMix64)，只用于统计估算，不用于真实发牌
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64{state: seed}
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// [0, 1) 内的随机小数
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// [0, bound) 内的均匀随机数，用拒绝采样消除取模偏差
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "bound must be positive");
+        let zone = u64::MAX - (u64::MAX % bound + 1) % bound;
+        loop {
+            let value = self.next_u64();
+            if value <= zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(text: &str) -> CardSet {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn aces_against_kings_preflop() {
+        let result = calculate_equity(&[set("AhAs"), set("KdKc")], CardSet::new(), CardSet::new(), EquityOptions::default()).unwrap();
+        assert!(result.exhaustive);
+        assert_eq!(result.boards, 1_712_304);
+        assert!((result.equity_percent(0) - 82.0).abs() < 1.0, "{}", result.equity_percent(0));
+        assert!((result.equity_percent(0) + result.equity_percent(1) - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn drawing_hand_on_the_turn_counts_its_outs_exactly() {
+        // 同花听牌加两头顺听牌：9 张红桃、3 张 T、3 张 A、3 张 K 共 18 张赢牌
+        let result = calculate_equity(&[set("AhKh"), set("2c2d")], set("QhJh3s4c"), CardSet::new(), EquityOptions::default()).unwrap();
+        assert!(result.exhaustive);
+        assert_eq!(result.boards, 44);
+        assert_eq!(result.players[0].wins, 18.0);
+        assert_eq!(result.players[1].wins, 26.0);
+        assert_eq!(result.tie_percent(0), 0.0);
+    }
+
+    #[test]
+    fn monte_carlo_stays_close_to_the_exact_result() {
+        let hands = [set("AsKs"), set("QdQc")];
+        let board = set("2s7s9h");
+        let exact = calculate_equity(&hands, board, CardSet::new(), EquityOptions::default()).unwrap();
+        let options = EquityOptions{exhaustive_limit: 0, iterations: 20_000, seed: 7};
+        let sampled = calculate_equity(&hands, board, CardSet::new(), options).unwrap();
+        assert!(exact.exhaustive && !sampled.exhaustive);
+        assert_eq!(sampled.boards, 20_000);
+        assert!((exact.equity_percent(0) - sampled.equity_percent(0)).abs() < 1.5);
+        assert_eq!(calculate_equity(&hands, board, CardSet::new(), options), Ok(sampled));
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let options = EquityOptions::default();
+        assert_eq!(calculate_equity(&[set("AhAs")], CardSet::new(), CardSet::new(), options), Err(EquityError::PlayerCount(1)));
+        assert_eq!(
+            calculate_equity(&[set("AhAs"), set("Kd")], CardSet::new(), CardSet::new(), options),
+            Err(EquityError::HoleCardCount{player: 1, count: 1}),
+        );
+        assert_eq!(
+            calculate_equity(&[set("AhAs"), set("KdKc")], set("AhQd2c"), CardSet::new(), options),
+            Err(EquityError::DuplicateCards(set("Ah"))),
+        );
+    }
+}
@@ -0,0 +1,365 @@
+use std::any::Any;
+use std::fmt;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::game::game_item::GameItem;
+use crate::game::game_items::poker::poker::Card;
+use crate::game::game_items::poker::card_set::CardSet;
+
+/// 德州扑克牌型，按从小到大的顺序排列
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HandCategory {
+    HighCard,      // 高牌
+    OnePair,       // 一对
+    TwoPair,       // 两对
+    ThreeOfAKind,  // 三条
+    Straight,      // 顺子
+    Flush,         // 同花
+    FullHouse,     // 葫芦
+    FourOfAKind,   // 四条
+    StraightFlush, // 同花顺
+    RoyalFlush,    // 皇家同花顺
+}
+
+impl fmt::Display for HandCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HandCategory::HighCard => write!(f, "High Card"),
+            HandCategory::OnePair => write!(f, "One Pair"),
+            HandCategory::TwoPair => write!(f, "Two Pair"),
+            HandCategory::ThreeOfAKind => write!(f, "Three of a Kind"),
+            HandCategory::Straight => write!(f, "Straight"),
+            HandCategory::Flush => write!(f, "Flush"),
+            HandCategory::FullHouse => write!(f, "Full House"),
+            HandCategory::FourOfAKind => write!(f, "Four of a Kind"),
+            HandCategory::StraightFlush => write!(f, "Straight Flush"),
+            HandCategory::RoyalFlush => write!(f, "Royal Flush"),
+        }
+    }
+}
+
+/// 牌型之间的大小规则
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum HandRanking {
+    #[default]
+    Standard,  // 标准 52 张牌
+    ShortDeck, // 短牌(6+)：同花大于葫芦，A-6-7-8-9 为最小的顺子
+}
+
+impl HandRanking {
+    // 牌型在该规则下的大小次序
+    fn category_order(&self, category: HandCategory) -> u8 {
+        match (self, category) {
+            (HandRanking::ShortDeck, HandCategory::Flush) => HandCategory::FullHouse as u8,
+            (HandRanking::ShortDeck, HandCategory::FullHouse) => HandCategory::Flush as u8,
+            _ => category as u8,
+        }
+    }
+}
+
+/// 牌力值：先按牌型在规则中的次序比较，再按顺序逐个比较 kickers
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandValue {
+    order: u8, // 牌型次序，只有同一规则下的牌力可以互相比较
+    category: HandCategory,
+    kickers: [u8; 5], // 按比较优先级排列的点数，不足 5 个的位置补 0
+}
+
+impl HandValue {
+    pub fn new(category: HandCategory, kickers: [u8; 5]) -> Self {
+        HandValue::with_ranking(HandRanking::Standard, category, kickers)
+    }
+
+    pub fn with_ranking(ranking: HandRanking, category: HandCategory, kickers: [u8; 5]) -> Self {
+        HandValue{order: ranking.category_order(category), category, kickers}
+    }
+
+    pub fn get_category(&self) -> HandCategory {
+        self.category
+    }
+
+    pub fn get_kickers(&self) -> [u8; 5] {
+        self.kickers
+    }
+}
+
+impl fmt::Display for HandValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {:?}", self.category, self.kickers)
+    }
+}
+
+/// 计算恰好 5 张牌的牌力
+pub fn evaluate_five(cards: [&Card; 5]) -> HandValue {
+    evaluate_five_with(HandRanking::Standard, cards)
+}
+
+/// 按指定规则计算恰好 5 张牌的牌力
+pub fn evaluate_five_with(ranking: HandRanking, cards: [&Card; 5]) -> HandValue {
+    let mut values: Vec<u8> = cards.iter().map(|card| card.rank.value()).collect();
+    values.sort_unstable_by(|a, b| b.cmp(a));
+
+    let is_flush = cards.iter().all(|card| card.suit == cards[0].suit);
+    let straight_high = straight_high(ranking, &values);
+
+    // 按 (张数, 点数) 从大到小分组，例如葫芦 K-K-K-7-7 => [(3, 13), (2, 7)]
+    let mut count_map: HashMap<u8, u8> = HashMap::new();
+    for value in &values {
+        *count_map.entry(*value).or_insert(0) += 1;
+    }
+    let mut groups: Vec<(u8, u8)> = count_map.into_iter().map(|(value, count)| (count, value)).collect();
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut kickers = [0u8; 5];
+    let category = match (straight_high, is_flush) {
+        (Some(14), true) => {
+            kickers[0] = 14;
+            HandCategory::RoyalFlush
+        }
+        (Some(high), true) => {
+            kickers[0] = high;
+            HandCategory::StraightFlush
+        }
+        (Some(high), false) => {
+            kickers[0] = high;
+            HandCategory::Straight
+        }
+        (None, true) => {
+            kickers.copy_from_slice(&values);
+            HandCategory::Flush
+        }
+        (None, false) => {
+            for (index, (_, value)) in groups.iter().enumerate() {
+                kickers[index] = *value;
+            }
+            match (groups[0].0, groups[1].0) {
+                (4, _) => HandCategory::FourOfAKind,
+                (3, 2) => HandCategory::FullHouse,
+                (3, _) => HandCategory::ThreeOfAKind,
+                (2, 2) => HandCategory::TwoPair,
+                (2, _) => HandCategory::OnePair,
+                _ => HandCategory::HighCard,
+            }
+        }
+    };
+
+    HandValue::with_ranking(ranking, category, kickers)
+}
+
+/// 从 5~7 张(或更多)牌中选出最大的 5 张组合，不足 5 张返回 None
+pub fn evaluate(cards: &[Card]) -> Option<HandValue> {
+    evaluate_with(HandRanking::Standard, cards)
+}
+
+/// 短牌规则下的 evaluate
+pub fn evaluate_short_deck(cards: &[Card]) -> Option<HandValue> {
+    evaluate_with(HandRanking::ShortDeck, cards)
+}
+
+/// 按指定规则从 5 张以上的牌中选出最大的 5 张组合，不足 5 张返回 None
+pub fn evaluate_with(ranking: HandRanking, cards: &[Card]) -> Option<HandValue> {
+    if cards.len() < 5 {
+        return None;
+    }
+
+    let mut best: Option<HandValue> = None;
+    let n = cards.len();
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    for e in d + 1..n {
+                        let value = evaluate_five_with(ranking, [&cards[a], &cards[b], &cards[c], &cards[d], &cards[e]]);
+                        if best.is_none_or(|best| value > best) {
+                            best = Some(value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+/// 奥马哈求值：必须恰好用两张底牌加三张公共牌，底牌不足 2 张或公共牌不足 3 张返回 None
+pub fn evaluate_omaha(hole_cards: &[Card], board: &[Card]) -> Option<HandValue> {
+    let mut best: Option<HandValue> = None;
+    for (a, b) in index_pairs(hole_cards.len()) {
+        for (c, d, e) in index_triples(board.len()) {
+            let value = evaluate_five([&hole_cards[a], &hole_cards[b], &board[c], &board[d], &board[e]]);
+            if best.is_none_or(|best| value > best) {
+                best = Some(value);
+            }
+        }
+    }
+    best
+}
+
+/// 位图形式的 evaluate
+pub fn evaluate_set(cards: CardSet) -> Option<HandValue> {
+    evaluate(&cards.to_cards())
+}
+
+/// 按 GameRule::compare 的签名比较两手牌，左边严格大于右边时返回 true
+pub fn compare_hands(
+    left: &Vec<&dyn GameItem>,
+    right: &Vec<&dyn GameItem>,
+    _context: Arc<HashMap<String, Arc<dyn Any + Send + Sync>>>,
+) -> bool {
+    let left_cards: Vec<Card> = left.iter().filter_map(|item| Card::from_item(*item)).collect();
+    let right_cards: Vec<Card> = right.iter().filter_map(|item| Card::from_item(*item)).collect();
+    evaluate(&left_cards) > evaluate(&right_cards)
+}
+
+/// 短牌版本的 compare_hands
+pub fn compare_short_deck_hands(
+    left: &Vec<&dyn GameItem>,
+    right: &Vec<&dyn GameItem>,
+    _context: Arc<HashMap<String, Arc<dyn Any + Send + Sync>>>,
+) -> bool {
+    let left_cards: Vec<Card> = left.iter().filter_map(|item| Card::from_item(*item)).collect();
+    let right_cards: Vec<Card> = right.iter().filter_map(|item| Card::from_item(*item)).collect();
+    evaluate_short_deck(&left_cards) > evaluate_short_deck(&right_cards)
+}
+
+/// 奥马哈版本的 compare_hands，两边都是 hole_card_count 张底牌后接公共牌
+pub fn compare_omaha_hands(
+    left: &Vec<&dyn GameItem>,
+    right: &Vec<&dyn GameItem>,
+    hole_card_count: usize,
+) -> bool {
+    let split = |items: &Vec<&dyn GameItem>| {
+        let cards: Vec<Card> = items.iter().filter_map(|item| Card::from_item(*item)).collect();
+        let hole_card_count = hole_card_count.min(cards.len());
+        evaluate_omaha(&cards[..hole_card_count], &cards[hole_card_count..])
+    };
+    split(left) > split(right)
+}
+
+// n 个元素中任取 2 个的全部下标组合
+fn index_pairs(n: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..n).flat_map(move |a| (a + 1..n).map(move |b| (a, b)))
+}
+
+// n 个元素中任取 3 个的全部下标组合
+fn index_triples(n: usize) -> impl Iterator<Item = (usize, usize, usize)> {
+    (0..n).flat_map(move |a| index_pairs(n).filter(move |(b, _)| *b > a).map(move |(b, c)| (a, b, c)))
+}
+
+// 已按从大到小排好序的 5 个点数是否组成顺子，是则返回顺子最大点数，
+// A 可以当 1 用：标准规则中 A-2-3-4-5 按 5 计，短牌中 A-6-7-8-9 按 9 计
+fn straight_high(ranking: HandRanking, sorted_values: &[u8]) -> Option<u8> {
+    match ranking {
+        HandRanking::Standard if sorted_values == [14, 5, 4, 3, 2] => return Some(5),
+        HandRanking::ShortDeck if sorted_values == [14, 9, 8, 7, 6] => return Some(9),
+        _ => {}
+    }
+    let is_straight = sorted_values.windows(2).all(|pair| pair[0] == pair[1] + 1);
+    if is_straight { Some(sorted_values[0]) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn cards(text: &str) -> Vec<Card> {
+        text.parse::<CardSet>().unwrap().to_cards()
+    }
+
+    fn value(text: &str) -> HandValue {
+        evaluate(&cards(text)).unwrap()
+    }
+
+    #[test]
+    fn wheel_is_the_lowest_straight() {
+        let wheel = value("As2d3c4h5s");
+        assert_eq!(wheel.get_category(), HandCategory::Straight);
+        assert_eq!(wheel.get_kickers()[0], 5);
+        assert!(wheel < value("2d3c4h5s6d"));
+        assert!(wheel > value("AsAd3c4h5s"));
+    }
+
+    #[test]
+    fn steel_wheel_is_the_lowest_straight_flush() {
+        let steel_wheel = value("As2s3s4s5s");
+        assert_eq!(steel_wheel.get_category(), HandCategory::StraightFlush);
+        assert_eq!(steel_wheel.get_kickers()[0], 5);
+        assert!(steel_wheel < value("2h3h4h5h6h"));
+        assert!(steel_wheel > value("AsAdAcAh5s"));
+        assert_eq!(value("AhKhQhJhTh").get_category(), HandCategory::RoyalFlush);
+    }
+
+    #[test]
+    fn kickers_break_ties_within_a_category() {
+        assert!(value("AsAdKc7h4s") > value("AhAcQd7s4d"));
+        assert!(value("KsKdQcQh9s") > value("KhKcQdQs8d"));
+        assert!(value("AsJs8s6s3s") > value("AhJh8h6h2h"));
+        assert_eq!(value("9s9d9c4h4s").get_kickers(), [9, 4, 0, 0, 0]);
+        assert_eq!(value("AsAdKc7h4s").get_kickers(), [14, 13, 7, 4, 0]);
+    }
+
+    #[test]
+    fn best_five_of_seven_cards() {
+        assert_eq!(value("2h3dAsKsQsJsTs").get_category(), HandCategory::RoyalFlush);
+        let full_house = value("KsKdKc7s7d7c2h");
+        assert_eq!(full_house.get_category(), HandCategory::FullHouse);
+        assert_eq!(full_house.get_kickers(), [13, 7, 0, 0, 0]);
+        // 第六、七张牌比不上已选出的五张时不影响牌力
+        assert_eq!(value("AsAdKc7h4s3d2c").cmp(&value("AsAdKc7h4s")), Ordering::Equal);
+        assert_eq!(evaluate(&cards("AsAdKc7h")), None);
+    }
+
+    #[test]
+    fn identical_ranks_in_different_suits_compare_equal() {
+        let board = "2c7d9hTsJs";
+        let left = value(&format!("{}8s3d", board));
+        let right = value(&format!("{}8h4c", board));
+        assert_eq!(left.cmp(&right), Ordering::Equal);
+
+        let left_items: Vec<Card> = cards(&format!("{}8s3d", board));
+        let right_items: Vec<Card> = cards(&format!("{}8h4c", board));
+        let left_refs: Vec<&dyn GameItem> = left_items.iter().map(|card| card as &dyn GameItem).collect();
+        let right_refs: Vec<&dyn GameItem> = right_items.iter().map(|card| card as &dyn GameItem).collect();
+        assert!(!compare_hands(&left_refs, &right_refs, Arc::new(HashMap::new())));
+        assert!(!compare_hands(&right_refs, &left_refs, Arc::new(HashMap::new())));
+    }
+
+    #[test]
+    fn omaha_needs_two_suited_hole_cards_for_a_flush() {
+        let omaha = |hole: &str, board: &str| evaluate_omaha(&cards(hole), &cards(board)).unwrap();
+        // 四张同花底牌只能用两张，公共牌上只有一张红桃
+        let hole = "AhKhQhJh";
+        let board = "2h3c4d9s8c";
+        assert_ne!(omaha(hole, board).get_category(), HandCategory::Flush);
+        assert_eq!(value(&format!("{}{}", hole, board)).get_category(), HandCategory::Flush);
+        // 公共牌上四张红桃，底牌只有一张红桃
+        assert_ne!(omaha("Ah7c7d2s", "2h5h8hJhTd").get_category(), HandCategory::Flush);
+        assert_eq!(omaha("Ah3h7d2s", "2h5h8hJhTd").get_category(), HandCategory::Flush);
+        // 公共牌的四条只能用三张，配上底牌的一对成为葫芦
+        assert_eq!(omaha("AsAdQc2c", "9s9d9c9hTd").get_category(), HandCategory::FullHouse);
+    }
+
+    #[test]
+    fn short_deck_ranks_flush_over_full_house() {
+        let short = |text: &str| evaluate_short_deck(&cards(text)).unwrap();
+        assert!(short("AsJs9s7s6s") > short("KsKdKc6h6s"));
+        assert!(value("AsJs9s7s6s") < value("KsKdKc6h6s"));
+        // A-6-7-8-9 是最小的顺子
+        let lowest = short("As6d7c8h9s");
+        assert_eq!(lowest.get_category(), HandCategory::Straight);
+        assert_eq!(lowest.get_kickers()[0], 9);
+        assert!(lowest < short("6d7c8h9sTs"));
+        assert_eq!(value("As6d7c8h9s").get_category(), HandCategory::HighCard);
+        assert_eq!(short("As6s7s8s9s").get_category(), HandCategory::StraightFlush);
+    }
+
+    #[test]
+    fn omaha_accepts_four_to_six_hole_cards() {
+        let board = cards("2h5h8hJhTd");
+        assert!(evaluate_omaha(&cards("Ah3h7d2s6c"), &board).is_some());
+        assert!(evaluate_omaha(&cards("Ah3h7d2s6c9c"), &board).is_some());
+        assert_eq!(evaluate_omaha(&cards("Ah"), &board), None);
+        assert_eq!(evaluate_omaha(&cards("Ah3h7d2s"), &cards("2h5h")), None);
+    }
+}
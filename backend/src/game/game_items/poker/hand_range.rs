@@ -0,0 +1,288 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::game::game_items::poker::card_set::CardSet;
+use crate::game::game_items::poker::poker::{Card, Rank, Suit};
+
+/// 范围中的一手具体手牌及其权重
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightedCombo {
+    pub cards: CardSet, // 两张手牌
+    pub weight: f64,    // (0, 1]，1 表示总是以这手牌进入
+}
+
+impl WeightedCombo {
+    pub fn get_cards(&self) -> Vec<Card> {
+        self.cards.to_cards()
+    }
+}
+
+/// 解析范围记法失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeParseError {
+    UnknownHand(String),      // 无法识别的手牌记法
+    InvalidSpan(String),      // 无法展开的 "-" 区间
+    WeightOutOfRange(String), // 权重不在 (0, 1] 内
+}
+
+impl fmt::Display for RangeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RangeParseError::UnknownHand(token) => write!(f, "invalid hand '{}'", token),
+            RangeParseError::InvalidSpan(token) => write!(f, "invalid range span '{}'", token),
+            RangeParseError::WeightOutOfRange(token) => write!(f, "invalid weight '{}'", token),
+        }
+    }
+}
+
+/// 手牌范围，例如 "22+, AJs+, KQo, T9s:0.5, AsKd"
+///
+/// 支持的记法：
+/// - 对子 "77"、"77+"(77 到 AA)、"99-66"
+/// - 非对子 "AK"(16 手)、"AKs"(同花 4 手)、"AKo"(非同花 12 手)
+/// - "ATs+"(踢脚从 T 升到 K)、"KTo-K7o"(同一高牌的踢脚区间)
+/// - 具体手牌 "AsKd"
+/// - 任意一项后加 ":权重"，例如 "AQo:0.25"
+///
+/// 同一手牌出现多次时以最后一次的权重为准
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HandRange {
+    combos: Vec<WeightedCombo>,
+}
+
+impl HandRange {
+    pub fn new() -> Self {
+        HandRange{combos: Vec::new()}
+    }
+
+    pub fn from_combos(combos: Vec<WeightedCombo>) -> Self {
+        let mut range = HandRange::new();
+        for combo in combos {
+            range.insert(combo.cards, combo.weight);
+        }
+        range
+    }
+
+    pub fn get_combos(&self) -> &[WeightedCombo] {
+        &self.combos
+    }
+
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    /// 所有手牌的权重之和
+    pub fn total_weight(&self) -> f64 {
+        self.combos.iter().map(|combo| combo.weight).sum()
+    }
+
+    /// 加入一手牌，已存在时更新权重
+    pub fn insert(&mut self, cards: CardSet, weight: f64) {
+        match self.combos.iter_mut().find(|combo| combo.cards == cards) {
+            Some(combo) => combo.weight = weight,
+            None => self.combos.push(WeightedCombo{cards, weight}),
+        }
+    }
+
+    /// 移除与已知牌(公共牌、死牌、对手手牌)冲突的手牌
+    pub fn without(&self, known: CardSet) -> HandRange {
+        HandRange{combos: self.combos.iter().copied().filter(|combo| combo.cards.is_disjoint(known)).collect()}
+    }
+}
+
+impl FromStr for HandRange {
+    type Err = RangeParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut range = HandRange::new();
+        for token in text.split(|symbol: char| symbol == ',' || symbol.is_whitespace()).filter(|token| !token.is_empty()) {
+            let (hands, weight) = match token.split_once(':') {
+                Some((hands, weight)) => (hands, parse_weight(weight)?),
+                None => (token, 1.0),
+            };
+            for cards in expand_token(hands)? {
+                range.insert(cards, weight);
+            }
+        }
+        Ok(range)
+    }
+}
+
+fn parse_weight(text: &str) -> Result<f64, RangeParseError> {
+    match text.parse::<f64>() {
+        Ok(weight) if weight > 0.0 && weight <= 1.0 => Ok(weight),
+        _ => Err(RangeParseError::WeightOutOfRange(text.to_string())),
+    }
+}
+
+// 手牌类别，例如 AKs => (A, K, Some(true))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HandClass {
+    high: Rank,
+    low: Rank,
+    suited: Option<bool>, // None 表示同花和非同花都包含，对子恒为 None
+}
+
+impl HandClass {
+    fn parse(text: &str) -> Option<HandClass> {
+        let symbols: Vec<char> = text.chars().collect();
+        if symbols.len() < 2 || symbols.len() > 3 {
+            return None;
+        }
+        let first = Rank::from_char(symbols[0])?;
+        let second = Rank::from_char(symbols[1])?;
+        let suited = match symbols.get(2).map(|symbol| symbol.to_ascii_lowercase()) {
+            None => None,
+            Some('s') => Some(true),
+            Some('o') => Some(false),
+            Some(_) => return None,
+        };
+        if first == second && suited.is_some() {
+            return None;
+        }
+        let (high, low) = if first.value() >= second.value() { (first, second) } else { (second, first) };
+        Some(HandClass{high, low, suited})
+    }
+
+    fn is_pair(&self) -> bool {
+        self.high == self.low
+    }
+
+    fn combos(&self) -> Vec<CardSet> {
+        let mut combos = Vec::new();
+        for (first_index, first_suit) in Suit::ALL_SUITS.iter().enumerate() {
+            for (second_index, second_suit) in Suit::ALL_SUITS.iter().enumerate() {
+                let keep = if self.is_pair() {
+                    first_index < second_index
+                } else {
+                    match self.suited {
+                        Some(true) => first_index == second_index,
+                        Some(false) => first_index != second_index,
+                        None => true,
+                    }
+                };
+                if keep {
+                    combos.push(CardSet::from_cards(&[Card::new(*first_suit, self.high), Card::new(*second_suit, self.low)]));
+                }
+            }
+        }
+        combos
+    }
+}
+
+// 点数从 low 到 high(含)的所有点数
+fn ranks_between(low: Rank, high: Rank) -> Vec<Rank> {
+    let mut ranks: Vec<Rank> = Rank::ALL_RANKS.into_iter()
+        .filter(|rank| rank.value() >= low.value() && rank.value() <= high.value())
+        .collect();
+    ranks.sort_by_key(|rank| rank.value());
+    ranks
+}
+
+fn expand_token(token: &str) -> Result<Vec<CardSet>, RangeParseError> {
+    let invalid_hand = || RangeParseError::UnknownHand(token.to_string());
+
+    // 具体手牌
+    if token.len() == 4 {
+        if let Ok(cards) = token.parse::<CardSet>() {
+            return if cards.count() == 2 { Ok(vec![cards]) } else { Err(invalid_hand()) };
+        }
+    }
+
+    if let Some((from, to)) = token.split_once('-') {
+        let invalid_span = || RangeParseError::InvalidSpan(token.to_string());
+        let from = HandClass::parse(from).ok_or_else(invalid_span)?;
+        let to = HandClass::parse(to).ok_or_else(invalid_span)?;
+        let classes: Vec<HandClass> = if from.is_pair() && to.is_pair() {
+            let (low, high) = if from.high.value() <= to.high.value() { (from.high, to.high) } else { (to.high, from.high) };
+            ranks_between(low, high).into_iter().map(|rank| HandClass{high: rank, low: rank, suited: None}).collect()
+        } else if !from.is_pair() && !to.is_pair() && from.high == to.high && from.suited == to.suited {
+            let (low, high) = if from.low.value() <= to.low.value() { (from.low, to.low) } else { (to.low, from.low) };
+            ranks_between(low, high).into_iter().map(|rank| HandClass{high: from.high, low: rank, suited: from.suited}).collect()
+        } else {
+            return Err(invalid_span());
+        };
+        return Ok(classes.iter().flat_map(|class| class.combos()).collect());
+    }
+
+    if let Some(class) = token.strip_suffix('+') {
+        let class = HandClass::parse(class).ok_or_else(invalid_hand)?;
+        let classes: Vec<HandClass> = if class.is_pair() {
+            ranks_between(class.high, Rank::Ace).into_iter().map(|rank| HandClass{high: rank, low: rank, suited: None}).collect()
+        } else {
+            // 踢脚升到比高牌小一级为止
+            ranks_between(class.low, class.high).into_iter()
+                .filter(|rank| *rank != class.high)
+                .map(|rank| HandClass{high: class.high, low: rank, suited: class.suited})
+                .collect()
+        };
+        return Ok(classes.iter().flat_map(|class| class.combos()).collect());
+    }
+
+    HandClass::parse(token).map(|class| class.combos()).ok_or_else(invalid_hand)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(text: &str) -> HandRange {
+        text.parse().unwrap()
+    }
+
+    fn contains(range: &HandRange, cards: &str) -> bool {
+        let cards: CardSet = cards.parse().unwrap();
+        range.get_combos().iter().any(|combo| combo.cards == cards)
+    }
+
+    #[test]
+    fn hand_classes_expand_to_their_combos() {
+        assert_eq!(range("TT").len(), 6);
+        assert_eq!(range("AKs").len(), 4);
+        assert_eq!(range("KQo").len(), 12);
+        assert_eq!(range("AK").len(), 16);
+        assert!(contains(&range("AKs"), "AhKh"));
+        assert!(!contains(&range("AKs"), "AhKd"));
+        assert!(contains(&range("KQo"), "QsKd"));
+        assert!(!contains(&range("KQo"), "KsQs"));
+        assert!(contains(&range("AsKd"), "AsKd"));
+        assert_eq!(range("AsKd").len(), 1);
+    }
+
+    #[test]
+    fn plus_and_span_notation() {
+        // TT、JJ、QQ、KK、AA
+        assert_eq!(range("TT+").len(), 30);
+        assert!(contains(&range("TT+"), "AsAd"));
+        assert!(!contains(&range("TT+"), "9s9d"));
+        // A2s、A3s、A4s、A5s
+        let span = range("A2s-A5s");
+        assert_eq!(span.len(), 16);
+        assert!(contains(&span, "As2s") && contains(&span, "Ah5h"));
+        assert!(!contains(&span, "Ac6c"));
+        // ATs 到 AKs
+        assert_eq!(range("ATs+").len(), 16);
+        assert_eq!(range("99-66").len(), 24);
+    }
+
+    #[test]
+    fn weights_and_duplicates() {
+        let weighted = range("AKs, AhKh:0.5, QQ:0.25");
+        assert_eq!(weighted.len(), 10);
+        assert!((weighted.total_weight() - (3.0 + 0.5 + 6.0 * 0.25)).abs() < 1e-9);
+        assert_eq!(weighted.without("Ah".parse().unwrap()).len(), 9);
+    }
+
+    #[test]
+    fn malformed_ranges_are_rejected() {
+        assert_eq!("AXs".parse::<HandRange>(), Err(RangeParseError::UnknownHand("AXs".to_string())));
+        assert_eq!("AAs".parse::<HandRange>(), Err(RangeParseError::UnknownHand("AAs".to_string())));
+        assert_eq!("AsAs".parse::<HandRange>(), Err(RangeParseError::UnknownHand("AsAs".to_string())));
+        assert_eq!("A2s-K5s".parse::<HandRange>(), Err(RangeParseError::InvalidSpan("A2s-K5s".to_string())));
+        assert_eq!("KQo:1.5".parse::<HandRange>(), Err(RangeParseError::WeightOutOfRange("1.5".to_string())));
+        assert_eq!("KQo:0".parse::<HandRange>(), Err(RangeParseError::WeightOutOfRange("0".to_string())));
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use crate::game::game_items::poker::card_set::{card_from_index, CardSet};
+use crate::game::game_items::poker::hand_evaluator::{self, HandCategory, HandValue};
+use crate::game::game_items::poker::poker::{Card, Rank, Suit};
+use crate::game::provably_fair::commit_reveal::FairRng;
+
+/// 5 张牌一共有 7462 种不同的牌力
+pub const HAND_RANK_COUNT: u16 = 7462;
+
+// 查表支持的最多张数
+const MAX_CARDS: usize = 7;
+
+/// 查表得到的牌力等级，1 为最小的 7-5-4-3-2 高牌，7462 为皇家同花顺，
+/// 大小顺序与 hand_evaluator::evaluate 完全一致
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandRank {
+    rank: u16,
+}
+
+impl HandRank {
+    pub fn get_rank(&self) -> u16 {
+        self.rank
+    }
+
+    /// 还原为参考实现的牌力值
+    pub fn to_hand_value(self) -> HandValue {
+        tables().hand_values[(self.rank - 1) as usize]
+    }
+
+    pub fn get_category(&self) -> HandCategory {
+        self.to_hand_value().get_category()
+    }
+}
+
+impl fmt::Display for HandRank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (#{})", self.to_hand_value(), self.rank)
+    }
+}
+
+/// 计算 5~7 张牌中最大 5 张的牌力，张数不符返回 None
+pub fn evaluate(cards: CardSet) -> Option<HandRank> {
+    let card_count = cards.count() as usize;
+    if !(5..=MAX_CARDS).contains(&card_count) {
+        return None;
+    }
+    let tables = tables();
+
+    // 7 张以内有 5 张同花时不可能再组成四条或葫芦，同花(顺)就是最大牌型
+    for suit in Suit::ALL_SUITS {
+        let suit_mask = cards.suit_mask(suit);
+        if suit_mask.count_ones() >= 5 {
+            return Some(HandRank{rank: tables.flush[suit_mask as usize]});
+        }
+    }
+
+    let mut rank_counts = [0u8; 13];
+    let mut bits = cards.get_bits();
+    while bits != 0 {
+        rank_counts[(bits.trailing_zeros() % 13) as usize] += 1;
+        bits &= bits - 1;
+    }
+    let index = tables.quinary_index(&rank_counts, card_count);
+    Some(HandRank{rank: tables.no_flush[card_count][index]})
+}
+
+/// Card 切片形式的 evaluate，重复的牌只计一次
+pub fn evaluate_cards(cards: &[Card]) -> Option<HandRank> {
+    evaluate(CardSet::from_cards(cards))
+}
+
+/// 提前构建查找表，避免首次求值时的初始化开销
+pub fn init_tables() {
+    tables();
+}
+
+// 查找表
+//
+// 同花：按该花色 13 位点数掩码直接索引；
+// 非同花：只与各点数的张数有关，把 13 个点数的张数(0~4)看成五进制数，
+// 再用组合计数把张数之和为 n 的所有组合一一映射到 [0, 组合数) 的下标(完美哈希)
+struct LookupTables {
+    hand_values: Vec<HandValue>,   // 下标 + 1 即牌力等级
+    flush: Vec<u16>,               // 8192 项
+    no_flush: Vec<Vec<u16>>,       // 按张数 0~7 分表，只有 5~7 有内容
+    offsets: Vec<[[u32; 5]; MAX_CARDS + 1]>, // offsets[点数][剩余张数][该点数张数]
+}
+
+static LOOKUP_TABLES: OnceLock<LookupTables> = OnceLock::new();
+
+fn tables() -> &'static LookupTables {
+    LOOKUP_TABLES.get_or_init(LookupTables::build)
+}
+
+impl LookupTables {
+    fn build() -> Self {
+        // suffix_ways[r][k]：把 k 张牌分配到点数 r..13、每个点数不超过 4 张的方案数
+        let mut suffix_ways = vec![[0u32; MAX_CARDS + 1]; 14];
+        suffix_ways[13][0] = 1;
+        for rank in (0..13).rev() {
+            for remaining in 0..=MAX_CARDS {
+                suffix_ways[rank][remaining] = (0..=remaining.min(4)).map(|count| suffix_ways[rank + 1][remaining - count]).sum();
+            }
+        }
+        let mut offsets = vec![[[0u32; 5]; MAX_CARDS + 1]; 13];
+        for rank in 0..13 {
+            for remaining in 0..=MAX_CARDS {
+                for count in 1..=remaining.min(4) {
+                    offsets[rank][remaining][count] = offsets[rank][remaining][count - 1] + suffix_ways[rank + 1][remaining - (count - 1)];
+                }
+            }
+        }
+
+        let mut tables = LookupTables {
+            hand_values: Vec::new(),
+            flush: vec![0; 1 << 13],
+            no_flush: (0..=MAX_CARDS).map(|card_count| vec![0; suffix_ways[0][card_count] as usize]).collect(),
+            offsets,
+        };
+
+        // 用参考实现给全部 7462 种 5 张牌力排序
+        let five_card_patterns = rank_count_patterns(5);
+        let mut hand_values: Vec<HandValue> = five_card_patterns.iter()
+            .map(|rank_counts| hand_evaluator::evaluate_five(pattern_cards(rank_counts).each_ref()))
+            .collect();
+        let flush_masks: Vec<u16> = (0u16..1 << 13).filter(|mask| mask.count_ones() == 5).collect();
+        hand_values.extend(flush_masks.iter().map(|mask| hand_evaluator::evaluate_five(flush_cards(*mask).each_ref())));
+        hand_values.sort_unstable();
+        hand_values.dedup();
+        debug_assert_eq!(hand_values.len(), HAND_RANK_COUNT as usize);
+        let ranks: HashMap<HandValue, u16> = hand_values.iter()
+            .enumerate()
+            .map(|(index, value)| (*value, index as u16 + 1))
+            .collect();
+
+        // 同花表：5 张直接查，6~7 张取去掉任意一张后的最大值(子集的下标更小，已经算好)
+        for mask in 0u16..1 << 13 {
+            tables.flush[mask as usize] = match mask.count_ones() {
+                5 => ranks[&hand_evaluator::evaluate_five(flush_cards(mask).each_ref())],
+                6..=7 => (0..13)
+                    .filter(|bit| mask & (1 << bit) != 0)
+                    .map(|bit| tables.flush[(mask & !(1 << bit)) as usize])
+                    .max()
+                    .unwrap_or(0),
+                _ => 0,
+            };
+        }
+
+        // 非同花表：同理，6~7 张取去掉任意一张后的最大值
+        for rank_counts in &five_card_patterns {
+            let index = tables.quinary_index(rank_counts, 5);
+            tables.no_flush[5][index] = ranks[&hand_evaluator::evaluate_five(pattern_cards(rank_counts).each_ref())];
+        }
+        for card_count in 6..=MAX_CARDS {
+            for rank_counts in rank_count_patterns(card_count) {
+                let mut best = 0;
+                for rank in 0..13 {
+                    if rank_counts[rank] > 0 {
+                        let mut smaller = rank_counts;
+                        smaller[rank] -= 1;
+                        best = best.max(tables.no_flush[card_count - 1][tables.quinary_index(&smaller, card_count - 1)]);
+                    }
+                }
+                let index = tables.quinary_index(&rank_counts, card_count);
+                tables.no_flush[card_count][index] = best;
+            }
+        }
+
+        tables.hand_values = hand_values;
+        tables
+    }
+
+    // 张数之和为 card_count 的组合在分表中的下标
+    fn quinary_index(&self, rank_counts: &[u8; 13], card_count: usize) -> usize {
+        let mut remaining = card_count;
+        let mut index = 0;
+        for (rank, count) in rank_counts.iter().enumerate() {
+            index += self.offsets[rank][remaining][*count as usize];
+            remaining -= *count as usize;
+        }
+        index as usize
+    }
+}
+
+// 枚举张数之和为 card_count、每个点数不超过 4 张的全部组合
+fn rank_count_patterns(card_count: usize) -> Vec<[u8; 13]> {
+    fn fill(rank: usize, remaining: usize, current: &mut [u8; 13], patterns: &mut Vec<[u8; 13]>) {
+        if rank == 13 {
+            if remaining == 0 {
+                patterns.push(*current);
+            }
+            return;
+        }
+        for count in 0..=remaining.min(4) {
+            current[rank] = count as u8;
+            fill(rank + 1, remaining - count, current, patterns);
+        }
+        current[rank] = 0;
+    }
+    let mut patterns = Vec::new();
+    fill(0, card_count, &mut [0; 13], &mut patterns);
+    patterns
+}
+
+// 按点数张数构造 5 张牌，花色依次轮换，保证同点数不重复且不成同花
+fn pattern_cards(rank_counts: &[u8; 13]) -> [Card; 5] {
+    let mut cards = [Card::new(Suit::Spades, Rank::Two); 5];
+    let mut position = 0;
+    for (rank, count) in rank_counts.iter().enumerate() {
+        for _ in 0..*count {
+            let suit_index = position % 4;
+            cards[position] = card_from_index((suit_index * 13 + rank) as u8).unwrap_or(cards[position]);
+            position += 1;
+        }
+    }
+    cards
+}
+
+// 13 位点数掩码对应的 5 张同花牌
+fn flush_cards(mask: u16) -> [Card; 5] {
+    let mut cards = [Card::new(Suit::Spades, Rank::Two); 5];
+    let mut bits = mask;
+    for card in cards.iter_mut() {
+        *card = card_from_index(bits.trailing_zeros() as u8).unwrap_or(*card);
+        bits &= bits - 1;
+    }
+    cards
+}
+
+/// 基准测试结果
+#[derive(Debug, Clone)]
+pub struct BenchmarkReport {
+    pub hands: usize,
+    pub cards_per_hand: usize,
+    pub lookup_elapsed: Duration,
+    pub reference_elapsed: Duration,
+    pub mismatches: usize, // 两种实现排序不一致的次数，应为 0
+}
+
+impl BenchmarkReport {
+    pub fn lookup_per_second(&self) -> f64 {
+        self.hands as f64 / self.lookup_elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn reference_per_second(&self) -> f64 {
+        self.hands as f64 / self.reference_elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} hands of {} cards", self.hands, self.cards_per_hand)?;
+        writeln!(f, "lookup:    {:?} ({:.0} hands/s)", self.lookup_elapsed, self.lookup_per_second())?;
+        writeln!(f, "reference: {:?} ({:.0} hands/s)", self.reference_elapsed, self.reference_per_second())?;
+        write!(f, "mismatches: {}", self.mismatches)
+    }
+}
+
+/// 用同一批随机手牌分别计时查表实现和参考实现，并逐手比对两者的大小顺序
+pub fn run_benchmark(hands: usize, cards_per_hand: usize, seed: &str) -> BenchmarkReport {
+    assert!((5..=MAX_CARDS).contains(&cards_per_hand), "cards_per_hand must be between 5 and 7");
+    init_tables();
+
+    let mut rng = FairRng::new(seed, &[], 0);
+    let sample: Vec<CardSet> = (0..hands)
+        .map(|_| {
+            let mut hand = CardSet::new();
+            while (hand.count() as usize) < cards_per_hand {
+                hand.insert(card_from_index(rng.next_below(52) as u8).unwrap_or(Card::new(Suit::Spades, Rank::Two)));
+            }
+            hand
+        })
+        .collect();
+    let sample_cards: Vec<Vec<Card>> = sample.iter().map(|hand| hand.to_cards()).collect();
+
+    let started = Instant::now();
+    let lookup_ranks: Vec<Option<HandRank>> = sample.iter().map(|hand| evaluate(*hand)).collect();
+    let lookup_elapsed = started.elapsed();
+
+    let started = Instant::now();
+    let reference_values: Vec<Option<HandValue>> = sample_cards.iter().map(|cards| hand_evaluator::evaluate(cards)).collect();
+    let reference_elapsed = started.elapsed();
+
+    let mismatches = lookup_ranks.iter()
+        .zip(reference_values.iter())
+        .filter(|(rank, value)| rank.map(|rank| rank.to_hand_value()) != **value)
+        .count();
+
+    BenchmarkReport{hands, cards_per_hand, lookup_elapsed, reference_elapsed, mismatches}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rank(text: &str) -> Option<HandRank> {
+        evaluate(text.parse().unwrap())
+    }
+
+    #[test]
+    fn lookup_matches_reference_evaluator() {
+        for cards_per_hand in 5..=MAX_CARDS {
+            let report = run_benchmark(2000, cards_per_hand, "lookup-test");
+            assert_eq!(report.hands, 2000);
+            assert_eq!(report.mismatches, 0, "{} cards", cards_per_hand);
+        }
+    }
+
+    #[test]
+    fn ranks_span_the_whole_table() {
+        assert_eq!(rank("7s5d4c3h2s").map(|rank| rank.get_rank()), Some(1));
+        assert_eq!(rank("AsKsQsJsTs").map(|rank| rank.get_rank()), Some(HAND_RANK_COUNT));
+        assert_eq!(rank("AsKsQsJsTs2d3d").unwrap().get_category(), HandCategory::RoyalFlush);
+        assert_eq!(rank("As2d3c4h5s").unwrap().get_category(), HandCategory::Straight);
+        assert_eq!(rank("AsKsQsJs"), None);
+        assert_eq!(rank("AsKsQsJsTs2d3d4d"), None);
+    }
+}
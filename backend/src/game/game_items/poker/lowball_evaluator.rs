@@ -0,0 +1,228 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use crate::game::game_items::poker::hand_evaluator::{evaluate_five, HandCategory};
+use crate::game::game_items::poker::poker::{Card, Rank};
+
+/// 8-or-better：低牌的五张点数必须互不相同且都不大于 8
+pub const EIGHT_OR_BETTER: u8 = 8;
+
+/// 低牌计法
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum LowballRule {
+    AceToFive,    // A 最小，不计顺子和同花，最好的牌为 5-4-3-2-A
+    DeuceToSeven, // A 最大，顺子和同花都算，最好的牌为 7-5-4-3-2 杂色
+}
+
+/// 低牌牌力
+///
+/// 数值越小的牌越好，为了能和高牌一样直接用于 distribute_pots 取最大值，
+/// Ord 是反向的：越好的低牌比较时越大
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct LowHandValue {
+    rule: LowballRule,
+    category: HandCategory, // A-5 只会出现高牌到四条
+    ranks: [u8; 5],          // 按比较优先级排列的点数，A-5 中 A 记为 1
+}
+
+impl LowHandValue {
+    pub fn get_rule(&self) -> LowballRule {
+        self.rule
+    }
+
+    pub fn get_category(&self) -> HandCategory {
+        self.category
+    }
+
+    pub fn get_ranks(&self) -> [u8; 5] {
+        self.ranks
+    }
+
+    /// 是否满足 8-or-better 的资格：五张不同点数且最大不超过 8
+    pub fn is_eight_or_better(&self) -> bool {
+        self.rule == LowballRule::AceToFive && self.category == HandCategory::HighCard && self.ranks[0] <= EIGHT_OR_BETTER
+    }
+}
+
+impl Ord for LowHandValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.category, other.ranks).cmp(&(self.category, self.ranks))
+    }
+}
+
+impl PartialOrd for LowHandValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// 例如 "8-6-4-2-A"
+impl fmt::Display for LowHandValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ranks: Vec<String> = self.ranks.iter()
+            .filter(|rank| **rank > 0)
+            .map(|rank| match rank {
+                1 | 14 => "A".to_string(),
+                11 => "J".to_string(),
+                12 => "Q".to_string(),
+                13 => "K".to_string(),
+                _ => rank.to_string(),
+            })
+            .collect();
+        write!(f, "{} {}", self.category, ranks.join("-"))
+    }
+}
+
+/// A-5 计法下恰好 5 张牌的低牌牌力
+pub fn evaluate_ace_to_five_five(cards: [&Card; 5]) -> LowHandValue {
+    let values: Vec<u8> = cards.iter()
+        .map(|card| if card.rank == Rank::Ace { 1 } else { card.rank.value() })
+        .collect();
+
+    // 按 (张数, 点数) 从大到小分组，对子越多越差，同组内点数越大越差
+    let mut count_map: HashMap<u8, u8> = HashMap::new();
+    for value in &values {
+        *count_map.entry(*value).or_insert(0) += 1;
+    }
+    let mut groups: Vec<(u8, u8)> = count_map.into_iter().map(|(value, count)| (count, value)).collect();
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut ranks = [0u8; 5];
+    for (index, (_, value)) in groups.iter().enumerate() {
+        ranks[index] = *value;
+    }
+    let category = match (groups[0].0, groups.get(1).map(|group| group.0).unwrap_or(0)) {
+        (4, _) => HandCategory::FourOfAKind,
+        (3, 2) => HandCategory::FullHouse,
+        (3, _) => HandCategory::ThreeOfAKind,
+        (2, 2) => HandCategory::TwoPair,
+        (2, _) => HandCategory::OnePair,
+        _ => HandCategory::HighCard,
+    };
+    LowHandValue{rule: LowballRule::AceToFive, category, ranks}
+}
+
+/// 2-7 计法下恰好 5 张牌的低牌牌力：按高牌规则算，但 A 只能当最大，A-2-3-4-5 不是顺子
+pub fn evaluate_deuce_to_seven_five(cards: [&Card; 5]) -> LowHandValue {
+    let high = evaluate_five(cards);
+    let kickers = high.get_kickers();
+    let (category, ranks) = match high.get_category() {
+        // evaluate_five 把 A-2-3-4-5 当作 5 高的顺子
+        HandCategory::Straight if kickers[0] == 5 => (HandCategory::HighCard, [14, 5, 4, 3, 2]),
+        HandCategory::StraightFlush if kickers[0] == 5 => (HandCategory::Flush, [14, 5, 4, 3, 2]),
+        category => (category, kickers),
+    };
+    LowHandValue{rule: LowballRule::DeuceToSeven, category, ranks}
+}
+
+/// 从 5 张以上的牌中选出最好的 A-5 低牌，不足 5 张返回 None
+pub fn evaluate_ace_to_five(cards: &[Card]) -> Option<LowHandValue> {
+    best_of_five(cards, evaluate_ace_to_five_five)
+}
+
+/// 从 5 张以上的牌中选出最好的 2-7 低牌，不足 5 张返回 None
+pub fn evaluate_deuce_to_seven(cards: &[Card]) -> Option<LowHandValue> {
+    best_of_five(cards, evaluate_deuce_to_seven_five)
+}
+
+/// 最好的 8-or-better 低牌，不合格时返回 None
+pub fn evaluate_eight_or_better(cards: &[Card]) -> Option<LowHandValue> {
+    evaluate_ace_to_five(cards).filter(|low| low.is_eight_or_better())
+}
+
+/// 奥马哈高低的低牌：必须恰好用两张底牌加三张公共牌，不合格时返回 None
+pub fn evaluate_omaha_eight_or_better(hole_cards: &[Card], board: &[Card]) -> Option<LowHandValue> {
+    let mut best: Option<LowHandValue> = None;
+    for a in 0..hole_cards.len() {
+        for b in a + 1..hole_cards.len() {
+            for c in 0..board.len() {
+                for d in c + 1..board.len() {
+                    for e in d + 1..board.len() {
+                        let low = evaluate_ace_to_five_five([&hole_cards[a], &hole_cards[b], &board[c], &board[d], &board[e]]);
+                        if low.is_eight_or_better() && best.is_none_or(|best| low > best) {
+                            best = Some(low);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+// 枚举全部 5 张组合取最好的低牌
+fn best_of_five(cards: &[Card], evaluate: fn([&Card; 5]) -> LowHandValue) -> Option<LowHandValue> {
+    let mut best: Option<LowHandValue> = None;
+    let n = cards.len();
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    for e in d + 1..n {
+                        let low = evaluate([&cards[a], &cards[b], &cards[c], &cards[d], &cards[e]]);
+                        if best.is_none_or(|best| low > best) {
+                            best = Some(low);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_items::poker::card_set::CardSet;
+
+    fn cards(text: &str) -> Vec<Card> {
+        text.parse::<CardSet>().unwrap().to_cards()
+    }
+
+    #[test]
+    fn ace_to_five_wheel_is_the_best_low() {
+        let wheel = evaluate_ace_to_five(&cards("As2d3c4h5s")).unwrap();
+        assert_eq!(wheel.get_category(), HandCategory::HighCard);
+        assert_eq!(wheel.get_ranks(), [5, 4, 3, 2, 1]);
+        // 顺子和同花不影响 A-5 低牌
+        assert_eq!(evaluate_ace_to_five(&cards("As2s3s4s5s")), Some(wheel));
+        assert!(wheel > evaluate_ace_to_five(&cards("6s4d3c2hAs")).unwrap());
+        assert!(evaluate_ace_to_five(&cards("KsQdJc9h8s")).unwrap() > evaluate_ace_to_five(&cards("2s2d3c4h5s")).unwrap());
+    }
+
+    #[test]
+    fn eight_or_better_qualifier() {
+        assert!(evaluate_eight_or_better(&cards("8s6d4c2hAs")).is_some());
+        assert_eq!(evaluate_eight_or_better(&cards("9s6d4c2hAs")), None);
+        assert_eq!(evaluate_eight_or_better(&cards("8s8d4c2hAs")), None);
+        // 7 张牌中选出合格的五张
+        assert_eq!(evaluate_eight_or_better(&cards("KsKd7c5h3s2dAc")).unwrap().get_ranks(), [7, 5, 3, 2, 1]);
+    }
+
+    #[test]
+    fn deuce_to_seven_counts_straights_and_flushes_and_aces_high() {
+        let best = evaluate_deuce_to_seven(&cards("7s5d4c3h2s")).unwrap();
+        assert_eq!(best.get_category(), HandCategory::HighCard);
+        // 6-5-4-3-2 是顺子，比 7-5-4-3-2 差
+        let straight = evaluate_deuce_to_seven(&cards("6s5d4c3h2s")).unwrap();
+        assert_eq!(straight.get_category(), HandCategory::Straight);
+        assert!(best > straight);
+        let flush = evaluate_deuce_to_seven(&cards("7s5s4s3s2s")).unwrap();
+        assert_eq!(flush.get_category(), HandCategory::Flush);
+        assert!(best > flush);
+        // A 只能当最大，A-2-3-4-5 是 A 高的杂牌
+        let ace_high = evaluate_deuce_to_seven(&cards("As5d4c3h2s")).unwrap();
+        assert_eq!(ace_high.get_category(), HandCategory::HighCard);
+        assert_eq!(ace_high.get_ranks(), [14, 5, 4, 3, 2]);
+        assert!(evaluate_deuce_to_seven(&cards("Ks5d4c3h2s")).unwrap() > ace_high);
+    }
+
+    #[test]
+    fn omaha_low_uses_exactly_two_hole_cards() {
+        let board = cards("3c4d5h9sKs");
+        assert_eq!(evaluate_omaha_eight_or_better(&cards("As2dKhKd"), &board).unwrap().get_ranks(), [5, 4, 3, 2, 1]);
+        // 只有一张小牌的底牌做不出低牌
+        assert_eq!(evaluate_omaha_eight_or_better(&cards("AsQdKhKd"), &board), None);
+    }
+}
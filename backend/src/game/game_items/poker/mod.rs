@@ -1 +1,10 @@
-pub mod poker;
\ No newline at end of file
+pub mod poker;
+pub mod hand_evaluator;
+pub mod card_set;
+pub mod lookup_evaluator;
+pub mod equity;
+pub mod hand_range;
+pub mod lowball_evaluator;
+pub mod three_card_evaluator;
+pub mod niu_niu_evaluator;
+pub mod shoe;
\ No newline at end of file
@@ -0,0 +1,168 @@
+use std::fmt;
+use crate::game::game_items::poker::poker::{Card, Rank, Suit};
+
+/// 斗牛一手牌的张数
+pub const NIU_NIU_HAND_SIZE: usize = 5;
+
+/// 斗牛牌型，从小到大排列
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NiuCategory {
+    NoBull,        // 无牛：任意三张的点数和都不是 10 的倍数
+    Bull(u8),      // 牛一到牛九：三张凑成 10 的倍数后，剩余两张点数和的个位
+    BullBull,      // 牛牛：剩余两张点数和也是 10 的倍数
+    FiveFaceBull,  // 五花牛：五张都是 J、Q、K
+    Bomb,          // 炸弹：四张点数相同
+    FiveSmallBull, // 五小牛：五张都小于 5 点且点数和不超过 10
+}
+
+impl NiuCategory {
+    /// 按牌型结算的赔率倍数：无牛到牛六 1 倍，牛七牛八 2 倍，牛九 3 倍，牛牛 4 倍，五花牛 5 倍，炸弹 6 倍，五小牛 8 倍
+    pub fn multiplier(&self) -> u32 {
+        match self {
+            NiuCategory::NoBull => 1,
+            NiuCategory::Bull(bull) if *bull <= 6 => 1,
+            NiuCategory::Bull(bull) if *bull <= 8 => 2,
+            NiuCategory::Bull(_) => 3,
+            NiuCategory::BullBull => 4,
+            NiuCategory::FiveFaceBull => 5,
+            NiuCategory::Bomb => 6,
+            NiuCategory::FiveSmallBull => 8,
+        }
+    }
+}
+
+impl fmt::Display for NiuCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const BULL_NAMES: [&str; 9] = ["牛一", "牛二", "牛三", "牛四", "牛五", "牛六", "牛七", "牛八", "牛九"];
+        match self {
+            NiuCategory::NoBull => write!(f, "无牛"),
+            NiuCategory::Bull(bull) => write!(f, "{}", BULL_NAMES[(*bull as usize).clamp(1, 9) - 1]),
+            NiuCategory::BullBull => write!(f, "牛牛"),
+            NiuCategory::FiveFaceBull => write!(f, "五花牛"),
+            NiuCategory::Bomb => write!(f, "炸弹"),
+            NiuCategory::FiveSmallBull => write!(f, "五小牛"),
+        }
+    }
+}
+
+/// 斗牛牌力：先比牌型，炸弹再比四张的点数，其余比最大的单张，单张点数相同时比花色
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NiuHandValue {
+    category: NiuCategory,
+    high_rank: u8, // K 最大记为 13，A 最小记为 1；炸弹为四张的点数
+    high_suit: u8, // 黑桃 > 红桃 > 梅花 > 方块
+}
+
+impl NiuHandValue {
+    pub fn get_category(&self) -> NiuCategory {
+        self.category
+    }
+
+    /// 结算倍数
+    pub fn multiplier(&self) -> u32 {
+        self.category.multiplier()
+    }
+}
+
+impl fmt::Display for NiuHandValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.category)
+    }
+}
+
+/// 计算五张牌的斗牛牌力，牌数不是 5 时返回 None
+pub fn evaluate_niu_niu(cards: &[Card]) -> Option<NiuHandValue> {
+    if cards.len() != NIU_NIU_HAND_SIZE {
+        return None;
+    }
+    let points: Vec<u32> = cards.iter().map(|card| card_points(card.rank) as u32).collect();
+    let total: u32 = points.iter().sum();
+    let high_card = cards.iter().copied().max_by_key(|card| (rank_order(card.rank), suit_order(card.suit)))?;
+    let high_rank = rank_order(high_card.rank);
+    let high_suit = suit_order(high_card.suit);
+
+    let bomb_rank = cards.iter()
+        .map(|card| card.rank)
+        .find(|rank| cards.iter().filter(|card| card.rank == *rank).count() == 4);
+    let has_bull = (0..NIU_NIU_HAND_SIZE).any(|i| {
+        (i + 1..NIU_NIU_HAND_SIZE).any(|j| (j + 1..NIU_NIU_HAND_SIZE).any(|k| (points[i] + points[j] + points[k]).is_multiple_of(10)))
+    });
+
+    let (category, high_rank, high_suit) = if points.iter().all(|point| *point < 5) && total <= 10 {
+        (NiuCategory::FiveSmallBull, high_rank, high_suit)
+    } else if let Some(bomb_rank) = bomb_rank {
+        (NiuCategory::Bomb, rank_order(bomb_rank), 0)
+    } else if cards.iter().all(|card| matches!(card.rank, Rank::Jack | Rank::Queen | Rank::King)) {
+        (NiuCategory::FiveFaceBull, high_rank, high_suit)
+    } else if !has_bull {
+        (NiuCategory::NoBull, high_rank, high_suit)
+    } else if total.is_multiple_of(10) {
+        // 凑成 10 的倍数的三张拿掉后，剩余两张的点数和与五张总和同余
+        (NiuCategory::BullBull, high_rank, high_suit)
+    } else {
+        (NiuCategory::Bull((total % 10) as u8), high_rank, high_suit)
+    };
+    Some(NiuHandValue{category, high_rank, high_suit})
+}
+
+// 计点：A 为 1，2~10 为牌面，J、Q、K 为 10
+fn card_points(rank: Rank) -> u8 {
+    rank_order(rank).min(10)
+}
+
+// 比单张时的点数大小：K 最大，A 最小
+fn rank_order(rank: Rank) -> u8 {
+    if rank == Rank::Ace { 1 } else { rank.value() }
+}
+
+fn suit_order(suit: Suit) -> u8 {
+    match suit {
+        Suit::Spades => 3,
+        Suit::Hearts => 2,
+        Suit::Clubs => 1,
+        Suit::Diamonds => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> NiuHandValue {
+        let cards: Vec<Card> = text.split_whitespace().map(|card| card.parse().unwrap()).collect();
+        evaluate_niu_niu(&cards).unwrap()
+    }
+
+    #[test]
+    fn special_hands_are_recognised() {
+        let five_small = value("As Ad 2c 2h 4s");
+        assert_eq!(five_small.get_category(), NiuCategory::FiveSmallBull);
+        assert_eq!(five_small.multiplier(), 8);
+        // 点数和超过 10 不算五小牛
+        assert_eq!(value("As 2d 3c 4h 4s").get_category(), NiuCategory::Bull(4));
+
+        let bomb = value("7s 7d 7c 7h Ks");
+        assert_eq!(bomb.get_category(), NiuCategory::Bomb);
+        assert_eq!(bomb.multiplier(), 6);
+
+        let five_face = value("Js Qd Kc Kh Qs");
+        assert_eq!(five_face.get_category(), NiuCategory::FiveFaceBull);
+        assert_eq!(five_face.multiplier(), 5);
+        // 带 10 的五张是牛牛而不是五花牛
+        assert_eq!(value("Ts Qd Kc Kh Qs").get_category(), NiuCategory::BullBull);
+
+        assert!(five_small > bomb && bomb > five_face && five_face > value("Ts Qd Kc Kh Qs"));
+        assert!(value("8s 8d 8c 8h 2s") > value("7s 7d 7c 7h Ks"));
+    }
+
+    #[test]
+    fn bulls_and_no_bull() {
+        assert_eq!(value("3s 7d Tc 4h 5s").get_category(), NiuCategory::Bull(9));
+        assert_eq!(value("3s 7d Tc 4h 5s").multiplier(), 3);
+        assert_eq!(value("As 3d 5c 8h Js").get_category(), NiuCategory::NoBull);
+        assert!(value("As 3d 5c 8h Js") < value("As 9d Tc 2h 3s"));
+        // 牌型相同比最大单张，点数相同比花色
+        assert!(value("Ks 2d 8c 4h 6s") > value("Kh 2c 8d 4s 6h"));
+        assert_eq!(evaluate_niu_niu(&[]), None);
+    }
+}
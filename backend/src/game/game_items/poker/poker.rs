@@ -1,10 +1,13 @@
 // 引入标准库的 Vec
+use std::any::Any;
 use std::vec::Vec;
+use std::fmt;
 use std::fmt::Debug;
 use crate::game::game_item::GameItem;
+use crate::game::provably_fair::commit_reveal::{FairRng, SeedReveal};
 
 // 扑克花色
-#[derive(Debug, Clone, Copy)] // 添加 derive 宏以方便复制和调试
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)] // 添加 derive 宏以方便复制和调试
 pub enum Suit {
     Spades,
     Hearts,
@@ -15,10 +18,24 @@ pub enum Suit {
 // 为 Suit 提供一个包含所有花色的常量数组
 impl Suit {
     pub const ALL_SUITS: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Clubs, Suit::Diamonds];
+
+    /// 花色的单字母记法：s/h/c/d
+    pub fn to_char(self) -> char {
+        match self {
+            Suit::Spades => 's',
+            Suit::Hearts => 'h',
+            Suit::Clubs => 'c',
+            Suit::Diamonds => 'd',
+        }
+    }
+
+    pub fn from_char(symbol: char) -> Option<Suit> {
+        Suit::ALL_SUITS.into_iter().find(|suit| suit.to_char() == symbol.to_ascii_lowercase())
+    }
 }
 
 // 扑克点数
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Rank {
     Ace,   // A
     Two,   // 2
@@ -41,10 +58,50 @@ impl Rank {
         Rank::Ace, Rank::Two, Rank::Three, Rank::Four, Rank::Five, Rank::Six,
         Rank::Seven, Rank::Eight, Rank::Nine, Rank::Ten, Rank::Jack, Rank::Queen, Rank::King
     ];
+
+    /// 短牌(6+)使用的点数，去掉了 2~5
+    pub const SHORT_DECK_RANKS: [Rank; 9] = [
+        Rank::Ace, Rank::Six, Rank::Seven, Rank::Eight, Rank::Nine, Rank::Ten, Rank::Jack, Rank::Queen, Rank::King
+    ];
+
+    /// 点数大小，A 按最大计为 14
+    pub fn value(&self) -> u8 {
+        match self {
+            Rank::Ace => 14,
+            Rank::Two => 2,
+            Rank::Three => 3,
+            Rank::Four => 4,
+            Rank::Five => 5,
+            Rank::Six => 6,
+            Rank::Seven => 7,
+            Rank::Eight => 8,
+            Rank::Nine => 9,
+            Rank::Ten => 10,
+            Rank::Jack => 11,
+            Rank::Queen => 12,
+            Rank::King => 13,
+        }
+    }
+
+    /// 点数的单字符记法：2~9、T、J、Q、K、A
+    pub fn to_char(self) -> char {
+        match self {
+            Rank::Ace => 'A',
+            Rank::Ten => 'T',
+            Rank::Jack => 'J',
+            Rank::Queen => 'Q',
+            Rank::King => 'K',
+            _ => char::from(b'0' + self.value()),
+        }
+    }
+
+    pub fn from_char(symbol: char) -> Option<Rank> {
+        Rank::ALL_RANKS.into_iter().find(|rank| rank.to_char() == symbol.to_ascii_uppercase())
+    }
 }
 
 // 扑克卡对象
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Card{
     pub suit: Suit,
     pub rank: Rank,
@@ -56,6 +113,19 @@ impl Card{
     pub fn new(suit: Suit, rank: Rank) -> Self {
         Card{suit, rank}
     }
+
+    /// 从 GameItem 还原出扑克牌，非扑克牌返回 None
+    pub fn from_item(item: &dyn GameItem) -> Option<Card> {
+        let any: &dyn Any = item;
+        any.downcast_ref::<Card>().copied()
+    }
+}
+
+// 两字符记法，例如 As、Td
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.rank.to_char(), self.suit.to_char())
+    }
 }
 
 // 获取完整的52张牌组
@@ -72,4 +142,30 @@ pub fn get_all_cards() -> Vec<Card> {
     }
 
     deck
+}
+
+// 获取去掉 2~5 的36张短牌牌组
+pub fn get_short_deck_cards() -> Vec<Card> {
+    get_all_cards()
+        .into_iter()
+        .filter(|card| Rank::SHORT_DECK_RANKS.contains(&card.rank))
+        .collect()
+}
+
+// 用可验证的随机数流洗一副完整的52张牌
+pub fn get_shuffled_cards(rng: &mut FairRng) -> Vec<Card> {
+    let mut deck = get_all_cards();
+    rng.shuffle(&mut deck);
+    deck
+}
+
+// 按公开的种子重算牌序，校验与实际使用的牌序是否一致
+pub fn verify_deck(reveal: &SeedReveal, deck: &[Card]) -> bool {
+    verify_shuffle(reveal, get_all_cards(), deck)
+}
+
+// 同 verify_deck，但洗牌前的初始牌组由调用方给出，例如短牌
+pub fn verify_shuffle(reveal: &SeedReveal, mut initial_deck: Vec<Card>, deck: &[Card]) -> bool {
+    reveal.rng().shuffle(&mut initial_deck);
+    reveal.verify_commitment() && initial_deck == deck
 }
\ No newline at end of file
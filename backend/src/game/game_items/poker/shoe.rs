@@ -0,0 +1,120 @@
+use crate::game::game_items::poker::poker::{get_all_cards, verify_shuffle, Card};
+use crate::game::provably_fair::commit_reveal::{FairRng, SeedReveal};
+
+/// 牌靴在 game_context 中的 key，牌靴跨多局保留，直到切牌位置才重新洗牌
+pub const SHOE_KEY: &str = "shoe";
+
+/// 多副牌组成的牌靴
+///
+/// 一靴牌洗一次、发多局，服务端种子要等整靴用完重新洗牌时才能公开，
+/// 否则玩家可以据此推算出剩余的牌序
+#[derive(Debug, Clone, Default)]
+pub struct Shoe {
+    cards: Vec<Card>,
+    position: usize,           // 下一张要发的牌
+    decks: u8,
+    penetration_percent: u8,   // 发到百分之多少的牌时放切牌
+    previous_cards: Vec<Card>, // 上一靴的牌序，公开种子后用于校验
+}
+
+impl Shoe {
+    /// 未洗牌的空牌靴，首次使用前需要 shuffle
+    pub fn new(decks: u8, penetration_percent: u8) -> Self {
+        Shoe{cards: Vec::new(), position: 0, decks, penetration_percent: penetration_percent.min(100), previous_cards: Vec::new()}
+    }
+
+    /// 修改副数和切牌位置，下次洗牌时生效
+    pub fn configure(&mut self, decks: u8, penetration_percent: u8) {
+        self.decks = decks;
+        self.penetration_percent = penetration_percent.min(100);
+    }
+
+    /// 重新装入 decks 副牌并洗牌
+    pub fn shuffle(&mut self, rng: &mut FairRng) {
+        self.previous_cards = std::mem::replace(&mut self.cards, get_shoe_cards(self.decks));
+        rng.shuffle(&mut self.cards);
+        self.position = 0;
+    }
+
+    /// 发一张牌，牌发完时返回 None
+    pub fn draw(&mut self) -> Option<Card> {
+        let card = self.cards.get(self.position).copied()?;
+        self.position += 1;
+        Some(card)
+    }
+
+    /// 烧掉若干张牌，返回被烧掉的牌
+    pub fn burn(&mut self, count: usize) -> Vec<Card> {
+        (0..count).filter_map(|_| self.draw()).collect()
+    }
+
+    /// 已发到切牌位置(或从未洗过牌)，下一局开始前需要重新洗牌
+    pub fn needs_shuffle(&self) -> bool {
+        self.cards.is_empty() || self.position >= self.cut_card()
+    }
+
+    /// 切牌位置，从牌靴顶部数起
+    pub fn cut_card(&self) -> usize {
+        self.cards.len() * self.penetration_percent as usize / 100
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.cards.len() - self.position
+    }
+
+    pub fn get_decks(&self) -> u8 {
+        self.decks
+    }
+
+    pub fn get_penetration_percent(&self) -> u8 {
+        self.penetration_percent
+    }
+
+    /// 当前这一靴的牌序
+    pub fn get_cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    /// 上一靴的牌序，它的种子在本靴洗牌时公开
+    pub fn get_previous_cards(&self) -> &[Card] {
+        &self.previous_cards
+    }
+}
+
+/// 未洗牌的 decks 副牌
+pub fn get_shoe_cards(decks: u8) -> Vec<Card> {
+    (0..decks).flat_map(|_| get_all_cards()).collect()
+}
+
+/// 按公开的种子重算整靴牌序，校验与实际使用的是否一致
+pub fn verify_shoe(reveal: &SeedReveal, decks: u8, cards: &[Card]) -> bool {
+    verify_shuffle(reveal, get_shoe_cards(decks), cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shoe_needs_shuffle_at_the_cut_card() {
+        let mut shoe = Shoe::new(2, 50);
+        assert!(shoe.needs_shuffle());
+        assert_eq!(shoe.draw(), None);
+
+        shoe.shuffle(&mut FairRng::new("server", &[], 0));
+        assert_eq!((shoe.get_cards().len(), shoe.cut_card()), (104, 52));
+        assert!(!shoe.needs_shuffle());
+        assert_eq!(shoe.burn(51).len(), 51);
+        assert!(!shoe.needs_shuffle());
+        shoe.draw();
+        assert!(shoe.needs_shuffle());
+        assert_eq!(shoe.remaining(), 52);
+
+        // 重新洗牌后保留上一靴的牌序用于校验
+        let first_shoe = shoe.get_cards().to_vec();
+        shoe.configure(1, 150);
+        shoe.shuffle(&mut FairRng::new("server", &[], 1));
+        assert_eq!(shoe.get_previous_cards(), first_shoe.as_slice());
+        assert_eq!((shoe.get_cards().len(), shoe.get_penetration_percent(), shoe.remaining()), (52, 100, 52));
+    }
+}
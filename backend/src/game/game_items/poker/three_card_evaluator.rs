@@ -0,0 +1,180 @@
+use std::cmp::Ordering;
+use std::fmt;
+use crate::game::game_items::poker::poker::Card;
+
+/// 三张牌(炸金花)的牌型，从小到大排列
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThreeCardCategory {
+    HighCard,      // 单张
+    Pair,          // 对子
+    Straight,      // 顺子
+    Flush,         // 金花
+    StraightFlush, // 顺金
+    Leopard,       // 豹子
+}
+
+impl fmt::Display for ThreeCardCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ThreeCardCategory::HighCard => "单张",
+            ThreeCardCategory::Pair => "对子",
+            ThreeCardCategory::Straight => "顺子",
+            ThreeCardCategory::Flush => "金花",
+            ThreeCardCategory::StraightFlush => "顺金",
+            ThreeCardCategory::Leopard => "豹子",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 三张牌的牌力，先比牌型再依次比 ranks，不比花色
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ThreeCardHandValue {
+    category: ThreeCardCategory,
+    ranks: [u8; 3], // 按比较优先级排列的点数，A 记为 14，A-2-3 顺子记为 3-2-1
+}
+
+impl ThreeCardHandValue {
+    pub fn get_category(&self) -> ThreeCardCategory {
+        self.category
+    }
+
+    pub fn get_ranks(&self) -> [u8; 3] {
+        self.ranks
+    }
+
+    /// 不同花色的 2、3、5
+    pub fn is_235(&self) -> bool {
+        self.category == ThreeCardCategory::HighCard && self.ranks == [5, 3, 2]
+    }
+
+    /// 带 235 特殊规则的比较：杂色 235 只大于豹子，同时也是最小的单张
+    pub fn compare_with_235(&self, other: &Self) -> Ordering {
+        match (self.is_235(), other.is_235()) {
+            (true, false) if other.category == ThreeCardCategory::Leopard => Ordering::Greater,
+            (false, true) if self.category == ThreeCardCategory::Leopard => Ordering::Less,
+            _ => self.cmp(other),
+        }
+    }
+}
+
+// 例如 "对子 Q-Q-9"
+impl fmt::Display for ThreeCardHandValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ranks: Vec<String> = self.ranks.iter()
+            .map(|rank| match rank {
+                1 | 14 => "A".to_string(),
+                11 => "J".to_string(),
+                12 => "Q".to_string(),
+                13 => "K".to_string(),
+                _ => rank.to_string(),
+            })
+            .collect();
+        write!(f, "{} {}", self.category, ranks.join("-"))
+    }
+}
+
+/// 计算三张牌的牌力，牌数不是 3 时返回 None
+///
+/// A-2-3 是最小的顺子，Q-K-A 是最大的顺子
+pub fn evaluate_three_card(cards: &[Card]) -> Option<ThreeCardHandValue> {
+    if cards.len() != 3 {
+        return None;
+    }
+    let mut ranks: Vec<u8> = cards.iter().map(|card| card.rank.value()).collect();
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+    let is_flush = cards.iter().all(|card| card.suit == cards[0].suit);
+    let straight_ranks = if ranks == [14, 3, 2] {
+        Some([3, 2, 1])
+    } else if ranks[0] == ranks[1] + 1 && ranks[1] == ranks[2] + 1 {
+        Some([ranks[0], ranks[1], ranks[2]])
+    } else {
+        None
+    };
+
+    let (category, ranks) = if ranks[0] == ranks[2] {
+        (ThreeCardCategory::Leopard, [ranks[0], ranks[1], ranks[2]])
+    } else if let Some(straight_ranks) = straight_ranks {
+        let category = if is_flush { ThreeCardCategory::StraightFlush } else { ThreeCardCategory::Straight };
+        (category, straight_ranks)
+    } else if is_flush {
+        (ThreeCardCategory::Flush, [ranks[0], ranks[1], ranks[2]])
+    } else if ranks[0] == ranks[1] {
+        (ThreeCardCategory::Pair, [ranks[0], ranks[1], ranks[2]])
+    } else if ranks[1] == ranks[2] {
+        // 对子放在前面比较
+        (ThreeCardCategory::Pair, [ranks[1], ranks[2], ranks[0]])
+    } else {
+        (ThreeCardCategory::HighCard, [ranks[0], ranks[1], ranks[2]])
+    };
+    Some(ThreeCardHandValue{category, ranks})
+}
+
+/// 比牌：left 是否严格大于 right，special_235 为 true 时启用 235 吃豹子
+pub fn beats_three_card(left: &[Card], right: &[Card], special_235: bool) -> bool {
+    match (evaluate_three_card(left), evaluate_three_card(right)) {
+        (Some(left), Some(right)) if special_235 => left.compare_with_235(&right) == Ordering::Greater,
+        (left, right) => left > right,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_items::poker::card_set::CardSet;
+
+    fn cards(text: &str) -> Vec<Card> {
+        text.parse::<CardSet>().unwrap().to_cards()
+    }
+
+    fn value(text: &str) -> ThreeCardHandValue {
+        evaluate_three_card(&cards(text)).unwrap()
+    }
+
+    #[test]
+    fn categories_rank_from_high_card_to_leopard() {
+        let hands = ["As Qd 9c", "9s 9d Ac", "4s 5d 6c", "2h 7h 9h", "4h 5h 6h", "2s 2d 2c"];
+        let categories = [
+            ThreeCardCategory::HighCard,
+            ThreeCardCategory::Pair,
+            ThreeCardCategory::Straight,
+            ThreeCardCategory::Flush,
+            ThreeCardCategory::StraightFlush,
+            ThreeCardCategory::Leopard,
+        ];
+        for (hand, category) in hands.iter().zip(categories) {
+            assert_eq!(value(hand).get_category(), category);
+        }
+        assert!(hands.windows(2).all(|pair| value(pair[0]) < value(pair[1])));
+        assert_eq!(value("9s 9d Ac").get_ranks(), [9, 9, 14]);
+        assert!(value("9s 9d Ac") < value("Ts Td 2c"));
+        assert_eq!(evaluate_three_card(&cards("As Kd")), None);
+    }
+
+    #[test]
+    fn ace_two_three_is_the_lowest_straight_and_queen_king_ace_the_highest() {
+        let lowest = value("As 2d 3c");
+        assert_eq!(lowest.get_category(), ThreeCardCategory::Straight);
+        assert_eq!(lowest.get_ranks(), [3, 2, 1]);
+        assert!(lowest < value("2s 3d 4c"));
+        let highest = value("Qs Kd Ac");
+        assert_eq!(highest.get_category(), ThreeCardCategory::Straight);
+        assert!(highest > value("Js Qd Kc"));
+        assert_eq!(value("Ks Ad 2c").get_category(), ThreeCardCategory::HighCard);
+    }
+
+    #[test]
+    fn offsuit_235_beats_only_a_leopard() {
+        let special = cards("2s 3d 5c");
+        let leopard = cards("As Ad Ac");
+        assert!(value("2s 3d 5c").is_235());
+        assert!(!value("2s 3s 5s").is_235());
+        assert!(beats_three_card(&special, &leopard, true));
+        assert!(!beats_three_card(&leopard, &special, true));
+        assert!(!beats_three_card(&special, &leopard, false));
+        // 对其他牌型 235 仍是最小的单张
+        assert!(beats_three_card(&cards("2h 4d 5s"), &special, true));
+        // 牌力相同时双方都不严格大于对方
+        assert!(!beats_three_card(&cards("Ks Qd 9c"), &cards("Kh Qc 9d"), true));
+    }
+}
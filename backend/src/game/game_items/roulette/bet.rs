@@ -0,0 +1,217 @@
+use crate::game::game_items::roulette::wheel::{Pocket, PocketColor, Wheel, WheelLayout};
+
+/// 轮盘的下注方式
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum RouletteBet {
+    Straight(Pocket),       // 单号
+    Split(Pocket, Pocket),  // 相邻两个号码
+    Street(u8),             // 一行三个号码，行号 1~12
+    Corner(u8),             // 四个号码的方块，参数为左上角号码
+    TopLine,                // 欧式 0、1、2、3，美式 0、00、1、2、3
+    SixLine(u8),            // 相邻两行六个号码，参数为起始行 1~11
+    Dozen(u8),              // 1~12、13~24、25~36，参数 1~3
+    Column(u8),             // 三列之一，参数 1~3
+    Red,
+    Black,
+    Odd,
+    Even,
+    Low,                    // 1~18
+    High,                   // 19~36
+    Neighbours(Pocket, u8), // 跑道邻号注：轮盘上中心号码及左右各 n 个，注额平分为单号注
+}
+
+/// 赔率表中的下注类别
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum RouletteBetKind {
+    Straight,
+    Split,
+    Street,
+    Corner,
+    TopLine,
+    SixLine,
+    Dozen,
+    Column,
+    EvenMoney,
+}
+
+/// 赔率表：(类别, 覆盖的格子数, 赔率)
+pub const PAYOUT_TABLE: [(RouletteBetKind, usize, u32); 10] = [
+    (RouletteBetKind::Straight, 1, 35),
+    (RouletteBetKind::Split, 2, 17),
+    (RouletteBetKind::Street, 3, 11),
+    (RouletteBetKind::Corner, 4, 8),
+    (RouletteBetKind::TopLine, 4, 8),
+    (RouletteBetKind::TopLine, 5, 6),
+    (RouletteBetKind::SixLine, 6, 5),
+    (RouletteBetKind::Dozen, 12, 2),
+    (RouletteBetKind::Column, 12, 2),
+    (RouletteBetKind::EvenMoney, 18, 1),
+];
+
+impl RouletteBet {
+    /// 赔率表中的类别，邻号注按单号注赔付
+    pub fn kind(&self) -> RouletteBetKind {
+        match self {
+            RouletteBet::Straight(_) | RouletteBet::Neighbours(_, _) => RouletteBetKind::Straight,
+            RouletteBet::Split(_, _) => RouletteBetKind::Split,
+            RouletteBet::Street(_) => RouletteBetKind::Street,
+            RouletteBet::Corner(_) => RouletteBetKind::Corner,
+            RouletteBet::TopLine => RouletteBetKind::TopLine,
+            RouletteBet::SixLine(_) => RouletteBetKind::SixLine,
+            RouletteBet::Dozen(_) => RouletteBetKind::Dozen,
+            RouletteBet::Column(_) => RouletteBetKind::Column,
+            RouletteBet::Red | RouletteBet::Black | RouletteBet::Odd
+            | RouletteBet::Even | RouletteBet::Low | RouletteBet::High => RouletteBetKind::EvenMoney,
+        }
+    }
+
+    /// 1:1 的外围注，法式规则下开出 0 时适用分享规则或入狱规则
+    pub fn is_even_money(&self) -> bool {
+        self.kind() == RouletteBetKind::EvenMoney
+    }
+
+    /// 这一注覆盖的格子，在该轮盘上不成立的下注返回 None
+    pub fn covered_pockets(&self, wheel: &Wheel) -> Option<Vec<Pocket>> {
+        let numbers = |filter: &dyn Fn(u8) -> bool| -> Vec<Pocket> {
+            (1..=36).filter(|number| filter(*number)).map(Pocket::Number).collect()
+        };
+        let pockets = match *self {
+            RouletteBet::Straight(pocket) => wheel.contains(pocket).then(|| vec![pocket])?,
+            RouletteBet::Split(first, second) => {
+                if !wheel.contains(first) || !wheel.contains(second) || !is_adjacent(wheel.get_layout(), first, second) {
+                    return None;
+                }
+                vec![first.min(second), first.max(second)]
+            }
+            RouletteBet::Street(row) if (1..=12).contains(&row) => numbers(&|number| number.div_ceil(3) == row),
+            RouletteBet::Corner(top_left) if (1..=32).contains(&top_left) && top_left % 3 != 0 => {
+                [top_left, top_left + 1, top_left + 3, top_left + 4].into_iter().map(Pocket::Number).collect()
+            }
+            RouletteBet::TopLine => match wheel.get_layout() {
+                WheelLayout::European => vec![Pocket::Zero, Pocket::Number(1), Pocket::Number(2), Pocket::Number(3)],
+                WheelLayout::American => vec![Pocket::Zero, Pocket::DoubleZero, Pocket::Number(1), Pocket::Number(2), Pocket::Number(3)],
+            },
+            RouletteBet::SixLine(row) if (1..=11).contains(&row) => numbers(&|number| number.div_ceil(3) == row || number.div_ceil(3) == row + 1),
+            RouletteBet::Dozen(dozen) if (1..=3).contains(&dozen) => numbers(&|number| number.div_ceil(12) == dozen),
+            RouletteBet::Column(column) if (1..=3).contains(&column) => numbers(&|number| (number - 1) % 3 + 1 == column),
+            RouletteBet::Red => numbers(&|number| Pocket::Number(number).color() == PocketColor::Red),
+            RouletteBet::Black => numbers(&|number| Pocket::Number(number).color() == PocketColor::Black),
+            RouletteBet::Odd => numbers(&|number| number % 2 == 1),
+            RouletteBet::Even => numbers(&|number| number % 2 == 0),
+            RouletteBet::Low => numbers(&|number| number <= 18),
+            RouletteBet::High => numbers(&|number| number >= 19),
+            RouletteBet::Neighbours(center, radius) if radius > 0 => wheel.neighbours(center, radius)?,
+            _ => return None,
+        };
+        Some(pockets)
+    }
+
+    /// 按赔率表查这一注的赔率，找不到对应行时返回 None
+    pub fn payout(&self, wheel: &Wheel) -> Option<u32> {
+        let covered = match self {
+            RouletteBet::Neighbours(_, _) => 1,
+            bet => bet.covered_pockets(wheel)?.len(),
+        };
+        PAYOUT_TABLE.iter()
+            .find(|(kind, count, _)| *kind == self.kind() && *count == covered)
+            .map(|(_, _, payout)| *payout)
+    }
+
+    /// 检查注额能否下在这一注上，邻号注的注额必须能平分到每个号码
+    pub fn accepts_stake(&self, wheel: &Wheel, stake: u32) -> bool {
+        match self.covered_pockets(wheel) {
+            Some(covered) if matches!(self, RouletteBet::Neighbours(_, _)) => stake.is_multiple_of(covered.len() as u32),
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// 球落在 pocket 时这一注退还的筹码(含本金)，输了为 0；不含开出 0 时的法式规则
+    pub fn settle(&self, wheel: &Wheel, stake: u32, pocket: Pocket) -> u32 {
+        let (Some(covered), Some(payout)) = (self.covered_pockets(wheel), self.payout(wheel)) else {
+            return 0;
+        };
+        if !covered.contains(&pocket) {
+            return 0;
+        }
+        // 邻号注只有落中的那一份赢，其余份输
+        let unit = match self {
+            RouletteBet::Neighbours(_, _) => stake / covered.len() as u32,
+            _ => stake,
+        };
+        unit * (payout + 1)
+    }
+}
+
+/// 校验赔率表：每一行的赔率都不能超过 36 / 覆盖格子数 - 1，保证庄家优势只来自 0 和 00
+pub fn validate_payout_table() -> bool {
+    PAYOUT_TABLE.iter().all(|(_, count, payout)| *count > 0 && *payout == 36 / *count as u32 - 1)
+}
+
+// 两个格子在下注桌面上是否相邻，可以下分注
+fn is_adjacent(layout: WheelLayout, first: Pocket, second: Pocket) -> bool {
+    let (low, high) = (first.min(second), first.max(second));
+    match (low, high, layout) {
+        (Pocket::Number(low), Pocket::Number(high), _) => high - low == 3 || (high - low == 1 && low % 3 != 0),
+        (Pocket::Zero, Pocket::Number(number), WheelLayout::European) => (1..=3).contains(&number),
+        (Pocket::Zero, Pocket::Number(number), WheelLayout::American) => (1..=2).contains(&number),
+        (Pocket::Zero, Pocket::DoubleZero, WheelLayout::American) => true,
+        (Pocket::DoubleZero, Pocket::Number(number), WheelLayout::American) => (2..=3).contains(&number),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payout_table_pays_true_odds_less_the_zero() {
+        assert!(validate_payout_table());
+        let european = Wheel::new(WheelLayout::European);
+        let american = Wheel::new(WheelLayout::American);
+        let payouts = [
+            (RouletteBet::Straight(Pocket::Number(17)), 35),
+            (RouletteBet::Split(Pocket::Number(17), Pocket::Number(20)), 17),
+            (RouletteBet::Street(6), 11),
+            (RouletteBet::Corner(17), 8),
+            (RouletteBet::SixLine(6), 5),
+            (RouletteBet::Dozen(2), 2),
+            (RouletteBet::Column(2), 2),
+            (RouletteBet::Odd, 1),
+            (RouletteBet::Black, 1),
+            (RouletteBet::Low, 1),
+        ];
+        for (bet, payout) in payouts {
+            assert_eq!(bet.payout(&european), Some(payout), "{:?}", bet);
+            assert_eq!(bet.settle(&european, 10, Pocket::Number(17)), 10 * (payout + 1), "{:?}", bet);
+            assert_eq!(bet.settle(&european, 10, Pocket::Zero), 0, "{:?}", bet);
+        }
+        // 欧式 TopLine 四个号码 8:1，美式五个号码 6:1
+        assert_eq!(RouletteBet::TopLine.payout(&european), Some(8));
+        assert_eq!(RouletteBet::TopLine.payout(&american), Some(6));
+        assert_eq!(RouletteBet::TopLine.settle(&american, 10, Pocket::DoubleZero), 70);
+    }
+
+    #[test]
+    fn invalid_bets_are_rejected() {
+        let european = Wheel::new(WheelLayout::European);
+        let american = Wheel::new(WheelLayout::American);
+        assert!(!RouletteBet::Straight(Pocket::DoubleZero).accepts_stake(&european, 10));
+        assert!(RouletteBet::Straight(Pocket::DoubleZero).accepts_stake(&american, 10));
+        assert!(!RouletteBet::Split(Pocket::Number(3), Pocket::Number(4)).accepts_stake(&european, 10));
+        assert!(!RouletteBet::Corner(3).accepts_stake(&european, 10));
+        assert!(!RouletteBet::Dozen(4).accepts_stake(&european, 10));
+    }
+
+    #[test]
+    fn neighbours_split_the_stake_into_straight_bets() {
+        let european = Wheel::new(WheelLayout::European);
+        let bet = RouletteBet::Neighbours(Pocket::Zero, 1);
+        assert_eq!(bet.covered_pockets(&european), Some(vec![Pocket::Number(26), Pocket::Zero, Pocket::Number(32)]));
+        assert!(!bet.accepts_stake(&european, 10));
+        assert!(bet.accepts_stake(&european, 30));
+        assert_eq!(bet.settle(&european, 30, Pocket::Number(32)), 360);
+        assert_eq!(bet.settle(&european, 30, Pocket::Number(15)), 0);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod wheel;
+pub mod bet;
\ No newline at end of file
@@ -0,0 +1,212 @@
+use std::any::Any;
+use std::fmt;
+use crate::game::game_item::GameItem;
+use crate::game::provably_fair::commit_reveal::FairRng;
+
+/// 欧式轮盘上顺时针的号码顺序
+pub const EUROPEAN_WHEEL_ORDER: [u8; 37] = [
+    0, 32, 15, 19, 4, 21, 2, 25, 17, 34, 6, 27, 13, 36, 11, 30, 8, 23, 10,
+    5, 24, 16, 33, 1, 20, 14, 31, 9, 22, 18, 29, 7, 28, 12, 35, 3, 26,
+];
+
+/// 美式轮盘上顺时针的号码顺序，37 代表 00
+pub const AMERICAN_WHEEL_ORDER: [u8; 38] = [
+    0, 28, 9, 26, 30, 11, 7, 20, 32, 17, 5, 22, 34, 15, 3, 24, 36, 13, 1,
+    37, 27, 10, 25, 29, 12, 8, 19, 31, 18, 6, 21, 33, 16, 4, 23, 35, 14, 2,
+];
+
+/// 红色号码
+pub const RED_NUMBERS: [u8; 18] = [1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36];
+
+/// 轮盘上的一个格子
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pocket {
+    Zero,
+    DoubleZero, // 美式轮盘的 00
+    Number(u8), // 1~36
+}
+
+/// 格子的颜色
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum PocketColor {
+    Green,
+    Red,
+    Black,
+}
+
+impl Pocket {
+    /// 1~36 的号码，0 和 00 返回 None
+    pub fn number(&self) -> Option<u8> {
+        match self {
+            Pocket::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.number().is_none()
+    }
+
+    pub fn color(&self) -> PocketColor {
+        match self.number() {
+            None => PocketColor::Green,
+            Some(number) if RED_NUMBERS.contains(&number) => PocketColor::Red,
+            Some(_) => PocketColor::Black,
+        }
+    }
+
+    // 轮盘顺序表中的编号，00 记为 37
+    fn from_code(code: u8) -> Pocket {
+        match code {
+            0 => Pocket::Zero,
+            37 => Pocket::DoubleZero,
+            number => Pocket::Number(number),
+        }
+    }
+}
+
+impl fmt::Display for Pocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pocket::Zero => write!(f, "0"),
+            Pocket::DoubleZero => write!(f, "00"),
+            Pocket::Number(number) => write!(f, "{}", number),
+        }
+    }
+}
+
+/// 轮盘布局
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum WheelLayout {
+    #[default]
+    European, // 单零，法式轮盘与欧式布局相同
+    American, // 0 和 00
+}
+
+/// 轮盘，作为 GameItem 放在对局的物品中，不需要扑克牌也能驱动对局
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wheel {
+    layout: WheelLayout,
+}
+
+impl GameItem for Wheel {}
+
+impl Wheel {
+    pub fn new(layout: WheelLayout) -> Self {
+        Wheel{layout}
+    }
+
+    pub fn get_layout(&self) -> WheelLayout {
+        self.layout
+    }
+
+    /// 按轮盘上顺时针的顺序列出所有格子
+    pub fn pockets(&self) -> Vec<Pocket> {
+        let order: &[u8] = match self.layout {
+            WheelLayout::European => &EUROPEAN_WHEEL_ORDER,
+            WheelLayout::American => &AMERICAN_WHEEL_ORDER,
+        };
+        order.iter().map(|code| Pocket::from_code(*code)).collect()
+    }
+
+    /// 格子是否在这个轮盘上
+    pub fn contains(&self, pocket: Pocket) -> bool {
+        match pocket {
+            Pocket::Zero => true,
+            Pocket::DoubleZero => self.layout == WheelLayout::American,
+            Pocket::Number(number) => (1..=36).contains(&number),
+        }
+    }
+
+    /// 轮盘上以 center 为中心、左右各 radius 个的相邻格子，用于跑道上的邻号注
+    pub fn neighbours(&self, center: Pocket, radius: u8) -> Option<Vec<Pocket>> {
+        let pockets = self.pockets();
+        let position = pockets.iter().position(|pocket| *pocket == center)?;
+        let radius = radius as usize;
+        if radius * 2 + 1 > pockets.len() {
+            return None;
+        }
+        Some((0..=radius * 2)
+            .map(|offset| pockets[(position + pockets.len() * 2 + offset - radius) % pockets.len()])
+            .collect())
+    }
+
+    /// 转动轮盘，球落入的格子由可验证的随机数决定
+    pub fn spin(&self, rng: &mut FairRng) -> Ball {
+        let pockets = self.pockets();
+        let index = rng.next_below(pockets.len() as u32) as usize;
+        Ball{pocket: Some(pockets[index])}
+    }
+
+    /// 从 GameItem 还原出轮盘
+    pub fn from_item(item: &dyn GameItem) -> Option<Wheel> {
+        let any: &dyn Any = item;
+        any.downcast_ref::<Wheel>().copied()
+    }
+}
+
+/// 轮盘上的球，pocket 为球停下的格子，转动前为 None
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ball {
+    pub pocket: Option<Pocket>,
+}
+
+impl GameItem for Ball {}
+
+impl Ball {
+    /// 从 GameItem 还原出球
+    pub fn from_item(item: &dyn GameItem) -> Option<Ball> {
+        let any: &dyn Any = item;
+        any.downcast_ref::<Ball>().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts_hold_every_pocket_once() {
+        for (layout, size) in [(WheelLayout::European, 37), (WheelLayout::American, 38)] {
+            let wheel = Wheel::new(layout);
+            let mut pockets = wheel.pockets();
+            pockets.sort();
+            pockets.dedup();
+            assert_eq!(pockets.len(), size);
+            assert!(pockets.iter().all(|pocket| wheel.contains(*pocket)));
+        }
+        assert!(!Wheel::new(WheelLayout::European).contains(Pocket::DoubleZero));
+        assert!(!Wheel::new(WheelLayout::American).contains(Pocket::Number(37)));
+        assert_eq!(Pocket::DoubleZero.to_string(), "00");
+    }
+
+    #[test]
+    fn pocket_colors() {
+        assert_eq!(Pocket::Zero.color(), PocketColor::Green);
+        assert_eq!(Pocket::DoubleZero.color(), PocketColor::Green);
+        assert_eq!(Pocket::Number(1).color(), PocketColor::Red);
+        assert_eq!(Pocket::Number(2).color(), PocketColor::Black);
+        let reds = (1..=36).filter(|number| Pocket::Number(*number).color() == PocketColor::Red).count();
+        assert_eq!(reds, 18);
+    }
+
+    #[test]
+    fn neighbours_wrap_around_the_wheel() {
+        let wheel = Wheel::new(WheelLayout::European);
+        let numbers = |pockets: Vec<Pocket>| -> Vec<String> { pockets.iter().map(|pocket| pocket.to_string()).collect() };
+        assert_eq!(numbers(wheel.neighbours(Pocket::Zero, 2).unwrap()), ["3", "26", "0", "32", "15"]);
+        assert_eq!(numbers(wheel.neighbours(Pocket::Number(26), 1).unwrap()), ["3", "26", "0"]);
+        assert_eq!(wheel.neighbours(Pocket::DoubleZero, 1), None);
+        assert_eq!(wheel.neighbours(Pocket::Zero, 19), None);
+        assert_eq!(wheel.neighbours(Pocket::Zero, 18).unwrap().len(), 37);
+    }
+
+    #[test]
+    fn spin_is_reproducible_from_the_seed() {
+        let wheel = Wheel::new(WheelLayout::American);
+        let spin = |nonce: u64| wheel.spin(&mut FairRng::new("server", &["client".to_string()], nonce)).pocket;
+        assert_eq!(spin(7), spin(7));
+        assert!((0..200).all(|nonce| spin(nonce).is_some_and(|pocket| wheel.contains(pocket))));
+        assert_eq!(Ball::default().pocket, None);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod slot_machine;
+pub mod rtp;
\ No newline at end of file
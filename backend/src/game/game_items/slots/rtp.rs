@@ -0,0 +1,167 @@
+use crate::game::provably_fair::commit_reveal::FairRng;
+use super::slot_machine::SlotMachine;
+
+// 模拟时单次基础转动连续触发的免费转动上限，防止配置错误导致死循环
+const MAX_FREE_SPINS_PER_TRIGGER: u32 = 100_000;
+
+/// RTP 与波动性统计，返还率均以一次转动的总花费为 1
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RtpReport {
+    pub spins: u64,                          // 统计的基础转动次数(穷举时为全部停止组合数)
+    pub rtp: f64,                            // 含免费转动的总返还率
+    pub base_rtp: f64,                       // 仅基础转动的返还率
+    pub hit_frequency: f64,                  // 基础转动中有赔付的比例
+    pub free_spin_trigger_frequency: f64,    // 基础转动触发免费转动的比例
+    pub volatility: f64,                     // 每次基础转动返还的标准差
+    pub max_win: f64,                        // 单次基础转动(含其触发的免费转动)的最大返还
+}
+
+/// 穷举所有停止组合计算精确的 RTP
+///
+/// 免费转动与基础转动使用相同的轴带，因此期望的免费转动次数为 F / (1 - F)，F 为单次转动期望获得的免费次数；
+/// F >= 1 时免费转动无法结束，返回 None。
+/// 波动性与最大返还按基础转动计算，不含免费转动的累积
+pub fn exhaustive_rtp(machine: &SlotMachine) -> Option<RtpReport> {
+    let reels = machine.get_reels();
+    let cost = machine.get_cost() as f64;
+    let mut stops = vec![0usize; reels.len()];
+    let mut spins = 0u64;
+    let mut pay_sum = 0f64;
+    let mut pay_square_sum = 0f64;
+    let mut hits = 0u64;
+    let mut triggers = 0u64;
+    let mut free_spin_sum = 0f64;
+    let mut max_pay = 0u32;
+
+    loop {
+        let result = machine.evaluate(&stops);
+        let pay = result.total_pay();
+        spins += 1;
+        pay_sum += pay as f64;
+        pay_square_sum += (pay as f64) * (pay as f64);
+        hits += (pay > 0) as u64;
+        triggers += (result.free_spins > 0) as u64;
+        free_spin_sum += result.free_spins as f64;
+        max_pay = max_pay.max(pay);
+
+        // 逐轴进位枚举下一个组合
+        let mut reel = 0;
+        while reel < reels.len() {
+            stops[reel] += 1;
+            if stops[reel] < reels[reel].len() {
+                break;
+            }
+            stops[reel] = 0;
+            reel += 1;
+        }
+        if reel == reels.len() {
+            break;
+        }
+    }
+
+    let total = spins as f64;
+    let free_spins_per_spin = free_spin_sum / total;
+    if free_spins_per_spin >= 1.0 {
+        return None;
+    }
+    let base_rtp = pay_sum / total / cost;
+    let expected_free_spins = free_spins_per_spin / (1.0 - free_spins_per_spin);
+    let mean_square = pay_square_sum / total / (cost * cost);
+    Some(RtpReport {
+        spins,
+        rtp: base_rtp * (1.0 + machine.get_free_spin_multiplier() as f64 * expected_free_spins),
+        base_rtp,
+        hit_frequency: hits as f64 / total,
+        free_spin_trigger_frequency: triggers as f64 / total,
+        volatility: (mean_square - base_rtp * base_rtp).max(0.0).sqrt(),
+        max_win: max_pay as f64 / cost,
+    })
+}
+
+/// 用给定的随机数模拟若干次基础转动，每次基础转动连同其触发的免费转动一起计入返还
+///
+/// 适用于轴带过长无法穷举的配置，也可与 exhaustive_rtp 对照
+pub fn simulate_rtp(machine: &SlotMachine, rng: &mut FairRng, spins: u64) -> RtpReport {
+    let cost = machine.get_cost() as f64;
+    let multiplier = machine.get_free_spin_multiplier() as u64;
+    let mut pay_sum = 0f64;
+    let mut base_pay_sum = 0f64;
+    let mut pay_square_sum = 0f64;
+    let mut hits = 0u64;
+    let mut triggers = 0u64;
+    let mut max_pay = 0u64;
+
+    for _ in 0..spins {
+        let result = machine.spin(rng);
+        let base_pay = result.total_pay() as u64;
+        let mut pay = base_pay;
+        let mut remaining = result.free_spins;
+        let mut played = 0;
+        while remaining > 0 && played < MAX_FREE_SPINS_PER_TRIGGER {
+            let free = machine.spin(rng);
+            pay += free.total_pay() as u64 * multiplier;
+            remaining = remaining - 1 + free.free_spins;
+            played += 1;
+        }
+
+        base_pay_sum += base_pay as f64;
+        pay_sum += pay as f64;
+        pay_square_sum += (pay as f64) * (pay as f64);
+        hits += (base_pay > 0) as u64;
+        triggers += (result.free_spins > 0) as u64;
+        max_pay = max_pay.max(pay);
+    }
+
+    let total = spins.max(1) as f64;
+    let rtp = pay_sum / total / cost;
+    let mean_square = pay_square_sum / total / (cost * cost);
+    RtpReport {
+        spins,
+        rtp,
+        base_rtp: base_pay_sum / total / cost,
+        hit_frequency: hits as f64 / total,
+        free_spin_trigger_frequency: triggers as f64 / total,
+        volatility: (mean_square - rtp * rtp).max(0.0).sqrt(),
+        max_win: max_pay as f64 / cost,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine(text: &str) -> SlotMachine {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn exhaustive_rtp_matches_hand_computed_values() {
+        // 三个 A 的概率为 1/8，赔 8 倍
+        let report = exhaustive_rtp(&machine("rows = 1\nreel = A B\nreel = A B\nreel = A B\nline = 0 0 0\npay = A 3:8")).unwrap();
+        assert_eq!(report.spins, 8);
+        assert!((report.rtp - 1.0).abs() < 1e-9);
+        assert!((report.hit_frequency - 0.125).abs() < 1e-9);
+        assert!((report.volatility - 7f64.sqrt()).abs() < 1e-9);
+        assert_eq!(report.max_win, 8.0);
+    }
+
+    #[test]
+    fn free_spins_extend_the_base_rtp() {
+        // 每次转动期望获得 1/8 次免费转动，累计期望 1/7 次，免费转动赔付翻倍
+        let slots = machine("rows = 1\nscatter = S\nreel = A S\nreel = A S\nreel = A S\nline = 0 0 0\npay = A 3:8\nfree_spins = 3:1\nfree_spin_multiplier = 2");
+        let report = exhaustive_rtp(&slots).unwrap();
+        assert!((report.base_rtp - 1.0).abs() < 1e-9);
+        assert!((report.rtp - 9.0 / 7.0).abs() < 1e-9);
+        assert!((report.free_spin_trigger_frequency - 0.125).abs() < 1e-9);
+
+        let simulated = simulate_rtp(&slots, &mut FairRng::new("server", &[], 0), 200_000);
+        assert!((simulated.rtp - report.rtp).abs() < 0.05, "{}", simulated.rtp);
+        assert!((simulated.hit_frequency - report.hit_frequency).abs() < 0.01);
+    }
+
+    #[test]
+    fn endless_free_spins_have_no_rtp() {
+        let slots = machine("rows = 1\nscatter = S\nreel = S\nreel = S\nreel = S\nline = 0 0 0\nfree_spins = 3:1");
+        assert_eq!(exhaustive_rtp(&slots), None);
+    }
+}
@@ -0,0 +1,429 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::game::provably_fair::commit_reveal::FairRng;
+
+/// 内置的经典五轴十线老虎机配置
+pub const CLASSIC_SLOT_CONFIG: &str = include_str!("classic.slot");
+
+/// 中奖方式
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum WinMode {
+    #[default]
+    Lines, // 固定赔付线，从最左轴起连续相同
+    Ways,  // 全线，相邻轴上任意位置出现即可，组合数相乘
+}
+
+/// 解析老虎机配置失败的原因，附带出错的行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotParseError {
+    Io(String),             // 读取配置文件失败
+    UnknownKey(String),     // 无法识别的配置项
+    InvalidValue(String),   // 值的格式不正确
+    UnknownSymbol(String),  // 使用了未出现在任何轴上的符号
+    Inconsistent(String),   // 配置项之间互相矛盾，例如赔付线长度与轴数不符
+}
+
+impl fmt::Display for SlotParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SlotParseError::Io(reason) => write!(f, "cannot read slot config: {}", reason),
+            SlotParseError::UnknownKey(line) => write!(f, "unknown key in '{}'", line),
+            SlotParseError::InvalidValue(line) => write!(f, "invalid value in '{}'", line),
+            SlotParseError::UnknownSymbol(symbol) => write!(f, "unknown symbol '{}'", symbol),
+            SlotParseError::Inconsistent(reason) => write!(f, "inconsistent slot config: {}", reason),
+        }
+    }
+}
+
+/// 一条中奖记录，赔付以投注单位计
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineWin {
+    pub line: Option<usize>, // 赔付线下标，全线模式为 None
+    pub symbol: usize,
+    pub count: usize,        // 从最左轴起连续的轴数
+    pub ways: u32,           // 全线模式下的组合数，赔付线模式为 1
+    pub pay: u32,
+}
+
+/// 一次转动的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpinResult {
+    pub stops: Vec<usize>,       // 每个轴的停止位置
+    pub window: Vec<Vec<usize>>, // [轴][行] 上可见的符号
+    pub line_wins: Vec<LineWin>,
+    pub scatter_count: usize,
+    pub scatter_pay: u32,
+    pub free_spins: u32,         // 本次触发的免费转动次数
+}
+
+impl SpinResult {
+    /// 本次转动的总赔付(投注单位)，不含免费转动的倍数
+    pub fn total_pay(&self) -> u32 {
+        self.line_wins.iter().map(|win| win.pay).sum::<u32>() + self.scatter_pay
+    }
+}
+
+/// 老虎机：轴带、符号、百搭、分散、赔付线或全线、免费转动，均由配置文件描述
+///
+/// 配置为逐行的 "key = value"，"#" 之后为注释：
+/// - rows = 3：可见的行数
+/// - mode = lines | ways
+/// - cost = 10：每次转动花费的投注单位，赔付线模式下默认为赔付线条数
+/// - wild = W、scatter = S
+/// - reel = A K Q ...：一条轴带，按出现顺序为第 1、2… 轴
+/// - line = 1 1 1 1 1：一条赔付线，依次为每个轴上的行号(从 0 开始)
+/// - pay = A 3:5 4:20 5:100：符号连续 3、4、5 个时的赔付(投注单位)
+/// - scatter_pay = 3:2 4:10：分散符号个数对应的赔付，以一次转动的总花费为单位
+/// - free_spins = 3:10：分散符号个数对应的免费转动次数
+/// - free_spin_multiplier = 2：免费转动中赔付的倍数
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SlotMachine {
+    symbols: Vec<String>,
+    rows: usize,
+    mode: WinMode,
+    cost: u32,
+    wild: Option<usize>,
+    scatter: Option<usize>,
+    reels: Vec<Vec<usize>>,
+    lines: Vec<Vec<usize>>,
+    pays: Vec<Vec<u32>>, // [符号][连续个数]
+    scatter_pays: Vec<u32>,
+    free_spin_awards: Vec<u32>,
+    free_spin_multiplier: u32,
+}
+
+impl SlotMachine {
+    /// 内置的经典配置
+    pub fn classic() -> SlotMachine {
+        CLASSIC_SLOT_CONFIG.parse().expect("built-in slot config is valid")
+    }
+
+    /// 从配置文件加载
+    pub fn load(path: &str) -> Result<SlotMachine, SlotParseError> {
+        std::fs::read_to_string(path)
+            .map_err(|error| SlotParseError::Io(error.to_string()))?
+            .parse()
+    }
+
+    pub fn get_symbols(&self) -> &[String] {
+        &self.symbols
+    }
+
+    pub fn symbol_name(&self, symbol: usize) -> &str {
+        self.symbols.get(symbol).map(|name| name.as_str()).unwrap_or("?")
+    }
+
+    pub fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn get_mode(&self) -> WinMode {
+        self.mode
+    }
+
+    pub fn get_cost(&self) -> u32 {
+        self.cost
+    }
+
+    pub fn get_reels(&self) -> &[Vec<usize>] {
+        &self.reels
+    }
+
+    pub fn get_lines(&self) -> &[Vec<usize>] {
+        &self.lines
+    }
+
+    pub fn get_free_spin_multiplier(&self) -> u32 {
+        self.free_spin_multiplier
+    }
+
+    /// 用可验证的随机数决定每个轴的停止位置并计算结果
+    pub fn spin(&self, rng: &mut FairRng) -> SpinResult {
+        let stops: Vec<usize> = self.reels.iter().map(|reel| rng.next_below(reel.len() as u32) as usize).collect();
+        self.evaluate(&stops)
+    }
+
+    /// 按给定的停止位置计算结果，用于重算和穷举 RTP
+    pub fn evaluate(&self, stops: &[usize]) -> SpinResult {
+        let window = self.window(stops);
+        let line_wins = match self.mode {
+            WinMode::Lines => self.line_wins(&window),
+            WinMode::Ways => self.ways_wins(&window),
+        };
+        let scatter_count = self.scatter.map_or(0, |scatter| window.iter().flatten().filter(|symbol| **symbol == scatter).count());
+        let award = |table: &[u32]| table.get(scatter_count).copied().unwrap_or(0);
+        SpinResult {
+            stops: stops.to_vec(),
+            line_wins,
+            scatter_count,
+            scatter_pay: award(&self.scatter_pays) * self.cost,
+            free_spins: award(&self.free_spin_awards),
+            window,
+        }
+    }
+
+    // 每个轴从停止位置起向下 rows 个符号
+    fn window(&self, stops: &[usize]) -> Vec<Vec<usize>> {
+        self.reels.iter().zip(stops)
+            .map(|(reel, stop)| (0..self.rows).map(|row| reel[(stop + row) % reel.len()]).collect())
+            .collect()
+    }
+
+    fn pay(&self, symbol: usize, count: usize) -> u32 {
+        self.pays.get(symbol).and_then(|pays| pays.get(count)).copied().unwrap_or(0)
+    }
+
+    // 赔付线：取线上第一个非百搭符号，从最左轴起数连续的该符号或百搭；百搭开头时也比较纯百搭的赔付
+    fn line_wins(&self, window: &[Vec<usize>]) -> Vec<LineWin> {
+        let mut wins = Vec::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            let symbols: Vec<usize> = line.iter().enumerate().map(|(reel, row)| window[reel][*row]).collect();
+            let is_wild = |symbol: usize| Some(symbol) == self.wild;
+            let run = |target: usize| symbols.iter().take_while(|symbol| **symbol == target || is_wild(**symbol)).count();
+
+            let wild_run = symbols.iter().take_while(|symbol| is_wild(**symbol)).count();
+            let mut best: Option<(usize, usize, u32)> = self.wild
+                .map(|wild| (wild, wild_run, self.pay(wild, wild_run)))
+                .filter(|(_, _, pay)| *pay > 0);
+            if let Some(target) = symbols.iter().copied().find(|symbol| !is_wild(*symbol)) {
+                if Some(target) != self.scatter {
+                    let count = run(target);
+                    let pay = self.pay(target, count);
+                    if pay > best.map_or(0, |(_, _, best_pay)| best_pay) {
+                        best = Some((target, count, pay));
+                    }
+                }
+            }
+            if let Some((symbol, count, pay)) = best {
+                wins.push(LineWin{line: Some(index), symbol, count, ways: 1, pay});
+            }
+        }
+        wins
+    }
+
+    // 全线：每个符号从最左轴起，相邻轴上出现该符号或百搭即可延续，组合数为各轴出现次数之积
+    fn ways_wins(&self, window: &[Vec<usize>]) -> Vec<LineWin> {
+        let mut wins = Vec::new();
+        for symbol in 0..self.symbols.len() {
+            if Some(symbol) == self.wild || Some(symbol) == self.scatter {
+                continue;
+            }
+            let mut ways = 1u32;
+            let mut count = 0;
+            for column in window {
+                let hits = column.iter().filter(|cell| **cell == symbol || Some(**cell) == self.wild).count() as u32;
+                if hits == 0 {
+                    break;
+                }
+                ways *= hits;
+                count += 1;
+            }
+            let pay = self.pay(symbol, count);
+            if pay > 0 {
+                wins.push(LineWin{line: None, symbol, count, ways, pay: pay * ways});
+            }
+        }
+        wins
+    }
+}
+
+impl FromStr for SlotMachine {
+    type Err = SlotParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut machine = SlotMachine{rows: 3, free_spin_multiplier: 1, ..SlotMachine::default()};
+        let mut cost = None;
+        let mut wild = None;
+        let mut scatter = None;
+        let mut lines: Vec<Vec<usize>> = Vec::new();
+        let mut pays: Vec<(String, Vec<(usize, u32)>)> = Vec::new();
+
+        for raw_line in text.lines() {
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || SlotParseError::InvalidValue(line.to_string());
+            let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+            let value = value.trim();
+            let tokens: Vec<&str> = value.split_whitespace().collect();
+            match key.trim() {
+                "rows" => machine.rows = value.parse().map_err(|_| invalid())?,
+                "mode" => machine.mode = match value {
+                    "lines" => WinMode::Lines,
+                    "ways" => WinMode::Ways,
+                    _ => return Err(invalid()),
+                },
+                "cost" => cost = Some(value.parse().map_err(|_| invalid())?),
+                "wild" => wild = Some(value.to_string()),
+                "scatter" => scatter = Some(value.to_string()),
+                "reel" => {
+                    if tokens.is_empty() {
+                        return Err(invalid());
+                    }
+                    let reel = tokens.iter().map(|token| machine.intern(token)).collect();
+                    machine.reels.push(reel);
+                }
+                "line" => lines.push(tokens.iter().map(|token| token.parse().map_err(|_| invalid())).collect::<Result<_, _>>()?),
+                "pay" => {
+                    let (symbol, table) = tokens.split_first().ok_or_else(invalid)?;
+                    pays.push((symbol.to_string(), parse_table(table).ok_or_else(invalid)?));
+                }
+                "scatter_pay" => machine.scatter_pays = to_lookup(&parse_table(&tokens).ok_or_else(invalid)?),
+                "free_spins" => machine.free_spin_awards = to_lookup(&parse_table(&tokens).ok_or_else(invalid)?),
+                "free_spin_multiplier" => machine.free_spin_multiplier = value.parse().map_err(|_| invalid())?,
+                _ => return Err(SlotParseError::UnknownKey(line.to_string())),
+            }
+        }
+
+        let lookup = |name: &str, machine: &SlotMachine| {
+            machine.symbols.iter().position(|symbol| symbol == name).ok_or_else(|| SlotParseError::UnknownSymbol(name.to_string()))
+        };
+        machine.wild = wild.map(|name| lookup(&name, &machine)).transpose()?;
+        machine.scatter = scatter.map(|name| lookup(&name, &machine)).transpose()?;
+        machine.pays = vec![Vec::new(); machine.symbols.len()];
+        for (name, table) in pays {
+            let symbol = lookup(&name, &machine)?;
+            machine.pays[symbol] = to_lookup(&table);
+        }
+
+        if machine.reels.is_empty() || machine.rows == 0 || machine.free_spin_multiplier == 0 {
+            return Err(SlotParseError::Inconsistent("at least one reel and one row are required".to_string()));
+        }
+        if lines.iter().any(|line| line.len() != machine.reels.len() || line.iter().any(|row| *row >= machine.rows)) {
+            return Err(SlotParseError::Inconsistent("every line needs one row below rows per reel".to_string()));
+        }
+        if machine.mode == WinMode::Lines && lines.is_empty() {
+            return Err(SlotParseError::Inconsistent("lines mode needs at least one line".to_string()));
+        }
+        machine.cost = match (cost, machine.mode) {
+            (Some(cost), _) => cost,
+            (None, WinMode::Lines) => lines.len() as u32,
+            (None, WinMode::Ways) => return Err(SlotParseError::Inconsistent("ways mode needs a cost".to_string())),
+        };
+        if machine.cost == 0 {
+            return Err(SlotParseError::Inconsistent("cost must be positive".to_string()));
+        }
+        machine.lines = lines;
+        Ok(machine)
+    }
+}
+
+impl SlotMachine {
+    // 符号名转为下标，第一次出现时登记
+    fn intern(&mut self, name: &str) -> usize {
+        match self.symbols.iter().position(|symbol| symbol == name) {
+            Some(index) => index,
+            None => {
+                self.symbols.push(name.to_string());
+                self.symbols.len() - 1
+            }
+        }
+    }
+}
+
+// 解析 "3:5 4:20" 形式的 (个数, 值) 表
+fn parse_table(tokens: &[&str]) -> Option<Vec<(usize, u32)>> {
+    tokens.iter()
+        .map(|token| {
+            let (count, value) = token.split_once(':')?;
+            Some((count.parse().ok()?, value.parse().ok()?))
+        })
+        .collect()
+}
+
+// 转成按个数下标直接查找的表
+fn to_lookup(table: &[(usize, u32)]) -> Vec<u32> {
+    let size = table.iter().map(|(count, _)| count + 1).max().unwrap_or(0);
+    let mut lookup = vec![0; size];
+    for (count, value) in table {
+        lookup[*count] = *value;
+    }
+    lookup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL_LINES: &str = "
+        rows = 1
+        mode = lines
+        wild = W
+        scatter = S
+        reel = A W B S
+        reel = A W B S
+        reel = A W B S
+        line = 0 0 0
+        pay = A 3:10
+        pay = W 3:50
+        pay = B 2:1 3:5
+        scatter_pay = 2:1 3:5
+        free_spins = 3:5
+    ";
+
+    fn pays(machine: &SlotMachine, stops: &[usize]) -> Vec<(String, usize, u32)> {
+        machine.evaluate(stops).line_wins.iter()
+            .map(|win| (machine.symbol_name(win.symbol).to_string(), win.count, win.pay))
+            .collect()
+    }
+
+    #[test]
+    fn classic_config_parses() {
+        let machine = SlotMachine::classic();
+        assert_eq!((machine.get_reels().len(), machine.get_lines().len(), machine.get_rows()), (5, 10, 3));
+        assert_eq!((machine.get_mode(), machine.get_cost(), machine.get_free_spin_multiplier()), (WinMode::Lines, 10, 2));
+        assert!(machine.get_symbols().iter().any(|symbol| symbol == "W"));
+    }
+
+    #[test]
+    fn malformed_configs_are_rejected() {
+        assert!(matches!("bogus = 1".parse::<SlotMachine>(), Err(SlotParseError::UnknownKey(_))));
+        assert!(matches!("mode = diagonal".parse::<SlotMachine>(), Err(SlotParseError::InvalidValue(_))));
+        assert!(matches!("reel = A B\nline = 0\npay = Z 3:1".parse::<SlotMachine>(), Err(SlotParseError::UnknownSymbol(_))));
+        assert!(matches!("reel = A B\nreel = A B\nline = 0".parse::<SlotMachine>(), Err(SlotParseError::Inconsistent(_))));
+        assert!(matches!("mode = ways\nreel = A B".parse::<SlotMachine>(), Err(SlotParseError::Inconsistent(_))));
+        assert!(matches!("reel = A B".parse::<SlotMachine>(), Err(SlotParseError::Inconsistent(_))));
+    }
+
+    #[test]
+    fn wilds_substitute_and_pay_on_their_own() {
+        let machine: SlotMachine = SMALL_LINES.parse().unwrap();
+        assert_eq!(pays(&machine, &[0, 1, 0]), vec![("A".to_string(), 3, 10)]);
+        assert_eq!(pays(&machine, &[1, 0, 0]), vec![("A".to_string(), 3, 10)]);
+        assert_eq!(pays(&machine, &[1, 1, 1]), vec![("W".to_string(), 3, 50)]);
+        // 百搭开头时取赔付更高的一种
+        assert_eq!(pays(&machine, &[1, 1, 2]), vec![("B".to_string(), 3, 5)]);
+        assert_eq!(pays(&machine, &[2, 2, 0]), vec![("B".to_string(), 2, 1)]);
+        assert_eq!(pays(&machine, &[0, 2, 0]), vec![]);
+    }
+
+    #[test]
+    fn scatters_pay_anywhere_and_award_free_spins() {
+        let machine: SlotMachine = SMALL_LINES.parse().unwrap();
+        let result = machine.evaluate(&[3, 3, 3]);
+        assert!(result.line_wins.is_empty());
+        assert_eq!((result.scatter_count, result.scatter_pay, result.free_spins), (3, 5, 5));
+        let result = machine.evaluate(&[3, 0, 3]);
+        assert_eq!((result.scatter_count, result.scatter_pay, result.free_spins, result.total_pay()), (2, 1, 0, 1));
+    }
+
+    #[test]
+    fn ways_multiply_hits_per_reel() {
+        let machine: SlotMachine = "
+            rows = 2
+            mode = ways
+            cost = 25
+            wild = W
+            reel = A B
+            reel = A W
+            reel = A B
+            reel = B B
+            pay = A 3:10
+            pay = B 3:4 4:8
+        ".parse().unwrap();
+        let wins = pays(&machine, &[0, 0, 0, 0]);
+        assert_eq!(wins, vec![("A".to_string(), 3, 20), ("B".to_string(), 4, 16)]);
+        assert!(machine.evaluate(&[0, 0, 0, 0]).line_wins.iter().all(|win| win.line.is_none()));
+    }
+}
@@ -0,0 +1,610 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::poker::poker::{Card, Rank};
+use crate::game::game_items::poker::shoe::{Shoe, SHOE_KEY};
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, collect, commit_next_seed, commit_seed_on_join, find_player, pay, sync_action_players};
+
+/// 牌局状态在 game_context 中的 key
+pub const BACCARAT_STATE_KEY: &str = "baccarat_state";
+
+/// 一桌最多的下注玩家数
+pub const BACCARAT_MAX_PLAYERS: usize = 14;
+
+/// 庄闲两方
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum BaccaratSide {
+    Player, // 闲
+    Banker, // 庄
+}
+
+/// 百家乐可下注的区域
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum BaccaratWager {
+    Player,                   // 闲赢 1:1
+    Banker,                   // 庄赢，抽水或免佣
+    Tie,                      // 和局
+    Pair(BaccaratSide),       // 该方前两张成对
+    Dragon(BaccaratSide),     // 龙宝：该方例牌赢或以点差赢
+}
+
+/// 庄赢的赔付方式
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum BaccaratCommission {
+    #[default]
+    Commission,   // 庄赢赔 0.95:1
+    NoCommission, // 免佣，庄以 6 点赢只赔 1:2，其余 1:1
+}
+
+/// 百家乐玩家行动，一次提交本局全部下注，空列表为本局不下注
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BaccaratAction {
+    Bet(Vec<(BaccaratWager, u32)>),
+}
+
+/// 对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum BaccaratPhase {
+    #[default]
+    Betting, // 等待下注
+    Settled, // 已发牌并结算
+}
+
+/// 一注的结算结果，net 为正表示玩家赢
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaccaratWagerResult {
+    pub user_id: u32,
+    pub wager: BaccaratWager,
+    pub stake: u32,
+    pub net: i32,
+}
+
+/// 百家乐牌局状态，保存在 game_context 中；牌靴单独保存在 SHOE_KEY 下
+#[derive(Debug, Default)]
+pub struct BaccaratState {
+    pub phase: BaccaratPhase,
+    pub dealer_user: Option<u32>,
+    pub bets: HashMap<u32, Vec<(BaccaratWager, u32)>>,
+    pub burned: Vec<Card>, // 新靴开局时烧掉的牌，第一张决定烧牌张数
+    pub player_cards: Vec<Card>,
+    pub banker_cards: Vec<Card>,
+    pub results: Vec<BaccaratWagerResult>,
+}
+
+impl BaccaratState {
+    /// 闲家点数
+    pub fn player_score(&self) -> u8 {
+        baccarat_score(&self.player_cards)
+    }
+
+    /// 庄家点数
+    pub fn banker_score(&self) -> u8 {
+        baccarat_score(&self.banker_cards)
+    }
+
+    /// 赢的一方，和局为 None
+    pub fn winner(&self) -> Option<BaccaratSide> {
+        match self.player_score().cmp(&self.banker_score()) {
+            std::cmp::Ordering::Greater => Some(BaccaratSide::Player),
+            std::cmp::Ordering::Less => Some(BaccaratSide::Banker),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// 还没下注的玩家
+    pub fn pending_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        if self.phase != BaccaratPhase::Betting {
+            return Vec::new();
+        }
+        players.iter()
+            .filter(|player| player.get_player_role() == PlayerRole::Player)
+            .map(|player| player.get_user().get_id())
+            .filter(|user_id| !self.bets.contains_key(user_id))
+            .collect()
+    }
+
+    fn cards(&self, side: BaccaratSide) -> &[Card] {
+        match side {
+            BaccaratSide::Player => &self.player_cards,
+            BaccaratSide::Banker => &self.banker_cards,
+        }
+    }
+
+    fn reset_hand(&mut self) {
+        self.phase = BaccaratPhase::Betting;
+        self.dealer_user = None;
+        self.bets.clear();
+        self.burned.clear();
+        self.player_cards.clear();
+        self.banker_cards.clear();
+        self.results.clear();
+    }
+}
+
+impl ActingPlayers for BaccaratState {
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players(players)
+    }
+}
+
+/// 百家乐规则配置，庄家由 PlayerRole::Dealer 的玩家担任，所有注额都与他的筹码结算
+#[derive(Debug, Clone, Copy)]
+pub struct BaccaratGameRules {
+    pub decks: u8,
+    pub penetration_percent: u8,
+    pub commission: BaccaratCommission,
+    pub tie_payout: u32,  // 和局赔率，常见 8:1
+    pub pair_payout: u32, // 对子赔率，常见 11:1
+    pub min_bet: u32,
+    pub max_bet: u32,     // 每个下注区域的上限
+}
+
+impl BaccaratGameRules {
+    pub fn new(min_bet: u32, max_bet: u32) -> Self {
+        BaccaratGameRules {
+            decks: 8,
+            penetration_percent: 80,
+            commission: BaccaratCommission::Commission,
+            tie_payout: 8,
+            pair_payout: 11,
+            min_bet,
+            max_bet,
+        }
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.decks == 0 || config.penetration_percent == 0 || config.min_bet == 0 || config.max_bet < config.min_bet {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|left, right, _| {
+                let cards = |items: &Vec<&dyn GameItem>| -> Vec<Card> {
+                    items.iter().filter_map(|item| Card::from_item(*item)).collect()
+                };
+                baccarat_score(&cards(left)) > baccarat_score(&cards(right))
+            }),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_start(config, players, game_items, context)),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_finish(config, players, game_items, context)),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+// 开局：找出庄家，牌靴到切牌位置时公开上一靴的种子，重新洗牌并烧牌
+fn game_start(
+    config: BaccaratGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<BaccaratState>(&context, BACCARAT_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_hand();
+
+    let dealer = players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer);
+    let player_count = players.iter().filter(|player| player.get_player_role() == PlayerRole::Player).count();
+    let Some(dealer) = dealer else {
+        state.phase = BaccaratPhase::Settled;
+        return;
+    };
+    if player_count == 0 || player_count > BACCARAT_MAX_PLAYERS {
+        state.phase = BaccaratPhase::Settled;
+        return;
+    }
+    state.dealer_user = Some(dealer.get_user().get_id());
+
+    let shoe = get_or_insert_state::<Shoe>(&context, SHOE_KEY);
+    let mut shoe = lock_or_recover(&shoe);
+    if shoe.needs_shuffle() || shoe.get_decks() != config.decks || shoe.get_penetration_percent() != config.penetration_percent {
+        let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+        let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+        let mut fairness = lock_or_recover(&fairness);
+        let mut rng = fairness.reshuffle_shoe(&seat_order);
+        shoe.configure(config.decks, config.penetration_percent);
+        shoe.shuffle(&mut rng);
+        // 翻开第一张牌，按它的点数(10 点牌算 10)再烧掉相应张数
+        if let Some(first) = shoe.draw() {
+            let count = match card_points(first.rank) {
+                0 => 10,
+                points => points as usize,
+            };
+            state.burned.push(first);
+            let burned = shoe.burn(count);
+            state.burned.extend(burned);
+        }
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家提交下注，最后一位下注后发牌
+fn player_action(
+    config: BaccaratGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<BaccaratState>(&context, BACCARAT_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(BaccaratAction::Bet(wagers)) = action.downcast_ref::<BaccaratAction>().cloned() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players(&players).contains(&user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+    for (index, (wager, stake)) in wagers.iter().enumerate() {
+        if *stake < config.min_bet || *stake > config.max_bet {
+            return ActionOutcome::Rejected(format!("bet must be between {} and {}", config.min_bet, config.max_bet));
+        }
+        if wagers[..index].iter().any(|(placed, _)| placed == wager) {
+            return ActionOutcome::Rejected("duplicate wager".to_string());
+        }
+    }
+    let total: u32 = wagers.iter().map(|(_, stake)| stake).sum();
+    if total > player.get_token() as u32 {
+        return ActionOutcome::Rejected("not enough tokens".to_string());
+    }
+    player.take_token(total as u16);
+    state.bets.insert(user_id, wagers);
+
+    let outcome = if state.pending_players(&players).is_empty() {
+        let shoe = get_or_insert_state::<Shoe>(&context, SHOE_KEY);
+        deal(&mut state, &mut lock_or_recover(&shoe));
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 闲庄各两张，按补牌规则决定第三张
+fn deal(state: &mut BaccaratState, shoe: &mut Shoe) {
+    for _ in 0..2 {
+        state.player_cards.extend(shoe.draw());
+        state.banker_cards.extend(shoe.draw());
+    }
+    let player_score = state.player_score();
+    let banker_score = state.banker_score();
+    // 任一方 8、9 点为例牌，双方都不补
+    if player_score >= 8 || banker_score >= 8 {
+        return;
+    }
+
+    let player_third = if player_score <= 5 {
+        let card = shoe.draw();
+        state.player_cards.extend(card);
+        card.map(|card| card_points(card.rank))
+    } else {
+        None
+    };
+    if banker_draws(banker_score, player_third) {
+        state.banker_cards.extend(shoe.draw());
+    }
+}
+
+/// 庄家补牌规则：闲家不补牌时庄家与闲家一样 0~5 补；闲家补牌时看闲家第三张牌的点数
+pub fn banker_draws(banker_score: u8, player_third: Option<u8>) -> bool {
+    match (banker_score, player_third) {
+        (score, None) => score <= 5,
+        (0..=2, Some(_)) => true,
+        (3, Some(third)) => third != 8,
+        (4, Some(third)) => (2..=7).contains(&third),
+        (5, Some(third)) => (4..=7).contains(&third),
+        (6, Some(third)) => (6..=7).contains(&third),
+        _ => false,
+    }
+}
+
+// 逐注结算：赢的注额加彩金从庄家筹码支付，输的注额归庄家，平局退回
+fn game_finish(
+    config: BaccaratGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<BaccaratState>(&context, BACCARAT_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == BaccaratPhase::Settled {
+        return;
+    }
+    state.phase = BaccaratPhase::Settled;
+
+    let dealer = state.dealer_user.and_then(|user_id| find_player(&players, user_id));
+
+    let mut results = Vec::new();
+    for player in players.iter() {
+        let user_id = player.get_user().get_id();
+        let Some(wagers) = state.bets.get(&user_id) else {
+            continue;
+        };
+        for (wager, stake) in wagers.iter().copied() {
+            let net = match wager_winnings(config, &state, wager, stake) {
+                Some(winnings) => {
+                    player.add_token(stake as u16);
+                    pay(&dealer, player, winnings) as i32
+                }
+                None => {
+                    collect(&dealer, stake);
+                    -(stake as i32)
+                }
+            };
+            results.push(BaccaratWagerResult{user_id, wager, stake, net});
+        }
+    }
+    state.results = results;
+    sync_action_players(&state, &players, &context);
+}
+
+// 一注赢得的彩金(不含本金)，Some(0) 为退回本金，None 为输
+fn wager_winnings(config: BaccaratGameRules, state: &BaccaratState, wager: BaccaratWager, stake: u32) -> Option<u32> {
+    let winner = state.winner();
+    match wager {
+        BaccaratWager::Player => match winner {
+            Some(BaccaratSide::Player) => Some(stake),
+            Some(BaccaratSide::Banker) => None,
+            None => Some(0),
+        },
+        BaccaratWager::Banker => match (winner, config.commission) {
+            (Some(BaccaratSide::Banker), BaccaratCommission::Commission) => Some(stake * 95 / 100),
+            (Some(BaccaratSide::Banker), BaccaratCommission::NoCommission) if state.banker_score() == 6 => Some(stake / 2),
+            (Some(BaccaratSide::Banker), BaccaratCommission::NoCommission) => Some(stake),
+            (Some(BaccaratSide::Player), _) => None,
+            (None, _) => Some(0),
+        },
+        BaccaratWager::Tie => winner.is_none().then_some(stake * config.tie_payout),
+        BaccaratWager::Pair(side) => {
+            let cards = state.cards(side);
+            (cards.len() >= 2 && cards[0].rank == cards[1].rank).then_some(stake * config.pair_payout)
+        }
+        BaccaratWager::Dragon(side) => {
+            let cards = state.cards(side);
+            let natural = cards.len() == 2 && baccarat_score(cards) >= 8;
+            if winner.is_none() {
+                // 例牌和局退回，其他和局输
+                return natural.then_some(0);
+            }
+            if winner != Some(side) {
+                return None;
+            }
+            if natural {
+                return Some(stake);
+            }
+            dragon_multiplier(state.player_score().abs_diff(state.banker_score())).map(|multiplier| stake * multiplier)
+        }
+    }
+}
+
+/// 龙宝非例牌赢时按点差的赔率，点差不足 4 点算输
+pub fn dragon_multiplier(margin: u8) -> Option<u32> {
+    match margin {
+        9 => Some(30),
+        8 => Some(10),
+        7 => Some(6),
+        6 => Some(4),
+        5 => Some(2),
+        4 => Some(1),
+        _ => None,
+    }
+}
+
+// 发牌前离桌的玩家退回注额，不再等待他下注；剩下的玩家都已下注时照常发牌结算
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<BaccaratState>(&context, BACCARAT_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == BaccaratPhase::Settled {
+        return ActionOutcome::Continue;
+    }
+    for player in leave_players {
+        if let Some(wagers) = state.bets.remove(&player.get_user().get_id()) {
+            let total: u32 = wagers.iter().map(|(_, stake)| stake).sum();
+            player.add_token(total as u16);
+        }
+    }
+
+    // 还没开局时只退注，不发牌
+    let outcome = if state.dealer_user.is_some() && state.pending_players(&current_players).is_empty() {
+        let shoe = get_or_insert_state::<Shoe>(&context, SHOE_KEY);
+        deal(&mut state, &mut lock_or_recover(&shoe));
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+/// 百家乐点数：各牌点数之和的个位数
+pub fn baccarat_score(cards: &[Card]) -> u8 {
+    cards.iter().map(|card| card_points(card.rank)).sum::<u8>() % 10
+}
+
+// A 计 1 点，10、J、Q、K 计 0 点
+fn card_points(rank: Rank) -> u8 {
+    match rank {
+        Rank::Ace => 1,
+        rank if rank.value() >= 10 => 0,
+        rank => rank.value(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::Game;
+    use crate::game::game_context::get_state;
+    use crate::game::game_items::poker::poker::Suit;
+    use crate::game::game_items::poker::shoe::verify_shoe;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+
+    fn cards(ranks: &[Rank]) -> Vec<Card> {
+        ranks.iter().map(|rank| Card::new(Suit::Hearts, *rank)).collect()
+    }
+
+    fn hand(player_cards: &[Rank], banker_cards: &[Rank]) -> BaccaratState {
+        BaccaratState{player_cards: cards(player_cards), banker_cards: cards(banker_cards), ..BaccaratState::default()}
+    }
+
+    fn table() -> (Game, Arc<Player>, Arc<Player>, Arc<Player>) {
+        let mut game = game(BaccaratGameRules::new(10, 500).build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Baccarat, 10_000);
+        let first = player(1, PlayerRole::Player, GameProject::Baccarat, 1000);
+        let second = player(2, PlayerRole::Player, GameProject::Baccarat, 1000);
+        game.player_join(vec![dealer.clone(), first.clone(), second.clone()]);
+        (game, dealer, first, second)
+    }
+
+    fn baccarat_state(game: &Game) -> Arc<Mutex<BaccaratState>> {
+        get_state::<BaccaratState>(&game.get_game_context(), BACCARAT_STATE_KEY).unwrap()
+    }
+
+    #[test]
+    fn payout_table() {
+        let config = BaccaratGameRules::new(10, 500);
+        let mut no_commission = config;
+        no_commission.commission = BaccaratCommission::NoCommission;
+
+        // 闲 9 点例牌赢
+        let state = hand(&[Rank::Four, Rank::Five], &[Rank::King, Rank::Seven]);
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Player, 100), Some(100));
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Banker, 100), None);
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Tie, 100), None);
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Dragon(BaccaratSide::Player), 100), Some(100));
+
+        // 庄 6 点赢：抽水 0.95:1，免佣 1:2
+        let state = hand(&[Rank::King, Rank::Five], &[Rank::Queen, Rank::Six]);
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Banker, 100), Some(95));
+        assert_eq!(wager_winnings(no_commission, &state, BaccaratWager::Banker, 100), Some(50));
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Dragon(BaccaratSide::Banker), 100), None);
+
+        // 和局：闲庄退回，和赔 8:1，非例牌和局的龙宝输
+        let state = hand(&[Rank::Seven, Rank::Seven, Rank::Three], &[Rank::Ten, Rank::Four, Rank::Three]);
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Player, 100), Some(0));
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Banker, 100), Some(0));
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Tie, 100), Some(800));
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Dragon(BaccaratSide::Player), 100), None);
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Pair(BaccaratSide::Player), 100), Some(1100));
+        assert_eq!(wager_winnings(config, &state, BaccaratWager::Pair(BaccaratSide::Banker), 100), None);
+
+        assert_eq!(dragon_multiplier(9), Some(30));
+        assert_eq!(dragon_multiplier(4), Some(1));
+        assert_eq!(dragon_multiplier(3), None);
+    }
+
+    #[test]
+    fn banker_drawing_rules() {
+        assert!(banker_draws(5, None));
+        assert!(!banker_draws(6, None));
+        assert!(banker_draws(2, Some(9)));
+        assert!(!banker_draws(3, Some(8)));
+        assert!(banker_draws(4, Some(2)));
+        assert!(!banker_draws(4, Some(1)));
+        assert!(banker_draws(5, Some(4)));
+        assert!(!banker_draws(5, Some(3)));
+        assert!(banker_draws(6, Some(7)));
+        assert!(!banker_draws(6, Some(5)));
+        assert!(!banker_draws(7, Some(6)));
+    }
+
+    #[test]
+    fn burn_follows_committed_shoe_seed() {
+        let (mut game, _dealer, first, second) = table();
+        let fairness = get_state::<FairnessState>(&game.get_game_context(), PROVABLY_FAIR_KEY).unwrap();
+        let commitment = lock_or_recover(&fairness).get_commitment().unwrap();
+        game.game_start();
+
+        let shoe = get_state::<Shoe>(&game.get_game_context(), SHOE_KEY).unwrap();
+        {
+            // 烧牌是洗好的牌靴顶部的牌，第一张决定烧掉的张数
+            let state = baccarat_state(&game);
+            let state = lock_or_recover(&state);
+            let shoe = lock_or_recover(&shoe);
+            let count = match card_points(state.burned[0].rank) {
+                0 => 10,
+                points => points as usize,
+            };
+            assert_eq!(state.burned.len(), count + 1);
+            assert_eq!(&shoe.get_cards()[..count + 1], state.burned.as_slice());
+        }
+        assert_ne!(lock_or_recover(&fairness).get_commitment(), Some(commitment.clone()));
+        assert!(lock_or_recover(&fairness).get_last_reveal().is_none());
+
+        game.player_action(first, Arc::new(BaccaratAction::Bet(Vec::new())));
+        game.player_action(second, Arc::new(BaccaratAction::Bet(Vec::new())));
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        game.game_wait_start();
+        {
+            let mut shoe = lock_or_recover(&shoe);
+            let remaining = shoe.remaining();
+            shoe.burn(remaining);
+        }
+        game.game_start();
+
+        let reveal = lock_or_recover(&fairness).get_last_reveal().cloned().unwrap();
+        assert_eq!(reveal.commitment, commitment);
+        assert!(verify_shoe(&reveal, 8, lock_or_recover(&shoe).get_previous_cards()));
+    }
+
+    #[test]
+    fn last_pending_player_leaving_deals_the_hand() {
+        let (mut game, dealer, first, second) = table();
+        game.game_start();
+        let outcome = game.player_action(first.clone(), Arc::new(BaccaratAction::Bet(vec![(BaccaratWager::Player, 100)])));
+        assert_eq!(outcome, ActionOutcome::Continue);
+
+        assert_eq!(game.player_leave(vec![second.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        let state = baccarat_state(&game);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.results.len(), 1);
+        assert_eq!(first.get_token() as i32 + dealer.get_token() as i32, 11_000);
+        assert_eq!(second.get_token(), 1000);
+    }
+
+    #[test]
+    fn leaving_before_start_does_not_deal() {
+        let (mut game, _dealer, _first, second) = table();
+        assert_eq!(game.player_leave(vec![second]), ActionOutcome::Continue);
+        assert_eq!(game.get_game_state(), GameState::NotStarted);
+    }
+}
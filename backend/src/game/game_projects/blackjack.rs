@@ -0,0 +1,968 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::poker::poker::{Card, Rank};
+use crate::game::game_items::poker::shoe::{Shoe, SHOE_KEY};
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, collect, commit_next_seed, commit_seed_on_join, find_player, pay, sync_action_players};
+
+/// 牌局状态在 game_context 中的 key
+pub const BLACKJACK_STATE_KEY: &str = "blackjack_state";
+
+/// 一桌最多的闲家数
+pub const BLACKJACK_MAX_PLAYERS: usize = 7;
+
+/// 二十一点
+pub const BLACKJACK: u8 = 21;
+
+/// 黑杰克赔率
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum BlackjackPayout {
+    #[default]
+    ThreeToTwo, // 3:2
+    SixToFive,  // 6:5
+}
+
+impl BlackjackPayout {
+    /// 黑杰克赢得的筹码(不含本金)，不足一个筹码的部分舍去
+    pub fn winnings(&self, bet: u32) -> u32 {
+        match self {
+            BlackjackPayout::ThreeToTwo => bet * 3 / 2,
+            BlackjackPayout::SixToFive => bet * 6 / 5,
+        }
+    }
+}
+
+/// 二十一点对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum BlackjackPhase {
+    #[default]
+    Betting,   // 闲家下注
+    Insurance, // 庄家明牌为 A，闲家决定是否买保险
+    Playing,   // 闲家逐手行动
+    Settled,   // 庄家补牌并结算
+}
+
+/// 二十一点玩家行动，除下注和保险外都作用于当前轮到的那一手牌
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlackjackAction {
+    Bet(u32),        // 下注，0 为本局不参与
+    Insurance(bool), // 是否买保险，保险额为下注的一半
+    Hit,             // 要牌
+    Stand,           // 停牌
+    Double,          // 加倍，再下一份注并只要一张牌
+    Split,           // 分牌，两张点数相同的牌拆成两手
+    Surrender,       // 投降，输一半注额
+}
+
+/// 一手牌的结算结果
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum HandOutcome {
+    Blackjack,
+    Win,
+    Push,
+    Lose,
+    Bust,
+    Surrender,
+}
+
+/// 闲家的一手牌，分牌后一位闲家可以有多手
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlackjackHand {
+    pub cards: Vec<Card>,
+    pub bet: u32,
+    pub doubled: bool,
+    pub surrendered: bool,
+    pub from_split: bool,
+    pub split_aces: bool, // 分 A 后的手牌只发一张，不能再要牌
+    pub finished: bool,
+    pub outcome: Option<HandOutcome>,
+    pub net: i32, // 结算后的输赢，正数为赢
+}
+
+impl BlackjackHand {
+    /// 点数和是否为软牌(A 计为 11)
+    pub fn total(&self) -> (u8, bool) {
+        hand_total(&self.cards)
+    }
+
+    /// 未分牌的前两张为 21 点
+    pub fn is_blackjack(&self) -> bool {
+        !self.from_split && is_blackjack(&self.cards)
+    }
+
+    pub fn is_bust(&self) -> bool {
+        self.total().0 > BLACKJACK
+    }
+
+    // 两张点数相同的牌(10、J、Q、K 都算 10 点)
+    fn is_pair(&self) -> bool {
+        self.cards.len() == 2 && card_points(self.cards[0].rank) == card_points(self.cards[1].rank)
+    }
+}
+
+/// 一位闲家在本局的全部手牌和保险
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlackjackSeat {
+    pub user_id: u32,
+    pub hands: Vec<BlackjackHand>,
+    pub insurance: Option<u32>, // None 为尚未决定
+    pub insurance_net: i32,
+}
+
+/// 二十一点牌局状态，保存在 game_context 中；牌靴单独保存在 SHOE_KEY 下
+#[derive(Debug, Default)]
+pub struct BlackjackState {
+    pub phase: BlackjackPhase,
+    pub dealer_user: Option<u32>,
+    pub dealer_cards: Vec<Card>,
+    pub bets: HashMap<u32, u32>, // 下注阶段收到的注额
+    pub seats: Vec<BlackjackSeat>,
+    pub acting: Option<(usize, usize)>, // 当前行动的 (座位, 手牌) 下标
+}
+
+/// 某位观察者看到的牌桌，庄家暗牌在结算前不可见
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlackjackTableView {
+    pub phase: BlackjackPhase,
+    pub dealer_cards: Vec<Card>,
+    pub dealer_hole_hidden: bool,
+    pub seats: Vec<BlackjackSeat>,
+    pub next_to_act: Option<(u32, usize)>,
+}
+
+impl BlackjackState {
+    /// 当前行动的闲家和他的第几手牌
+    pub fn next_to_act(&self) -> Option<(u32, usize)> {
+        self.acting.map(|(seat, hand)| (self.seats[seat].user_id, hand))
+    }
+
+    /// 还需要行动的闲家：下注阶段为未下注的闲家，保险阶段为未决定的闲家，行动阶段为当前手牌的主人
+    pub fn pending_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        match self.phase {
+            BlackjackPhase::Betting => players.iter()
+                .filter(|player| player.get_player_role() == PlayerRole::Player)
+                .map(|player| player.get_user().get_id())
+                .filter(|user_id| !self.bets.contains_key(user_id))
+                .collect(),
+            BlackjackPhase::Insurance => self.seats.iter()
+                .filter(|seat| seat.insurance.is_none())
+                .map(|seat| seat.user_id)
+                .collect(),
+            BlackjackPhase::Playing => self.next_to_act().map(|(user_id, _)| user_id).into_iter().collect(),
+            BlackjackPhase::Settled => Vec::new(),
+        }
+    }
+
+    /// 庄家手牌的点数和
+    pub fn dealer_total(&self) -> (u8, bool) {
+        hand_total(&self.dealer_cards)
+    }
+
+    pub fn view(&self) -> BlackjackTableView {
+        let hidden = self.phase != BlackjackPhase::Settled && self.dealer_cards.len() > 1;
+        BlackjackTableView {
+            phase: self.phase,
+            dealer_cards: if hidden { self.dealer_cards[..1].to_vec() } else { self.dealer_cards.clone() },
+            dealer_hole_hidden: hidden,
+            seats: self.seats.clone(),
+            next_to_act: self.next_to_act(),
+        }
+    }
+
+    // 开始新的一局前清空上一局的数据，牌靴不受影响
+    fn reset_hand(&mut self) {
+        self.phase = BlackjackPhase::Betting;
+        self.dealer_user = None;
+        self.dealer_cards.clear();
+        self.bets.clear();
+        self.seats.clear();
+        self.acting = None;
+    }
+
+    // 从 (seat, hand) 开始找下一手未结束的牌
+    fn advance_from(&mut self, seat: usize, hand: usize) {
+        self.acting = None;
+        for seat_index in seat..self.seats.len() {
+            let first_hand = if seat_index == seat { hand } else { 0 };
+            let hands = &self.seats[seat_index].hands;
+            if let Some(hand_index) = (first_hand..hands.len()).find(|index| !hands[*index].finished) {
+                self.acting = Some((seat_index, hand_index));
+                return;
+            }
+        }
+    }
+}
+
+impl ActingPlayers for BlackjackState {
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players(players)
+    }
+}
+
+/// 二十一点规则配置，庄家由 PlayerRole::Dealer 的玩家担任，输赢都从他的筹码中结算
+#[derive(Debug, Clone, Copy)]
+pub struct BlackjackGameRules {
+    pub decks: u8,
+    pub penetration_percent: u8, // 发到这个比例时切牌，下一局前重新洗牌
+    pub dealer_hits_soft_17: bool, // H17 为 true，S17 为 false
+    pub double_after_split: bool,  // DAS
+    pub resplit_aces: bool,
+    pub max_split_hands: u8,       // 分牌后最多的手数
+    pub surrender: bool,           // 庄家确认没有黑杰克后允许投降
+    pub blackjack_payout: BlackjackPayout,
+    pub min_bet: u32,
+    pub max_bet: u32,
+}
+
+impl BlackjackGameRules {
+    pub fn new(min_bet: u32, max_bet: u32) -> Self {
+        BlackjackGameRules {
+            decks: 6,
+            penetration_percent: 75,
+            dealer_hits_soft_17: false,
+            double_after_split: true,
+            resplit_aces: false,
+            max_split_hands: 4,
+            surrender: true,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+            min_bet,
+            max_bet,
+        }
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.decks == 0 || config.penetration_percent == 0 || config.max_split_hands == 0
+            || config.min_bet == 0 || config.max_bet < config.min_bet {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|left, right, _| {
+                let cards = |items: &Vec<&dyn GameItem>| -> Vec<Card> {
+                    items.iter().filter_map(|item| Card::from_item(*item)).collect()
+                };
+                hand_rank(&cards(left)) > hand_rank(&cards(right))
+            }),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_start(config, players, game_items, context)),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_finish(config, players, game_items, context)),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+// 开局：找出庄家，牌靴到切牌位置时公开上一靴的种子并重新洗牌，然后等待闲家下注
+fn game_start(
+    config: BlackjackGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<BlackjackState>(&context, BLACKJACK_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_hand();
+
+    let dealer = players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer);
+    let player_count = players.iter().filter(|player| player.get_player_role() == PlayerRole::Player).count();
+    let Some(dealer) = dealer else {
+        state.phase = BlackjackPhase::Settled;
+        return;
+    };
+    if player_count == 0 || player_count > BLACKJACK_MAX_PLAYERS {
+        state.phase = BlackjackPhase::Settled;
+        return;
+    }
+    state.dealer_user = Some(dealer.get_user().get_id());
+
+    let shoe = get_or_insert_state::<Shoe>(&context, SHOE_KEY);
+    let mut shoe = lock_or_recover(&shoe);
+    // 剩余的牌不够首轮发牌时也提前洗牌
+    let initial_cards = (players.len() + 1) * 2;
+    if shoe.needs_shuffle() || shoe.remaining() < initial_cards
+        || shoe.get_decks() != config.decks || shoe.get_penetration_percent() != config.penetration_percent {
+        let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+        let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+        let mut fairness = lock_or_recover(&fairness);
+        let mut rng = fairness.reshuffle_shoe(&seat_order);
+        shoe.configure(config.decks, config.penetration_percent);
+        shoe.shuffle(&mut rng);
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 闲家下注、买保险或对当前手牌行动
+fn player_action(
+    config: BlackjackGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<BlackjackState>(&context, BLACKJACK_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let shoe = get_or_insert_state::<Shoe>(&context, SHOE_KEY);
+    let mut shoe = lock_or_recover(&shoe);
+
+    let Some(action) = action.downcast_ref::<BlackjackAction>().copied() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players(&players).contains(&user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+
+    let outcome = match (state.phase, action) {
+        (BlackjackPhase::Betting, BlackjackAction::Bet(amount)) => {
+            if amount != 0 && (amount < config.min_bet || amount > config.max_bet) {
+                return ActionOutcome::Rejected(format!("bet must be between {} and {}", config.min_bet, config.max_bet));
+            }
+            if amount > player.get_token() as u32 {
+                return ActionOutcome::Rejected("not enough tokens".to_string());
+            }
+            player.take_token(amount as u16);
+            state.bets.insert(user_id, amount);
+            if state.pending_players(&players).is_empty() {
+                deal_initial(&mut state, &mut shoe, &players)
+            } else {
+                ActionOutcome::Continue
+            }
+        }
+        (BlackjackPhase::Insurance, BlackjackAction::Insurance(take)) => {
+            let seat_index = state.seats.iter().position(|seat| seat.user_id == user_id).unwrap_or(0);
+            let insurance = if take { state.seats[seat_index].hands[0].bet / 2 } else { 0 };
+            if insurance > player.get_token() as u32 {
+                return ActionOutcome::Rejected("not enough tokens".to_string());
+            }
+            player.take_token(insurance as u16);
+            state.seats[seat_index].insurance = Some(insurance);
+            if state.pending_players(&players).is_empty() {
+                start_playing(&mut state)
+            } else {
+                ActionOutcome::Continue
+            }
+        }
+        (BlackjackPhase::Playing, action) => {
+            let Some((seat_index, hand_index)) = state.acting else {
+                return ActionOutcome::Rejected("no hand to play".to_string());
+            };
+            if let Err(reason) = play_hand(config, &mut state, &mut shoe, &player, seat_index, hand_index, action) {
+                return ActionOutcome::Rejected(reason);
+            }
+            if state.seats[seat_index].hands[hand_index].finished {
+                state.advance_from(seat_index, hand_index + 1);
+            }
+            if state.acting.is_none() { ActionOutcome::GameComplete } else { ActionOutcome::Continue }
+        }
+        _ => return ActionOutcome::Rejected("action does not match the current phase".to_string()),
+    };
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 所有闲家下注后发牌：每人一张、庄家明牌、每人第二张、庄家暗牌
+fn deal_initial(state: &mut BlackjackState, shoe: &mut Shoe, players: &[Arc<Player>]) -> ActionOutcome {
+    state.seats = players.iter()
+        .map(|player| player.get_user().get_id())
+        .filter_map(|user_id| state.bets.get(&user_id).filter(|bet| **bet > 0).map(|bet| (user_id, *bet)))
+        .map(|(user_id, bet)| BlackjackSeat {
+            user_id,
+            hands: vec![BlackjackHand{bet, ..BlackjackHand::default()}],
+            insurance: None,
+            insurance_net: 0,
+        })
+        .collect();
+    if state.seats.is_empty() {
+        return ActionOutcome::GameComplete;
+    }
+    for _ in 0..2 {
+        for seat in state.seats.iter_mut() {
+            seat.hands[0].cards.extend(shoe.draw());
+        }
+        state.dealer_cards.extend(shoe.draw());
+    }
+    for seat in state.seats.iter_mut() {
+        seat.hands[0].finished = seat.hands[0].is_blackjack();
+    }
+
+    if state.dealer_cards.first().is_some_and(|card| card.rank == Rank::Ace) {
+        state.phase = BlackjackPhase::Insurance;
+        return ActionOutcome::Continue;
+    }
+    for seat in state.seats.iter_mut() {
+        seat.insurance = Some(0);
+    }
+    start_playing(state)
+}
+
+// 庄家明牌为 A 或 10 点时先看暗牌，庄家黑杰克直接结算，否则闲家开始行动
+fn start_playing(state: &mut BlackjackState) -> ActionOutcome {
+    if is_blackjack(&state.dealer_cards) {
+        state.acting = None;
+        return ActionOutcome::GameComplete;
+    }
+    state.phase = BlackjackPhase::Playing;
+    state.advance_from(0, 0);
+    if state.acting.is_none() { ActionOutcome::GameComplete } else { ActionOutcome::Continue }
+}
+
+// 对当前手牌执行行动，不合法时返回原因
+fn play_hand(
+    config: BlackjackGameRules,
+    state: &mut BlackjackState,
+    shoe: &mut Shoe,
+    player: &Player,
+    seat_index: usize,
+    hand_index: usize,
+    action: BlackjackAction,
+) -> Result<(), String> {
+    let hand_count = state.seats[seat_index].hands.len();
+    let hand = &mut state.seats[seat_index].hands[hand_index];
+    let is_first_action = hand.cards.len() == 2;
+    match action {
+        BlackjackAction::Hit => {
+            if hand.split_aces {
+                return Err("split aces receive one card only".to_string());
+            }
+            // 牌靴在局中发完时这手牌按停牌处理
+            match shoe.draw() {
+                Some(card) => hand.cards.push(card),
+                None => hand.finished = true,
+            }
+        }
+        BlackjackAction::Stand => {
+            hand.finished = true;
+        }
+        BlackjackAction::Double => {
+            if !is_first_action || hand.split_aces || (hand.from_split && !config.double_after_split) {
+                return Err("cannot double this hand".to_string());
+            }
+            if hand.bet > player.get_token() as u32 {
+                return Err("not enough tokens".to_string());
+            }
+            if shoe.remaining() < 1 {
+                return Err("not enough cards left in the shoe".to_string());
+            }
+            player.take_token(hand.bet as u16);
+            hand.bet *= 2;
+            hand.doubled = true;
+            hand.cards.extend(shoe.draw());
+            hand.finished = true;
+        }
+        BlackjackAction::Split => {
+            let is_aces = hand.cards.first().is_some_and(|card| card.rank == Rank::Ace);
+            if !hand.is_pair() || hand_count >= config.max_split_hands as usize || (hand.split_aces && !config.resplit_aces) {
+                return Err("cannot split this hand".to_string());
+            }
+            if hand.bet > player.get_token() as u32 {
+                return Err("not enough tokens".to_string());
+            }
+            if shoe.remaining() < 2 {
+                return Err("not enough cards left in the shoe".to_string());
+            }
+            player.take_token(hand.bet as u16);
+            let moved = hand.cards.pop();
+            let mut new_hand = BlackjackHand{bet: hand.bet, from_split: true, split_aces: is_aces, ..BlackjackHand::default()};
+            new_hand.cards.extend(moved);
+            hand.from_split = true;
+            hand.split_aces = is_aces;
+            hand.cards.extend(shoe.draw());
+            new_hand.cards.extend(shoe.draw());
+            state.seats[seat_index].hands.insert(hand_index + 1, new_hand);
+            // 分 A 后每手只有一张牌，除非允许再分且又拿到 A
+            let hand_count = state.seats[seat_index].hands.len();
+            for index in [hand_index, hand_index + 1] {
+                let hand = &mut state.seats[seat_index].hands[index];
+                let can_resplit = config.resplit_aces && hand.is_pair() && hand_count < config.max_split_hands as usize;
+                if hand.split_aces && !can_resplit {
+                    hand.finished = true;
+                }
+            }
+        }
+        BlackjackAction::Surrender => {
+            if !config.surrender || !is_first_action || hand.from_split {
+                return Err("cannot surrender this hand".to_string());
+            }
+            hand.surrendered = true;
+            hand.finished = true;
+        }
+        BlackjackAction::Bet(_) | BlackjackAction::Insurance(_) => {
+            return Err("action does not match the current phase".to_string());
+        }
+    }
+
+    // 爆牌或到 21 点时这手牌自动结束
+    let hand = &mut state.seats[seat_index].hands[hand_index];
+    if hand.total().0 >= BLACKJACK {
+        hand.finished = true;
+    }
+    Ok(())
+}
+
+// 庄家按 H17/S17 补牌，然后逐手结算：赢家的彩金和输家的注额都与庄家的筹码往来
+fn game_finish(
+    config: BlackjackGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<BlackjackState>(&context, BLACKJACK_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let shoe = get_or_insert_state::<Shoe>(&context, SHOE_KEY);
+    let mut shoe = lock_or_recover(&shoe);
+    if state.phase == BlackjackPhase::Settled {
+        return;
+    }
+    state.phase = BlackjackPhase::Settled;
+    state.acting = None;
+
+    let dealer = state.dealer_user.and_then(|user_id| find_player(&players, user_id));
+
+    // 还有没爆牌、没投降且不是黑杰克的手牌时庄家才需要补牌
+    let dealer_blackjack = is_blackjack(&state.dealer_cards);
+    let needs_dealer = !dealer_blackjack && state.seats.iter()
+        .flat_map(|seat| seat.hands.iter())
+        .any(|hand| !hand.is_bust() && !hand.surrendered && !hand.is_blackjack());
+    while needs_dealer && dealer_hits(config, &state.dealer_cards) {
+        match shoe.draw() {
+            Some(card) => state.dealer_cards.push(card),
+            None => break,
+        }
+    }
+    let dealer_total = state.dealer_total().0;
+
+    for seat in state.seats.iter_mut() {
+        let Some(player) = find_player(&players, seat.user_id) else {
+            continue;
+        };
+        // 保险赔 2:1
+        if let Some(insurance) = seat.insurance.filter(|insurance| *insurance > 0) {
+            seat.insurance_net = if dealer_blackjack {
+                player.add_token(insurance as u16);
+                pay(&dealer, &player, insurance * 2) as i32
+            } else {
+                collect(&dealer, insurance);
+                -(insurance as i32)
+            };
+        }
+        for hand in seat.hands.iter_mut() {
+            let (outcome, winnings) = if hand.surrendered {
+                (HandOutcome::Surrender, None)
+            } else if hand.is_bust() {
+                (HandOutcome::Bust, None)
+            } else if hand.is_blackjack() && !dealer_blackjack {
+                (HandOutcome::Blackjack, Some(config.blackjack_payout.winnings(hand.bet)))
+            } else if dealer_blackjack && !hand.is_blackjack() {
+                (HandOutcome::Lose, None)
+            } else if hand.is_blackjack() || hand.total().0 == dealer_total {
+                (HandOutcome::Push, Some(0))
+            } else if dealer_total > BLACKJACK || hand.total().0 > dealer_total {
+                (HandOutcome::Win, Some(hand.bet))
+            } else {
+                (HandOutcome::Lose, None)
+            };
+            hand.net = match (outcome, winnings) {
+                (HandOutcome::Surrender, _) => {
+                    let returned = hand.bet / 2;
+                    player.add_token(returned as u16);
+                    collect(&dealer, hand.bet - returned);
+                    -((hand.bet - returned) as i32)
+                }
+                (_, Some(winnings)) => {
+                    player.add_token(hand.bet as u16);
+                    pay(&dealer, &player, winnings) as i32
+                }
+                (_, None) => {
+                    collect(&dealer, hand.bet);
+                    -(hand.bet as i32)
+                }
+            };
+            hand.outcome = Some(outcome);
+        }
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 闲家离桌时已下的注归庄家，剩余手牌视为停牌；离桌后没人需要行动时照常发牌或结算
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<BlackjackState>(&context, BLACKJACK_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == BlackjackPhase::Settled {
+        return ActionOutcome::Continue;
+    }
+    let dealer = state.dealer_user.and_then(|user_id| find_player(&current_players, user_id));
+
+    for player in leave_players {
+        let user_id = player.get_user().get_id();
+        if let Some(bet) = state.bets.remove(&user_id) {
+            if state.phase == BlackjackPhase::Betting {
+                // 还没发牌，注额退回
+                player.add_token(bet as u16);
+                continue;
+            }
+        }
+        let Some(seat_index) = state.seats.iter().position(|seat| seat.user_id == user_id) else {
+            continue;
+        };
+        let seat = state.seats.remove(seat_index);
+        let forfeited: u32 = seat.hands.iter().map(|hand| hand.bet).sum::<u32>() + seat.insurance.unwrap_or(0);
+        collect(&dealer, forfeited);
+        state.advance_from(0, 0);
+    }
+
+    let outcome = if !state.pending_players(&current_players).is_empty() {
+        ActionOutcome::Continue
+    } else {
+        match state.phase {
+            BlackjackPhase::Betting => {
+                let shoe = get_or_insert_state::<Shoe>(&context, SHOE_KEY);
+                let mut shoe = lock_or_recover(&shoe);
+                deal_initial(&mut state, &mut shoe, &current_players)
+            }
+            BlackjackPhase::Insurance => start_playing(&mut state),
+            BlackjackPhase::Playing => ActionOutcome::GameComplete,
+            BlackjackPhase::Settled => ActionOutcome::Continue,
+        }
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+/// 手牌点数和，A 先按 1 计，能不爆牌时把一张 A 计为 11 并标记为软牌
+pub fn hand_total(cards: &[Card]) -> (u8, bool) {
+    let hard: u8 = cards.iter().map(|card| card_points(card.rank)).sum();
+    let has_ace = cards.iter().any(|card| card.rank == Rank::Ace);
+    if has_ace && hard + 10 <= BLACKJACK {
+        (hard + 10, true)
+    } else {
+        (hard, false)
+    }
+}
+
+// 庄家 17 点以下必须要牌，H17 时软 17 也要牌
+fn dealer_hits(config: BlackjackGameRules, cards: &[Card]) -> bool {
+    let (total, soft) = hand_total(cards);
+    total < 17 || (total == 17 && soft && config.dealer_hits_soft_17)
+}
+
+/// 两张牌组成 21 点
+pub fn is_blackjack(cards: &[Card]) -> bool {
+    cards.len() == 2 && hand_total(cards).0 == BLACKJACK
+}
+
+// 比较两手牌：爆牌最小，黑杰克大于普通 21 点
+fn hand_rank(cards: &[Card]) -> (bool, u8, bool) {
+    let total = hand_total(cards).0;
+    (total <= BLACKJACK, if total <= BLACKJACK { total } else { 0 }, is_blackjack(cards))
+}
+
+// A 计 1 点，J、Q、K 计 10 点
+fn card_points(rank: Rank) -> u8 {
+    match rank {
+        Rank::Ace => 1,
+        rank => rank.value().min(10),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::Game;
+    use crate::game::game_context::get_state;
+    use crate::game::game_items::poker::poker::Suit;
+    use crate::game::game_items::poker::shoe::verify_shoe;
+    use crate::game::provably_fair::commit_reveal::FairRng;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{acting, game, player};
+
+    fn card(rank: Rank) -> Card {
+        Card::new(Suit::Spades, rank)
+    }
+
+    fn hand(ranks: &[Rank], bet: u32) -> BlackjackHand {
+        BlackjackHand{cards: ranks.iter().map(|rank| card(*rank)).collect(), bet, ..BlackjackHand::default()}
+    }
+
+    fn seat(user_id: u32, hand: BlackjackHand) -> BlackjackSeat {
+        BlackjackSeat{user_id, hands: vec![hand], insurance: Some(0), insurance_net: 0}
+    }
+
+    fn table(config: BlackjackGameRules) -> (Game, Arc<Player>, Arc<Player>, Arc<Player>) {
+        let mut game = game(config.build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Blackjack, 10_000);
+        let first = player(1, PlayerRole::Player, GameProject::Blackjack, 1000);
+        let second = player(2, PlayerRole::Player, GameProject::Blackjack, 1000);
+        game.player_join(vec![dealer.clone(), first.clone(), second.clone()]);
+        game.game_start();
+        (game, dealer, first, second)
+    }
+
+    fn blackjack_state(game: &Game) -> Arc<Mutex<BlackjackState>> {
+        get_state::<BlackjackState>(&game.get_game_context(), BLACKJACK_STATE_KEY).unwrap()
+    }
+
+    fn fairness(game: &Game) -> Arc<Mutex<FairnessState>> {
+        get_state::<FairnessState>(&game.get_game_context(), PROVABLY_FAIR_KEY).unwrap()
+    }
+
+    // 直接按给定的手牌结算，庄家 18 点不再补牌
+    fn settle(config: BlackjackGameRules, dealer_cards: &[Rank], hands: Vec<BlackjackHand>) -> Vec<BlackjackSeat> {
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Blackjack, 10_000);
+        let mut players = vec![dealer];
+        let context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>> = Arc::new(Mutex::new(HashMap::new()));
+        {
+            let state = get_or_insert_state::<BlackjackState>(&context, BLACKJACK_STATE_KEY);
+            let mut state = lock_or_recover(&state);
+            state.phase = BlackjackPhase::Playing;
+            state.dealer_user = Some(100);
+            state.dealer_cards = dealer_cards.iter().map(|rank| card(*rank)).collect();
+            for (index, hand) in hands.into_iter().enumerate() {
+                let user_id = index as u32 + 1;
+                players.push(player(user_id, PlayerRole::Player, GameProject::Blackjack, 1000));
+                state.seats.push(seat(user_id, hand));
+            }
+        }
+        game_finish(config, Arc::new(Mutex::new(players)), Arc::new(Mutex::new(Vec::new())), context.clone());
+        let state = get_or_insert_state::<BlackjackState>(&context, BLACKJACK_STATE_KEY);
+        let seats = lock_or_recover(&state).seats.clone();
+        seats
+    }
+
+    #[test]
+    fn payout_table() {
+        let config = BlackjackGameRules::new(10, 500);
+        let seats = settle(config, &[Rank::King, Rank::Eight], vec![
+            hand(&[Rank::Ace, Rank::King], 100),
+            hand(&[Rank::Ten, Rank::Nine], 100),
+            hand(&[Rank::Ten, Rank::Eight], 100),
+            hand(&[Rank::Ten, Rank::Seven], 100),
+            hand(&[Rank::Ten, Rank::Six, Rank::Nine], 100),
+            hand(&[Rank::Ten, Rank::Six], 100),
+        ]);
+        let results: Vec<(Option<HandOutcome>, i32)> = seats.iter().map(|seat| (seat.hands[0].outcome, seat.hands[0].net)).collect();
+        assert_eq!(results, vec![
+            (Some(HandOutcome::Blackjack), 150),
+            (Some(HandOutcome::Win), 100),
+            (Some(HandOutcome::Push), 0),
+            (Some(HandOutcome::Lose), -100),
+            (Some(HandOutcome::Bust), -100),
+            (Some(HandOutcome::Lose), -100),
+        ]);
+
+        // 6:5 的黑杰克，投降退一半
+        let mut six_to_five = config;
+        six_to_five.blackjack_payout = BlackjackPayout::SixToFive;
+        let mut surrendered = hand(&[Rank::Ten, Rank::Six], 100);
+        surrendered.surrendered = true;
+        let seats = settle(six_to_five, &[Rank::King, Rank::Eight], vec![hand(&[Rank::Ace, Rank::Queen], 100), surrendered]);
+        assert_eq!(seats[0].hands[0].net, 120);
+        assert_eq!((seats[1].hands[0].outcome, seats[1].hands[0].net), (Some(HandOutcome::Surrender), -50));
+    }
+
+    #[test]
+    fn insurance_pays_two_to_one_on_dealer_blackjack() {
+        let config = BlackjackGameRules::new(10, 500);
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Blackjack, 10_000);
+        let insured = player(1, PlayerRole::Player, GameProject::Blackjack, 1000);
+        let context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>> = Arc::new(Mutex::new(HashMap::new()));
+        {
+            let state = get_or_insert_state::<BlackjackState>(&context, BLACKJACK_STATE_KEY);
+            let mut state = lock_or_recover(&state);
+            state.phase = BlackjackPhase::Playing;
+            state.dealer_user = Some(100);
+            state.dealer_cards = vec![card(Rank::Ace), card(Rank::King)];
+            insured.take_token(150);
+            state.seats.push(BlackjackSeat{user_id: 1, hands: vec![hand(&[Rank::Ten, Rank::Nine], 100)], insurance: Some(50), insurance_net: 0});
+        }
+        let players = Arc::new(Mutex::new(vec![dealer.clone(), insured.clone()]));
+        game_finish(config, players, Arc::new(Mutex::new(Vec::new())), context.clone());
+
+        let state = get_or_insert_state::<BlackjackState>(&context, BLACKJACK_STATE_KEY);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.seats[0].insurance_net, 100);
+        assert_eq!(state.seats[0].hands[0].net, -100);
+        // 保险赢 100、主注输 100，正好打平
+        assert_eq!(insured.get_token(), 1000);
+        assert_eq!(dealer.get_token(), 10_000);
+    }
+
+    #[test]
+    fn shoe_seed_is_committed_before_shuffle_and_revealed_at_next_shoe() {
+        let config = BlackjackGameRules::new(10, 500);
+        let mut game = game(config.build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Blackjack, 10_000);
+        let bettor = player(1, PlayerRole::Player, GameProject::Blackjack, 1000);
+        game.player_join(vec![dealer, bettor.clone()]);
+        let first_commitment = lock_or_recover(&fairness(&game)).get_commitment().unwrap();
+
+        // 洗牌用的是入座时承诺的种子，发牌前已经承诺好下一靴的种子
+        game.game_start();
+        let second_commitment = lock_or_recover(&fairness(&game)).get_commitment().unwrap();
+        assert_ne!(first_commitment, second_commitment);
+        assert!(lock_or_recover(&fairness(&game)).get_last_reveal().is_none());
+
+        assert_eq!(game.player_action(bettor.clone(), Arc::new(BlackjackAction::Bet(0))), ActionOutcome::GameComplete);
+        game.game_wait_start();
+        assert_eq!(lock_or_recover(&fairness(&game)).get_commitment(), Some(second_commitment.clone()));
+
+        // 发到切牌位置后下一局重新洗牌，这时才公开上一靴的种子
+        let shoe = get_state::<Shoe>(&game.get_game_context(), SHOE_KEY).unwrap();
+        {
+            let mut shoe = lock_or_recover(&shoe);
+            let remaining = shoe.remaining();
+            shoe.burn(remaining);
+        }
+        game.game_start();
+        let reveal = lock_or_recover(&fairness(&game)).get_last_reveal().cloned().unwrap();
+        assert_eq!(reveal.commitment, first_commitment);
+        assert!(reveal.verify_commitment());
+        assert!(verify_shoe(&reveal, config.decks, lock_or_recover(&shoe).get_previous_cards()));
+        assert_ne!(lock_or_recover(&fairness(&game)).get_commitment(), Some(second_commitment));
+    }
+
+    #[test]
+    fn last_bettor_leaving_deals_the_hand() {
+        let (mut game, _dealer, first, second) = table(BlackjackGameRules::new(10, 500));
+        assert_eq!(game.player_action(first.clone(), Arc::new(BlackjackAction::Bet(100))), ActionOutcome::Continue);
+        game.player_leave(vec![second]);
+
+        let state = blackjack_state(&game);
+        let state = lock_or_recover(&state);
+        assert_ne!(state.phase, BlackjackPhase::Betting);
+        assert_eq!(state.seats.len(), 1);
+        assert!(game.get_game_state() == GameState::Finished || !acting(&game).is_empty());
+    }
+
+    #[test]
+    fn last_undecided_insurance_leaving_starts_play() {
+        let (mut game, dealer, first, second) = table(BlackjackGameRules::new(10, 500));
+        {
+            let state = blackjack_state(&game);
+            let mut state = lock_or_recover(&state);
+            state.phase = BlackjackPhase::Insurance;
+            state.dealer_cards = vec![card(Rank::Ace), card(Rank::Nine)];
+            state.seats = vec![seat(1, hand(&[Rank::Ten, Rank::Six], 100)), seat(2, hand(&[Rank::Ten, Rank::Five], 100))];
+            state.seats[1].insurance = None;
+        }
+        first.take_token(100);
+        second.take_token(100);
+
+        assert_eq!(game.player_leave(vec![second]), ActionOutcome::Continue);
+        assert_eq!(acting(&game), vec![1]);
+        assert_eq!(lock_or_recover(&blackjack_state(&game)).phase, BlackjackPhase::Playing);
+        assert_eq!(dealer.get_token(), 10_100);
+    }
+
+    #[test]
+    fn acting_player_leaving_settles_the_hand() {
+        let (mut game, dealer, first, second) = table(BlackjackGameRules::new(10, 500));
+        {
+            let state = blackjack_state(&game);
+            let mut state = lock_or_recover(&state);
+            state.phase = BlackjackPhase::Playing;
+            state.dealer_cards = vec![card(Rank::King), card(Rank::Eight)];
+            let mut stood = hand(&[Rank::Ten, Rank::Nine], 100);
+            stood.finished = true;
+            state.seats = vec![seat(1, stood), seat(2, hand(&[Rank::Ten, Rank::Six], 100))];
+            state.acting = Some((1, 0));
+        }
+        first.take_token(100);
+        second.take_token(100);
+
+        assert_eq!(game.player_leave(vec![second.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(first.get_token(), 1100);
+        assert_eq!(second.get_token(), 900);
+        assert_eq!(dealer.get_token(), 10_000);
+    }
+
+    #[test]
+    fn soft_totals_and_dealer_soft_17() {
+        assert_eq!(hand_total(&[card(Rank::Ace), card(Rank::Six)]), (17, true));
+        assert_eq!(hand_total(&[card(Rank::Ace), card(Rank::Six), card(Rank::King)]), (17, false));
+        assert_eq!(hand_total(&[card(Rank::Ace), card(Rank::Ace), card(Rank::Nine)]), (21, true));
+        assert!(is_blackjack(&[card(Rank::Ace), card(Rank::Jack)]));
+        assert!(!is_blackjack(&[card(Rank::Seven), card(Rank::Seven), card(Rank::Seven)]));
+
+        let mut config = BlackjackGameRules::new(10, 500);
+        let soft_17 = [card(Rank::Ace), card(Rank::Six)];
+        assert!(!dealer_hits(config, &soft_17));
+        assert!(dealer_hits(config, &[card(Rank::Ten), card(Rank::Six)]));
+        config.dealer_hits_soft_17 = true;
+        assert!(dealer_hits(config, &soft_17));
+        assert!(!dealer_hits(config, &[card(Rank::Ten), card(Rank::Seven)]));
+    }
+
+    fn playing(hand: BlackjackHand) -> (BlackjackState, Shoe, Arc<Player>) {
+        let mut state = BlackjackState::default();
+        state.seats.push(seat(1, hand));
+        let mut shoe = Shoe::new(1, 75);
+        shoe.shuffle(&mut FairRng::new("server", &[], 0));
+        (state, shoe, player(1, PlayerRole::Player, GameProject::Blackjack, 1000))
+    }
+
+    #[test]
+    fn split_double_and_surrender_rules() {
+        let mut config = BlackjackGameRules::new(10, 500);
+        config.double_after_split = false;
+        let (mut state, mut shoe, bettor) = playing(hand(&[Rank::Eight, Rank::Eight], 100));
+        assert_eq!(play_hand(config, &mut state, &mut shoe, &bettor, 0, 0, BlackjackAction::Split), Ok(()));
+        let hands = &state.seats[0].hands;
+        assert_eq!(hands.len(), 2);
+        assert!(hands.iter().all(|hand| hand.cards.len() == 2 && hand.cards[0].rank == Rank::Eight && hand.bet == 100));
+        assert_eq!(bettor.get_token(), 900);
+        // 不允许分牌后加倍，分牌后的手牌也不能投降
+        assert!(play_hand(config, &mut state, &mut shoe, &bettor, 0, 0, BlackjackAction::Double).is_err());
+        assert!(play_hand(config, &mut state, &mut shoe, &bettor, 0, 1, BlackjackAction::Surrender).is_err());
+
+        let (mut state, mut shoe, bettor) = playing(hand(&[Rank::Five, Rank::Six], 100));
+        assert_eq!(play_hand(config, &mut state, &mut shoe, &bettor, 0, 0, BlackjackAction::Double), Ok(()));
+        let doubled = &state.seats[0].hands[0];
+        assert!(doubled.doubled && doubled.finished);
+        assert_eq!((doubled.cards.len(), doubled.bet, bettor.get_token()), (3, 200, 900));
+    }
+
+    #[test]
+    fn split_aces_get_one_card_each() {
+        let config = BlackjackGameRules::new(10, 500);
+        let (mut state, mut shoe, bettor) = playing(hand(&[Rank::Ace, Rank::Ace], 100));
+        assert_eq!(play_hand(config, &mut state, &mut shoe, &bettor, 0, 0, BlackjackAction::Split), Ok(()));
+        assert!(state.seats[0].hands.iter().all(|hand| hand.split_aces && hand.finished && hand.cards.len() == 2));
+        assert!(play_hand(config, &mut state, &mut shoe, &bettor, 0, 0, BlackjackAction::Hit).is_err());
+        assert!(play_hand(config, &mut state, &mut shoe, &bettor, 0, 1, BlackjackAction::Split).is_err());
+    }
+}
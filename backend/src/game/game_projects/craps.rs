@@ -0,0 +1,664 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::dice::dice::{dice_total, roll_dice, Dice, STANDARD_DICE_SIDES};
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, collect, commit_next_seed, commit_seed_on_join, find_player, pay, sync_action_players};
+
+/// 牌局状态在 game_context 中的 key，跨多次掷骰的注都保存在这里
+pub const CRAPS_STATE_KEY: &str = "craps_state";
+
+/// 一桌最多的下注玩家数
+pub const CRAPS_MAX_PLAYERS: usize = 16;
+
+/// 可以成为点数的号码
+pub const CRAPS_POINT_NUMBERS: [u8; 6] = [4, 5, 6, 8, 9, 10];
+
+/// 点数注的真实赔率：(点数, 分子, 分母)，反方向的赔率取倒数
+pub const CRAPS_TRUE_ODDS: [(u8, u32, u32); 6] = [(4, 2, 1), (5, 3, 2), (6, 6, 5), (8, 6, 5), (9, 3, 2), (10, 2, 1)];
+
+/// 买号注的赔率：(号码, 分子, 分母)
+pub const CRAPS_PLACE_PAYOUTS: [(u8, u32, u32); 6] = [(4, 9, 5), (5, 7, 5), (6, 7, 6), (8, 7, 6), (9, 7, 5), (10, 9, 5)];
+
+/// 难骰注的赔率：(号码, 赔率)
+pub const CRAPS_HARDWAY_PAYOUTS: [(u8, u32); 4] = [(4, 7), (6, 9), (8, 9), (10, 7)];
+
+/// 花旗骰的下注方式
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum CrapsBet {
+    PassLine,    // 过关线，只能在出场掷前下
+    DontPass,    // 不过关线，只能在出场掷前下
+    Come,        // 来注，只能在确定点数后下
+    DontCome,    // 不来注，只能在确定点数后下
+    Odds(u8),    // 给该点数上的过关线或来注加赔率注
+    LayOdds(u8), // 给该点数上的不过关线或不来注加赔率注
+    Place(u8),   // 买号，出场掷时不生效，赢后保留
+    Hardway(u8), // 难骰，出场掷时不生效，赢后保留
+}
+
+impl CrapsBet {
+    /// 押庄家输的一方(不过关线、不来注)
+    pub fn is_dont(&self) -> bool {
+        matches!(self, CrapsBet::DontPass | CrapsBet::DontCome)
+    }
+
+    /// 线注：过关线、不过关线、来注、不来注
+    pub fn is_line(&self) -> bool {
+        matches!(self, CrapsBet::PassLine | CrapsBet::DontPass | CrapsBet::Come | CrapsBet::DontCome)
+    }
+
+    /// 可以随时收回的注
+    pub fn is_removable(&self) -> bool {
+        matches!(self, CrapsBet::Place(_) | CrapsBet::Hardway(_))
+    }
+}
+
+/// 花旗骰玩家行动
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrapsAction {
+    Bet(Vec<(CrapsBet, u32)>), // 提交本次掷骰前的新注，空列表为不加注，所有玩家提交后掷骰
+    TakeDown(CrapsBet),        // 收回买号或难骰注
+}
+
+/// 每次掷骰的阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum CrapsPhase {
+    #[default]
+    Betting, // 等待下注
+    Settled, // 已掷骰并结算
+}
+
+/// 桌上的一注，线注的 point 为这一注自己的点数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrapsWager {
+    pub user_id: u32,
+    pub bet: CrapsBet,
+    pub stake: u32,
+    pub odds: u32,
+    pub point: Option<u8>,
+}
+
+/// 一注在某次掷骰中的结果
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum CrapsOutcome {
+    PointSet(u8), // 线注移到点数上
+    Won,
+    Lost,
+    Push,
+}
+
+/// 一注的结算记录，net 为正表示玩家赢
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrapsWagerResult {
+    pub user_id: u32,
+    pub bet: CrapsBet,
+    pub outcome: CrapsOutcome,
+    pub net: i32,
+}
+
+/// 花旗骰对局状态；每次掷骰是一局，桌面点数和未结算的注跨局保留
+#[derive(Debug, Default)]
+pub struct CrapsState {
+    pub phase: CrapsPhase,
+    pub dealer_user: Option<u32>,
+    pub point: Option<u8>, // None 为出场掷
+    pub wagers: Vec<CrapsWager>,
+    pub submitted: Vec<u32>, // 本次掷骰已提交下注的玩家
+    pub dice: Vec<Dice>,
+    pub results: Vec<CrapsWagerResult>,
+}
+
+impl CrapsState {
+    /// 还没提交下注的玩家
+    pub fn pending_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        if self.phase != CrapsPhase::Betting {
+            return Vec::new();
+        }
+        players.iter()
+            .filter(|player| player.get_player_role() == PlayerRole::Player)
+            .map(|player| player.get_user().get_id())
+            .filter(|user_id| !self.submitted.contains(user_id))
+            .collect()
+    }
+
+    /// 某位玩家还在桌上的注
+    pub fn wagers_of(&self, user_id: u32) -> Vec<CrapsWager> {
+        self.wagers.iter().filter(|wager| wager.user_id == user_id).copied().collect()
+    }
+
+    fn reset_roll(&mut self) {
+        self.phase = CrapsPhase::Betting;
+        self.dealer_user = None;
+        self.submitted.clear();
+        self.dice.clear();
+        self.results.clear();
+    }
+}
+
+impl ActingPlayers for CrapsState {
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players(players)
+    }
+}
+
+// 一注在本次掷骰中的处理：refund 为从已扣下的注额中退还的部分，winnings 为庄家另付的彩金
+#[derive(Debug, PartialEq, Eq)]
+enum Resolution {
+    Unchanged,
+    PointSet(u8),
+    Settled{refund: u32, winnings: u32, remove: bool},
+}
+
+/// 花旗骰规则配置，庄家由 PlayerRole::Dealer 的玩家担任，所有注额都与他的筹码结算
+#[derive(Debug, Clone, Copy)]
+pub struct CrapsGameRules {
+    pub max_odds: u32, // 赔率注上限为线注的倍数，不过关方按可赢的数额计算
+    pub min_bet: u32,
+    pub max_bet: u32,  // 每一注线注、买号、难骰的上限
+}
+
+impl CrapsGameRules {
+    pub fn new(min_bet: u32, max_bet: u32) -> Self {
+        CrapsGameRules{max_odds: 3, min_bet, max_bet}
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.min_bet == 0 || config.max_bet < config.min_bet {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|left, right, _| {
+                let total = |items: &Vec<&dyn GameItem>| -> u8 {
+                    items.iter().filter_map(|item| Dice::from_item(*item)).map(|dice| dice.get_value()).sum()
+                };
+                total(left) > total(right)
+            }),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_start),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_finish),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+// 每次掷骰开局：找出庄家，桌面点数和已有的注保持不变
+fn game_start(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<CrapsState>(&context, CRAPS_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_roll();
+
+    let dealer = players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer);
+    let player_count = players.iter().filter(|player| player.get_player_role() == PlayerRole::Player).count();
+    match dealer {
+        Some(dealer) if player_count > 0 && player_count <= CRAPS_MAX_PLAYERS => {
+            state.dealer_user = Some(dealer.get_user().get_id());
+        }
+        _ => state.phase = CrapsPhase::Settled,
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家加注或收回注，所有玩家提交后掷骰
+fn player_action(
+    config: CrapsGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<CrapsState>(&context, CRAPS_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(action) = action.downcast_ref::<CrapsAction>().cloned() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players(&players).contains(&user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+
+    match action {
+        CrapsAction::TakeDown(bet) => {
+            if !bet.is_removable() {
+                return ActionOutcome::Rejected("bet cannot be taken down".to_string());
+            }
+            let Some(index) = state.wagers.iter().position(|wager| wager.user_id == user_id && wager.bet == bet) else {
+                return ActionOutcome::Rejected("no such bet".to_string());
+            };
+            let wager = state.wagers.remove(index);
+            player.add_token(wager.stake as u16);
+            ActionOutcome::Continue
+        }
+        CrapsAction::Bet(bets) => {
+            // 先在副本上逐注校验并合并，全部通过后再扣筹码
+            let mut wagers = state.wagers.clone();
+            for (bet, amount) in bets.iter().copied() {
+                if let Err(reason) = place_bet(config, &mut wagers, state.point, user_id, bet, amount) {
+                    return ActionOutcome::Rejected(reason);
+                }
+            }
+            let total: u32 = bets.iter().map(|(_, amount)| amount).sum();
+            if total > player.get_token() as u32 {
+                return ActionOutcome::Rejected("not enough tokens".to_string());
+            }
+            player.take_token(total as u16);
+            state.wagers = wagers;
+            state.submitted.push(user_id);
+
+            let outcome = if state.pending_players(&players).is_empty() { ActionOutcome::GameComplete } else { ActionOutcome::Continue };
+            sync_action_players(&state, &players, &context);
+            outcome
+        }
+    }
+}
+
+// 把一注放到桌上，不合法时返回原因
+fn place_bet(
+    config: CrapsGameRules,
+    wagers: &mut Vec<CrapsWager>,
+    table_point: Option<u8>,
+    user_id: u32,
+    bet: CrapsBet,
+    amount: u32,
+) -> Result<(), String> {
+    if let CrapsBet::Odds(point) | CrapsBet::LayOdds(point) = bet {
+        let dont = matches!(bet, CrapsBet::LayOdds(_));
+        let wager = wagers.iter_mut()
+            .filter(|wager| wager.user_id == user_id && wager.bet.is_line() && wager.bet.is_dont() == dont && wager.point == Some(point))
+            .find(|wager| odds_within_limit(config, wager, wager.odds + amount))
+            .ok_or_else(|| "no line bet to back with these odds".to_string())?;
+        if amount == 0 {
+            return Err("odds must be positive".to_string());
+        }
+        wager.odds += amount;
+        return Ok(());
+    }
+
+    if amount < config.min_bet || amount > config.max_bet {
+        return Err(format!("bet must be between {} and {}", config.min_bet, config.max_bet));
+    }
+    let allowed = match bet {
+        CrapsBet::PassLine | CrapsBet::DontPass => table_point.is_none(),
+        CrapsBet::Come | CrapsBet::DontCome => table_point.is_some(),
+        CrapsBet::Place(number) => CRAPS_POINT_NUMBERS.contains(&number),
+        CrapsBet::Hardway(number) => CRAPS_HARDWAY_PAYOUTS.iter().any(|(hard, _)| *hard == number),
+        CrapsBet::Odds(_) | CrapsBet::LayOdds(_) => false,
+    };
+    if !allowed {
+        return Err(format!("bet {:?} is not allowed now", bet));
+    }
+    // 同一位玩家的买号、难骰和尚未移到点数上的线注合并成一注
+    let existing = wagers.iter_mut().find(|wager| wager.user_id == user_id && wager.bet == bet && wager.point.is_none());
+    match existing {
+        Some(wager) if wager.stake + amount <= config.max_bet => wager.stake += amount,
+        Some(_) => return Err(format!("bet must be between {} and {}", config.min_bet, config.max_bet)),
+        None => wagers.push(CrapsWager{user_id, bet, stake: amount, odds: 0, point: None}),
+    }
+    Ok(())
+}
+
+// 赔率注是否超过上限：过关方按注额，不过关方按可赢的数额
+fn odds_within_limit(config: CrapsGameRules, wager: &CrapsWager, odds: u32) -> bool {
+    let limit = wager.stake * config.max_odds;
+    match wager.point {
+        Some(point) if wager.bet.is_dont() => odds_winnings(point, odds, true) <= limit,
+        Some(_) => odds <= limit,
+        None => false,
+    }
+}
+
+/// 赔率注按真实赔率赢得的彩金，dont 为不过关方，不足一个筹码的部分舍去
+pub fn odds_winnings(point: u8, odds: u32, dont: bool) -> u32 {
+    let Some((_, numerator, denominator)) = CRAPS_TRUE_ODDS.iter().find(|(number, _, _)| *number == point) else {
+        return 0;
+    };
+    if dont { odds * denominator / numerator } else { odds * numerator / denominator }
+}
+
+// 下注结束后才开始本次的随机数并掷骰，随即公开种子，然后结算所有受影响的注并更新桌面点数
+fn game_finish(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<CrapsState>(&context, CRAPS_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == CrapsPhase::Settled {
+        return;
+    }
+    state.phase = CrapsPhase::Settled;
+
+    let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    let mut fairness = lock_or_recover(&fairness);
+    let mut rng = fairness.begin_round(&seat_order);
+    state.dice = roll_dice(&mut rng, 2, STANDARD_DICE_SIDES);
+    fairness.reveal();
+    drop(fairness);
+    *lock_or_recover(&game_items) = state.dice.iter().map(|dice| Arc::new(*dice) as Arc<dyn GameItem>).collect();
+
+    let total = dice_total(&state.dice);
+    let hard = state.dice[0] == state.dice[1];
+    let table_point = state.point;
+    let dealer = state.dealer_user.and_then(|user_id| find_player(&players, user_id));
+
+    let mut results = Vec::new();
+    let mut remaining = Vec::new();
+    for mut wager in std::mem::take(&mut state.wagers) {
+        let outcome = match resolve(&wager, table_point, total, hard) {
+            Resolution::Unchanged => None,
+            Resolution::PointSet(point) => {
+                wager.point = Some(point);
+                Some((CrapsOutcome::PointSet(point), 0))
+            }
+            Resolution::Settled{refund, winnings, remove} => {
+                let escrow = if remove { wager.stake + wager.odds } else { 0 };
+                let paid = match find_player(&players, wager.user_id) {
+                    Some(player) => {
+                        player.add_token(refund as u16);
+                        pay(&dealer, &player, winnings)
+                    }
+                    None => 0,
+                };
+                collect(&dealer, escrow - refund);
+                let outcome = if winnings > 0 {
+                    CrapsOutcome::Won
+                } else if refund == escrow {
+                    CrapsOutcome::Push
+                } else {
+                    CrapsOutcome::Lost
+                };
+                if !remove {
+                    remaining.push(wager);
+                }
+                results.push(CrapsWagerResult{user_id: wager.user_id, bet: wager.bet, outcome, net: paid as i32 + refund as i32 - escrow as i32});
+                continue;
+            }
+        };
+        if let Some((outcome, net)) = outcome {
+            results.push(CrapsWagerResult{user_id: wager.user_id, bet: wager.bet, outcome, net});
+        }
+        remaining.push(wager);
+    }
+    state.wagers = remaining;
+    state.results = results;
+
+    // 出场掷出点数号码时确定点数；点数或 7 出现后回到出场掷
+    state.point = match table_point {
+        None if CRAPS_POINT_NUMBERS.contains(&total) => Some(total),
+        Some(point) if total == point || total == 7 => None,
+        point => point,
+    };
+    sync_action_players(&state, &players, &context);
+}
+
+// 按掷出的点数决定一注的去向
+fn resolve(wager: &CrapsWager, table_point: Option<u8>, total: u8, hard: bool) -> Resolution {
+    let flat_and_odds = wager.stake + wager.odds;
+    let lose = Resolution::Settled{refund: 0, winnings: 0, remove: true};
+    match wager.bet {
+        CrapsBet::PassLine | CrapsBet::Come => match wager.point {
+            None => match total {
+                7 | 11 => Resolution::Settled{refund: wager.stake, winnings: wager.stake, remove: true},
+                2 | 3 | 12 => lose,
+                point => Resolution::PointSet(point),
+            },
+            // 来注的赔率注在出场掷时不生效，结算时原数退回
+            Some(point) if table_point.is_none() && wager.bet == CrapsBet::Come && (total == point || total == 7) => {
+                let flat_won = if total == point { wager.stake } else { 0 };
+                Resolution::Settled{refund: wager.odds + flat_won, winnings: flat_won, remove: true}
+            }
+            Some(point) if total == point => Resolution::Settled {
+                refund: flat_and_odds,
+                winnings: wager.stake + odds_winnings(point, wager.odds, false),
+                remove: true,
+            },
+            Some(_) if total == 7 => lose,
+            Some(_) => Resolution::Unchanged,
+        },
+        CrapsBet::DontPass | CrapsBet::DontCome => match wager.point {
+            None => match total {
+                2 | 3 => Resolution::Settled{refund: wager.stake, winnings: wager.stake, remove: true},
+                12 => Resolution::Settled{refund: wager.stake, winnings: 0, remove: true},
+                7 | 11 => lose,
+                point => Resolution::PointSet(point),
+            },
+            Some(point) if total == 7 => Resolution::Settled {
+                refund: flat_and_odds,
+                winnings: wager.stake + odds_winnings(point, wager.odds, true),
+                remove: true,
+            },
+            Some(point) if total == point => lose,
+            Some(_) => Resolution::Unchanged,
+        },
+        // 买号和难骰在出场掷时不生效
+        _ if table_point.is_none() => Resolution::Unchanged,
+        CrapsBet::Place(number) => match total {
+            7 => lose,
+            total if total == number => {
+                let (_, numerator, denominator) = CRAPS_PLACE_PAYOUTS.iter()
+                    .find(|(place, _, _)| *place == number)
+                    .copied()
+                    .unwrap_or((number, 1, 1));
+                Resolution::Settled{refund: 0, winnings: wager.stake * numerator / denominator, remove: false}
+            }
+            _ => Resolution::Unchanged,
+        },
+        CrapsBet::Hardway(number) => match total {
+            7 => lose,
+            total if total == number && hard => {
+                let payout = CRAPS_HARDWAY_PAYOUTS.iter().find(|(hardway, _)| *hardway == number).map(|(_, payout)| *payout).unwrap_or(0);
+                Resolution::Settled{refund: 0, winnings: wager.stake * payout, remove: false}
+            }
+            total if total == number => lose,
+            _ => Resolution::Unchanged,
+        },
+        CrapsBet::Odds(_) | CrapsBet::LayOdds(_) => Resolution::Unchanged,
+    }
+}
+
+// 离桌玩家的注：还没移到点数上的线注、赔率注、买号和难骰退回，已有点数的线注归庄家；
+// 剩下的玩家都已提交时照常掷骰
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<CrapsState>(&context, CRAPS_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let dealer = current_players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer).cloned();
+
+    for player in leave_players {
+        let user_id = player.get_user().get_id();
+        for wager in state.wagers_of(user_id) {
+            let forfeited = if wager.bet.is_line() && wager.point.is_some() { wager.stake } else { 0 };
+            player.add_token((wager.stake + wager.odds - forfeited) as u16);
+            collect(&dealer, forfeited);
+        }
+        state.wagers.retain(|wager| wager.user_id != user_id);
+        state.submitted.retain(|submitted| *submitted != user_id);
+    }
+
+    // 还没开局时只退注，不掷骰
+    let outcome = if state.dealer_user.is_some() && state.phase == CrapsPhase::Betting
+        && state.pending_players(&current_players).is_empty() {
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+
+    #[test]
+    fn odds_pay_true_odds() {
+        assert_eq!(odds_winnings(4, 10, false), 20);
+        assert_eq!(odds_winnings(5, 10, false), 15);
+        assert_eq!(odds_winnings(6, 10, false), 12);
+        assert_eq!(odds_winnings(4, 20, true), 10);
+        assert_eq!(odds_winnings(6, 12, true), 10);
+        assert_eq!(odds_winnings(7, 10, false), 0);
+    }
+
+    fn wager(bet: CrapsBet, stake: u32, odds: u32, point: Option<u8>) -> CrapsWager {
+        CrapsWager{user_id: 1, bet, stake, odds, point}
+    }
+
+    fn settled(refund: u32, winnings: u32, remove: bool) -> Resolution {
+        Resolution::Settled{refund, winnings, remove}
+    }
+
+    #[test]
+    fn line_bets_on_the_come_out_and_the_point() {
+        let pass = wager(CrapsBet::PassLine, 100, 0, None);
+        assert_eq!(resolve(&pass, None, 7, false), settled(100, 100, true));
+        assert_eq!(resolve(&pass, None, 11, false), settled(100, 100, true));
+        assert_eq!(resolve(&pass, None, 2, true), settled(0, 0, true));
+        assert_eq!(resolve(&pass, None, 6, false), Resolution::PointSet(6));
+
+        let pass = wager(CrapsBet::PassLine, 100, 50, Some(6));
+        assert_eq!(resolve(&pass, Some(6), 6, false), settled(150, 160, true));
+        assert_eq!(resolve(&pass, Some(6), 7, false), settled(0, 0, true));
+        assert_eq!(resolve(&pass, Some(6), 8, false), Resolution::Unchanged);
+
+        let dont = wager(CrapsBet::DontPass, 100, 0, None);
+        assert_eq!(resolve(&dont, None, 3, false), settled(100, 100, true));
+        assert_eq!(resolve(&dont, None, 12, true), settled(100, 0, true));
+        assert_eq!(resolve(&dont, None, 11, false), settled(0, 0, true));
+        let dont = wager(CrapsBet::DontPass, 100, 40, Some(4));
+        assert_eq!(resolve(&dont, Some(4), 7, false), settled(140, 120, true));
+        assert_eq!(resolve(&dont, Some(4), 4, true), settled(0, 0, true));
+    }
+
+    #[test]
+    fn come_odds_are_off_on_the_come_out() {
+        let come = wager(CrapsBet::Come, 100, 30, Some(5));
+        assert_eq!(resolve(&come, Some(8), 5, false), settled(130, 145, true));
+        // 新一轮出场掷时来注本身照常结算，赔率注原数退回
+        assert_eq!(resolve(&come, None, 5, false), settled(130, 100, true));
+        assert_eq!(resolve(&come, None, 7, false), settled(30, 0, true));
+    }
+
+    #[test]
+    fn place_and_hardway_bets_stay_up_after_winning() {
+        let place = wager(CrapsBet::Place(6), 30, 0, None);
+        assert_eq!(resolve(&place, None, 6, false), Resolution::Unchanged);
+        assert_eq!(resolve(&place, Some(4), 6, false), settled(0, 35, false));
+        assert_eq!(resolve(&place, Some(4), 7, false), settled(0, 0, true));
+
+        let hard = wager(CrapsBet::Hardway(8), 10, 0, None);
+        assert_eq!(resolve(&hard, Some(5), 8, true), settled(0, 90, false));
+        assert_eq!(resolve(&hard, Some(5), 8, false), settled(0, 0, true));
+        assert_eq!(resolve(&hard, Some(5), 6, true), Resolution::Unchanged);
+    }
+
+    #[test]
+    fn bets_must_fit_the_table_point() {
+        let config = CrapsGameRules::new(10, 500);
+        let mut wagers = Vec::new();
+        assert!(place_bet(config, &mut wagers, None, 1, CrapsBet::Come, 100).is_err());
+        assert!(place_bet(config, &mut wagers, Some(6), 1, CrapsBet::PassLine, 100).is_err());
+        assert!(place_bet(config, &mut wagers, None, 1, CrapsBet::Place(7), 100).is_err());
+        assert!(place_bet(config, &mut wagers, None, 1, CrapsBet::Odds(6), 100).is_err());
+
+        wagers.push(wager(CrapsBet::PassLine, 100, 0, Some(6)));
+        assert_eq!(place_bet(config, &mut wagers, Some(6), 1, CrapsBet::Odds(6), 300), Ok(()));
+        assert!(place_bet(config, &mut wagers, Some(6), 1, CrapsBet::Odds(6), 1).is_err());
+        assert_eq!(wagers[0].odds, 300);
+    }
+
+    #[test]
+    fn line_bet_rides_across_rolls_until_the_point_resolves() {
+        let mut game = game(CrapsGameRules::new(10, 500).build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Craps, 10_000);
+        let shooter = player(1, PlayerRole::Player, GameProject::Craps, 1000);
+        game.player_join(vec![dealer.clone(), shooter.clone()]);
+        let mut bets = vec![(CrapsBet::PassLine, 100)];
+        for _ in 0..200 {
+            game.game_start();
+            let state = get_state::<CrapsState>(&game.get_game_context(), CRAPS_STATE_KEY).unwrap();
+            let point = lock_or_recover(&state).point;
+            let outcome = game.player_action(shooter.clone(), Arc::new(CrapsAction::Bet(std::mem::take(&mut bets))));
+            assert_eq!(outcome, ActionOutcome::GameComplete);
+            assert_eq!(shooter.get_token() as u32 + dealer.get_token() as u32 + lock_or_recover(&state).wagers.iter().map(|wager| wager.stake).sum::<u32>(), 11_000);
+            let state = lock_or_recover(&state);
+            match (point, state.point) {
+                // 确定点数后线注留在桌上，之后每次掷骰只提交空的下注
+                (None, Some(new_point)) => {
+                    assert_eq!(state.wagers, vec![CrapsWager{user_id: 1, bet: CrapsBet::PassLine, stake: 100, odds: 0, point: Some(new_point)}]);
+                }
+                (Some(_), Some(_)) => assert_eq!(state.wagers.len(), 1),
+                (_, None) => {
+                    assert!(state.wagers.is_empty());
+                    if point.is_some() {
+                        return;
+                    }
+                    bets = vec![(CrapsBet::PassLine, 100)];
+                }
+            }
+            drop(state);
+            game.game_wait_start();
+        }
+        panic!("the point never resolved");
+    }
+
+    #[test]
+    fn last_pending_player_leaving_rolls_the_dice() {
+        let mut game = game(CrapsGameRules::new(10, 500).build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Craps, 10_000);
+        let shooter = player(1, PlayerRole::Player, GameProject::Craps, 1000);
+        let leaver = player(2, PlayerRole::Player, GameProject::Craps, 1000);
+        game.player_join(vec![dealer, shooter.clone(), leaver.clone()]);
+        game.game_start();
+        let outcome = game.player_action(shooter, Arc::new(CrapsAction::Bet(vec![(CrapsBet::PassLine, 100)])));
+        assert_eq!(outcome, ActionOutcome::Continue);
+
+        assert_eq!(game.player_leave(vec![leaver.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        let state = get_state::<CrapsState>(&game.get_game_context(), CRAPS_STATE_KEY).unwrap();
+        assert_eq!(lock_or_recover(&state).dice.len(), 2);
+        assert_eq!(leaver.get_token(), 1000);
+    }
+}
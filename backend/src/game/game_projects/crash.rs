@@ -0,0 +1,540 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::hash_chain::{verify_chain_link, HashChain};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, collect, find_player, pay, sync_action_players};
+use crate::timer::timer::CBTimesMethod;
+
+/// 对局状态在 game_context 中的 key
+pub const CRASH_STATE_KEY: &str = "crash_state";
+
+/// 哈希链在 game_context 中的 key，链跨局保留，用完后重新生成
+pub const CRASH_CHAIN_KEY: &str = "crash_chain";
+
+/// 一局最多的下注玩家数
+pub const CRASH_MAX_PLAYERS: usize = 64;
+
+/// 倍数以百分之一为单位，100 即 1.00x
+pub const CRASH_MULTIPLIER_BASE: u32 = 100;
+
+/// 崩盘玩家行动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashAction {
+    Bet{amount: u32, auto_cash_out: Option<u32>}, // 下注，0 为本局不参与；auto_cash_out 为自动兑现倍数(百分之一)
+    CashOut,                                      // 按当前倍数兑现
+}
+
+/// 对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum CrashPhase {
+    #[default]
+    Betting, // 等待下注
+    Running, // 倍数随定时器上升
+    Crashed, // 已崩盘并结算
+}
+
+/// 一位玩家本局的下注
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrashBet {
+    pub stake: u32,
+    pub auto_cash_out: Option<u32>,
+    pub cashed_out: Option<u32>, // 兑现时的倍数
+    pub net: i32,
+    settled: bool,
+}
+
+impl CrashBet {
+    /// 还在等待兑现或崩盘的下注
+    pub fn is_live(&self) -> bool {
+        self.stake > 0 && !self.settled
+    }
+}
+
+/// 崩盘对局状态，保存在 game_context 中；本局的哈希和崩盘点在崩盘前不可见
+#[derive(Debug, Default)]
+pub struct CrashState {
+    pub phase: CrashPhase,
+    pub dealer_user: Option<u32>,
+    pub terminal_hash: String, // 当前哈希链公布的终点哈希
+    pub tick: u32,
+    pub multiplier: u32,
+    pub bets: HashMap<u32, CrashBet>,
+    game_hash: Option<String>,
+    crash_point: u32,
+}
+
+impl CrashState {
+    /// 崩盘后公开的本局哈希
+    pub fn get_game_hash(&self) -> Option<&str> {
+        if self.phase != CrashPhase::Crashed {
+            return None;
+        }
+        self.game_hash.as_deref()
+    }
+
+    /// 崩盘后公开的崩盘点
+    pub fn get_crash_point(&self) -> Option<u32> {
+        (self.phase == CrashPhase::Crashed && self.game_hash.is_some()).then_some(self.crash_point)
+    }
+
+    /// 还需要行动的玩家：下注阶段为未下注的玩家，飞行阶段为可以兑现的玩家
+    pub fn pending_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        match self.phase {
+            CrashPhase::Betting => players.iter()
+                .filter(|player| player.get_player_role() == PlayerRole::Player)
+                .map(|player| player.get_user().get_id())
+                .filter(|user_id| !self.bets.contains_key(user_id))
+                .collect(),
+            CrashPhase::Running => players.iter()
+                .map(|player| player.get_user().get_id())
+                .filter(|user_id| self.bets.get(user_id).is_some_and(|bet| bet.is_live()))
+                .collect(),
+            CrashPhase::Crashed => Vec::new(),
+        }
+    }
+
+    fn has_live_bets(&self) -> bool {
+        self.bets.values().any(|bet| bet.is_live())
+    }
+
+    fn reset_round(&mut self) {
+        self.phase = CrashPhase::Betting;
+        self.dealer_user = None;
+        self.tick = 0;
+        self.multiplier = CRASH_MULTIPLIER_BASE;
+        self.bets.clear();
+        self.game_hash = None;
+        self.crash_point = CRASH_MULTIPLIER_BASE;
+    }
+}
+
+impl ActingPlayers for CrashState {
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players(players)
+    }
+}
+
+/// 崩盘规则配置，庄家由 PlayerRole::Dealer 的玩家担任，兑现的彩金和崩盘输掉的注额都与他的筹码结算
+#[derive(Debug, Clone, Copy)]
+pub struct CrashGameRules {
+    pub house_edge_bps: u32,      // 庄家优势，万分之一为单位，100 即 1%
+    pub tick: Duration,           // 定时器推进倍数的周期
+    pub growth_bps_per_tick: u32, // 每个周期倍数增长的比例，万分之一为单位
+    pub chain_length: usize,      // 每条哈希链的局数
+    pub min_bet: u32,
+    pub max_bet: u32,
+}
+
+impl CrashGameRules {
+    pub fn new(min_bet: u32, max_bet: u32) -> Self {
+        CrashGameRules {
+            house_edge_bps: 100,
+            tick: Duration::from_millis(100),
+            growth_bps_per_tick: 60,
+            chain_length: 10_000,
+            min_bet,
+            max_bet,
+        }
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule，倍数由对局定时器推进
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.house_edge_bps >= 10_000 || config.tick.is_zero() || config.growth_bps_per_tick == 0
+            || config.chain_length == 0 || config.min_bet == 0 || config.max_bet < config.min_bet {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|_, _, _| false),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_start(config, players, game_items, context)),
+            Arc::new(game_progress),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_finish),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_timeout(config, players, game_items, context)),
+            Some(config.tick),
+            Some(CBTimesMethod::Multi),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+/// 由本局哈希计算崩盘点(百分之一)：取前 52 位 h，崩盘点 = (1 - 庄家优势) / (1 - h / 2^52)，不足 1.00x 时按 1.00x 计
+///
+/// 这样倍数达到 m 的概率为 (1 - 庄家优势) / m，任何兑现策略的期望返还都是 1 - 庄家优势
+pub fn crash_point(game_hash: &str, house_edge_bps: u32) -> u32 {
+    let prefix = game_hash.get(..13).unwrap_or("0");
+    let h = u64::from_str_radix(prefix, 16).unwrap_or(0) as u128;
+    let e = 1u128 << 52;
+    let point = (10_000 - house_edge_bps.min(10_000)) as u128 * e / ((e - h) * 100);
+    point.clamp(CRASH_MULTIPLIER_BASE as u128, u32::MAX as u128) as u32
+}
+
+/// 第 tick 个周期时的倍数(百分之一)
+pub fn multiplier_at(tick: u32, growth_bps_per_tick: u32) -> u32 {
+    let growth = 1.0 + growth_bps_per_tick as f64 / 10_000.0;
+    (CRASH_MULTIPLIER_BASE as f64 * growth.powi(tick.min(i32::MAX as u32) as i32)).min(u32::MAX as f64) as u32
+}
+
+/// 校验公开的某局哈希属于公布的哈希链，返回它是链上第几局以及对应的崩盘点
+pub fn verify_crash_round(terminal_hash: &str, game_hash: &str, max_steps: usize, house_edge_bps: u32) -> Option<(usize, u32)> {
+    let index = verify_chain_link(terminal_hash, game_hash, max_steps)?;
+    Some((index, crash_point(game_hash, house_edge_bps)))
+}
+
+// 开局：找出庄家，从哈希链取出本局哈希并算出崩盘点，等待下注
+fn game_start(
+    config: CrashGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<CrashState>(&context, CRASH_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_round();
+
+    let dealer = players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer);
+    let player_count = players.iter().filter(|player| player.get_player_role() == PlayerRole::Player).count();
+    let Some(dealer) = dealer else {
+        state.phase = CrashPhase::Crashed;
+        return;
+    };
+    if player_count == 0 || player_count > CRASH_MAX_PLAYERS {
+        state.phase = CrashPhase::Crashed;
+        return;
+    }
+    state.dealer_user = Some(dealer.get_user().get_id());
+
+    let chain = get_or_insert_state::<HashChain>(&context, CRASH_CHAIN_KEY);
+    let mut chain = lock_or_recover(&chain);
+    if chain.remaining() == 0 {
+        *chain = HashChain::generate(config.chain_length);
+    }
+    state.terminal_hash = chain.get_terminal_hash().to_string();
+    if let Some(game_hash) = chain.next_hash() {
+        state.crash_point = crash_point(&game_hash, config.house_edge_bps);
+        state.game_hash = Some(game_hash);
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家下注或兑现
+fn player_action(
+    config: CrashGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<CrashState>(&context, CRASH_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(action) = action.downcast_ref::<CrashAction>().copied() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players(&players).contains(&user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+
+    let outcome = match (state.phase, action) {
+        (CrashPhase::Betting, CrashAction::Bet{amount, auto_cash_out}) => {
+            if amount != 0 && (amount < config.min_bet || amount > config.max_bet) {
+                return ActionOutcome::Rejected(format!("bet must be between {} and {}", config.min_bet, config.max_bet));
+            }
+            if auto_cash_out.is_some_and(|target| target <= CRASH_MULTIPLIER_BASE) {
+                return ActionOutcome::Rejected("auto cash out must be above 1.00x".to_string());
+            }
+            if amount > player.get_token() as u32 {
+                return ActionOutcome::Rejected("not enough tokens".to_string());
+            }
+            player.take_token(amount as u16);
+            state.bets.insert(user_id, CrashBet{stake: amount, auto_cash_out, cashed_out: None, net: 0, settled: false});
+            round_outcome(&state, &players)
+        }
+        (CrashPhase::Running, CrashAction::CashOut) => {
+            let dealer = find_dealer(&state, &players);
+            let multiplier = state.multiplier;
+            if let Some(bet) = state.bets.get_mut(&user_id) {
+                cash_out(bet, &dealer, &player, multiplier);
+            }
+            round_outcome(&state, &players)
+        }
+        _ => return ActionOutcome::Rejected("action does not match the current phase".to_string()),
+    };
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 下注阶段所有人下注后开始飞行，无人下注、全部兑现或崩盘后结算
+fn round_outcome(state: &CrashState, players: &[Arc<Player>]) -> ActionOutcome {
+    match state.phase {
+        CrashPhase::Betting if !state.pending_players(players).is_empty() => ActionOutcome::Continue,
+        CrashPhase::Betting if state.has_live_bets() => ActionOutcome::RoundComplete,
+        CrashPhase::Running if state.has_live_bets() => ActionOutcome::Continue,
+        _ => ActionOutcome::GameComplete,
+    }
+}
+
+// 下注结束，开始飞行；崩盘点为 1.00x 时立即崩盘
+fn game_progress(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<CrashState>(&context, CRASH_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase != CrashPhase::Betting {
+        return;
+    }
+    state.phase = CrashPhase::Running;
+    state.tick = 0;
+    state.multiplier = CRASH_MULTIPLIER_BASE;
+    if state.crash_point <= CRASH_MULTIPLIER_BASE {
+        crash(&mut state, &players);
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 定时器每个周期推进一次倍数：先处理达到目标的自动兑现，倍数到达崩盘点时崩盘并通知 Game 结算
+fn game_timeout(
+    config: CrashGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<CrashState>(&context, CRASH_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    match state.phase {
+        CrashPhase::Betting => return ActionOutcome::Continue,
+        // 开局时没有庄家或玩家，或者开始飞行即崩盘
+        CrashPhase::Crashed => return ActionOutcome::GameComplete,
+        CrashPhase::Running => {}
+    }
+    state.tick += 1;
+    let multiplier = multiplier_at(state.tick, config.growth_bps_per_tick);
+    let crash_point = state.crash_point;
+    let dealer = find_dealer(&state, &players);
+
+    // 自动兑现按玩家设定的倍数成交，目标必须低于崩盘点
+    for player in players.iter() {
+        let Some(bet) = state.bets.get_mut(&player.get_user().get_id()) else {
+            continue;
+        };
+        if let Some(target) = bet.auto_cash_out.filter(|target| *target <= multiplier && *target < crash_point) {
+            if bet.is_live() {
+                cash_out(bet, &dealer, player, target);
+            }
+        }
+    }
+
+    if multiplier >= crash_point {
+        crash(&mut state, &players);
+    } else {
+        state.multiplier = multiplier;
+    }
+    sync_action_players(&state, &players, &context);
+    round_outcome(&state, &players)
+}
+
+// 结算：没来得及兑现的下注全部输掉
+fn game_finish(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<CrashState>(&context, CRASH_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase != CrashPhase::Crashed {
+        crash(&mut state, &players);
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 崩盘：倍数停在崩盘点，未兑现的注额归庄家
+fn crash(state: &mut CrashState, players: &[Arc<Player>]) {
+    let dealer = find_dealer(state, players);
+    state.phase = CrashPhase::Crashed;
+    state.multiplier = state.multiplier.max(state.crash_point);
+    for bet in state.bets.values_mut().filter(|bet| bet.is_live()) {
+        collect(&dealer, bet.stake);
+        bet.net = -(bet.stake as i32);
+        bet.settled = true;
+    }
+}
+
+// 按倍数兑现：退回本金，彩金由庄家支付
+fn cash_out(bet: &mut CrashBet, dealer: &Option<Arc<Player>>, player: &Player, multiplier: u32) {
+    let payout = (bet.stake as u64 * multiplier as u64 / CRASH_MULTIPLIER_BASE as u64).min(u32::MAX as u64) as u32;
+    player.add_token(bet.stake as u16);
+    let paid = pay(dealer, player, payout.saturating_sub(bet.stake));
+    bet.cashed_out = Some(multiplier);
+    bet.net = paid as i32;
+    bet.settled = true;
+}
+
+fn find_dealer(state: &CrashState, players: &[Arc<Player>]) -> Option<Arc<Player>> {
+    find_player(players, state.dealer_user?)
+}
+
+// 下注阶段离开的玩家退回注额，飞行中离开的玩家按当前倍数兑现；离开后无需再等待时通知 Game 推进
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<CrashState>(&context, CRASH_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let dealer = find_dealer(&state, &current_players);
+    let phase = state.phase;
+    let multiplier = state.multiplier;
+
+    for player in leave_players {
+        let user_id = player.get_user().get_id();
+        match phase {
+            CrashPhase::Betting => {
+                if let Some(bet) = state.bets.remove(&user_id) {
+                    player.add_token(bet.stake as u16);
+                }
+            }
+            CrashPhase::Running => {
+                if let Some(bet) = state.bets.get_mut(&user_id).filter(|bet| bet.is_live()) {
+                    cash_out(bet, &dealer, &player, multiplier);
+                }
+            }
+            CrashPhase::Crashed => {}
+        }
+    }
+    sync_action_players(&state, &current_players, &context);
+    round_outcome(&state, &current_players)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_context::get_state;
+    use std::thread;
+    use crate::game::game::Game;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+
+    fn table(bettors: u32) -> (Game, Arc<Player>, Vec<Arc<Player>>) {
+        let mut config = CrashGameRules::new(10, 1000);
+        config.tick = Duration::from_millis(1);
+        config.growth_bps_per_tick = 10_000;
+        let mut game = game(config.build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Crash, 50_000);
+        let players: Vec<Arc<Player>> = (1..=bettors).map(|id| player(id, PlayerRole::Player, GameProject::Crash, 1000)).collect();
+        game.player_join(vec![dealer.clone()]);
+        game.player_join(players.clone());
+        game.game_start();
+        (game, dealer, players)
+    }
+
+    fn crash_state(game: &Game) -> Arc<Mutex<CrashState>> {
+        get_state::<CrashState>(&game.get_game_context(), CRASH_STATE_KEY).unwrap()
+    }
+
+    #[test]
+    fn crash_point_follows_hash_prefix() {
+        // h = 0 时低于 1.00x，按 1.00x 计
+        assert_eq!(crash_point(&"0".repeat(64), 100), 100);
+        // h = 2^51 时为 0.99 / 0.5
+        assert_eq!(crash_point(&format!("8{}", "0".repeat(63)), 100), 198);
+        assert_eq!(crash_point(&format!("8{}", "0".repeat(63)), 0), 200);
+        assert_eq!(crash_point(&format!("c{}", "0".repeat(63)), 0), 400);
+        assert_eq!(multiplier_at(0, 60), 100);
+        assert_eq!(multiplier_at(1, 10_000), 200);
+    }
+
+    #[test]
+    fn published_round_verifies_against_chain() {
+        let mut chain = HashChain::from_seed("crash", 50);
+        let terminal_hash = chain.get_terminal_hash().to_string();
+        for index in 0..50 {
+            let game_hash = chain.next_hash().unwrap();
+            assert_eq!(verify_crash_round(&terminal_hash, &game_hash, 50, 100), Some((index, crash_point(&game_hash, 100))));
+        }
+        assert_eq!(verify_crash_round(&terminal_hash, &"0".repeat(64), 50, 100), None);
+    }
+
+    #[test]
+    fn timer_crash_finishes_game() {
+        let (mut game, dealer, players) = table(2);
+        assert_eq!(game.player_action(players[0].clone(), Arc::new(CrashAction::Bet{amount: 100, auto_cash_out: None})), ActionOutcome::Continue);
+        assert_eq!(game.player_action(players[1].clone(), Arc::new(CrashAction::Bet{amount: 50, auto_cash_out: None})), ActionOutcome::RoundComplete);
+
+        for _ in 0..1000 {
+            if game.get_game_state() == GameState::Finished {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+            game.update_timers();
+        }
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        let state = crash_state(&game);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.phase, CrashPhase::Crashed);
+        assert!(state.get_crash_point().is_some());
+        assert!(state.bets.values().all(|bet| bet.net == -(bet.stake as i32)));
+        assert_eq!(dealer.get_token() as u32 + players.iter().map(|player| player.get_token() as u32).sum::<u32>(), 52_000);
+    }
+
+    #[test]
+    fn actions_after_crash_are_rejected() {
+        let (mut game, _, players) = table(1);
+        lock_or_recover(&crash_state(&game)).phase = CrashPhase::Crashed;
+        let outcome = game.player_action(players[0].clone(), Arc::new(CrashAction::CashOut));
+        assert!(matches!(outcome, ActionOutcome::Rejected(_)));
+        assert_eq!(game.get_game_state(), GameState::InProgress);
+    }
+
+    #[test]
+    fn last_unbet_player_leaving_starts_the_round() {
+        let (mut game, _, players) = table(2);
+        game.player_action(players[0].clone(), Arc::new(CrashAction::Bet{amount: 100, auto_cash_out: None}));
+        assert_eq!(game.player_leave(vec![players[1].clone()]), ActionOutcome::RoundComplete);
+        // 本局开始即崩盘时直接进入 Crashed，由下一次定时器结算
+        assert_ne!(lock_or_recover(&crash_state(&game)).phase, CrashPhase::Betting);
+    }
+
+    #[test]
+    fn last_live_bettor_leaving_finishes_game() {
+        let (mut game, dealer, players) = table(1);
+        game.player_action(players[0].clone(), Arc::new(CrashAction::Bet{amount: 100, auto_cash_out: None}));
+        if lock_or_recover(&crash_state(&game)).phase != CrashPhase::Running {
+            // 本局开始即崩盘
+            return;
+        }
+        assert_eq!(game.player_leave(vec![players[0].clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(players[0].get_token() as u32 + dealer.get_token() as u32, 51_000);
+    }
+}
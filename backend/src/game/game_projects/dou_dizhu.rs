@@ -0,0 +1,609 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::dou_dizhu::combination::{parse_combination, Combination};
+use crate::game::game_items::dou_dizhu::dou_dizhu_card::{get_dou_dizhu_cards, DouDizhuCard};
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, commit_next_seed, commit_seed_on_join, sync_action_players};
+
+/// 牌局状态在 game_context 中的 key
+pub const DOU_DIZHU_STATE_KEY: &str = "dou_dizhu_state";
+
+/// 斗地主固定三人
+pub const DOU_DIZHU_PLAYERS: usize = 3;
+
+/// 每人发牌张数，剩余 3 张为底牌
+pub const DOU_DIZHU_HAND_SIZE: usize = 17;
+
+/// 斗地主对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum DouDizhuPhase {
+    #[default]
+    Bidding,  // 叫分抢地主
+    Playing,  // 出牌
+    Finished, // 有人出完牌，或无人叫分、有人离桌导致本局作废
+}
+
+/// 春天：地主赢且农民一张牌没出；反春：农民赢且地主只出过第一手牌
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum SpringKind {
+    Spring,
+    AntiSpring,
+}
+
+/// 斗地主玩家行动
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DouDizhuAction {
+    Bid(u8),                 // 叫分，0 为不叫
+    Play(Vec<DouDizhuCard>), // 出牌
+    Pass,                    // 不出
+}
+
+/// 一局结束时每位玩家的输赢
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DouDizhuResult {
+    pub user_id: u32,
+    pub net: i32,
+}
+
+/// 斗地主牌局状态，保存在 game_context 中
+#[derive(Debug, Default)]
+pub struct DouDizhuState {
+    pub phase: DouDizhuPhase,
+    pub hands: HashMap<u32, Vec<DouDizhuCard>>,
+    pub kitty: Vec<DouDizhuCard>, // 底牌，地主确定后公开并归地主
+    pub order: Vec<u32>,          // 座位顺序，出牌按此顺序轮转
+    pub acting: Option<usize>,    // 当前行动玩家在 order 中的位置
+    pub bids: Vec<(u32, u8)>,     // 按叫分顺序记录
+    pub landlord_user: Option<u32>,
+    pub bid_score: u8,            // 地主的叫分
+    pub last_play: Option<(u32, Combination, Vec<DouDizhuCard>)>, // 当前需要压过的一手牌
+    pub play_counts: HashMap<u32, u32>, // 每位玩家出牌(不含不出)的次数
+    pub bomb_count: u32,          // 本局打出的炸弹和王炸
+    pub spring: Option<SpringKind>,
+    pub results: Vec<DouDizhuResult>,
+}
+
+/// 某位观察者看到的一个座位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DouDizhuSeatView {
+    pub user_id: u32,
+    pub cards: Option<Vec<DouDizhuCard>>, // 本人或本局结束后可见
+    pub card_count: usize,
+    pub is_landlord: bool,
+}
+
+/// 某位观察者看到的牌桌
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DouDizhuTableView {
+    pub phase: DouDizhuPhase,
+    pub seats: Vec<DouDizhuSeatView>,
+    pub kitty: Option<Vec<DouDizhuCard>>, // 地主确定后可见
+    pub bids: Vec<(u32, u8)>,
+    pub last_play: Option<(u32, Vec<DouDizhuCard>)>,
+    pub multiplier: u32,
+    pub next_to_act: Option<u32>,
+}
+
+impl DouDizhuState {
+    /// 下一位行动的玩家
+    pub fn next_to_act(&self) -> Option<u32> {
+        self.acting.map(|index| self.order[index])
+    }
+
+    /// 当前倍数：每个炸弹或王炸翻一倍，春天或反春再翻一倍，不含叫分
+    pub fn multiplier(&self) -> u32 {
+        let spring = if self.spring.is_some() { 2 } else { 1 };
+        2u32.saturating_pow(self.bomb_count).saturating_mul(spring)
+    }
+
+    /// viewer 看到的牌桌，viewer 为 None 时是旁观者视角
+    pub fn view_for(&self, viewer: Option<u32>) -> DouDizhuTableView {
+        let seats: Vec<DouDizhuSeatView> = self.order.iter()
+            .map(|user_id| {
+                let cards = self.hands.get(user_id).cloned().unwrap_or_default();
+                let visible = viewer == Some(*user_id) || self.phase == DouDizhuPhase::Finished;
+                DouDizhuSeatView {
+                    user_id: *user_id,
+                    card_count: cards.len(),
+                    cards: if visible { Some(cards) } else { None },
+                    is_landlord: self.landlord_user == Some(*user_id),
+                }
+            })
+            .collect();
+        DouDizhuTableView {
+            phase: self.phase,
+            seats,
+            kitty: self.landlord_user.map(|_| self.kitty.clone()),
+            bids: self.bids.clone(),
+            last_play: self.last_play.as_ref().map(|(user_id, _, cards)| (*user_id, cards.clone())),
+            multiplier: self.multiplier(),
+            next_to_act: self.next_to_act(),
+        }
+    }
+
+    // 开始新的一局前清空上一局的数据
+    fn reset_hand(&mut self) {
+        self.phase = DouDizhuPhase::Bidding;
+        self.hands.clear();
+        self.kitty.clear();
+        self.order.clear();
+        self.acting = None;
+        self.bids.clear();
+        self.landlord_user = None;
+        self.bid_score = 0;
+        self.last_play = None;
+        self.play_counts.clear();
+        self.bomb_count = 0;
+        self.spring = None;
+        self.results.clear();
+    }
+
+    // 轮到下一个座位
+    fn advance(&mut self) {
+        self.acting = self.acting.map(|index| (index + 1) % self.order.len());
+    }
+}
+
+impl ActingPlayers for DouDizhuState {
+    fn acting_players(&self, _players: &[Arc<Player>]) -> Vec<u32> {
+        self.next_to_act().into_iter().collect()
+    }
+}
+
+/// 斗地主规则配置
+#[derive(Debug, Clone, Copy)]
+pub struct DouDizhuGameRules {
+    pub base_score: u16, // 底分
+    pub max_bid: u8,     // 最高叫分，叫到最高分时立即成为地主
+}
+
+impl DouDizhuGameRules {
+    pub fn new(base_score: u16) -> Self {
+        DouDizhuGameRules{base_score, max_bid: 3}
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.base_score == 0 || config.max_bid == 0 {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            // 斗地主比的是一手出牌能否压过上一手
+            Arc::new(|left, right, _| {
+                let cards = |items: &Vec<&dyn GameItem>| -> Vec<DouDizhuCard> {
+                    items.iter().filter_map(|item| DouDizhuCard::from_item(*item)).collect()
+                };
+                match (parse_combination(&cards(left)), parse_combination(&cards(right))) {
+                    (Some(left), Some(right)) => left.beats(&right),
+                    _ => false,
+                }
+            }),
+            Arc::new(allocate),
+            Arc::new(game_start),
+            Arc::new(game_progress),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_finish(config, players, game_items, context)),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+// 重置牌局状态，洗 54 张牌并随机决定第一个叫分的玩家
+fn game_start(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<DouDizhuState>(&context, DOU_DIZHU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_hand();
+    for player in &players {
+        player.update_player_role(PlayerRole::Player);
+    }
+
+    if players.len() != DOU_DIZHU_PLAYERS {
+        state.phase = DouDizhuPhase::Finished;
+        return;
+    }
+    state.order = players.iter().map(|player| player.get_user().get_id()).collect();
+
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    let mut rng = lock_or_recover(&fairness).begin_round(&state.order);
+    let mut deck = get_dou_dizhu_cards();
+    rng.shuffle(&mut deck);
+    state.acting = Some(rng.next_below(DOU_DIZHU_PLAYERS as u32) as usize);
+    *lock_or_recover(&game_items) = deck
+        .into_iter()
+        .map(|card| Arc::new(card) as Arc<dyn GameItem>)
+        .collect();
+}
+
+// 每人轮流发 17 张，剩余 3 张作为底牌
+fn allocate(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<DouDizhuState>(&context, DOU_DIZHU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+    if state.phase != DouDizhuPhase::Bidding {
+        return;
+    }
+
+    for _ in 0..DOU_DIZHU_HAND_SIZE {
+        for user_id in state.order.clone() {
+            if let Some(card) = draw_card(&mut deck) {
+                state.hands.entry(user_id).or_default().push(card);
+            }
+        }
+    }
+    while let Some(card) = draw_card(&mut deck) {
+        state.kitty.push(card);
+    }
+    for cards in state.hands.values_mut() {
+        sort_cards(cards);
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 叫分结束：叫分最高的玩家成为地主(Dealer 角色)，拿走底牌并先出牌
+fn game_progress(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<DouDizhuState>(&context, DOU_DIZHU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase != DouDizhuPhase::Bidding {
+        return;
+    }
+
+    // 叫分只能比前面的人高，最后一个非零叫分即为最高
+    let Some((landlord_user, bid_score)) = state.bids.iter().rev().find(|(_, bid)| *bid > 0).copied() else {
+        return;
+    };
+    state.landlord_user = Some(landlord_user);
+    state.bid_score = bid_score;
+    let kitty = state.kitty.clone();
+    if let Some(cards) = state.hands.get_mut(&landlord_user) {
+        cards.extend(kitty);
+        sort_cards(cards);
+    }
+    for player in &players {
+        let role = if player.get_user().get_id() == landlord_user { PlayerRole::Dealer } else { PlayerRole::Player };
+        player.update_player_role(role);
+    }
+    state.acting = state.order.iter().position(|user_id| *user_id == landlord_user);
+    state.phase = DouDizhuPhase::Playing;
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家叫分、出牌或不出
+fn player_action(
+    config: DouDizhuGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<DouDizhuState>(&context, DOU_DIZHU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(action) = action.downcast_ref::<DouDizhuAction>().cloned() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if state.next_to_act() != Some(user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+
+    let outcome = match (state.phase, action) {
+        (DouDizhuPhase::Bidding, DouDizhuAction::Bid(bid)) => {
+            let highest = state.bids.iter().map(|(_, bid)| *bid).max().unwrap_or(0);
+            if bid != 0 && (bid <= highest || bid > config.max_bid) {
+                return ActionOutcome::Rejected(format!("bid must be 0 or between {} and {}", highest + 1, config.max_bid));
+            }
+            state.bids.push((user_id, bid));
+            state.advance();
+            let everyone_bid = state.bids.len() >= DOU_DIZHU_PLAYERS;
+            if bid == config.max_bid || (everyone_bid && highest.max(bid) > 0) {
+                ActionOutcome::RoundComplete
+            } else if everyone_bid {
+                // 无人叫分，本局作废，需要重新发牌
+                ActionOutcome::GameComplete
+            } else {
+                ActionOutcome::Continue
+            }
+        }
+        (DouDizhuPhase::Playing, DouDizhuAction::Play(cards)) => {
+            let Some(combination) = parse_combination(&cards) else {
+                return ActionOutcome::Rejected("cards do not form a valid combination".to_string());
+            };
+            if let Some((_, previous, _)) = &state.last_play {
+                if !combination.beats(previous) {
+                    return ActionOutcome::Rejected(format!("{} cannot beat {}", combination, previous));
+                }
+            }
+            let Some(remaining) = remove_cards(&state.hands[&user_id], &cards) else {
+                return ActionOutcome::Rejected("cards are not in hand".to_string());
+            };
+            let is_empty = remaining.is_empty();
+            state.hands.insert(user_id, remaining);
+            if combination.is_bomb() {
+                state.bomb_count += 1;
+            }
+            *state.play_counts.entry(user_id).or_insert(0) += 1;
+            state.last_play = Some((user_id, combination, cards));
+            state.advance();
+            if is_empty { ActionOutcome::GameComplete } else { ActionOutcome::Continue }
+        }
+        (DouDizhuPhase::Playing, DouDizhuAction::Pass) => {
+            if state.last_play.is_none() {
+                return ActionOutcome::Rejected("the leading player must play".to_string());
+            }
+            state.advance();
+            // 其余两家都不出，轮回出牌的人自由出牌
+            if state.last_play.as_ref().map(|(last_user, _, _)| *last_user) == state.next_to_act() {
+                state.last_play = None;
+            }
+            ActionOutcome::Continue
+        }
+        _ => return ActionOutcome::Rejected("action does not match the current phase".to_string()),
+    };
+    if outcome == ActionOutcome::GameComplete {
+        state.acting = None;
+    }
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 结算：每位农民与地主结算 底分 × 叫分 × 倍数，地主输赢两份
+fn game_finish(
+    config: DouDizhuGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<DouDizhuState>(&context, DOU_DIZHU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let was_playing = state.phase == DouDizhuPhase::Playing;
+    state.phase = DouDizhuPhase::Finished;
+    state.acting = None;
+
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    lock_or_recover(&fairness).reveal();
+    sync_action_players(&state, &players, &context);
+
+    if !was_playing {
+        return;
+    }
+    let Some(landlord_user) = state.landlord_user else {
+        return;
+    };
+    let Some(winner) = state.hands.iter().find(|(_, cards)| cards.is_empty()).map(|(user_id, _)| *user_id) else {
+        return;
+    };
+    let Some(landlord) = players.iter().find(|player| player.get_user().get_id() == landlord_user).cloned() else {
+        return;
+    };
+
+    let landlord_wins = winner == landlord_user;
+    let farmer_plays: u32 = state.play_counts.iter()
+        .filter(|(user_id, _)| **user_id != landlord_user)
+        .map(|(_, count)| *count)
+        .sum();
+    let landlord_plays = state.play_counts.get(&landlord_user).copied().unwrap_or(0);
+    state.spring = match landlord_wins {
+        true if farmer_plays == 0 => Some(SpringKind::Spring),
+        false if landlord_plays == 1 => Some(SpringKind::AntiSpring),
+        _ => None,
+    };
+
+    let unit = (config.base_score as u32)
+        .saturating_mul(state.bid_score as u32)
+        .saturating_mul(state.multiplier())
+        .min(u16::MAX as u32) as u16;
+    let mut nets: HashMap<u32, i32> = HashMap::new();
+    for farmer in players.iter().filter(|player| player.get_user().get_id() != landlord_user) {
+        let (payer, payee) = if landlord_wins { (farmer, &landlord) } else { (&landlord, farmer) };
+        let paid = payer.take_token(unit);
+        payee.add_token(paid);
+        *nets.entry(payer.get_user().get_id()).or_insert(0) -= paid as i32;
+        *nets.entry(payee.get_user().get_id()).or_insert(0) += paid as i32;
+    }
+    state.results = state.order.iter()
+        .map(|user_id| DouDizhuResult{user_id: *user_id, net: nets.get(user_id).copied().unwrap_or(0)})
+        .collect();
+}
+
+// 对局中有人离桌时本局作废，不结算，直接结束这一局并公开种子
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<DouDizhuState>(&context, DOU_DIZHU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    for player in &leave_players {
+        player.update_player_role(PlayerRole::Player);
+    }
+    let in_hand = leave_players.iter().any(|player| state.order.contains(&player.get_user().get_id()));
+    let outcome = if in_hand && state.phase != DouDizhuPhase::Finished {
+        state.phase = DouDizhuPhase::Finished;
+        state.acting = None;
+        state.landlord_user = None;
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+// 从 hand 中拿掉 cards，有牌不在手中时返回 None
+fn remove_cards(hand: &[DouDizhuCard], cards: &[DouDizhuCard]) -> Option<Vec<DouDizhuCard>> {
+    let mut remaining = hand.to_vec();
+    for card in cards {
+        let index = remaining.iter().position(|held| held == card)?;
+        remaining.remove(index);
+    }
+    Some(remaining)
+}
+
+// 手牌按点数从小到大排列
+fn sort_cards(cards: &mut [DouDizhuCard]) {
+    cards.sort_by_key(|card| card.rank_value());
+}
+
+// 从牌堆顶摸一张牌
+fn draw_card(deck: &mut Vec<Arc<dyn GameItem>>) -> Option<DouDizhuCard> {
+    if deck.is_empty() {
+        return None;
+    }
+    DouDizhuCard::from_item(deck.remove(0).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::Game;
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{acting, game, player};
+
+    fn table() -> (Game, Vec<Arc<Player>>) {
+        let mut game = game(DouDizhuGameRules::new(10).build().unwrap());
+        let players: Vec<Arc<Player>> = (1..=3).map(|id| player(id, PlayerRole::Player, GameProject::DouDizhu, 1000)).collect();
+        game.player_join(players.clone());
+        game.game_start();
+        (game, players)
+    }
+
+    fn seat(players: &[Arc<Player>], user_id: u32) -> Arc<Player> {
+        players.iter().find(|player| player.get_user().get_id() == user_id).unwrap().clone()
+    }
+
+    fn dou_dizhu_state(game: &Game) -> Arc<Mutex<DouDizhuState>> {
+        get_state::<DouDizhuState>(&game.get_game_context(), DOU_DIZHU_STATE_KEY).unwrap()
+    }
+
+    fn cards(text: &str) -> Vec<DouDizhuCard> {
+        text.split_whitespace().map(|card| DouDizhuCard::Standard(card.parse().unwrap())).collect()
+    }
+
+    fn act(game: &mut Game, players: &[Arc<Player>], action: DouDizhuAction) -> ActionOutcome {
+        let actor = seat(players, acting(game)[0]);
+        game.player_action(actor, Arc::new(action))
+    }
+
+    #[test]
+    fn highest_bidder_becomes_landlord_and_takes_the_kitty() {
+        let (mut game, players) = table();
+        let first = acting(&game)[0];
+        assert_eq!(act(&mut game, &players, DouDizhuAction::Bid(1)), ActionOutcome::Continue);
+        assert!(matches!(act(&mut game, &players, DouDizhuAction::Bid(1)), ActionOutcome::Rejected(_)));
+        assert_eq!(act(&mut game, &players, DouDizhuAction::Bid(0)), ActionOutcome::Continue);
+        assert_eq!(act(&mut game, &players, DouDizhuAction::Bid(2)), ActionOutcome::RoundComplete);
+
+        let state = dou_dizhu_state(&game);
+        let state = lock_or_recover(&state);
+        let landlord = state.landlord_user.unwrap();
+        assert_ne!(landlord, first);
+        assert_eq!((state.phase, state.bid_score), (DouDizhuPhase::Playing, 2));
+        assert_eq!(state.hands[&landlord].len(), 20);
+        assert_eq!(state.next_to_act(), Some(landlord));
+        assert_eq!(seat(&players, landlord).get_player_role(), PlayerRole::Dealer);
+        // 农民看不到地主的手牌，底牌公开
+        let view = state.view_for(Some(first));
+        assert!(view.kitty.is_some());
+        assert!(view.seats.iter().all(|seat| seat.cards.is_some() == (seat.user_id == first)));
+    }
+
+    #[test]
+    fn nobody_bidding_voids_the_hand() {
+        let (mut game, players) = table();
+        for _ in 0..2 {
+            assert_eq!(act(&mut game, &players, DouDizhuAction::Bid(0)), ActionOutcome::Continue);
+        }
+        assert_eq!(act(&mut game, &players, DouDizhuAction::Bid(0)), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert!(players.iter().all(|player| player.get_token() == 1000));
+    }
+
+    #[test]
+    fn spring_with_a_bomb_quadruples_the_score() {
+        let (mut game, players) = table();
+        assert_eq!(act(&mut game, &players, DouDizhuAction::Bid(3)), ActionOutcome::RoundComplete);
+        let landlord = {
+            let state = dou_dizhu_state(&game);
+            let mut state = lock_or_recover(&state);
+            let landlord = state.landlord_user.unwrap();
+            state.hands.insert(landlord, cards("3s 3d 3c 3h 4s"));
+            landlord
+        };
+
+        assert!(matches!(act(&mut game, &players, DouDizhuAction::Play(cards("3s 4s"))), ActionOutcome::Rejected(_)));
+        assert!(matches!(act(&mut game, &players, DouDizhuAction::Pass), ActionOutcome::Rejected(_)));
+        assert_eq!(act(&mut game, &players, DouDizhuAction::Play(cards("3s 3d 3c 3h"))), ActionOutcome::Continue);
+        assert_eq!(act(&mut game, &players, DouDizhuAction::Pass), ActionOutcome::Continue);
+        assert_eq!(act(&mut game, &players, DouDizhuAction::Pass), ActionOutcome::Continue);
+        // 两家都不出，地主重新自由出牌
+        assert_eq!(act(&mut game, &players, DouDizhuAction::Play(cards("4s"))), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+
+        // 底分 10 × 叫分 3 × 炸弹 2 × 春天 2，每位农民付 120
+        let state = dou_dizhu_state(&game);
+        let state = lock_or_recover(&state);
+        assert_eq!((state.spring, state.multiplier()), (Some(SpringKind::Spring), 4));
+        for player in &players {
+            let expected = if player.get_user().get_id() == landlord { 1240 } else { 880 };
+            assert_eq!(player.get_token(), expected);
+        }
+    }
+
+    #[test]
+    fn leaving_mid_hand_voids_and_finishes_the_hand() {
+        let (mut game, players) = table();
+        assert_eq!(acting(&game).len(), 1);
+
+        assert_eq!(game.player_leave(vec![players[2].clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert!(acting(&game).is_empty());
+        assert!(players.iter().all(|player| player.get_token() == 1000));
+        let fairness = get_state::<FairnessState>(&game.get_game_context(), PROVABLY_FAIR_KEY).unwrap();
+        assert!(lock_or_recover(&fairness).get_last_reveal().is_some());
+    }
+}
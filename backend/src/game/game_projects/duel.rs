@@ -0,0 +1,480 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::sha256_hex;
+use crate::game::game_projects::rule_helpers::{ActingPlayers, sync_action_players};
+use crate::timer::timer::CBTimesMethod;
+
+/// 对局状态在 game_context 中的 key
+pub const DUEL_STATE_KEY: &str = "duel_state";
+
+/// 双人对赌的玩法
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum DuelGame {
+    CoinFlip,          // 猜硬币：两人选择相同为正面，先入座者赢；不同为反面，后入座者赢
+    RockPaperScissors, // 石头剪刀布，平局退回注额
+}
+
+/// 玩家的选择
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum DuelChoice {
+    Heads,
+    Tails,
+    Rock,
+    Paper,
+    Scissors,
+}
+
+impl DuelChoice {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuelChoice::Heads => "heads",
+            DuelChoice::Tails => "tails",
+            DuelChoice::Rock => "rock",
+            DuelChoice::Paper => "paper",
+            DuelChoice::Scissors => "scissors",
+        }
+    }
+
+    /// 该选择是否属于此玩法
+    pub fn fits(&self, game: DuelGame) -> bool {
+        match game {
+            DuelGame::CoinFlip => matches!(self, DuelChoice::Heads | DuelChoice::Tails),
+            DuelGame::RockPaperScissors => matches!(self, DuelChoice::Rock | DuelChoice::Paper | DuelChoice::Scissors),
+        }
+    }
+}
+
+/// 对赌玩家行动：先提交选择与盐的哈希，双方都提交后再公开
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DuelAction {
+    Commit(String),
+    Reveal{choice: DuelChoice, salt: String},
+}
+
+/// 对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum DuelPhase {
+    #[default]
+    Committing, // 等待双方提交哈希
+    Revealing,  // 等待双方公开
+    Settled,    // 已结算
+}
+
+/// 对赌对局状态，保存在 game_context 中；对方公开前只能看到哈希
+#[derive(Debug, Default)]
+pub struct DuelState {
+    pub phase: DuelPhase,
+    pub seats: Vec<u32>,                 // 两位玩家按入座顺序
+    pub commitments: HashMap<u32, String>,
+    pub choices: HashMap<u32, DuelChoice>,
+    pub escrow: HashMap<u32, u32>,       // 提交哈希时扣下的注额
+    pub winner: Option<u32>,
+    pub stalled: Vec<u32>,               // 超时未行动的玩家
+    pub nets: HashMap<u32, i32>,
+}
+
+impl DuelState {
+    /// 还需要行动的玩家
+    pub fn pending_players(&self) -> Vec<u32> {
+        let done = |user_id: &u32| match self.phase {
+            DuelPhase::Committing => self.commitments.contains_key(user_id),
+            DuelPhase::Revealing => self.choices.contains_key(user_id),
+            DuelPhase::Settled => true,
+        };
+        self.seats.iter().copied().filter(|user_id| !done(user_id)).collect()
+    }
+
+    fn reset_round(&mut self) {
+        self.phase = DuelPhase::Committing;
+        self.seats.clear();
+        self.commitments.clear();
+        self.choices.clear();
+        self.escrow.clear();
+        self.winner = None;
+        self.stalled.clear();
+        self.nets.clear();
+    }
+}
+
+impl ActingPlayers for DuelState {
+    fn acting_players(&self, _players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players()
+    }
+}
+
+/// 对赌规则配置，没有庄家，双方的注额由赢家全部拿走
+///
+/// 玩家定时器从最近一次有效行动起计时，reveal_timeout 内没有人行动时，未行动的一方判负
+#[derive(Debug, Clone, Copy)]
+pub struct DuelGameRules {
+    pub game: DuelGame,
+    pub stake: u32,
+    pub reveal_timeout: Duration,
+}
+
+impl DuelGameRules {
+    pub fn new(game: DuelGame, stake: u32) -> Self {
+        DuelGameRules{game, stake, reveal_timeout: Duration::from_secs(30)}
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        // 赢家一次拿走双方的注额，总额不能超过 u16
+        if config.stake == 0 || config.stake > u16::MAX as u32 / 2 || config.reveal_timeout.is_zero() {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|_, _, _| false),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_start),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_finish(config, players, game_items, context)),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(players_timeout),
+            Some(config.reveal_timeout),
+            Some(CBTimesMethod::ONCE),
+        )
+    }
+}
+
+/// 选择与盐的承诺值
+pub fn duel_commitment(choice: DuelChoice, salt: &str) -> String {
+    sha256_hex(format!("{}:{}", choice.as_str(), salt).as_bytes())
+}
+
+/// 按入座顺序的两个选择决定赢家的座位，平局返回 None
+pub fn duel_winner(game: DuelGame, first: DuelChoice, second: DuelChoice) -> Option<usize> {
+    match game {
+        DuelGame::CoinFlip => Some(if first == second { 0 } else { 1 }),
+        DuelGame::RockPaperScissors => match (first, second) {
+            _ if first == second => None,
+            (DuelChoice::Rock, DuelChoice::Scissors) | (DuelChoice::Paper, DuelChoice::Rock) | (DuelChoice::Scissors, DuelChoice::Paper) => Some(0),
+            _ => Some(1),
+        },
+    }
+}
+
+// 开局：正好两位玩家时开始，等待提交哈希
+fn game_start(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<DuelState>(&context, DUEL_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_round();
+
+    let seats: Vec<u32> = players.iter()
+        .filter(|player| player.get_player_role() == PlayerRole::Player)
+        .map(|player| player.get_user().get_id())
+        .collect();
+    if seats.len() != 2 {
+        state.phase = DuelPhase::Settled;
+        return;
+    }
+    state.seats = seats;
+    sync_action_players(&state, &players, &context);
+}
+
+// 提交哈希时扣下注额；双方都提交后进入公开阶段，双方都公开后结算
+fn player_action(
+    config: DuelGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<DuelState>(&context, DUEL_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(action) = action.downcast_ref::<DuelAction>().cloned() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players().contains(&user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+
+    let outcome = match (state.phase, action) {
+        (DuelPhase::Committing, DuelAction::Commit(commitment)) => {
+            if commitment.len() != 64 || !commitment.chars().all(|c| c.is_ascii_hexdigit()) {
+                return ActionOutcome::Rejected("commitment must be a sha256 hex digest".to_string());
+            }
+            if config.stake > player.get_token() as u32 {
+                return ActionOutcome::Rejected("not enough tokens".to_string());
+            }
+            player.take_token(config.stake as u16);
+            state.escrow.insert(user_id, config.stake);
+            state.commitments.insert(user_id, commitment.to_lowercase());
+            if state.pending_players().is_empty() {
+                state.phase = DuelPhase::Revealing;
+            }
+            ActionOutcome::Continue
+        }
+        (DuelPhase::Revealing, DuelAction::Reveal{choice, salt}) => {
+            if !choice.fits(config.game) {
+                return ActionOutcome::Rejected(format!("{:?} is not a choice in {:?}", choice, config.game));
+            }
+            if state.commitments.get(&user_id) != Some(&duel_commitment(choice, &salt)) {
+                return ActionOutcome::Rejected("reveal does not match the commitment".to_string());
+            }
+            state.choices.insert(user_id, choice);
+            if state.pending_players().is_empty() { ActionOutcome::GameComplete } else { ActionOutcome::Continue }
+        }
+        _ => return ActionOutcome::Rejected("action does not match the current phase".to_string()),
+    };
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 双方都公开后比较选择，赢家拿走全部注额，平局各自退回
+fn game_finish(
+    config: DuelGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<DuelState>(&context, DUEL_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == DuelPhase::Settled {
+        return;
+    }
+    let choices: Vec<DuelChoice> = state.seats.iter().filter_map(|user_id| state.choices.get(user_id).copied()).collect();
+    let winner = match choices.as_slice() {
+        [first, second] => duel_winner(config.game, *first, *second).map(|seat| state.seats[seat]),
+        _ => None,
+    };
+    settle(&mut state, &players, winner);
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家定时器到期：提交阶段未提交、公开阶段未公开的玩家判负，注额归按时行动的一方；双方都拖延时各自退回，随即结束这一局
+fn players_timeout(
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _current_action_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<DuelState>(&context, DUEL_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == DuelPhase::Settled {
+        return ActionOutcome::Continue;
+    }
+    let stalled = state.pending_players();
+    let punctual: Vec<u32> = state.seats.iter().copied().filter(|user_id| !stalled.contains(user_id)).collect();
+    let winner = match punctual.as_slice() {
+        [winner] => Some(*winner),
+        _ => None,
+    };
+    state.stalled = stalled;
+    settle(&mut state, &players, winner);
+    sync_action_players(&state, &players, &context);
+    ActionOutcome::GameComplete
+}
+
+// 离开的玩家视为拖延，注额归留下的一方
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<DuelState>(&context, DUEL_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == DuelPhase::Settled {
+        return ActionOutcome::Continue;
+    }
+    let leaving: Vec<u32> = leave_players.iter()
+        .map(|player| player.get_user().get_id())
+        .filter(|user_id| state.seats.contains(user_id))
+        .collect();
+    if leaving.is_empty() {
+        return ActionOutcome::Continue;
+    }
+    // 离开的玩家已不在当前玩家中，结算时一并查找
+    let mut players = current_players.clone();
+    players.extend(leave_players);
+    let staying: Vec<u32> = state.seats.iter().copied().filter(|user_id| !leaving.contains(user_id)).collect();
+    let winner = match staying.as_slice() {
+        [winner] if state.escrow.contains_key(winner) => Some(*winner),
+        _ => None,
+    };
+    state.stalled = leaving;
+    settle(&mut state, &players, winner);
+    sync_action_players(&state, &current_players, &context);
+    ActionOutcome::GameComplete
+}
+
+// 把扣下的注额交给赢家，没有赢家时各自退回
+fn settle(state: &mut DuelState, players: &[Arc<Player>], winner: Option<u32>) {
+    state.phase = DuelPhase::Settled;
+    state.winner = winner;
+    let find_player = |user_id: u32| players.iter().find(|player| player.get_user().get_id() == user_id);
+    let pot: u32 = state.escrow.values().sum();
+    for user_id in state.seats.clone() {
+        let stake = state.escrow.get(&user_id).copied().unwrap_or(0);
+        let returned = match winner {
+            Some(winner) if winner == user_id => pot,
+            Some(_) => 0,
+            None => stake,
+        };
+        if let Some(player) = find_player(user_id) {
+            player.add_token(returned.min(u16::MAX as u32) as u16);
+        }
+        state.nets.insert(user_id, returned as i32 - stake as i32);
+    }
+    state.escrow.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use crate::game::game::Game;
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{acting, game, player};
+
+    fn table(config: DuelGameRules) -> (Game, Vec<Arc<Player>>) {
+        let mut game = game(config.build().unwrap());
+        let players: Vec<Arc<Player>> = (1..=2).map(|id| player(id, PlayerRole::Player, GameProject::RockPaperScissors, 1000)).collect();
+        game.player_join(players.clone());
+        game.game_start();
+        (game, players)
+    }
+
+    fn duel_state(game: &Game) -> Arc<Mutex<DuelState>> {
+        get_state::<DuelState>(&game.get_game_context(), DUEL_STATE_KEY).unwrap()
+    }
+
+    fn commit(game: &mut Game, player: &Arc<Player>, choice: DuelChoice) -> ActionOutcome {
+        let salt = format!("salt{}", player.get_user().get_id());
+        game.player_action(player.clone(), Arc::new(DuelAction::Commit(duel_commitment(choice, &salt))))
+    }
+
+    fn reveal(game: &mut Game, player: &Arc<Player>, choice: DuelChoice) -> ActionOutcome {
+        let salt = format!("salt{}", player.get_user().get_id());
+        game.player_action(player.clone(), Arc::new(DuelAction::Reveal{choice, salt}))
+    }
+
+    #[test]
+    fn winner_table() {
+        use DuelChoice::*;
+        assert_eq!(duel_winner(DuelGame::CoinFlip, Heads, Heads), Some(0));
+        assert_eq!(duel_winner(DuelGame::CoinFlip, Tails, Tails), Some(0));
+        assert_eq!(duel_winner(DuelGame::CoinFlip, Heads, Tails), Some(1));
+        assert_eq!(duel_winner(DuelGame::RockPaperScissors, Rock, Scissors), Some(0));
+        assert_eq!(duel_winner(DuelGame::RockPaperScissors, Paper, Rock), Some(0));
+        assert_eq!(duel_winner(DuelGame::RockPaperScissors, Scissors, Paper), Some(0));
+        assert_eq!(duel_winner(DuelGame::RockPaperScissors, Scissors, Rock), Some(1));
+        assert_eq!(duel_winner(DuelGame::RockPaperScissors, Paper, Paper), None);
+    }
+
+    #[test]
+    fn stake_must_fit_the_pot() {
+        assert!(DuelGameRules::new(DuelGame::CoinFlip, u16::MAX as u32 / 2).build().is_ok());
+        assert!(matches!(DuelGameRules::new(DuelGame::CoinFlip, u16::MAX as u32 / 2 + 1).build(), Err(GameRuleError::UnsupportedOption)));
+        assert!(matches!(DuelGameRules::new(DuelGame::CoinFlip, 0).build(), Err(GameRuleError::UnsupportedOption)));
+    }
+
+    #[test]
+    fn reveal_must_match_commitment() {
+        let (mut game, players) = table(DuelGameRules::new(DuelGame::RockPaperScissors, 100));
+        assert_eq!(commit(&mut game, &players[0], DuelChoice::Rock), ActionOutcome::Continue);
+        assert!(matches!(reveal(&mut game, &players[0], DuelChoice::Rock), ActionOutcome::Rejected(_)));
+        assert_eq!(commit(&mut game, &players[1], DuelChoice::Paper), ActionOutcome::Continue);
+        assert_eq!(acting(&game), vec![1, 2]);
+
+        assert!(matches!(reveal(&mut game, &players[0], DuelChoice::Scissors), ActionOutcome::Rejected(_)));
+        assert_eq!(reveal(&mut game, &players[0], DuelChoice::Rock), ActionOutcome::Continue);
+        assert_eq!(reveal(&mut game, &players[1], DuelChoice::Paper), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(lock_or_recover(&duel_state(&game)).winner, Some(2));
+        assert_eq!((players[0].get_token(), players[1].get_token()), (900, 1100));
+    }
+
+    #[test]
+    fn tie_returns_both_stakes() {
+        let (mut game, players) = table(DuelGameRules::new(DuelGame::RockPaperScissors, 100));
+        commit(&mut game, &players[0], DuelChoice::Rock);
+        commit(&mut game, &players[1], DuelChoice::Rock);
+        reveal(&mut game, &players[0], DuelChoice::Rock);
+        assert_eq!(reveal(&mut game, &players[1], DuelChoice::Rock), ActionOutcome::GameComplete);
+        assert_eq!((players[0].get_token(), players[1].get_token()), (1000, 1000));
+    }
+
+    #[test]
+    fn timeout_forfeits_the_stalled_player_and_finishes_game() {
+        let mut config = DuelGameRules::new(DuelGame::RockPaperScissors, 100);
+        config.reveal_timeout = Duration::from_millis(20);
+        let (mut game, players) = table(config);
+        commit(&mut game, &players[0], DuelChoice::Rock);
+        commit(&mut game, &players[1], DuelChoice::Scissors);
+        assert_eq!(reveal(&mut game, &players[1], DuelChoice::Scissors), ActionOutcome::Continue);
+
+        for _ in 0..1000 {
+            if game.get_game_state() == GameState::Finished {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+            game.update_timers();
+        }
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        let state = duel_state(&game);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.phase, DuelPhase::Settled);
+        assert_eq!(state.stalled, vec![1]);
+        assert_eq!(state.winner, Some(2));
+        assert_eq!((players[0].get_token(), players[1].get_token()), (900, 1100));
+    }
+
+    #[test]
+    fn leaving_mid_duel_forfeits_and_finishes_game() {
+        let (mut game, players) = table(DuelGameRules::new(DuelGame::CoinFlip, 100));
+        commit(&mut game, &players[0], DuelChoice::Heads);
+        commit(&mut game, &players[1], DuelChoice::Tails);
+        assert_eq!(game.player_leave(vec![players[0].clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(lock_or_recover(&duel_state(&game)).winner, Some(2));
+        assert_eq!((players[0].get_token(), players[1].get_token()), (900, 1100));
+    }
+
+    #[test]
+    fn leaving_before_the_other_commits_refunds_the_stake() {
+        let (mut game, players) = table(DuelGameRules::new(DuelGame::CoinFlip, 100));
+        commit(&mut game, &players[1], DuelChoice::Heads);
+        assert_eq!(game.player_leave(vec![players[1].clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(lock_or_recover(&duel_state(&game)).winner, None);
+        assert_eq!((players[0].get_token(), players[1].get_token()), (1000, 1000));
+    }
+}
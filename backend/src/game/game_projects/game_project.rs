@@ -1,4 +1,26 @@
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum GameProject{
-    TexasHoldemPoker
+    TexasHoldemPoker,
+    OmahaFourCard,
+    OmahaFiveCard,
+    OmahaSixCard,
+    OmahaHiLo,
+    ShortDeckHoldem,
+    SevenCardStud,
+    SevenCardStudHiLo,
+    Razz,
+    ZhaJinHua,
+    NiuNiu,
+    DouDizhu,
+    Blackjack,
+    Baccarat,
+    Roulette,
+    SicBo,
+    Craps,
+    Crash,
+    Slots,
+    Mines,
+    Plinko,
+    CoinFlip,
+    RockPaperScissors,
 }
\ No newline at end of file
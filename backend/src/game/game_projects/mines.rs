@@ -0,0 +1,454 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairRng, FairnessState, SeedReveal, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, collect, commit_next_seed, commit_seed_on_join, find_player, pay, sync_action_players};
+
+/// 对局状态在 game_context 中的 key
+pub const MINES_STATE_KEY: &str = "mines_state";
+
+/// 倍数以百分之一为单位，100 即 1.00x
+pub const MINES_MULTIPLIER_BASE: u32 = 100;
+
+/// 扫雷玩家行动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinesAction {
+    Bet{amount: u32, mines: usize}, // 下注并选择地雷数，0 为本局不参与
+    Reveal(usize),                  // 翻开一格，格子按行优先编号
+    CashOut,                        // 按当前倍数兑现
+}
+
+/// 对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum MinesPhase {
+    #[default]
+    Betting, // 等待下注
+    Playing, // 逐格翻开
+    Settled, // 踩雷或兑现后结算
+}
+
+/// 扫雷对局状态，保存在 game_context 中；地雷位置在结算前不可见
+#[derive(Debug, Default)]
+pub struct MinesState {
+    pub phase: MinesPhase,
+    pub dealer_user: Option<u32>,
+    pub player_user: Option<u32>,
+    pub stake: u32,
+    pub mine_count: usize,
+    pub revealed: Vec<usize>,
+    pub multiplier: u32,      // 当前可兑现的倍数
+    pub busted: bool,
+    pub net: i32,
+    mines: Vec<usize>,
+    round_started: bool,
+}
+
+impl MinesState {
+    /// 结算后公开的地雷位置
+    pub fn get_mines(&self) -> Option<&[usize]> {
+        (self.phase == MinesPhase::Settled && self.round_started).then_some(self.mines.as_slice())
+    }
+
+    /// 还需要行动的玩家
+    pub fn pending_players(&self) -> Vec<u32> {
+        match self.phase {
+            MinesPhase::Betting | MinesPhase::Playing => self.player_user.into_iter().collect(),
+            MinesPhase::Settled => Vec::new(),
+        }
+    }
+
+    fn reset_round(&mut self) {
+        self.phase = MinesPhase::Betting;
+        self.dealer_user = None;
+        self.player_user = None;
+        self.stake = 0;
+        self.mine_count = 0;
+        self.revealed.clear();
+        self.multiplier = MINES_MULTIPLIER_BASE;
+        self.busted = false;
+        self.net = 0;
+        self.mines.clear();
+        self.round_started = false;
+    }
+}
+
+impl ActingPlayers for MinesState {
+    fn acting_players(&self, _players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players()
+    }
+}
+
+/// 扫雷规则配置，单人对庄家，庄家由 PlayerRole::Dealer 的玩家担任
+#[derive(Debug, Clone, Copy)]
+pub struct MinesGameRules {
+    pub rows: usize,
+    pub columns: usize,
+    pub house_edge_bps: u32, // 庄家优势，万分之一为单位，100 即 1%
+    pub min_bet: u32,
+    pub max_bet: u32,
+}
+
+impl MinesGameRules {
+    pub fn new(min_bet: u32, max_bet: u32) -> Self {
+        MinesGameRules{rows: 5, columns: 5, house_edge_bps: 100, min_bet, max_bet}
+    }
+
+    pub fn get_tiles(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.get_tiles() < 2 || config.house_edge_bps >= 10_000 || config.min_bet == 0 || config.max_bet < config.min_bet {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|_, _, _| false),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_start),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_finish),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+/// 翻开 revealed 个安全格后的兑现倍数(百分之一)：(1 - 庄家优势) / 连续翻开这么多安全格的概率
+pub fn mines_multiplier(tiles: usize, mines: usize, revealed: usize, house_edge_bps: u32) -> u32 {
+    if mines >= tiles || revealed > tiles - mines {
+        return 0;
+    }
+    let odds: f64 = (0..revealed).map(|i| (tiles - i) as f64 / (tiles - mines - i) as f64).product();
+    let multiplier = (10_000 - house_edge_bps.min(10_000)) as f64 / 100.0 * odds;
+    (multiplier.floor() as u32).max(MINES_MULTIPLIER_BASE)
+}
+
+/// 用本局的随机数洗乱全部格子，取前 mines 个作为地雷，按格子编号排序
+pub fn mine_layout(rng: &mut FairRng, tiles: usize, mines: usize) -> Vec<usize> {
+    let mut cells: Vec<usize> = (0..tiles).collect();
+    rng.shuffle(&mut cells);
+    let mut layout: Vec<usize> = cells.into_iter().take(mines).collect();
+    layout.sort_unstable();
+    layout
+}
+
+/// 用公开的种子重算某局的地雷位置，种子与承诺不符时返回 None
+pub fn verify_mines_round(reveal: &SeedReveal, tiles: usize, mines: usize) -> Option<Vec<usize>> {
+    if !reveal.verify_commitment() {
+        return None;
+    }
+    Some(mine_layout(&mut reveal.rng(), tiles, mines))
+}
+
+// 开局：需要一位庄家和一位玩家，等待下注
+fn game_start(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<MinesState>(&context, MINES_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_round();
+
+    let dealer = players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer);
+    let seated: Vec<&Arc<Player>> = players.iter().filter(|player| player.get_player_role() == PlayerRole::Player).collect();
+    let (Some(dealer), [player]) = (dealer, seated.as_slice()) else {
+        state.phase = MinesPhase::Settled;
+        return;
+    };
+    state.dealer_user = Some(dealer.get_user().get_id());
+    state.player_user = Some(player.get_user().get_id());
+    sync_action_players(&state, &players, &context);
+}
+
+// 下注后才开始本局的随机数并布雷；之后逐格翻开，踩雷或兑现即结束
+fn player_action(
+    config: MinesGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<MinesState>(&context, MINES_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(action) = action.downcast_ref::<MinesAction>().copied() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players().contains(&user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+    let tiles = config.get_tiles();
+
+    let outcome = match (state.phase, action) {
+        (MinesPhase::Betting, MinesAction::Bet{amount: 0, ..}) => ActionOutcome::GameComplete,
+        (MinesPhase::Betting, MinesAction::Bet{amount, mines}) => {
+            if amount < config.min_bet || amount > config.max_bet {
+                return ActionOutcome::Rejected(format!("bet must be between {} and {}", config.min_bet, config.max_bet));
+            }
+            if mines == 0 || mines >= tiles {
+                return ActionOutcome::Rejected(format!("mines must be between 1 and {}", tiles - 1));
+            }
+            if amount > player.get_token() as u32 {
+                return ActionOutcome::Rejected("not enough tokens".to_string());
+            }
+            player.take_token(amount as u16);
+            let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+            let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+            let mut rng = lock_or_recover(&fairness).begin_round(&seat_order);
+            state.mines = mine_layout(&mut rng, tiles, mines);
+            state.round_started = true;
+            state.stake = amount;
+            state.mine_count = mines;
+            state.phase = MinesPhase::Playing;
+            ActionOutcome::Continue
+        }
+        (MinesPhase::Playing, MinesAction::Reveal(tile)) => {
+            if tile >= tiles || state.revealed.contains(&tile) {
+                return ActionOutcome::Rejected(format!("invalid tile {}", tile));
+            }
+            state.revealed.push(tile);
+            if state.mines.contains(&tile) {
+                state.busted = true;
+                ActionOutcome::GameComplete
+            } else {
+                state.multiplier = mines_multiplier(tiles, state.mine_count, state.revealed.len(), config.house_edge_bps);
+                // 安全格全部翻开时自动兑现
+                if state.revealed.len() == tiles - state.mine_count { ActionOutcome::GameComplete } else { ActionOutcome::Continue }
+            }
+        }
+        (MinesPhase::Playing, MinesAction::CashOut) => {
+            if state.revealed.is_empty() {
+                return ActionOutcome::Rejected("reveal at least one tile before cashing out".to_string());
+            }
+            ActionOutcome::GameComplete
+        }
+        _ => return ActionOutcome::Rejected("action does not match the current phase".to_string()),
+    };
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 踩雷、兑现或翻完全部安全格后结算
+fn game_finish(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<MinesState>(&context, MINES_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let player = state.player_user.and_then(|user_id| find_player(&players, user_id));
+    settle(&mut state, &players, player.as_deref(), &context);
+    sync_action_players(&state, &players, &context);
+}
+
+// 每局只结算一次：踩雷则注额归庄家，否则按当前倍数兑现，随后公开本局种子
+fn settle(
+    state: &mut MinesState,
+    players: &[Arc<Player>],
+    player: Option<&Player>,
+    context: &Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>,
+) {
+    if state.phase == MinesPhase::Settled {
+        return;
+    }
+    state.phase = MinesPhase::Settled;
+    if !state.round_started {
+        return;
+    }
+    let fairness = get_or_insert_state::<FairnessState>(context, PROVABLY_FAIR_KEY);
+    lock_or_recover(&fairness).reveal();
+    let dealer = state.dealer_user.and_then(|user_id| find_player(players, user_id));
+    match player {
+        Some(player) if !state.busted => {
+            let payout = (state.stake as u64 * state.multiplier as u64 / MINES_MULTIPLIER_BASE as u64).min(u32::MAX as u64) as u32;
+            player.add_token(state.stake as u16);
+            state.net = pay(&dealer, player, payout.saturating_sub(state.stake)) as i32;
+        }
+        _ => {
+            collect(&dealer, state.stake);
+            state.net = -(state.stake as i32);
+        }
+    }
+}
+
+// 局中离开的玩家按当前倍数兑现，一格未翻时退回本金，这一局随即结束
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<MinesState>(&context, MINES_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let mut outcome = ActionOutcome::Continue;
+    for player in leave_players {
+        if state.player_user == Some(player.get_user().get_id()) && state.phase != MinesPhase::Settled {
+            settle(&mut state, &current_players, Some(&player), &context);
+            outcome = ActionOutcome::GameComplete;
+        }
+    }
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::Game;
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+
+    fn table() -> (Game, Arc<Player>, Arc<Player>) {
+        let mut game = game(MinesGameRules::new(10, 500).build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Mines, 10_000);
+        let bettor = player(1, PlayerRole::Player, GameProject::Mines, 1000);
+        game.player_join(vec![dealer.clone(), bettor.clone()]);
+        game.game_start();
+        (game, dealer, bettor)
+    }
+
+    fn mines_state(game: &Game) -> Arc<Mutex<MinesState>> {
+        get_state::<MinesState>(&game.get_game_context(), MINES_STATE_KEY).unwrap()
+    }
+
+    fn safe_tile(game: &Game) -> usize {
+        let state = mines_state(game);
+        let state = lock_or_recover(&state);
+        (0..25).find(|tile| !state.mines.contains(tile)).unwrap()
+    }
+
+    #[test]
+    fn multiplier_table() {
+        assert_eq!(mines_multiplier(25, 1, 0, 100), 100);
+        assert_eq!(mines_multiplier(25, 1, 1, 100), 103);
+        assert_eq!(mines_multiplier(25, 24, 1, 100), 2475);
+        assert_eq!(mines_multiplier(25, 3, 2, 0), 129);
+        assert_eq!(mines_multiplier(25, 25, 0, 100), 0);
+        assert_eq!(mines_multiplier(25, 3, 23, 100), 0);
+    }
+
+    #[test]
+    fn settled_round_verifies_against_revealed_seed() {
+        let (mut game, _dealer, bettor) = table();
+        let fairness = get_state::<FairnessState>(&game.get_game_context(), PROVABLY_FAIR_KEY).unwrap();
+        let commitment = lock_or_recover(&fairness).get_commitment().unwrap();
+        game.player_action(bettor.clone(), Arc::new(MinesAction::Bet{amount: 100, mines: 3}));
+        let tile = safe_tile(&game);
+        game.player_action(bettor.clone(), Arc::new(MinesAction::Reveal(tile)));
+        assert_eq!(game.player_action(bettor, Arc::new(MinesAction::CashOut)), ActionOutcome::GameComplete);
+
+        let reveal = lock_or_recover(&fairness).get_last_reveal().cloned().unwrap();
+        assert_eq!(reveal.commitment, commitment);
+        let state = mines_state(&game);
+        let state = lock_or_recover(&state);
+        assert_eq!(verify_mines_round(&reveal, 25, 3).as_deref(), state.get_mines());
+    }
+
+    #[test]
+    fn mine_layout_picks_distinct_tiles() {
+        let mines = mine_layout(&mut FairRng::new("server", &[], 0), 25, 5);
+        assert_eq!(mines.len(), 5);
+        assert!(mines.windows(2).all(|pair| pair[0] < pair[1]) && mines.iter().all(|tile| *tile < 25));
+        assert_eq!(mine_layout(&mut FairRng::new("server", &[], 0), 25, 5), mines);
+    }
+
+    #[test]
+    fn stepping_on_a_mine_loses_the_stake() {
+        let (mut game, dealer, bettor) = table();
+        game.player_action(bettor.clone(), Arc::new(MinesAction::Bet{amount: 100, mines: 3}));
+        let mine = lock_or_recover(&mines_state(&game)).mines[0];
+        assert_eq!(game.player_action(bettor.clone(), Arc::new(MinesAction::Reveal(mine))), ActionOutcome::GameComplete);
+        assert_eq!(lock_or_recover(&mines_state(&game)).net, -100);
+        assert_eq!((bettor.get_token(), dealer.get_token()), (900, 10_100));
+    }
+
+    #[test]
+    fn cash_out_pays_the_progressive_multiplier() {
+        let (mut game, dealer, bettor) = table();
+        assert!(matches!(game.player_action(bettor.clone(), Arc::new(MinesAction::Bet{amount: 100, mines: 25})), ActionOutcome::Rejected(_)));
+        game.player_action(bettor.clone(), Arc::new(MinesAction::Bet{amount: 100, mines: 3}));
+        assert!(matches!(game.player_action(bettor.clone(), Arc::new(MinesAction::CashOut)), ActionOutcome::Rejected(_)));
+        let safe: Vec<usize> = {
+            let state = mines_state(&game);
+            let state = lock_or_recover(&state);
+            (0..25).filter(|tile| !state.mines.contains(tile)).take(2).collect()
+        };
+        for tile in &safe {
+            assert_eq!(game.player_action(bettor.clone(), Arc::new(MinesAction::Reveal(*tile))), ActionOutcome::Continue);
+        }
+        assert!(matches!(game.player_action(bettor.clone(), Arc::new(MinesAction::Reveal(safe[0]))), ActionOutcome::Rejected(_)));
+        assert!(matches!(game.player_action(bettor.clone(), Arc::new(MinesAction::Reveal(25))), ActionOutcome::Rejected(_)));
+        assert_eq!(game.player_action(bettor.clone(), Arc::new(MinesAction::CashOut)), ActionOutcome::GameComplete);
+
+        // 3 雷翻开 2 格为 1.28 倍
+        assert_eq!(mines_multiplier(25, 3, 2, 100), 128);
+        assert_eq!(lock_or_recover(&mines_state(&game)).net, 28);
+        assert_eq!((bettor.get_token(), dealer.get_token()), (1028, 9972));
+    }
+
+    #[test]
+    fn clearing_every_safe_tile_cashes_out() {
+        let (mut game, _dealer, bettor) = table();
+        game.player_action(bettor.clone(), Arc::new(MinesAction::Bet{amount: 10, mines: 24}));
+        let tile = safe_tile(&game);
+        assert_eq!(game.player_action(bettor.clone(), Arc::new(MinesAction::Reveal(tile))), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(bettor.get_token(), 1000 - 10 + 247);
+    }
+
+    #[test]
+    fn leaving_mid_round_cashes_out_and_finishes() {
+        let (mut game, dealer, bettor) = table();
+        game.player_action(bettor.clone(), Arc::new(MinesAction::Bet{amount: 100, mines: 1}));
+        let tile = safe_tile(&game);
+        game.player_action(bettor.clone(), Arc::new(MinesAction::Reveal(tile)));
+
+        assert_eq!(game.player_leave(vec![bettor.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(bettor.get_token(), 1003);
+        assert_eq!(dealer.get_token(), 9997);
+    }
+
+    #[test]
+    fn leaving_before_any_tile_refunds_the_stake() {
+        let (mut game, dealer, bettor) = table();
+        game.player_action(bettor.clone(), Arc::new(MinesAction::Bet{amount: 100, mines: 1}));
+
+        assert_eq!(game.player_leave(vec![bettor.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(bettor.get_token(), 1000);
+        assert_eq!(dealer.get_token(), 10_000);
+    }
+}
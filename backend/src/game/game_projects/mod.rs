@@ -1,2 +1,23 @@
 pub mod texas_holdem_poker;
-pub mod game_project;
\ No newline at end of file
+pub mod game_project;
+pub mod omaha;
+pub mod short_deck;
+pub mod seven_card_stud;
+pub mod zha_jin_hua;
+pub mod niu_niu;
+pub mod dou_dizhu;
+pub mod blackjack;
+pub mod baccarat;
+pub mod roulette;
+pub mod sic_bo;
+pub mod craps;
+pub mod crash;
+pub mod slots;
+pub mod mines;
+pub mod plinko;
+pub mod duel;
+pub(crate) mod rule_helpers;
+#[cfg(feature = "mental-poker")]
+pub mod texas_holdem_mental_poker;
+#[cfg(test)]
+pub(crate) mod test_support;
\ No newline at end of file
@@ -0,0 +1,576 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::poker::niu_niu_evaluator::{evaluate_niu_niu, NiuHandValue, NIU_NIU_HAND_SIZE};
+use crate::game::game_items::poker::poker::{get_all_cards, Card};
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, commit_next_seed, commit_seed_on_join, sync_action_players};
+
+/// 牌局状态在 game_context 中的 key
+pub const NIU_NIU_STATE_KEY: &str = "niu_niu_state";
+
+/// 一桌最多的玩家数
+pub const NIU_NIU_MAX_PLAYERS: usize = 10;
+
+/// 抢庄时先发的明牌张数，第五张在下注结束后发
+pub const NIU_NIU_BIDDING_CARDS: usize = 4;
+
+/// 斗牛对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum NiuNiuPhase {
+    #[default]
+    Bidding,  // 每人看四张牌抢庄
+    Betting,  // 闲家选择下注倍数
+    Settled,  // 发完第五张牌并与庄家结算
+}
+
+/// 斗牛玩家行动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NiuNiuAction {
+    Bid(u8), // 抢庄倍数，0 为不抢
+    Bet(u8), // 闲家下注倍数，至少为 1
+}
+
+/// 一位闲家与庄家的结算结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NiuNiuResult {
+    pub user_id: u32,
+    pub hand_value: Option<NiuHandValue>,
+    pub net: i32, // 闲家的输赢，正数为赢
+}
+
+/// 斗牛牌局状态，保存在 game_context 中
+#[derive(Debug, Default)]
+pub struct NiuNiuState {
+    pub phase: NiuNiuPhase,
+    pub hands: HashMap<u32, Vec<Card>>,
+    pub order: Vec<u32>,           // 本手牌的座位顺序
+    pub bids: HashMap<u32, u8>,    // 抢庄倍数
+    pub bets: HashMap<u32, u8>,    // 闲家下注倍数
+    pub banker_user: Option<u32>,
+    pub banker_multiple: u32,      // 庄家倍数，无人抢庄时为 1
+    pub banker_hand_value: Option<NiuHandValue>,
+    pub banker_net: i32,
+    pub results: Vec<NiuNiuResult>,
+    tie_break: u32,                // 开局时由随机数流决定，抢庄倍数相同时据此选出庄家
+}
+
+/// 某位观察者看到的一个座位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NiuNiuSeatView {
+    pub user_id: u32,
+    pub cards: Option<Vec<Card>>, // 本人或结算后可见
+    pub bid: Option<u8>,
+    pub bet: Option<u8>,
+    pub is_banker: bool,
+}
+
+/// 某位观察者看到的牌桌
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NiuNiuTableView {
+    pub phase: NiuNiuPhase,
+    pub seats: Vec<NiuNiuSeatView>,
+    pub banker_multiple: u32,
+}
+
+impl NiuNiuState {
+    /// 还需要行动的玩家：抢庄阶段为未抢庄的玩家，下注阶段为未下注的闲家
+    pub fn pending_players(&self) -> Vec<u32> {
+        match self.phase {
+            NiuNiuPhase::Bidding => self.order.iter().copied().filter(|user_id| !self.bids.contains_key(user_id)).collect(),
+            NiuNiuPhase::Betting => self.order.iter()
+                .copied()
+                .filter(|user_id| Some(*user_id) != self.banker_user && !self.bets.contains_key(user_id))
+                .collect(),
+            NiuNiuPhase::Settled => Vec::new(),
+        }
+    }
+
+    /// viewer 看到的牌桌，viewer 为 None 时是旁观者视角
+    pub fn view_for(&self, viewer: Option<u32>) -> NiuNiuTableView {
+        let seats: Vec<NiuNiuSeatView> = self.order.iter()
+            .map(|user_id| {
+                let visible = viewer == Some(*user_id) || self.phase == NiuNiuPhase::Settled;
+                NiuNiuSeatView {
+                    user_id: *user_id,
+                    cards: if visible { self.hands.get(user_id).cloned() } else { None },
+                    bid: self.bids.get(user_id).copied(),
+                    bet: self.bets.get(user_id).copied(),
+                    is_banker: self.banker_user == Some(*user_id),
+                }
+            })
+            .collect();
+        NiuNiuTableView{phase: self.phase, seats, banker_multiple: self.banker_multiple}
+    }
+
+    // 开始新的一手牌前清空上一手的数据
+    fn reset_hand(&mut self) {
+        self.phase = NiuNiuPhase::Bidding;
+        self.hands.clear();
+        self.order.clear();
+        self.bids.clear();
+        self.bets.clear();
+        self.banker_user = None;
+        self.banker_multiple = 1;
+        self.banker_hand_value = None;
+        self.banker_net = 0;
+        self.results.clear();
+        self.tie_break = 0;
+    }
+
+    // 抢庄倍数最高的玩家坐庄，倍数相同时由开局的随机数决定，无人抢庄时所有人一起参与
+    fn choose_banker(&mut self) {
+        let highest = self.bids.values().copied().max().unwrap_or(0);
+        let candidates: Vec<u32> = self.order.iter()
+            .copied()
+            .filter(|user_id| self.bids.get(user_id).copied().unwrap_or(0) == highest)
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+        self.banker_user = Some(candidates[self.tie_break as usize % candidates.len()]);
+        self.banker_multiple = (highest as u32).max(1);
+    }
+
+    // 本手牌作废，不再发牌和结算
+    fn void_hand(&mut self) {
+        self.hands.clear();
+        self.banker_user = None;
+        self.phase = NiuNiuPhase::Settled;
+    }
+
+    // 抢庄全部完成时进入下注，下注全部完成或本手牌作废时结算
+    fn outcome(&self) -> ActionOutcome {
+        match (self.phase, self.pending_players().is_empty()) {
+            (NiuNiuPhase::Settled, _) => ActionOutcome::GameComplete,
+            (_, false) => ActionOutcome::Continue,
+            (NiuNiuPhase::Bidding, true) => ActionOutcome::RoundComplete,
+            (NiuNiuPhase::Betting, true) => ActionOutcome::GameComplete,
+        }
+    }
+}
+
+impl ActingPlayers for NiuNiuState {
+    fn acting_players(&self, _players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players()
+    }
+}
+
+/// 抢庄斗牛规则配置
+#[derive(Debug, Clone, Copy)]
+pub struct NiuNiuGameRules {
+    pub base_bet: u16,          // 底分
+    pub max_banker_multiple: u8, // 最高抢庄倍数
+    pub max_bet_multiple: u8,    // 闲家最高下注倍数
+}
+
+impl NiuNiuGameRules {
+    pub fn new(base_bet: u16) -> Self {
+        NiuNiuGameRules{base_bet, max_banker_multiple: 4, max_bet_multiple: 5}
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.base_bet == 0 || config.max_bet_multiple == 0 {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|left, right, _| {
+                let cards = |items: &Vec<&dyn GameItem>| -> Vec<Card> {
+                    items.iter().filter_map(|item| Card::from_item(*item)).collect()
+                };
+                evaluate_niu_niu(&cards(left)) > evaluate_niu_niu(&cards(right))
+            }),
+            Arc::new(allocate),
+            Arc::new(game_start),
+            Arc::new(game_progress),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_finish(config, players, game_items, context)),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+// 重置牌局状态并洗牌，所有人恢复为普通玩家
+fn game_start(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<NiuNiuState>(&context, NIU_NIU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_hand();
+    for player in &players {
+        player.update_player_role(PlayerRole::Player);
+    }
+
+    if players.len() < 2 || players.len() > NIU_NIU_MAX_PLAYERS {
+        return;
+    }
+    state.order = players.iter().map(|player| player.get_user().get_id()).collect();
+
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    let mut rng = lock_or_recover(&fairness).begin_round(&state.order);
+    let mut deck = get_all_cards();
+    rng.shuffle(&mut deck);
+    state.tie_break = rng.next_u32();
+    *lock_or_recover(&game_items) = deck
+        .into_iter()
+        .map(|card| Arc::new(card) as Arc<dyn GameItem>)
+        .collect();
+}
+
+// 每人先发四张牌，开始抢庄
+fn allocate(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<NiuNiuState>(&context, NIU_NIU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+
+    for _ in 0..NIU_NIU_BIDDING_CARDS {
+        deal_round(&mut state, &mut deck);
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 抢庄结束：选出庄家并设为 Dealer 角色，进入下注阶段
+fn game_progress(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<NiuNiuState>(&context, NIU_NIU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase != NiuNiuPhase::Bidding {
+        return;
+    }
+
+    state.choose_banker();
+    for player in &players {
+        let role = if Some(player.get_user().get_id()) == state.banker_user { PlayerRole::Dealer } else { PlayerRole::Player };
+        player.update_player_role(role);
+    }
+    state.phase = NiuNiuPhase::Betting;
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家抢庄或下注，所有人都完成后进入下一阶段
+fn player_action(
+    config: NiuNiuGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<NiuNiuState>(&context, NIU_NIU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(action) = action.downcast_ref::<NiuNiuAction>().copied() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players().contains(&user_id) {
+        return ActionOutcome::Rejected("player has nothing to do".to_string());
+    }
+
+    match (state.phase, action) {
+        (NiuNiuPhase::Bidding, NiuNiuAction::Bid(multiple)) => {
+            if multiple > config.max_banker_multiple {
+                return ActionOutcome::Rejected(format!("bid must be at most {}", config.max_banker_multiple));
+            }
+            state.bids.insert(user_id, multiple);
+        }
+        (NiuNiuPhase::Betting, NiuNiuAction::Bet(multiple)) => {
+            if multiple == 0 || multiple > config.max_bet_multiple {
+                return ActionOutcome::Rejected(format!("bet must be between 1 and {}", config.max_bet_multiple));
+            }
+            state.bets.insert(user_id, multiple);
+        }
+        _ => return ActionOutcome::Rejected("action does not match the current phase".to_string()),
+    }
+
+    let outcome = state.outcome();
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 发第五张牌，每位闲家与庄家比牌，赢家按自己牌型的倍数收取：底分 × 庄家倍数 × 闲家倍数 × 牌型倍数
+//
+// 先收输家的筹码再赔付赢家，庄家筹码不足以全额赔付时按各赢家应得的比例分配
+fn game_finish(
+    config: NiuNiuGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<NiuNiuState>(&context, NIU_NIU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+    if state.phase != NiuNiuPhase::Betting {
+        // 庄家离桌作废的一手牌不结算，只公开本手牌的服务端种子
+        if state.phase == NiuNiuPhase::Settled && state.banker_user.is_none() {
+            let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+            lock_or_recover(&fairness).reveal();
+        }
+        return;
+    }
+
+    while state.hands.values().any(|cards| cards.len() < NIU_NIU_HAND_SIZE) && !deck.is_empty() {
+        deal_round(&mut state, &mut deck);
+    }
+    state.phase = NiuNiuPhase::Settled;
+
+    let Some(banker_user) = state.banker_user else {
+        return;
+    };
+    let Some(banker) = players.iter().find(|player| player.get_user().get_id() == banker_user).cloned() else {
+        return;
+    };
+    let banker_hand_value = state.hands.get(&banker_user).and_then(|cards| evaluate_niu_niu(cards));
+    state.banker_hand_value = banker_hand_value;
+
+    let stake = config.base_bet as u32 * state.banker_multiple;
+    let mut winners: Vec<(Arc<Player>, u32)> = Vec::new();
+    let mut nets: HashMap<u32, i32> = HashMap::new();
+    let mut banker_net: i32 = 0;
+    for player in &players {
+        let user_id = player.get_user().get_id();
+        if user_id == banker_user || !state.hands.contains_key(&user_id) {
+            continue;
+        }
+        let hand_value = evaluate_niu_niu(&state.hands[&user_id]);
+        let bet = state.bets.get(&user_id).copied().unwrap_or(1) as u32;
+        if hand_value > banker_hand_value {
+            let multiplier = hand_value.map(|value| value.multiplier()).unwrap_or(1);
+            winners.push((player.clone(), stake * bet * multiplier));
+        } else {
+            let multiplier = banker_hand_value.map(|value| value.multiplier()).unwrap_or(1);
+            let lost = player.take_token((stake * bet * multiplier).min(u16::MAX as u32) as u16);
+            banker.add_token(lost);
+            banker_net += lost as i32;
+            nets.insert(user_id, -(lost as i32));
+        }
+    }
+    let owed: Vec<u64> = winners.iter().map(|(_, amount)| *amount as u64).collect();
+    let payouts = pro_rata(&owed, banker.get_token() as u64);
+    for ((player, _), amount) in winners.into_iter().zip(payouts) {
+        let paid = banker.take_token(amount.min(u16::MAX as u64) as u16);
+        player.add_token(paid);
+        banker_net -= paid as i32;
+        nets.insert(player.get_user().get_id(), paid as i32);
+    }
+
+    let order = state.order.clone();
+    state.results = order.iter()
+        .filter(|user_id| **user_id != banker_user && state.hands.contains_key(user_id))
+        .map(|user_id| NiuNiuResult {
+            user_id: *user_id,
+            hand_value: evaluate_niu_niu(&state.hands[user_id]),
+            net: nets.get(user_id).copied().unwrap_or(0),
+        })
+        .collect();
+    state.banker_net = banker_net;
+
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    lock_or_recover(&fairness).reveal();
+    sync_action_players(&state, &players, &context);
+}
+
+// 可赔付的筹码不足时按应得数额的比例分配，取整剩下的筹码依次补给余数最大的赢家，余数相同时按座位顺序
+fn pro_rata(owed: &[u64], available: u64) -> Vec<u64> {
+    let total: u64 = owed.iter().sum();
+    if total <= available {
+        return owed.to_vec();
+    }
+    let mut payouts: Vec<u64> = owed.iter().map(|amount| amount * available / total).collect();
+    let mut by_remainder: Vec<usize> = (0..owed.len()).collect();
+    by_remainder.sort_by_key(|index| std::cmp::Reverse(owed[*index] * available % total));
+    let leftover = available - payouts.iter().sum::<u64>();
+    for index in by_remainder.into_iter().take(leftover as usize) {
+        payouts[index] += 1;
+    }
+    payouts
+}
+
+// 闲家离桌时退出本手牌且不结算；庄家离桌或只剩一人时本手牌作废，其余人都已行动时进入下一阶段
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<NiuNiuState>(&context, NIU_NIU_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == NiuNiuPhase::Settled {
+        return ActionOutcome::Continue;
+    }
+
+    for player in leave_players {
+        let user_id = player.get_user().get_id();
+        player.update_player_role(PlayerRole::Player);
+        if state.banker_user == Some(user_id) {
+            state.void_hand();
+        }
+        state.hands.remove(&user_id);
+        state.order.retain(|seat| *seat != user_id);
+        state.bids.remove(&user_id);
+        state.bets.remove(&user_id);
+    }
+    if state.order.len() < 2 {
+        state.void_hand();
+    }
+    let outcome = state.outcome();
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+// 按座位顺序给每人发一张牌
+fn deal_round(state: &mut NiuNiuState, deck: &mut Vec<Arc<dyn GameItem>>) {
+    for user_id in state.order.clone() {
+        let Some(card) = draw_card(deck) else {
+            return;
+        };
+        state.hands.entry(user_id).or_default().push(card);
+    }
+}
+
+// 从牌堆顶摸一张牌
+fn draw_card(deck: &mut Vec<Arc<dyn GameItem>>) -> Option<Card> {
+    if deck.is_empty() {
+        return None;
+    }
+    Card::from_item(deck.remove(0).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::{Game, GameState};
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+
+    fn table() -> (Game, Vec<Arc<Player>>) {
+        let mut game = game(NiuNiuGameRules::new(1).build().unwrap());
+        let players: Vec<Arc<Player>> = (1..=3).map(|id| player(id, PlayerRole::Player, GameProject::NiuNiu, 1000)).collect();
+        game.player_join(players.clone());
+        game.game_start();
+        (game, players)
+    }
+
+    fn niu_niu_state(game: &Game) -> Arc<Mutex<NiuNiuState>> {
+        get_state::<NiuNiuState>(&game.get_game_context(), NIU_NIU_STATE_KEY).unwrap()
+    }
+
+    // 1 号抢庄，其余不抢
+    fn bid(game: &mut Game, players: &[Arc<Player>], skip_last: bool) {
+        let count = if skip_last { players.len() - 1 } else { players.len() };
+        for (index, player) in players.iter().take(count).enumerate() {
+            let multiple = if index == 0 { 2 } else { 0 };
+            game.player_action(player.clone(), Arc::new(NiuNiuAction::Bid(multiple)));
+        }
+    }
+
+    #[test]
+    fn last_bidder_leaving_moves_to_betting() {
+        let (mut game, players) = table();
+        bid(&mut game, &players, true);
+        assert_eq!(game.player_leave(vec![players[2].clone()]), ActionOutcome::RoundComplete);
+        let state = niu_niu_state(&game);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.phase, NiuNiuPhase::Betting);
+        assert_eq!(state.banker_user, Some(1));
+        assert_eq!(state.pending_players(), vec![2]);
+    }
+
+    #[test]
+    fn last_bettor_leaving_settles_hand() {
+        let (mut game, players) = table();
+        bid(&mut game, &players, false);
+        game.player_action(players[1].clone(), Arc::new(NiuNiuAction::Bet(1)));
+        assert_eq!(game.player_leave(vec![players[2].clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        let state = niu_niu_state(&game);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.phase, NiuNiuPhase::Settled);
+        assert_eq!(state.results.len(), 1);
+        assert_eq!(state.results[0].net, -state.banker_net);
+        assert_eq!(players[2].get_token(), 1000);
+    }
+
+    #[test]
+    fn banker_leaving_voids_hand() {
+        let (mut game, players) = table();
+        bid(&mut game, &players, false);
+        assert_eq!(game.player_leave(vec![players[0].clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert!(lock_or_recover(&niu_niu_state(&game)).results.is_empty());
+        assert!(players.iter().all(|player| player.get_token() == 1000));
+        assert_eq!(players[0].get_player_role(), PlayerRole::Player);
+    }
+
+    fn cards(text: &str) -> Vec<Card> {
+        text.split_whitespace().map(|card| card.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn short_banker_pays_winners_pro_rata() {
+        let mut game = game(NiuNiuGameRules::new(10).build().unwrap());
+        let players = vec![
+            player(1, PlayerRole::Player, GameProject::NiuNiu, 30),
+            player(2, PlayerRole::Player, GameProject::NiuNiu, 1000),
+            player(3, PlayerRole::Player, GameProject::NiuNiu, 1000),
+        ];
+        game.player_join(players.clone());
+        game.game_start();
+        bid(&mut game, &players, false);
+        {
+            let state = niu_niu_state(&game);
+            let mut state = lock_or_recover(&state);
+            state.hands.insert(1, cards("As 2d 3c 5h 9s"));
+            state.hands.insert(2, cards("Ks Qd Jc Kh Qs"));
+            state.hands.insert(3, cards("Kd Qh Jd Kc Qc"));
+        }
+        game.player_action(players[1].clone(), Arc::new(NiuNiuAction::Bet(1)));
+        game.player_action(players[2].clone(), Arc::new(NiuNiuAction::Bet(3)));
+
+        // 两位闲家都是五花牛，应得 200 和 600，庄家只有 30，按 1:3 分配为 7.5 和 22.5，剩下的 1 个筹码按座位顺序补给 2 号
+        assert_eq!(players.iter().map(|player| player.get_token()).collect::<Vec<_>>(), vec![0, 1008, 1022]);
+        let state = niu_niu_state(&game);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.banker_net, -30);
+        assert_eq!(state.results.iter().map(|result| result.net).collect::<Vec<_>>(), vec![8, 22]);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::game::betting::betting_round::BettingStructure;
+use crate::game::game_projects::game_project::GameProject;
+use crate::game::game_projects::texas_holdem_poker::{HoldemVariant, TexasHoldemPokerGameRules};
+use crate::game::game_rule::{GameRule, GameRuleError};
+
+/// 奥马哈的底牌张数
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum OmahaHoleCards {
+    Four, // PLO4
+    Five, // PLO5
+    Six,  // PLO6
+}
+
+impl OmahaHoleCards {
+    pub fn count(&self) -> usize {
+        match self {
+            OmahaHoleCards::Four => 4,
+            OmahaHoleCards::Five => 5,
+            OmahaHoleCards::Six => 6,
+        }
+    }
+
+    pub fn game_project(&self) -> GameProject {
+        match self {
+            OmahaHoleCards::Four => GameProject::OmahaFourCard,
+            OmahaHoleCards::Five => GameProject::OmahaFiveCard,
+            OmahaHoleCards::Six => GameProject::OmahaSixCard,
+        }
+    }
+
+    pub fn from_game_project(game_project: GameProject) -> Option<Self> {
+        match game_project {
+            GameProject::OmahaFourCard => Some(OmahaHoleCards::Four),
+            GameProject::OmahaFiveCard => Some(OmahaHoleCards::Five),
+            GameProject::OmahaSixCard => Some(OmahaHoleCards::Six),
+            _ => None,
+        }
+    }
+}
+
+/// 奥马哈规则配置，发牌、下注和结算沿用德州扑克的流程，牌局状态同样保存在 TEXAS_HOLDEM_STATE_KEY 下
+#[derive(Debug, Clone, Copy)]
+pub struct OmahaGameRules {
+    pub small_blind: u16,
+    pub big_blind: u16,
+    pub hole_cards: OmahaHoleCards,
+    pub betting_structure: BettingStructure,
+    pub hi_lo: bool, // 奥马哈高低(8-or-better)
+}
+
+impl OmahaGameRules {
+    /// 默认底池限注
+    pub fn new(small_blind: u16, big_blind: u16, hole_cards: OmahaHoleCards) -> Self {
+        OmahaGameRules {
+            small_blind,
+            big_blind,
+            hole_cards,
+            betting_structure: BettingStructure::PotLimit,
+            hi_lo: false,
+        }
+    }
+
+    /// 按 GameProject 创建对应的规则，OmahaHiLo 为四张底牌的高低玩法，非奥马哈项目返回 None
+    pub fn from_game_project(game_project: GameProject, small_blind: u16, big_blind: u16) -> Option<Self> {
+        if game_project == GameProject::OmahaHiLo {
+            let mut rules = OmahaGameRules::new(small_blind, big_blind, OmahaHoleCards::Four);
+            rules.hi_lo = true;
+            return Some(rules);
+        }
+        OmahaHoleCards::from_game_project(game_project).map(|hole_cards| OmahaGameRules::new(small_blind, big_blind, hole_cards))
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let mut holdem = TexasHoldemPokerGameRules::new(self.small_blind, self.big_blind, self.betting_structure);
+        let hole_cards = self.hole_cards.count();
+        holdem.variant = if self.hi_lo { HoldemVariant::OmahaHiLo{hole_cards} } else { HoldemVariant::Omaha{hole_cards} };
+        holdem.build()
+    }
+}
@@ -0,0 +1,365 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairRng, FairnessState, SeedReveal, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, collect, commit_next_seed, commit_seed_on_join, find_player, pay, sync_action_players};
+
+/// 对局状态在 game_context 中的 key
+pub const PLINKO_STATE_KEY: &str = "plinko_state";
+
+/// 倍数以百分之一为单位，100 即 1.00x
+pub const PLINKO_MULTIPLIER_BASE: u32 = 100;
+
+/// 风险等级，越高两端的倍数越大、中间越小
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum PlinkoRisk {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+// 各行数与风险等级的赔付表，从最左的落点到最右的落点
+const PLINKO_8_LOW: [u32; 9] = [560, 210, 110, 100, 50, 100, 110, 210, 560];
+const PLINKO_8_MEDIUM: [u32; 9] = [1300, 300, 130, 70, 40, 70, 130, 300, 1300];
+const PLINKO_8_HIGH: [u32; 9] = [2900, 400, 150, 30, 20, 30, 150, 400, 2900];
+const PLINKO_12_LOW: [u32; 13] = [1000, 300, 160, 140, 110, 100, 50, 100, 110, 140, 160, 300, 1000];
+const PLINKO_12_MEDIUM: [u32; 13] = [3300, 1100, 400, 200, 110, 60, 30, 60, 110, 200, 400, 1100, 3300];
+const PLINKO_12_HIGH: [u32; 13] = [17000, 2400, 810, 200, 70, 20, 20, 20, 70, 200, 810, 2400, 17000];
+const PLINKO_16_LOW: [u32; 17] = [1600, 900, 200, 140, 140, 120, 110, 100, 50, 100, 110, 120, 140, 140, 200, 900, 1600];
+const PLINKO_16_MEDIUM: [u32; 17] = [11000, 4100, 1000, 500, 300, 150, 100, 50, 30, 50, 100, 150, 300, 500, 1000, 4100, 11000];
+const PLINKO_16_HIGH: [u32; 17] = [100000, 13000, 2600, 900, 400, 200, 20, 20, 20, 20, 20, 200, 400, 900, 2600, 13000, 100000];
+
+/// 弹珠玩家行动，0 为本局不参与
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlinkoAction {
+    Drop(u32),
+}
+
+/// 对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum PlinkoPhase {
+    #[default]
+    Betting, // 等待下注
+    Settled, // 已落下并结算
+}
+
+/// 弹珠对局状态，保存在 game_context 中
+#[derive(Debug, Default)]
+pub struct PlinkoState {
+    pub phase: PlinkoPhase,
+    pub dealer_user: Option<u32>,
+    pub player_user: Option<u32>,
+    pub stake: u32,
+    pub path: Vec<bool>, // 每一行向右(true)或向左(false)
+    pub slot: Option<usize>,
+    pub multiplier: u32,
+    pub net: i32,
+}
+
+impl PlinkoState {
+    /// 还需要行动的玩家
+    pub fn pending_players(&self) -> Vec<u32> {
+        match self.phase {
+            PlinkoPhase::Betting => self.player_user.into_iter().collect(),
+            PlinkoPhase::Settled => Vec::new(),
+        }
+    }
+
+    fn reset_round(&mut self) {
+        self.phase = PlinkoPhase::Betting;
+        self.dealer_user = None;
+        self.player_user = None;
+        self.stake = 0;
+        self.path.clear();
+        self.slot = None;
+        self.multiplier = 0;
+        self.net = 0;
+    }
+}
+
+impl ActingPlayers for PlinkoState {
+    fn acting_players(&self, _players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players()
+    }
+}
+
+/// 弹珠规则配置，单人对庄家，庄家由 PlayerRole::Dealer 的玩家担任；支持 8、12、16 行
+#[derive(Debug, Clone, Copy)]
+pub struct PlinkoGameRules {
+    pub rows: u8,
+    pub risk: PlinkoRisk,
+    pub min_bet: u32,
+    pub max_bet: u32,
+}
+
+impl PlinkoGameRules {
+    pub fn new(rows: u8, risk: PlinkoRisk, min_bet: u32, max_bet: u32) -> Self {
+        PlinkoGameRules{rows, risk, min_bet, max_bet}
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if plinko_payouts(config.rows, config.risk).is_none() || config.min_bet == 0 || config.max_bet < config.min_bet {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|_, _, _| false),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_start),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_finish(config, players, game_items, context)),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+/// 行数与风险等级对应的赔付表(百分之一)，不支持的组合返回 None
+pub fn plinko_payouts(rows: u8, risk: PlinkoRisk) -> Option<&'static [u32]> {
+    match (rows, risk) {
+        (8, PlinkoRisk::Low) => Some(&PLINKO_8_LOW),
+        (8, PlinkoRisk::Medium) => Some(&PLINKO_8_MEDIUM),
+        (8, PlinkoRisk::High) => Some(&PLINKO_8_HIGH),
+        (12, PlinkoRisk::Low) => Some(&PLINKO_12_LOW),
+        (12, PlinkoRisk::Medium) => Some(&PLINKO_12_MEDIUM),
+        (12, PlinkoRisk::High) => Some(&PLINKO_12_HIGH),
+        (16, PlinkoRisk::Low) => Some(&PLINKO_16_LOW),
+        (16, PlinkoRisk::Medium) => Some(&PLINKO_16_MEDIUM),
+        (16, PlinkoRisk::High) => Some(&PLINKO_16_HIGH),
+        _ => None,
+    }
+}
+
+/// 赔付表的理论返还率：落点服从二项分布 B(rows, 1/2)
+pub fn plinko_rtp(rows: u8, risk: PlinkoRisk) -> Option<f64> {
+    let payouts = plinko_payouts(rows, risk)?;
+    let mut ways = 1f64;
+    let mut total = 0f64;
+    for (slot, multiplier) in payouts.iter().enumerate() {
+        total += ways * *multiplier as f64;
+        ways = ways * (rows as usize - slot) as f64 / (slot + 1) as f64;
+    }
+    Some(total / 2f64.powi(rows as i32) / PLINKO_MULTIPLIER_BASE as f64)
+}
+
+/// 用本局的随机数决定每一行向左还是向右
+pub fn plinko_path(rng: &mut FairRng, rows: u8) -> Vec<bool> {
+    (0..rows).map(|_| rng.next_below(2) == 1).collect()
+}
+
+/// 用公开的种子重算某局的路径，种子与承诺不符时返回 None
+pub fn verify_plinko_drop(reveal: &SeedReveal, rows: u8) -> Option<Vec<bool>> {
+    if !reveal.verify_commitment() {
+        return None;
+    }
+    Some(plinko_path(&mut reveal.rng(), rows))
+}
+
+// 开局：需要一位庄家和一位玩家，等待下注
+fn game_start(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<PlinkoState>(&context, PLINKO_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_round();
+
+    let dealer = players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer);
+    let seated: Vec<&Arc<Player>> = players.iter().filter(|player| player.get_player_role() == PlayerRole::Player).collect();
+    let (Some(dealer), [player]) = (dealer, seated.as_slice()) else {
+        state.phase = PlinkoPhase::Settled;
+        return;
+    };
+    state.dealer_user = Some(dealer.get_user().get_id());
+    state.player_user = Some(player.get_user().get_id());
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家下注后即结束本局，由 game_finish 落下弹珠
+fn player_action(
+    config: PlinkoGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<PlinkoState>(&context, PLINKO_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(PlinkoAction::Drop(amount)) = action.downcast_ref::<PlinkoAction>().copied() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    if !state.pending_players().contains(&player.get_user().get_id()) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+    if amount != 0 && (amount < config.min_bet || amount > config.max_bet) {
+        return ActionOutcome::Rejected(format!("bet must be between {} and {}", config.min_bet, config.max_bet));
+    }
+    if amount > player.get_token() as u32 {
+        return ActionOutcome::Rejected("not enough tokens".to_string());
+    }
+    player.take_token(amount as u16);
+    state.stake = amount;
+    state.phase = PlinkoPhase::Settled;
+    sync_action_players(&state, &players, &context);
+    ActionOutcome::GameComplete
+}
+
+// 下注结束后才开始本局的随机数并落下弹珠，按落点的倍数与庄家结算，随即公开种子
+fn game_finish(
+    config: PlinkoGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<PlinkoState>(&context, PLINKO_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.phase = PlinkoPhase::Settled;
+    if state.stake == 0 || state.slot.is_some() {
+        return;
+    }
+    let (Some(player), Some(payouts)) = (state.player_user.and_then(|user_id| find_player(&players, user_id)), plinko_payouts(config.rows, config.risk)) else {
+        return;
+    };
+
+    let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    let mut fairness = lock_or_recover(&fairness);
+    let path = plinko_path(&mut fairness.begin_round(&seat_order), config.rows);
+    fairness.reveal();
+
+    let slot = path.iter().filter(|right| **right).count();
+    let multiplier = payouts[slot];
+    let dealer = state.dealer_user.and_then(|user_id| find_player(&players, user_id));
+    let returned = (state.stake as u64 * multiplier as u64 / PLINKO_MULTIPLIER_BASE as u64).min(u32::MAX as u64) as u32;
+    let refunded = returned.min(state.stake);
+    player.add_token(refunded as u16);
+    collect(&dealer, state.stake - refunded);
+    let paid = pay(&dealer, &player, returned - refunded);
+    state.net = refunded as i32 + paid as i32 - state.stake as i32;
+    state.path = path;
+    state.slot = Some(slot);
+    state.multiplier = multiplier;
+}
+
+// 下注前离开的玩家不再需要行动，这一局不落弹珠直接结束
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<PlinkoState>(&context, PLINKO_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let left = leave_players.iter().any(|player| state.player_user == Some(player.get_user().get_id()));
+    let outcome = if left && state.phase == PlinkoPhase::Betting {
+        state.phase = PlinkoPhase::Settled;
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+
+    #[test]
+    fn payout_tables_keep_a_house_edge() {
+        for rows in [8, 12, 16] {
+            for risk in [PlinkoRisk::Low, PlinkoRisk::Medium, PlinkoRisk::High] {
+                let payouts = plinko_payouts(rows, risk).unwrap();
+                assert_eq!(payouts.len(), rows as usize + 1);
+                assert!(payouts.iter().eq(payouts.iter().rev()));
+                let rtp = plinko_rtp(rows, risk).unwrap();
+                assert!(rtp > 0.95 && rtp < 1.0, "{} rows {:?}: {}", rows, risk, rtp);
+            }
+        }
+        assert!(plinko_payouts(10, PlinkoRisk::Low).is_none());
+    }
+
+    #[test]
+    fn higher_risk_moves_payouts_to_the_edges() {
+        for rows in [8, 12, 16] {
+            let low = plinko_payouts(rows, PlinkoRisk::Low).unwrap();
+            let high = plinko_payouts(rows, PlinkoRisk::High).unwrap();
+            let center = rows as usize / 2;
+            assert!(high[0] > low[0], "{} rows", rows);
+            assert!(high[center] < low[center], "{} rows", rows);
+        }
+    }
+
+    #[test]
+    fn path_has_one_bounce_per_row() {
+        let path = plinko_path(&mut FairRng::new("server", &[], 0), 16);
+        assert_eq!(path.len(), 16);
+        assert_eq!(path, plinko_path(&mut FairRng::new("server", &[], 0), 16));
+    }
+
+    #[test]
+    fn drop_verifies_against_revealed_seed() {
+        let config = PlinkoGameRules::new(12, PlinkoRisk::Medium, 10, 500);
+        let mut game = game(config.build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Plinko, 10_000);
+        let bettor = player(1, PlayerRole::Player, GameProject::Plinko, 1000);
+        game.player_join(vec![dealer.clone(), bettor.clone()]);
+        game.game_start();
+        assert_eq!(game.player_action(bettor.clone(), Arc::new(PlinkoAction::Drop(100))), ActionOutcome::GameComplete);
+
+        let fairness = get_state::<FairnessState>(&game.get_game_context(), PROVABLY_FAIR_KEY).unwrap();
+        let reveal = lock_or_recover(&fairness).get_last_reveal().cloned().unwrap();
+        let state = get_state::<PlinkoState>(&game.get_game_context(), PLINKO_STATE_KEY).unwrap();
+        let state = lock_or_recover(&state);
+        assert_eq!(verify_plinko_drop(&reveal, 12), Some(state.path.clone()));
+        assert_eq!(state.multiplier, PLINKO_12_MEDIUM[state.slot.unwrap()]);
+        assert_eq!(bettor.get_token() as i32 + dealer.get_token() as i32, 11_000);
+        assert_eq!(state.net, bettor.get_token() as i32 - 1000);
+    }
+
+    #[test]
+    fn leaving_before_the_drop_finishes_the_round() {
+        let mut game = game(PlinkoGameRules::new(8, PlinkoRisk::Low, 10, 500).build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Plinko, 10_000);
+        let bettor = player(1, PlayerRole::Player, GameProject::Plinko, 1000);
+        game.player_join(vec![dealer, bettor.clone()]);
+        game.game_start();
+
+        assert_eq!(game.player_leave(vec![bettor.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(bettor.get_token(), 1000);
+    }
+}
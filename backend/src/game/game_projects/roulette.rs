@@ -0,0 +1,474 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::roulette::bet::{validate_payout_table, RouletteBet};
+use crate::game::game_items::roulette::wheel::{Ball, Pocket, Wheel, WheelLayout};
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, collect, commit_next_seed, commit_seed_on_join, find_player, pay, sync_action_players};
+
+/// 牌局状态在 game_context 中的 key
+pub const ROULETTE_STATE_KEY: &str = "roulette_state";
+
+/// 一桌最多的下注玩家数
+pub const ROULETTE_MAX_PLAYERS: usize = 16;
+
+/// 轮盘玩法
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum RouletteVariant {
+    European, // 单零
+    American, // 0 和 00
+    French,   // 单零，开出 0 时 1:1 的注输一半
+}
+
+/// 开出 0 时 1:1 外围注的处理方式
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum ZeroRule {
+    #[default]
+    None,      // 直接输
+    LaPartage, // 分享规则，退回一半
+    EnPrison,  // 入狱规则，注额留到下一转，下一转赢则退回本金
+}
+
+/// 轮盘玩家行动，一次提交本局全部下注，空列表为本局不下注
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouletteAction {
+    Bet(Vec<(RouletteBet, u32)>),
+}
+
+/// 对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum RoulettePhase {
+    #[default]
+    Betting, // 等待下注
+    Settled, // 已转动轮盘并结算
+}
+
+/// 一注的结算结果
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum RouletteOutcome {
+    Won,
+    Lost,
+    Halved,     // 分享规则退回一半
+    Imprisoned, // 入狱，留到下一转
+    Released,   // 入狱的注在这一转赢回本金
+}
+
+/// 一注的结算记录，net 为正表示玩家赢
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouletteWagerResult {
+    pub user_id: u32,
+    pub bet: RouletteBet,
+    pub stake: u32,
+    pub outcome: RouletteOutcome,
+    pub net: i32,
+}
+
+/// 轮盘对局状态，保存在 game_context 中，入狱的注跨局保留
+#[derive(Debug, Default)]
+pub struct RouletteState {
+    pub phase: RoulettePhase,
+    pub dealer_user: Option<u32>,
+    pub bets: HashMap<u32, Vec<(RouletteBet, u32)>>,
+    pub imprisoned: Vec<(u32, RouletteBet, u32)>,
+    pub pocket: Option<Pocket>,
+    pub results: Vec<RouletteWagerResult>,
+}
+
+impl RouletteState {
+    /// 还没下注的玩家
+    pub fn pending_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        if self.phase != RoulettePhase::Betting {
+            return Vec::new();
+        }
+        players.iter()
+            .filter(|player| player.get_player_role() == PlayerRole::Player)
+            .map(|player| player.get_user().get_id())
+            .filter(|user_id| !self.bets.contains_key(user_id))
+            .collect()
+    }
+
+    fn reset_spin(&mut self) {
+        self.phase = RoulettePhase::Betting;
+        self.dealer_user = None;
+        self.bets.clear();
+        self.pocket = None;
+        self.results.clear();
+    }
+}
+
+impl ActingPlayers for RouletteState {
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players(players)
+    }
+}
+
+/// 轮盘规则配置，庄家由 PlayerRole::Dealer 的玩家担任，所有注额都与他的筹码结算
+#[derive(Debug, Clone, Copy)]
+pub struct RouletteGameRules {
+    pub layout: WheelLayout,
+    pub zero_rule: ZeroRule,
+    pub min_bet: u32,
+    pub max_bet: u32, // 每一注的上限
+}
+
+impl RouletteGameRules {
+    pub fn new(variant: RouletteVariant, min_bet: u32, max_bet: u32) -> Self {
+        let (layout, zero_rule) = match variant {
+            RouletteVariant::European => (WheelLayout::European, ZeroRule::None),
+            RouletteVariant::American => (WheelLayout::American, ZeroRule::None),
+            RouletteVariant::French => (WheelLayout::European, ZeroRule::LaPartage),
+        };
+        RouletteGameRules{layout, zero_rule, min_bet, max_bet}
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.min_bet == 0 || config.max_bet < config.min_bet || !validate_payout_table() {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|left, right, _| {
+                let pocket = |items: &Vec<&dyn GameItem>| items.iter().find_map(|item| Ball::from_item(*item)).and_then(|ball| ball.pocket);
+                pocket(left) > pocket(right)
+            }),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_start(config, players, game_items, context)),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_finish(config, players, game_items, context)),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+// 开局：找出庄家，把轮盘和球放到桌上，等待下注
+fn game_start(
+    config: RouletteGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<RouletteState>(&context, ROULETTE_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_spin();
+
+    let dealer = players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer);
+    let player_count = players.iter().filter(|player| player.get_player_role() == PlayerRole::Player).count();
+    let Some(dealer) = dealer else {
+        state.phase = RoulettePhase::Settled;
+        return;
+    };
+    if player_count == 0 || player_count > ROULETTE_MAX_PLAYERS {
+        state.phase = RoulettePhase::Settled;
+        return;
+    }
+    state.dealer_user = Some(dealer.get_user().get_id());
+    *lock_or_recover(&game_items) = vec![Arc::new(Wheel::new(config.layout)), Arc::new(Ball::default())];
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家提交下注，最后一位下注后转动轮盘
+fn player_action(
+    config: RouletteGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<RouletteState>(&context, ROULETTE_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(RouletteAction::Bet(bets)) = action.downcast_ref::<RouletteAction>().cloned() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players(&players).contains(&user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+    let wheel = Wheel::new(config.layout);
+    for (bet, stake) in bets.iter() {
+        if *stake < config.min_bet || *stake > config.max_bet {
+            return ActionOutcome::Rejected(format!("bet must be between {} and {}", config.min_bet, config.max_bet));
+        }
+        if !bet.accepts_stake(&wheel, *stake) {
+            return ActionOutcome::Rejected(format!("invalid bet {:?}", bet));
+        }
+    }
+    let total: u32 = bets.iter().map(|(_, stake)| stake).sum();
+    if total > player.get_token() as u32 {
+        return ActionOutcome::Rejected("not enough tokens".to_string());
+    }
+    player.take_token(total as u16);
+    state.bets.insert(user_id, bets);
+
+    let outcome = if state.pending_players(&players).is_empty() { ActionOutcome::GameComplete } else { ActionOutcome::Continue };
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 下注结束后才开始本转的随机数并转动轮盘，随即公开种子；然后先结算入狱的注，再结算本转的注
+fn game_finish(
+    config: RouletteGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<RouletteState>(&context, ROULETTE_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == RoulettePhase::Settled {
+        return;
+    }
+    state.phase = RoulettePhase::Settled;
+
+    let mut items = lock_or_recover(&game_items);
+    let wheel = items.iter()
+        .find_map(|item| Wheel::from_item(item.as_ref()))
+        .unwrap_or(Wheel::new(config.layout));
+    let ball = {
+        let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+        let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+        let mut fairness = lock_or_recover(&fairness);
+        let mut rng = fairness.begin_round(&seat_order);
+        let ball = wheel.spin(&mut rng);
+        fairness.reveal();
+        ball
+    };
+    items.retain(|item| Ball::from_item(item.as_ref()).is_none());
+    items.push(Arc::new(ball));
+    drop(items);
+    let Some(pocket) = ball.pocket else {
+        return;
+    };
+    state.pocket = Some(pocket);
+
+    let dealer = state.dealer_user.and_then(|user_id| find_player(&players, user_id));
+    let mut results = Vec::new();
+
+    // 入狱的注：这一转赢则退回本金，否则归庄家
+    for (user_id, bet, stake) in std::mem::take(&mut state.imprisoned) {
+        let Some(player) = find_player(&players, user_id) else {
+            collect(&dealer, stake);
+            continue;
+        };
+        let released = bet.settle(&wheel, stake, pocket) > 0;
+        let (outcome, returned) = if released { (RouletteOutcome::Released, stake) } else { (RouletteOutcome::Lost, 0) };
+        let net = settle_wager(&dealer, &player, stake, returned);
+        results.push(RouletteWagerResult{user_id, bet, stake, outcome, net});
+    }
+
+    for player in players.iter() {
+        let user_id = player.get_user().get_id();
+        let Some(bets) = state.bets.get(&user_id).cloned() else {
+            continue;
+        };
+        for (bet, stake) in bets {
+            let (outcome, returned) = match (pocket.is_zero() && bet.is_even_money(), config.zero_rule) {
+                (true, ZeroRule::LaPartage) => (RouletteOutcome::Halved, stake / 2),
+                (true, ZeroRule::EnPrison) => {
+                    state.imprisoned.push((user_id, bet, stake));
+                    results.push(RouletteWagerResult{user_id, bet, stake, outcome: RouletteOutcome::Imprisoned, net: 0});
+                    continue;
+                }
+                _ => match bet.settle(&wheel, stake, pocket) {
+                    0 => (RouletteOutcome::Lost, 0),
+                    returned => (RouletteOutcome::Won, returned),
+                },
+            };
+            let net = settle_wager(&dealer, player, stake, returned);
+            results.push(RouletteWagerResult{user_id, bet, stake, outcome, net});
+        }
+    }
+    state.results = results;
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家下注时本金已扣下，按应退还的筹码与庄家结算，返回玩家的净输赢
+fn settle_wager(dealer: &Option<Arc<Player>>, player: &Player, stake: u32, returned: u32) -> i32 {
+    let refunded = returned.min(stake);
+    player.add_token(refunded as u16);
+    collect(dealer, stake - refunded);
+    let paid = pay(dealer, player, returned - refunded);
+    refunded as i32 + paid as i32 - stake as i32
+}
+
+// 转动前离桌的玩家退回本转注额，入狱的注归庄家；剩下的玩家都已下注时照常转动轮盘
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<RouletteState>(&context, ROULETTE_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let dealer = current_players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer).cloned();
+
+    for player in leave_players {
+        let user_id = player.get_user().get_id();
+        if state.phase == RoulettePhase::Betting {
+            if let Some(bets) = state.bets.remove(&user_id) {
+                let total: u32 = bets.iter().map(|(_, stake)| stake).sum();
+                player.add_token(total as u16);
+            }
+        }
+        let forfeited: u32 = state.imprisoned.iter()
+            .filter(|(owner, _, _)| *owner == user_id)
+            .map(|(_, _, stake)| stake)
+            .sum();
+        state.imprisoned.retain(|(owner, _, _)| *owner != user_id);
+        collect(&dealer, forfeited);
+    }
+
+    // 还没开局时只退注，不转动轮盘
+    let outcome = if state.dealer_user.is_some() && state.phase == RoulettePhase::Betting
+        && state.pending_players(&current_players).is_empty() {
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::Game;
+    use crate::game::game_context::get_state;
+    use crate::game::game_items::roulette::wheel::PocketColor;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+
+    fn table() -> (Game, Arc<Player>, Arc<Player>, Arc<Player>) {
+        let config = RouletteGameRules::new(RouletteVariant::European, 10, 500);
+        let mut game = game(config.build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Roulette, 10_000);
+        let first = player(1, PlayerRole::Player, GameProject::Roulette, 1000);
+        let second = player(2, PlayerRole::Player, GameProject::Roulette, 1000);
+        game.player_join(vec![dealer.clone(), first.clone(), second.clone()]);
+        (game, dealer, first, second)
+    }
+
+    #[test]
+    fn settle_wager_moves_chips_between_player_and_dealer() {
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Roulette, 10_000);
+        let house = Some(dealer.clone());
+        let bettor = player(1, PlayerRole::Player, GameProject::Roulette, 1000);
+        bettor.take_token(300);
+        // 单号 35:1、分享规则退回一半、输掉全部
+        assert_eq!(settle_wager(&house, &bettor, 100, 3600), 3500);
+        assert_eq!(settle_wager(&house, &bettor, 100, 50), -50);
+        assert_eq!(settle_wager(&house, &bettor, 100, 0), -100);
+        assert_eq!(bettor.get_token(), 1000 - 300 + 3600 + 50);
+        assert_eq!(dealer.get_token(), 10_000 - 3500 + 50 + 100);
+    }
+
+    #[test]
+    fn last_pending_player_leaving_spins_the_wheel() {
+        let (mut game, dealer, first, second) = table();
+        game.game_start();
+        let outcome = game.player_action(first.clone(), Arc::new(RouletteAction::Bet(vec![(RouletteBet::Red, 100)])));
+        assert_eq!(outcome, ActionOutcome::Continue);
+
+        assert_eq!(game.player_leave(vec![second.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        let state = get_state::<RouletteState>(&game.get_game_context(), ROULETTE_STATE_KEY).unwrap();
+        let state = lock_or_recover(&state);
+        assert!(state.pocket.is_some());
+        assert_eq!(state.results.len(), 1);
+        assert_eq!(first.get_token() as u32 + dealer.get_token() as u32, 11_000);
+        assert_eq!(second.get_token(), 1000);
+    }
+
+    fn roulette_state(game: &Game) -> Arc<Mutex<RouletteState>> {
+        get_state::<RouletteState>(&game.get_game_context(), ROULETTE_STATE_KEY).unwrap()
+    }
+
+    // 一直转到开出 0，每转都换一个新的服务端种子
+    fn spin_until_zero(zero_rule: ZeroRule) -> (Game, Arc<Player>, Arc<Player>) {
+        let mut config = RouletteGameRules::new(RouletteVariant::European, 10, 500);
+        config.zero_rule = zero_rule;
+        for _ in 0..2000 {
+            let mut game = game(config.build().unwrap());
+            let dealer = player(100, PlayerRole::Dealer, GameProject::Roulette, 10_000);
+            let bettor = player(1, PlayerRole::Player, GameProject::Roulette, 1000);
+            game.player_join(vec![dealer.clone(), bettor.clone()]);
+            game.game_start();
+            let bets = vec![(RouletteBet::Red, 100), (RouletteBet::Dozen(1), 100)];
+            assert_eq!(game.player_action(bettor.clone(), Arc::new(RouletteAction::Bet(bets))), ActionOutcome::GameComplete);
+            if lock_or_recover(&roulette_state(&game)).pocket == Some(Pocket::Zero) {
+                return (game, dealer, bettor);
+            }
+        }
+        panic!("zero never came up");
+    }
+
+    #[test]
+    fn la_partage_returns_half_of_even_money_bets_on_zero() {
+        let (game, dealer, bettor) = spin_until_zero(ZeroRule::LaPartage);
+        let state = roulette_state(&game);
+        let outcomes: Vec<(RouletteOutcome, i32)> = lock_or_recover(&state).results.iter().map(|result| (result.outcome, result.net)).collect();
+        // 打一打不是 1:1 的注，照常输掉
+        assert_eq!(outcomes, vec![(RouletteOutcome::Halved, -50), (RouletteOutcome::Lost, -100)]);
+        assert_eq!((bettor.get_token(), dealer.get_token()), (850, 10_150));
+    }
+
+    #[test]
+    fn en_prison_holds_even_money_bets_for_the_next_spin() {
+        let (mut game, dealer, bettor) = spin_until_zero(ZeroRule::EnPrison);
+        let state = roulette_state(&game);
+        {
+            let state = lock_or_recover(&state);
+            assert_eq!(state.imprisoned, vec![(1, RouletteBet::Red, 100)]);
+            assert_eq!(state.results[0].outcome, RouletteOutcome::Imprisoned);
+        }
+        assert_eq!((bettor.get_token(), dealer.get_token()), (800, 10_100));
+
+        // 下一转开出红色则退回本金，否则归庄家，再开出 0 也不会再次入狱
+        game.game_wait_start();
+        game.game_start();
+        assert_eq!(game.player_action(bettor.clone(), Arc::new(RouletteAction::Bet(vec![(RouletteBet::Straight(Pocket::Zero), 10)]))), ActionOutcome::GameComplete);
+        let state = lock_or_recover(&state);
+        let pocket = state.pocket.unwrap();
+        let released = pocket.color() == PocketColor::Red;
+        let outcome = if released { RouletteOutcome::Released } else { RouletteOutcome::Lost };
+        assert!(state.imprisoned.is_empty());
+        assert_eq!((state.results[0].outcome, state.results[0].net), (outcome, if released { 0 } else { -100 }));
+        let expected = 790 + if released { 100 } else { 0 } + if pocket == Pocket::Zero { 360 } else { 0 };
+        assert_eq!(bettor.get_token(), expected);
+        assert_eq!(bettor.get_token() as u32 + dealer.get_token() as u32, 11_000);
+    }
+
+    #[test]
+    fn leaving_before_start_does_not_spin() {
+        let (mut game, _dealer, _first, second) = table();
+        assert_eq!(game.player_leave(vec![second]), ActionOutcome::Continue);
+        assert_eq!(game.get_game_state(), GameState::NotStarted);
+    }
+}
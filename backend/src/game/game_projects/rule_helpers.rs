@@ -0,0 +1,122 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, get_state, lock_or_recover, CURRENT_ACTION_PLAYERS_KEY};
+use crate::game::game_item::GameItem;
+use crate::game::game_rule::ActionOutcome;
+use crate::game::player::Player;
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+
+/// 能给出当前需要行动玩家的对局状态
+pub(crate) trait ActingPlayers {
+    /// 需要行动的玩家 user id
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32>;
+}
+
+// 规则里的状态通常以锁守卫的形式持有
+impl<T: ActingPlayers> ActingPlayers for MutexGuard<'_, T> {
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        (**self).acting_players(players)
+    }
+}
+
+/// 把需要行动的玩家按座位顺序写入 current_action_players
+pub(crate) fn sync_action_players<S: ActingPlayers>(
+    state: &S,
+    players: &[Arc<Player>],
+    context: &Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>,
+) {
+    let Some(action_players) = get_state::<Vec<Arc<Player>>>(context, CURRENT_ACTION_PLAYERS_KEY) else {
+        return;
+    };
+    let acting = state.acting_players(players);
+    *lock_or_recover(&action_players) = players.iter()
+        .filter(|player| acting.contains(&player.get_user().get_id()))
+        .cloned()
+        .collect();
+}
+
+/// 按 user id 找到座位上的玩家
+pub(crate) fn find_player(players: &[Arc<Player>], user_id: u32) -> Option<Arc<Player>> {
+    players.iter().find(|player| player.get_user().get_id() == user_id).cloned()
+}
+
+/// 庄家向玩家支付彩金，返回实际支付的数额
+///
+/// 庄家筹码不足时付完为止；玩家筹码达到上限时只付能收下的部分，其余留在庄家手里
+pub(crate) fn pay(dealer: &Option<Arc<Player>>, player: &Player, amount: u32) -> u32 {
+    let Some(dealer) = dealer else {
+        return 0;
+    };
+    let room = u16::MAX - player.get_token();
+    let paid = dealer.take_token(amount.min(room as u32) as u16);
+    player.add_token(paid);
+    paid as u32
+}
+
+/// 玩家输掉的注额归庄家，返回庄家实际收下的数额
+///
+/// 庄家筹码达到上限时超出的部分无法入账，调用方可据此发现差额
+pub(crate) fn collect(dealer: &Option<Arc<Player>>, amount: u32) -> u32 {
+    let Some(dealer) = dealer else {
+        return 0;
+    };
+    let collected = amount.min((u16::MAX - dealer.get_token()) as u32) as u16;
+    dealer.add_token(collected);
+    collected as u32
+}
+
+/// 一局结束后为下一局承诺服务端种子，可直接作为 game_wait_start
+pub(crate) fn commit_next_seed(
+    _players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    lock_or_recover(&fairness).ensure_committed();
+}
+
+/// 有玩家入座时承诺首局的服务端种子，玩家可在开局前提交客户端种子，可直接作为 players_join
+pub(crate) fn commit_seed_on_join(
+    _join_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    lock_or_recover(&fairness).ensure_committed();
+    ActionOutcome::Continue
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::player;
+    use crate::game::player::PlayerRole;
+
+    #[test]
+    fn pay_stops_at_the_dealer_stack_and_the_player_limit() {
+        let dealer = Some(player(1, PlayerRole::Dealer, GameProject::Roulette, 50));
+        let winner = player(2, PlayerRole::Player, GameProject::Roulette, 10);
+        assert_eq!(pay(&dealer, &winner, 80), 50);
+        assert_eq!((dealer.as_ref().unwrap().get_token(), winner.get_token()), (0, 60));
+
+        let dealer = Some(player(1, PlayerRole::Dealer, GameProject::Roulette, 500));
+        let rich = player(3, PlayerRole::Player, GameProject::Roulette, u16::MAX - 100);
+        assert_eq!(pay(&dealer, &rich, 300), 100);
+        assert_eq!((dealer.as_ref().unwrap().get_token(), rich.get_token()), (400, u16::MAX));
+        assert_eq!(pay(&None, &rich, 300), 0);
+    }
+
+    #[test]
+    fn collect_reports_what_the_dealer_could_hold() {
+        let dealer = Some(player(1, PlayerRole::Dealer, GameProject::Roulette, u16::MAX - 10));
+        assert_eq!(collect(&dealer, 4), 4);
+        assert_eq!(collect(&dealer, 100_000), 6);
+        assert_eq!(dealer.as_ref().unwrap().get_token(), u16::MAX);
+        assert_eq!(collect(&None, 5), 0);
+    }
+}
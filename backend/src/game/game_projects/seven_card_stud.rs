@@ -0,0 +1,792 @@
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use crate::game::betting::betting_round::{BettingAction, BettingRound, BettingSeat, BettingStructure};
+use crate::game::betting::pot::{build_pots, distribute_hi_lo_pots, distribute_pots, Pot};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::poker::hand_evaluator::{compare_hands, evaluate, HandValue};
+use crate::game::game_items::poker::lowball_evaluator::{evaluate_ace_to_five, evaluate_eight_or_better, LowHandValue};
+use crate::game::game_items::poker::poker::{get_all_cards, Card, Rank, Suit};
+use crate::game::game_projects::game_project::GameProject;
+use crate::game::game_projects::texas_holdem_poker::ShowdownResult;
+use crate::game::game_rule::{ActionOutcome, CompareCB, GameRule, GameRuleError};
+use crate::game::player::Player;
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, commit_next_seed, commit_seed_on_join, sync_action_players};
+
+/// 牌局状态在 game_context 中的 key
+pub const SEVEN_CARD_STUD_STATE_KEY: &str = "seven_card_stud_state";
+
+/// 一副牌最多支持的玩家数，8 人时第七街可能需要发一张公共牌
+pub const STUD_MAX_PLAYERS: usize = 8;
+
+/// 梭哈类玩法
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum StudVariant {
+    #[default]
+    SevenCardStud, // 比高牌，最小的明牌下引入注
+    Razz,          // 比 A-5 低牌，最大的明牌下引入注
+    StudHiLo,      // 高低平分底池，低牌需 8-or-better
+}
+
+impl StudVariant {
+    pub fn game_project(&self) -> GameProject {
+        match self {
+            StudVariant::SevenCardStud => GameProject::SevenCardStud,
+            StudVariant::Razz => GameProject::Razz,
+            StudVariant::StudHiLo => GameProject::SevenCardStudHiLo,
+        }
+    }
+
+    pub fn from_game_project(game_project: GameProject) -> Option<Self> {
+        match game_project {
+            GameProject::SevenCardStud => Some(StudVariant::SevenCardStud),
+            GameProject::Razz => Some(StudVariant::Razz),
+            GameProject::SevenCardStudHiLo => Some(StudVariant::StudHiLo),
+            _ => None,
+        }
+    }
+}
+
+/// 下注轮，第三街发两张暗牌一张明牌，第四到第六街各发一张明牌，第七街发一张暗牌
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum StudStreet {
+    #[default]
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Showdown,
+}
+
+/// 七张梭哈牌局状态，保存在 game_context 中
+#[derive(Debug, Default)]
+pub struct SevenCardStudState {
+    pub variant: StudVariant,
+    pub street: StudStreet,
+    pub up_cards: HashMap<u32, Vec<Card>>,   // 明牌，所有人可见
+    pub down_cards: HashMap<u32, Vec<Card>>, // 暗牌，只有本人可见
+    pub community_card: Option<Card>,        // 第七街牌不够发时的公共牌
+    pub folded: HashSet<u32>,
+    pub contributions: HashMap<u32, u32>, // 本手牌每位玩家投入底池的筹码
+    pub antes: HashMap<u32, u32>,         // 其中的前注部分
+    pub bring_in_user: Option<u32>,       // 下引入注的玩家
+    pub betting: Option<BettingRound>,    // 当前街的下注轮
+    pub pots: Vec<Pot>,                   // 最近一次摊牌时的主池和边池
+    pub showdown: Vec<ShowdownResult>,
+}
+
+/// 牌桌上一个座位对某位观察者可见的信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StudSeatView {
+    pub user_id: u32,
+    pub up_cards: Vec<Card>,
+    pub down_cards: Option<Vec<Card>>, // 只有本人或摊牌后才可见
+    pub down_card_count: usize,
+    pub folded: bool,
+    pub contributed: u32,
+}
+
+/// 某位观察者看到的牌桌
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StudTableView {
+    pub street: StudStreet,
+    pub seats: Vec<StudSeatView>,
+    pub community_card: Option<Card>,
+    pub pot: u32,
+    pub bring_in_user: Option<u32>,
+    pub next_to_act: Option<u32>,
+}
+
+impl SevenCardStudState {
+    /// 底池总额
+    pub fn pot(&self) -> u32 {
+        self.contributions.values().sum()
+    }
+
+    /// 持有手牌且未弃牌的玩家，按座位顺序排列
+    pub fn live_players(&self, players: &[Arc<Player>]) -> Vec<Arc<Player>> {
+        players.iter()
+            .filter(|player| {
+                let user_id = player.get_user().get_id();
+                self.up_cards.contains_key(&user_id) && !self.folded.contains(&user_id)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 玩家的全部手牌(暗牌、明牌和公共牌)
+    pub fn cards_of(&self, user_id: u32) -> Vec<Card> {
+        let mut cards: Vec<Card> = self.down_cards.get(&user_id).cloned().unwrap_or_default();
+        cards.extend(self.up_cards.get(&user_id).into_iter().flatten().copied());
+        cards.extend(self.community_card);
+        cards
+    }
+
+    /// viewer 看到的牌桌，viewer 为 None 时是旁观者视角，摊牌后亮出参与摊牌玩家的暗牌
+    pub fn view_for(&self, players: &[Arc<Player>], viewer: Option<u32>) -> StudTableView {
+        let shown: HashSet<u32> = self.showdown.iter()
+            .filter(|result| result.hand_value.is_some() || result.low_hand_value.is_some())
+            .map(|result| result.user_id)
+            .collect();
+        let seats: Vec<StudSeatView> = players.iter()
+            .map(|player| player.get_user().get_id())
+            .filter(|user_id| self.up_cards.contains_key(user_id))
+            .map(|user_id| {
+                let down_cards = self.down_cards.get(&user_id).cloned().unwrap_or_default();
+                let visible = viewer == Some(user_id) || shown.contains(&user_id);
+                StudSeatView {
+                    user_id,
+                    up_cards: self.up_cards[&user_id].clone(),
+                    down_card_count: down_cards.len(),
+                    down_cards: if visible { Some(down_cards) } else { None },
+                    folded: self.folded.contains(&user_id),
+                    contributed: self.contributions.get(&user_id).copied().unwrap_or(0),
+                }
+            })
+            .collect();
+        StudTableView {
+            street: self.street,
+            seats,
+            community_card: self.community_card,
+            pot: self.pot(),
+            bring_in_user: self.bring_in_user,
+            next_to_act: self.betting.as_ref().and_then(|betting| betting.next_to_act()),
+        }
+    }
+
+    // 开始新的一手牌前清空上一手的数据
+    fn reset_hand(&mut self) {
+        self.street = StudStreet::Third;
+        self.up_cards.clear();
+        self.down_cards.clear();
+        self.community_card = None;
+        self.folded.clear();
+        self.contributions.clear();
+        self.antes.clear();
+        self.bring_in_user = None;
+        self.betting = None;
+        self.pots.clear();
+        self.showdown.clear();
+    }
+
+    // 玩家投入筹码到底池，返回实际投入的数额
+    fn commit(&mut self, player: &Player, amount: u32) -> u32 {
+        let taken = player.take_token(amount.min(u16::MAX as u32) as u16) as u32;
+        *self.contributions.entry(player.get_user().get_id()).or_insert(0) += taken;
+        taken
+    }
+}
+
+impl ActingPlayers for SevenCardStudState {
+    fn acting_players(&self, _players: &[Arc<Player>]) -> Vec<u32> {
+        self.betting.as_ref().and_then(|betting| betting.next_to_act()).into_iter().collect()
+    }
+}
+
+/// 七张梭哈规则配置，固定限注
+#[derive(Debug, Clone, Copy)]
+pub struct SevenCardStudGameRules {
+    pub variant: StudVariant,
+    pub ante: u16,
+    pub bring_in: u16,
+    pub small_bet: u16, // 第三、四街的注额
+    pub big_bet: u16,   // 第五街起的注额
+    pub cap: u8,        // 每轮最多的下注加注次数(含补足)
+}
+
+impl SevenCardStudGameRules {
+    pub fn new(variant: StudVariant, ante: u16, bring_in: u16, small_bet: u16, big_bet: u16) -> Self {
+        SevenCardStudGameRules{variant, ante, bring_in, small_bet, big_bet, cap: 4}
+    }
+
+    /// 按 GameProject 创建对应玩法的规则，非梭哈项目返回 None
+    pub fn from_game_project(game_project: GameProject, ante: u16, bring_in: u16, small_bet: u16, big_bet: u16) -> Option<Self> {
+        StudVariant::from_game_project(game_project).map(|variant| SevenCardStudGameRules::new(variant, ante, bring_in, small_bet, big_bet))
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        let compare: CompareCB = match config.variant {
+            StudVariant::Razz => Arc::new(|left, right, _| {
+                let low = |items: &Vec<&dyn GameItem>| {
+                    let cards: Vec<Card> = items.iter().filter_map(|item| Card::from_item(*item)).collect();
+                    evaluate_ace_to_five(&cards)
+                };
+                low(left) > low(right)
+            }),
+            StudVariant::SevenCardStud | StudVariant::StudHiLo => Arc::new(compare_hands),
+        };
+
+        GameRule::new(
+            compare,
+            Arc::new(move |players, game_items, context| allocate(config, players, game_items, context)),
+            Arc::new(move |players, game_items, context| game_start(config, players, game_items, context)),
+            Arc::new(move |players, game_items, context| game_progress(config, players, game_items, context)),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_finish),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(player_action),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+
+    fn betting_structure(&self) -> BettingStructure {
+        BettingStructure::FixedLimit{small_bet: self.small_bet as u32, big_bet: self.big_bet as u32, cap: self.cap}
+    }
+}
+
+// 重置牌局状态，洗牌并下前注
+fn game_start(
+    config: SevenCardStudGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SevenCardStudState>(&context, SEVEN_CARD_STUD_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_hand();
+    state.variant = config.variant;
+
+    if players.len() < 2 || players.len() > STUD_MAX_PLAYERS {
+        return;
+    }
+    for player in &players {
+        let ante = state.commit(player, config.ante as u32);
+        state.antes.insert(player.get_user().get_id(), ante);
+    }
+
+    let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    let mut rng = lock_or_recover(&fairness).begin_round(&seat_order);
+    let mut deck = get_all_cards();
+    rng.shuffle(&mut deck);
+    *lock_or_recover(&game_items) = deck
+        .into_iter()
+        .map(|card| Arc::new(card) as Arc<dyn GameItem>)
+        .collect();
+}
+
+// 第三街：每人两张暗牌一张明牌，由明牌决定的玩家下引入注后开始下注
+fn allocate(
+    config: SevenCardStudGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SevenCardStudState>(&context, SEVEN_CARD_STUD_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+
+    if players.len() < 2 || players.len() > STUD_MAX_PLAYERS {
+        return;
+    }
+    for is_up in [false, false, true] {
+        for player in &players {
+            deal_to(&mut state, &mut deck, player.get_user().get_id(), is_up);
+        }
+    }
+
+    let Some(bring_in_seat) = bring_in_seat(config.variant, &state, &players) else {
+        return;
+    };
+    let bring_in_player = players[bring_in_seat].clone();
+    state.bring_in_user = Some(bring_in_player.get_user().get_id());
+    state.commit(&bring_in_player, config.bring_in as u32);
+
+    open_betting_round(config, &mut state, &players, bring_in_seat + 1);
+    sync_action_players(&state, &players, &context);
+}
+
+// 进入下一条街，发牌后由明牌最好的玩家先行动
+fn game_progress(
+    config: SevenCardStudGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SevenCardStudState>(&context, SEVEN_CARD_STUD_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+
+    if state.live_players(&players).len() <= 1 {
+        state.street = StudStreet::Showdown;
+        return;
+    }
+
+    advance_street(&mut state, &players, &mut deck);
+
+    if state.street != StudStreet::Showdown {
+        let first_seat = first_to_act(config.variant, &state, &players);
+        open_betting_round(config, &mut state, &players, first_seat);
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家下注行动，本轮结束时通知 Game 进入下一条街或摊牌
+fn player_action(
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SevenCardStudState>(&context, SEVEN_CARD_STUD_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(action) = action.downcast_ref::<BettingAction>().copied() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+
+    let Some(betting) = state.betting.as_mut() else {
+        return ActionOutcome::Rejected("no betting round in progress".to_string());
+    };
+    let committed = match betting.apply(user_id, action) {
+        Ok(committed) => committed,
+        Err(error) => return ActionOutcome::Rejected(error.to_string()),
+    };
+    let outcome = betting_outcome(&state);
+
+    if action == BettingAction::Fold {
+        state.folded.insert(user_id);
+    }
+    state.commit(&player, committed);
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 根据当前下注轮决定继续等待行动、进入下一条街还是直接摊牌
+fn betting_outcome(state: &SevenCardStudState) -> ActionOutcome {
+    let Some(betting) = state.betting.as_ref() else {
+        return ActionOutcome::Continue;
+    };
+    if !betting.is_complete() {
+        ActionOutcome::Continue
+    } else if state.street == StudStreet::Seventh || betting.remaining_players().len() <= 1 || betting.active_players().len() <= 1 {
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::RoundComplete
+    }
+}
+
+// 摊牌，按玩法比较高牌、低牌或高低平分
+fn game_finish(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SevenCardStudState>(&context, SEVEN_CARD_STUD_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+
+    // 仍有多位玩家时把剩余的牌发完
+    let live_players = state.live_players(&players);
+    if live_players.len() > 1 {
+        while state.street != StudStreet::Showdown {
+            advance_street(&mut state, &players, &mut deck);
+        }
+    }
+    state.street = StudStreet::Showdown;
+
+    let is_showdown = live_players.len() > 1;
+    let variant = state.variant;
+    let mut hand_values: HashMap<u32, Option<HandValue>> = HashMap::new();
+    let mut low_hand_values: HashMap<u32, LowHandValue> = HashMap::new();
+    for player in &live_players {
+        let user_id = player.get_user().get_id();
+        let cards = state.cards_of(user_id);
+        let hand_value = if is_showdown && variant != StudVariant::Razz { evaluate(&cards) } else { None };
+        hand_values.insert(user_id, hand_value);
+        let low = match variant {
+            _ if !is_showdown => None,
+            StudVariant::Razz => evaluate_ace_to_five(&cards),
+            StudVariant::StudHiLo => evaluate_eight_or_better(&cards),
+            StudVariant::SevenCardStud => None,
+        };
+        if let Some(low) = low {
+            low_hand_values.insert(user_id, low);
+        }
+    }
+
+    let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+    let mut contributions: Vec<(u32, u32)> = seat_order.iter()
+        .map(|user_id| (*user_id, state.contributions.get(user_id).copied().unwrap_or(0)))
+        .collect();
+    // 已离桌玩家的投入留在池中，视为弃牌
+    let mut folded = state.folded.clone();
+    for (user_id, amount) in &state.contributions {
+        if !seat_order.contains(user_id) {
+            contributions.push((*user_id, *amount));
+            folded.insert(*user_id);
+        }
+    }
+
+    let pots = build_pots(&contributions, &folded);
+    let payouts = match variant {
+        StudVariant::SevenCardStud => distribute_pots(&pots, &hand_values, &seat_order),
+        // 只剩一位玩家时 low_hand_values 为空，按 hand_values 把底池给他
+        StudVariant::Razz if is_showdown => distribute_pots(&pots, &low_hand_values, &seat_order),
+        StudVariant::Razz => distribute_pots(&pots, &hand_values, &seat_order),
+        StudVariant::StudHiLo => distribute_hi_lo_pots(&pots, &hand_values, &low_hand_values, &seat_order),
+    };
+    let showdown: Vec<ShowdownResult> = live_players.iter()
+        .map(|player| {
+            let user_id = player.get_user().get_id();
+            let won = payouts.get(&user_id).copied().unwrap_or(0);
+            player.add_token(won.min(u16::MAX as u32) as u16);
+            ShowdownResult {
+                user_id,
+                hand_value: hand_values[&user_id],
+                low_hand_value: low_hand_values.get(&user_id).copied(),
+                won,
+            }
+        })
+        .collect();
+    state.pots = pots;
+
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    lock_or_recover(&fairness).reveal();
+    state.showdown = showdown;
+    state.contributions.clear();
+    state.betting = None;
+    sync_action_players(&state, &players, &context);
+}
+
+// 离桌玩家视为弃牌，已投入的筹码留在底池中；离桌导致本轮下注结束时照常进入下一条街或摊牌
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<SevenCardStudState>(&context, SEVEN_CARD_STUD_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let in_hand = !state.up_cards.is_empty();
+    let betting_open = state.betting.as_ref().is_some_and(|betting| !betting.is_complete());
+
+    for player in leave_players {
+        let user_id = player.get_user().get_id();
+        state.down_cards.remove(&user_id);
+        if state.up_cards.remove(&user_id).is_some() {
+            state.folded.insert(user_id);
+        }
+        if let Some(betting) = state.betting.as_mut() {
+            betting.fold_out(user_id);
+        }
+    }
+
+    let outcome = if in_hand && state.live_players(&current_players).len() <= 1 {
+        // 只剩一位玩家时底池直接归他
+        ActionOutcome::GameComplete
+    } else if betting_open {
+        betting_outcome(&state)
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+// 按街发牌：第四到第六街发明牌，第七街发暗牌，剩余的牌不够每人一张时改发一张公共牌
+fn advance_street(state: &mut SevenCardStudState, players: &[Arc<Player>], deck: &mut Vec<Arc<dyn GameItem>>) {
+    let (next_street, is_up) = match state.street {
+        StudStreet::Third => (StudStreet::Fourth, true),
+        StudStreet::Fourth => (StudStreet::Fifth, true),
+        StudStreet::Fifth => (StudStreet::Sixth, true),
+        StudStreet::Sixth => (StudStreet::Seventh, false),
+        StudStreet::Seventh | StudStreet::Showdown => (StudStreet::Showdown, false),
+    };
+    state.street = next_street;
+    state.betting = None;
+    if next_street == StudStreet::Showdown {
+        return;
+    }
+
+    let live_players = state.live_players(players);
+    if deck.len() < live_players.len() {
+        state.community_card = draw_card(deck);
+        return;
+    }
+    for player in live_players {
+        deal_to(state, deck, player.get_user().get_id(), is_up);
+    }
+}
+
+fn deal_to(state: &mut SevenCardStudState, deck: &mut Vec<Arc<dyn GameItem>>, user_id: u32, is_up: bool) {
+    let Some(card) = draw_card(deck) else {
+        return;
+    };
+    let cards = if is_up { &mut state.up_cards } else { &mut state.down_cards };
+    cards.entry(user_id).or_default().push(card);
+    // 保证只拿到暗牌的玩家也在 up_cards 中有记录
+    state.up_cards.entry(user_id).or_default();
+}
+
+// 从 first_seat 开始按座位顺序为未弃牌的玩家开启新一轮下注，第三街的引入注计入本轮已下注
+fn open_betting_round(config: SevenCardStudGameRules, state: &mut SevenCardStudState, players: &[Arc<Player>], first_seat: usize) {
+    let is_third = state.street == StudStreet::Third;
+    let is_big_street = matches!(state.street, StudStreet::Fifth | StudStreet::Sixth | StudStreet::Seventh);
+    let live_players = state.live_players(players);
+
+    let seats: Vec<BettingSeat> = (0..players.len())
+        .map(|offset| &players[(first_seat + offset) % players.len()])
+        .filter(|player| live_players.iter().any(|live_player| Arc::ptr_eq(live_player, player)))
+        .map(|player| {
+            let user_id = player.get_user().get_id();
+            let contributed = state.contributions.get(&user_id).copied().unwrap_or(0);
+            let ante = state.antes.get(&user_id).copied().unwrap_or(0);
+            BettingSeat {
+                user_id,
+                stack: player.get_token() as u32,
+                street_bet: if is_third { contributed - ante } else { 0 },
+            }
+        })
+        .collect();
+
+    let structure = config.betting_structure();
+    let bet_unit = structure.bet_unit(config.big_bet as u32, is_big_street);
+    state.betting = Some(if is_third {
+        BettingRound::with_bring_in(structure, bet_unit, state.antes.values().sum(), seats)
+    } else {
+        BettingRound::new(structure, bet_unit, state.pot(), seats)
+    });
+}
+
+// 第三街下引入注的座位：梭哈和高低为最小的明牌，Razz 为最大的明牌(A 最小)，同点数按花色决定
+fn bring_in_seat(variant: StudVariant, state: &SevenCardStudState, players: &[Arc<Player>]) -> Option<usize> {
+    let door_card = |seat: &usize| state.up_cards.get(&players[*seat].get_user().get_id()).and_then(|cards| cards.first().copied());
+    let seats = (0..players.len()).filter(|seat| door_card(seat).is_some());
+    match variant {
+        StudVariant::Razz => seats.max_by_key(|seat| door_card(seat).map(|card| (low_rank_value(card.rank), suit_order(card.suit)))),
+        StudVariant::SevenCardStud | StudVariant::StudHiLo => {
+            seats.min_by_key(|seat| door_card(seat).map(|card| (card.rank.value(), suit_order(card.suit))))
+        }
+    }
+}
+
+// 第四街起由明牌最好的玩家先行动：梭哈和高低比明牌组成的高牌，Razz 比明牌组成的低牌，相同时座位靠前者先行动
+fn first_to_act(variant: StudVariant, state: &SevenCardStudState, players: &[Arc<Player>]) -> usize {
+    let live_players = state.live_players(players);
+    let mut best: Option<(usize, Vec<i16>)> = None;
+    for (seat, player) in players.iter().enumerate() {
+        if !live_players.iter().any(|live_player| Arc::ptr_eq(live_player, player)) {
+            continue;
+        }
+        let up_cards = state.up_cards.get(&player.get_user().get_id()).cloned().unwrap_or_default();
+        let key = visible_strength(variant, &up_cards);
+        if best.as_ref().is_none_or(|(_, best_key)| key > *best_key) {
+            best = Some((seat, key));
+        }
+    }
+    best.map(|(seat, _)| seat).unwrap_or(0)
+}
+
+// 明牌的强弱，越大越先行动：先比同点数的张数组合(四条、三条、两对、一对、散牌)，再比点数，Razz 全部取反
+fn visible_strength(variant: StudVariant, up_cards: &[Card]) -> Vec<i16> {
+    let is_low = variant == StudVariant::Razz;
+    let mut counts: HashMap<u8, i16> = HashMap::new();
+    for card in up_cards {
+        let value = if is_low { low_rank_value(card.rank) } else { card.rank.value() };
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    let mut groups: Vec<(i16, i16)> = counts.into_iter().map(|(value, count)| (count, value as i16)).collect();
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+
+    let pattern: Vec<i16> = groups.iter().map(|(count, _)| *count).collect();
+    let mut key: Vec<i16> = pattern;
+    key.resize(up_cards.len(), 0);
+    key.extend(groups.iter().map(|(_, value)| *value));
+    if is_low {
+        key.iter_mut().for_each(|value| *value = -*value);
+    }
+    key
+}
+
+// A-5 低牌中的点数，A 最小
+fn low_rank_value(rank: Rank) -> u8 {
+    if rank == Rank::Ace { 1 } else { rank.value() }
+}
+
+// 引入注比较用的花色大小：梅花 < 方块 < 红桃 < 黑桃
+fn suit_order(suit: Suit) -> u8 {
+    match suit {
+        Suit::Clubs => 0,
+        Suit::Diamonds => 1,
+        Suit::Hearts => 2,
+        Suit::Spades => 3,
+    }
+}
+
+// 从牌堆顶摸一张牌
+fn draw_card(deck: &mut Vec<Arc<dyn GameItem>>) -> Option<Card> {
+    if deck.is_empty() {
+        return None;
+    }
+    Card::from_item(deck.remove(0).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::Game;
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::test_support::{acting, game, player};
+    use crate::game::player::PlayerRole;
+
+    fn table(count: u32) -> (Game, Vec<Arc<Player>>) {
+        let rule = SevenCardStudGameRules::new(StudVariant::SevenCardStud, 1, 2, 5, 10).build().unwrap();
+        let mut game = game(rule);
+        let players: Vec<Arc<Player>> = (1..=count)
+            .map(|id| player(id, PlayerRole::Player, GameProject::SevenCardStud, 100))
+            .collect();
+        game.player_join(players.clone());
+        game.game_start();
+        (game, players)
+    }
+
+    fn find(players: &[Arc<Player>], user_id: u32) -> Arc<Player> {
+        players.iter().find(|player| player.get_user().get_id() == user_id).unwrap().clone()
+    }
+
+    #[test]
+    fn waiting_player_leaving_heads_up_awards_pot() {
+        let (mut game, players) = table(2);
+        let actor = acting(&game)[0];
+        let leaver = players.iter().find(|player| player.get_user().get_id() != actor).unwrap().clone();
+
+        assert_eq!(game.player_leave(vec![leaver.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(find(&players, actor).get_token() + leaver.get_token(), 200);
+        assert!(leaver.get_token() < 100);
+    }
+
+    #[test]
+    fn acting_player_leaving_closes_betting_round() {
+        let (mut game, players) = table(3);
+        for _ in 0..2 {
+            let actor = find(&players, acting(&game)[0]);
+            assert_eq!(game.player_action(actor, Arc::new(BettingAction::Call)), ActionOutcome::Continue);
+        }
+        let bring_in = find(&players, acting(&game)[0]);
+
+        assert_eq!(game.player_leave(vec![bring_in]), ActionOutcome::RoundComplete);
+        assert_eq!(game.get_game_state(), GameState::InProgress);
+        let state = get_state::<SevenCardStudState>(&game.get_game_context(), SEVEN_CARD_STUD_STATE_KEY).unwrap();
+        assert_eq!(lock_or_recover(&state).street, StudStreet::Fourth);
+        assert_eq!(acting(&game).len(), 1);
+    }
+
+    // 按书写顺序解析的牌，例如 "Ks3d"
+    fn cards(text: &str) -> Vec<Card> {
+        text.as_bytes().chunks(2).map(|symbols| std::str::from_utf8(symbols).unwrap().parse().unwrap()).collect()
+    }
+
+    fn seated(up_cards: &[&str]) -> (SevenCardStudState, Vec<Arc<Player>>) {
+        let players: Vec<Arc<Player>> = (1..=up_cards.len() as u32)
+            .map(|id| player(id, PlayerRole::Player, GameProject::SevenCardStud, 100))
+            .collect();
+        let mut state = SevenCardStudState::default();
+        for (index, up) in up_cards.iter().enumerate() {
+            state.up_cards.insert(index as u32 + 1, cards(up));
+        }
+        (state, players)
+    }
+
+    #[test]
+    fn bring_in_goes_to_the_lowest_door_card_and_highest_in_razz() {
+        // 点数相同时梅花最小
+        let (state, players) = seated(&["7s", "2h", "2c"]);
+        assert_eq!(bring_in_seat(StudVariant::SevenCardStud, &state, &players), Some(2));
+        assert_eq!(bring_in_seat(StudVariant::StudHiLo, &state, &players), Some(2));
+        // Razz 中点数相同时黑桃最大，A 最小
+        let (state, players) = seated(&["Ks", "Kd", "5c"]);
+        assert_eq!(bring_in_seat(StudVariant::Razz, &state, &players), Some(0));
+        let (state, players) = seated(&["As", "2c"]);
+        assert_eq!(bring_in_seat(StudVariant::Razz, &state, &players), Some(1));
+        assert_eq!(bring_in_seat(StudVariant::SevenCardStud, &state, &players), Some(1));
+    }
+
+    #[test]
+    fn best_visible_hand_acts_first_on_later_streets() {
+        let (state, players) = seated(&["Ks3d", "5h5d", "AsQd"]);
+        assert_eq!(first_to_act(StudVariant::SevenCardStud, &state, &players), 1);
+        // Razz 中对子最差，明牌最小的先行动：Q-A 小于 K-3
+        assert_eq!(first_to_act(StudVariant::Razz, &state, &players), 2);
+        let (state, players) = seated(&["8s7d", "As2d", "2s2c"]);
+        assert_eq!(first_to_act(StudVariant::Razz, &state, &players), 1);
+        // 明牌相同时座位靠前者先行动
+        let (mut state, players) = seated(&["Ks3d", "Kh3c", "2s4c"]);
+        assert_eq!(first_to_act(StudVariant::SevenCardStud, &state, &players), 0);
+        state.folded.insert(1);
+        assert_eq!(first_to_act(StudVariant::SevenCardStud, &state, &players), 1);
+    }
+
+    #[test]
+    fn hi_lo_splits_between_the_best_high_and_the_best_low() {
+        let rule = SevenCardStudGameRules::new(StudVariant::StudHiLo, 1, 2, 5, 10).build().unwrap();
+        let mut game = game(rule);
+        let players: Vec<Arc<Player>> = (1..=2)
+            .map(|id| player(id, PlayerRole::Player, GameProject::SevenCardStudHiLo, 100))
+            .collect();
+        game.player_join(players.clone());
+        game.game_start();
+        let state = get_state::<SevenCardStudState>(&game.get_game_context(), SEVEN_CARD_STUD_STATE_KEY).unwrap();
+        {
+            let mut state = lock_or_recover(&state);
+            state.street = StudStreet::Seventh;
+            // 1 号葫芦没有低牌，2 号 7-4-3-2-A 的低牌
+            state.down_cards = [(1, cards("KsKd9c")), (2, cards("As2dJc"))].into_iter().collect();
+            state.up_cards = [(1, cards("KcQhQd2s")), (2, cards("3c4h7s8d"))].into_iter().collect();
+            state.contributions = [(1, 50), (2, 50)].into_iter().collect();
+        }
+        game.game_finish();
+
+        let state = lock_or_recover(&state);
+        let won: HashMap<u32, u32> = state.showdown.iter().map(|result| (result.user_id, result.won)).collect();
+        assert_eq!(won, [(1, 50), (2, 50)].into_iter().collect());
+        assert!(state.showdown.iter().find(|result| result.user_id == 1).unwrap().low_hand_value.is_none());
+    }
+
+    #[test]
+    fn down_cards_are_hidden_until_showdown() {
+        let (mut game, players) = table(3);
+        let state = get_state::<SevenCardStudState>(&game.get_game_context(), SEVEN_CARD_STUD_STATE_KEY).unwrap();
+        let view = lock_or_recover(&state).view_for(&players, Some(1));
+        assert_eq!(view.seats.len(), 3);
+        assert_eq!(view.seats[0].down_cards.as_ref().map(|cards| cards.len()), Some(2));
+        for seat in &view.seats[1..] {
+            assert_eq!(seat.down_cards, None);
+            assert_eq!(seat.down_card_count, 2);
+            assert_eq!(seat.up_cards.len(), 1);
+        }
+        let spectator = lock_or_recover(&state).view_for(&players, None);
+        assert!(spectator.seats.iter().all(|seat| seat.down_cards.is_none()));
+        assert!(spectator.bring_in_user.is_some());
+
+        game.game_finish();
+        let spectator = lock_or_recover(&state).view_for(&players, None);
+        assert!(spectator.seats.iter().all(|seat| seat.down_cards.as_ref().map(|cards| cards.len()) == Some(3)));
+    }
+}
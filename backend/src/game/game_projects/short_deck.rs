@@ -0,0 +1,51 @@
+use crate::game::betting::betting_round::BettingStructure;
+use crate::game::game_projects::texas_holdem_poker::{ForcedBets, HoldemVariant, TexasHoldemPokerGameRules};
+use crate::game::game_rule::{GameRule, GameRuleError};
+
+/// 短牌的强制下注模式
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum ShortDeckMode {
+    AnteOnly,    // 只有前注
+    ButtonBlind, // 前注加庄位盲注
+}
+
+/// 短牌(6+)德州扑克规则配置，发牌、下注和结算沿用德州扑克的流程，牌局状态同样保存在 TEXAS_HOLDEM_STATE_KEY 下
+#[derive(Debug, Clone, Copy)]
+pub struct ShortDeckGameRules {
+    pub ante: u16,
+    pub button_blind: u16, // 庄位盲注，AnteOnly 模式下只作为最小下注额
+    pub mode: ShortDeckMode,
+    pub betting_structure: BettingStructure,
+}
+
+impl ShortDeckGameRules {
+    /// 默认无限注、前注加庄位盲注
+    pub fn new(ante: u16, button_blind: u16) -> Self {
+        ShortDeckGameRules {
+            ante,
+            button_blind,
+            mode: ShortDeckMode::ButtonBlind,
+            betting_structure: BettingStructure::NoLimit,
+        }
+    }
+
+    /// 只有前注的模式，min_bet 为最小下注额
+    pub fn ante_only(ante: u16, min_bet: u16) -> Self {
+        ShortDeckGameRules {
+            mode: ShortDeckMode::AnteOnly,
+            ..ShortDeckGameRules::new(ante, min_bet)
+        }
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let mut holdem = TexasHoldemPokerGameRules::new(0, self.button_blind, self.betting_structure);
+        holdem.ante = self.ante;
+        holdem.forced_bets = match self.mode {
+            ShortDeckMode::AnteOnly => ForcedBets::AnteOnly,
+            ShortDeckMode::ButtonBlind => ForcedBets::ButtonBlind,
+        };
+        holdem.variant = HoldemVariant::ShortDeck;
+        holdem.build()
+    }
+}
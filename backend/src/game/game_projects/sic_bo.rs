@@ -0,0 +1,411 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::dice::dice::{dice_total, roll_dice, Dice, STANDARD_DICE_SIDES};
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, collect, commit_next_seed, commit_seed_on_join, find_player, pay, sync_action_players};
+
+/// 牌局状态在 game_context 中的 key
+pub const SIC_BO_STATE_KEY: &str = "sic_bo_state";
+
+/// 一桌最多的下注玩家数
+pub const SIC_BO_MAX_PLAYERS: usize = 16;
+
+/// 每局掷的骰子数
+pub const SIC_BO_DICE_COUNT: usize = 3;
+
+/// 点数和注的赔率：(点数和, 赔率)
+pub const SIC_BO_TOTAL_PAYOUTS: [(u8, u32); 14] = [
+    (4, 60), (5, 30), (6, 17), (7, 12), (8, 8), (9, 6), (10, 6),
+    (11, 6), (12, 6), (13, 8), (14, 12), (15, 17), (16, 30), (17, 60),
+];
+
+/// 骰宝的下注方式
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum SicBoBet {
+    Small,               // 小：点数和 4~10，围骰通杀
+    Big,                 // 大：点数和 11~17，围骰通杀
+    Odd,                 // 单，围骰通杀
+    Even,                // 双，围骰通杀
+    Total(u8),           // 点数和 4~17
+    SpecificTriple(u8),  // 指定围骰
+    AnyTriple,           // 全围
+    SpecificDouble(u8),  // 指定对子
+    Combination(u8, u8), // 两颗不同点数的组合
+    Single(u8),          // 单骰：出现一次 1:1，两次 2:1，三次 3:1
+}
+
+impl SicBoBet {
+    /// 下注本身是否成立
+    pub fn is_valid(&self) -> bool {
+        let face = |value: u8| (1..=STANDARD_DICE_SIDES).contains(&value);
+        match *self {
+            SicBoBet::Total(total) => (4..=17).contains(&total),
+            SicBoBet::SpecificTriple(value) | SicBoBet::SpecificDouble(value) | SicBoBet::Single(value) => face(value),
+            SicBoBet::Combination(first, second) => face(first) && face(second) && first != second,
+            _ => true,
+        }
+    }
+}
+
+/// 骰宝玩家行动，一次提交本局全部下注，空列表为本局不下注
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SicBoAction {
+    Bet(Vec<(SicBoBet, u32)>),
+}
+
+/// 对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum SicBoPhase {
+    #[default]
+    Betting, // 等待下注
+    Settled, // 已掷骰并结算
+}
+
+/// 一注的结算记录，net 为正表示玩家赢
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SicBoWagerResult {
+    pub user_id: u32,
+    pub bet: SicBoBet,
+    pub stake: u32,
+    pub net: i32,
+}
+
+/// 骰宝对局状态，保存在 game_context 中
+#[derive(Debug, Default)]
+pub struct SicBoState {
+    pub phase: SicBoPhase,
+    pub dealer_user: Option<u32>,
+    pub bets: HashMap<u32, Vec<(SicBoBet, u32)>>,
+    pub dice: Vec<Dice>,
+    pub results: Vec<SicBoWagerResult>,
+}
+
+impl SicBoState {
+    /// 还没下注的玩家
+    pub fn pending_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        if self.phase != SicBoPhase::Betting {
+            return Vec::new();
+        }
+        players.iter()
+            .filter(|player| player.get_player_role() == PlayerRole::Player)
+            .map(|player| player.get_user().get_id())
+            .filter(|user_id| !self.bets.contains_key(user_id))
+            .collect()
+    }
+
+    fn reset_round(&mut self) {
+        self.phase = SicBoPhase::Betting;
+        self.dealer_user = None;
+        self.bets.clear();
+        self.dice.clear();
+        self.results.clear();
+    }
+}
+
+impl ActingPlayers for SicBoState {
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players(players)
+    }
+}
+
+/// 骰宝规则配置，庄家由 PlayerRole::Dealer 的玩家担任，所有注额都与他的筹码结算
+#[derive(Debug, Clone, Copy)]
+pub struct SicBoGameRules {
+    pub specific_triple_payout: u32, // 指定围骰赔率，常见 150 或 180
+    pub any_triple_payout: u32,      // 全围赔率
+    pub specific_double_payout: u32, // 指定对子赔率
+    pub combination_payout: u32,     // 组合赔率
+    pub min_bet: u32,
+    pub max_bet: u32,                // 每一注的上限
+}
+
+impl SicBoGameRules {
+    pub fn new(min_bet: u32, max_bet: u32) -> Self {
+        SicBoGameRules {
+            specific_triple_payout: 180,
+            any_triple_payout: 30,
+            specific_double_payout: 10,
+            combination_payout: 5,
+            min_bet,
+            max_bet,
+        }
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.min_bet == 0 || config.max_bet < config.min_bet {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            Arc::new(|left, right, _| {
+                let total = |items: &Vec<&dyn GameItem>| -> u8 {
+                    items.iter().filter_map(|item| Dice::from_item(*item)).map(|dice| dice.get_value()).sum()
+                };
+                total(left) > total(right)
+            }),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_start),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_finish(config, players, game_items, context)),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+
+    /// 一注赢得的彩金(不含本金)，输了返回 None
+    pub fn winnings(&self, bet: SicBoBet, stake: u32, dice: &[Dice]) -> Option<u32> {
+        let values: Vec<u8> = dice.iter().map(|dice| dice.get_value()).collect();
+        let count = |value: u8| values.iter().filter(|face| **face == value).count() as u32;
+        let total = dice_total(dice);
+        let is_triple = values.windows(2).all(|pair| pair[0] == pair[1]);
+        let payout = match bet {
+            SicBoBet::Small => (!is_triple && (4..=10).contains(&total)).then_some(1),
+            SicBoBet::Big => (!is_triple && (11..=17).contains(&total)).then_some(1),
+            SicBoBet::Odd => (!is_triple && total % 2 == 1).then_some(1),
+            SicBoBet::Even => (!is_triple && total.is_multiple_of(2)).then_some(1),
+            SicBoBet::Total(target) => SIC_BO_TOTAL_PAYOUTS.iter()
+                .find(|(table_total, _)| *table_total == target && target == total)
+                .map(|(_, payout)| *payout),
+            SicBoBet::SpecificTriple(value) => (count(value) == 3).then_some(self.specific_triple_payout),
+            SicBoBet::AnyTriple => is_triple.then_some(self.any_triple_payout),
+            SicBoBet::SpecificDouble(value) => (count(value) >= 2).then_some(self.specific_double_payout),
+            SicBoBet::Combination(first, second) => (count(first) >= 1 && count(second) >= 1).then_some(self.combination_payout),
+            SicBoBet::Single(value) => Some(count(value)).filter(|times| *times > 0),
+        };
+        payout.map(|payout| stake * payout)
+    }
+}
+
+// 开局：找出庄家，等待下注
+fn game_start(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SicBoState>(&context, SIC_BO_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_round();
+
+    let dealer = players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer);
+    let player_count = players.iter().filter(|player| player.get_player_role() == PlayerRole::Player).count();
+    match dealer {
+        Some(dealer) if player_count > 0 && player_count <= SIC_BO_MAX_PLAYERS => {
+            state.dealer_user = Some(dealer.get_user().get_id());
+        }
+        _ => state.phase = SicBoPhase::Settled,
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家提交下注，最后一位下注后掷骰
+fn player_action(
+    config: SicBoGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SicBoState>(&context, SIC_BO_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(SicBoAction::Bet(bets)) = action.downcast_ref::<SicBoAction>().cloned() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players(&players).contains(&user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+    for (bet, stake) in bets.iter() {
+        if *stake < config.min_bet || *stake > config.max_bet {
+            return ActionOutcome::Rejected(format!("bet must be between {} and {}", config.min_bet, config.max_bet));
+        }
+        if !bet.is_valid() {
+            return ActionOutcome::Rejected(format!("invalid bet {:?}", bet));
+        }
+    }
+    let total: u32 = bets.iter().map(|(_, stake)| stake).sum();
+    if total > player.get_token() as u32 {
+        return ActionOutcome::Rejected("not enough tokens".to_string());
+    }
+    player.take_token(total as u16);
+    state.bets.insert(user_id, bets);
+
+    let outcome = if state.pending_players(&players).is_empty() { ActionOutcome::GameComplete } else { ActionOutcome::Continue };
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 下注结束后才开始本局的随机数并掷骰，随即公开种子，然后逐注结算
+fn game_finish(
+    config: SicBoGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SicBoState>(&context, SIC_BO_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == SicBoPhase::Settled {
+        return;
+    }
+    state.phase = SicBoPhase::Settled;
+
+    let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    let mut fairness = lock_or_recover(&fairness);
+    let mut rng = fairness.begin_round(&seat_order);
+    state.dice = roll_dice(&mut rng, SIC_BO_DICE_COUNT, STANDARD_DICE_SIDES);
+    fairness.reveal();
+    drop(fairness);
+    *lock_or_recover(&game_items) = state.dice.iter().map(|dice| Arc::new(*dice) as Arc<dyn GameItem>).collect();
+
+    let dealer = state.dealer_user.and_then(|user_id| find_player(&players, user_id));
+    let mut results = Vec::new();
+    for player in players.iter() {
+        let user_id = player.get_user().get_id();
+        let Some(bets) = state.bets.get(&user_id) else {
+            continue;
+        };
+        for (bet, stake) in bets.iter().copied() {
+            let net = match config.winnings(bet, stake, &state.dice) {
+                Some(winnings) => {
+                    player.add_token(stake as u16);
+                    pay(&dealer, player, winnings) as i32
+                }
+                None => {
+                    collect(&dealer, stake);
+                    -(stake as i32)
+                }
+            };
+            results.push(SicBoWagerResult{user_id, bet, stake, net});
+        }
+    }
+    state.results = results;
+    sync_action_players(&state, &players, &context);
+}
+
+// 掷骰前离桌的玩家退回注额；剩下的玩家都已下注时照常掷骰
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<SicBoState>(&context, SIC_BO_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == SicBoPhase::Settled {
+        return ActionOutcome::Continue;
+    }
+    for player in leave_players {
+        if let Some(bets) = state.bets.remove(&player.get_user().get_id()) {
+            let total: u32 = bets.iter().map(|(_, stake)| stake).sum();
+            player.add_token(total as u16);
+        }
+    }
+
+    // 还没开局时只退注，不掷骰
+    let outcome = if state.dealer_user.is_some() && state.pending_players(&current_players).is_empty() {
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::Game;
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+
+    fn dice(values: [u8; 3]) -> Vec<Dice> {
+        values.iter().map(|value| Dice::new(STANDARD_DICE_SIDES, *value).unwrap()).collect()
+    }
+
+    fn table() -> (Game, Arc<Player>, Arc<Player>, Arc<Player>) {
+        let mut game = game(SicBoGameRules::new(10, 500).build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::SicBo, 10_000);
+        let first = player(1, PlayerRole::Player, GameProject::SicBo, 1000);
+        let second = player(2, PlayerRole::Player, GameProject::SicBo, 1000);
+        game.player_join(vec![dealer.clone(), first.clone(), second.clone()]);
+        (game, dealer, first, second)
+    }
+
+    #[test]
+    fn payout_table() {
+        let config = SicBoGameRules::new(10, 500);
+        let mixed = dice([2, 2, 5]);
+        assert_eq!(config.winnings(SicBoBet::Small, 10, &mixed), Some(10));
+        assert_eq!(config.winnings(SicBoBet::Big, 10, &mixed), None);
+        assert_eq!(config.winnings(SicBoBet::Odd, 10, &mixed), Some(10));
+        assert_eq!(config.winnings(SicBoBet::Total(9), 10, &mixed), Some(60));
+        assert_eq!(config.winnings(SicBoBet::SpecificDouble(2), 10, &mixed), Some(100));
+        assert_eq!(config.winnings(SicBoBet::Combination(2, 5), 10, &mixed), Some(50));
+        assert_eq!(config.winnings(SicBoBet::Single(2), 10, &mixed), Some(20));
+        assert_eq!(config.winnings(SicBoBet::Single(6), 10, &mixed), None);
+
+        // 围骰时大小单双通杀
+        let triple = dice([4, 4, 4]);
+        assert_eq!(config.winnings(SicBoBet::Big, 10, &triple), None);
+        assert_eq!(config.winnings(SicBoBet::Even, 10, &triple), None);
+        assert_eq!(config.winnings(SicBoBet::SpecificTriple(4), 10, &triple), Some(1800));
+        assert_eq!(config.winnings(SicBoBet::AnyTriple, 10, &triple), Some(300));
+        assert_eq!(config.winnings(SicBoBet::Single(4), 10, &triple), Some(30));
+        assert!(!SicBoBet::Total(3).is_valid());
+        assert!(!SicBoBet::Combination(3, 3).is_valid());
+    }
+
+    #[test]
+    fn last_pending_player_leaving_rolls_the_dice() {
+        let (mut game, dealer, first, second) = table();
+        game.game_start();
+        let outcome = game.player_action(first.clone(), Arc::new(SicBoAction::Bet(vec![(SicBoBet::Big, 100)])));
+        assert_eq!(outcome, ActionOutcome::Continue);
+
+        assert_eq!(game.player_leave(vec![second.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        let state = get_state::<SicBoState>(&game.get_game_context(), SIC_BO_STATE_KEY).unwrap();
+        let state = lock_or_recover(&state);
+        assert_eq!(state.dice.len(), SIC_BO_DICE_COUNT);
+        assert_eq!(state.results.len(), 1);
+        assert_eq!(first.get_token() as u32 + dealer.get_token() as u32, 11_000);
+        assert_eq!(second.get_token(), 1000);
+    }
+
+    #[test]
+    fn leaving_before_start_does_not_roll() {
+        let (mut game, _dealer, _first, second) = table();
+        assert_eq!(game.player_leave(vec![second]), ActionOutcome::Continue);
+        assert_eq!(game.get_game_state(), GameState::NotStarted);
+    }
+}
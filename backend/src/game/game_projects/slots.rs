@@ -0,0 +1,343 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::slots::slot_machine::{SlotMachine, SpinResult};
+use crate::game::game_rule::{ActionOutcome, GameRule, GameRuleError};
+use crate::game::player::{Player, PlayerRole};
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, collect, commit_next_seed, commit_seed_on_join, find_player, pay, sync_action_players};
+
+/// 对局状态在 game_context 中的 key
+pub const SLOTS_STATE_KEY: &str = "slots_state";
+
+/// 同一台机器上一局最多的玩家数
+pub const SLOTS_MAX_PLAYERS: usize = 8;
+
+/// 老虎机玩家行动
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum SlotsAction {
+    Spin(u32), // 以给定的每单位注额转动一次，还有免费转动时忽略注额直接使用免费转动
+    SitOut,    // 本局不转，有免费转动时不允许
+}
+
+/// 对局阶段
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum SlotsPhase {
+    #[default]
+    Betting, // 等待玩家转动
+    Settled, // 已转动并结算
+}
+
+/// 一次转动的结算记录，net 为正表示玩家赢
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotsSpinRecord {
+    pub user_id: u32,
+    pub unit: u32,
+    pub free_spin: bool,
+    pub result: SpinResult,
+    pub net: i32,
+}
+
+/// 老虎机对局状态，保存在 game_context 中，未用完的免费转动跨局保留
+#[derive(Debug, Default)]
+pub struct SlotsState {
+    pub phase: SlotsPhase,
+    pub dealer_user: Option<u32>,
+    pub spins: HashMap<u32, (u32, u32)>,      // 本局的 (每单位注额, 已扣下的注额)，注额为 0 表示免费转动
+    pub sitting_out: Vec<u32>,
+    pub free_spins: HashMap<u32, (u32, u32)>, // 剩余的 (免费次数, 触发时的每单位注额)
+    pub records: Vec<SlotsSpinRecord>,
+}
+
+impl SlotsState {
+    /// 本局还没行动的玩家
+    pub fn pending_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        if self.phase != SlotsPhase::Betting {
+            return Vec::new();
+        }
+        players.iter()
+            .filter(|player| player.get_player_role() == PlayerRole::Player)
+            .map(|player| player.get_user().get_id())
+            .filter(|user_id| !self.spins.contains_key(user_id) && !self.sitting_out.contains(user_id))
+            .collect()
+    }
+
+    fn reset_round(&mut self) {
+        self.phase = SlotsPhase::Betting;
+        self.dealer_user = None;
+        self.spins.clear();
+        self.sitting_out.clear();
+        self.records.clear();
+    }
+}
+
+impl ActingPlayers for SlotsState {
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        self.pending_players(players)
+    }
+}
+
+/// 老虎机规则配置，庄家由 PlayerRole::Dealer 的玩家担任，所有注额都与他的筹码结算
+///
+/// 每次转动花费 unit * machine.get_cost() 个筹码，赔付为中奖的投注单位数乘以 unit
+#[derive(Debug, Clone)]
+pub struct SlotsGameRules {
+    pub machine: Arc<SlotMachine>,
+    pub min_unit: u32,
+    pub max_unit: u32,
+}
+
+impl SlotsGameRules {
+    pub fn new(machine: SlotMachine, min_unit: u32, max_unit: u32) -> Self {
+        SlotsGameRules{machine: Arc::new(machine), min_unit, max_unit}
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        if self.min_unit == 0 || self.max_unit < self.min_unit {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+        let start_config = self.clone();
+        let finish_config = self.clone();
+        let action_config = self.clone();
+
+        GameRule::new(
+            Arc::new(|_, _, _| false),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, _game_items, context| game_start(&start_config, players, context)),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, _game_items, context| game_finish(&finish_config, players, context)),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(move |player, action, players, _game_items, _game_state, context| {
+                player_action(&action_config, player, action, players, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+}
+
+// 开局：找出庄家，等待玩家转动
+fn game_start(
+    _config: &SlotsGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SlotsState>(&context, SLOTS_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_round();
+
+    let dealer = players.iter().find(|player| player.get_player_role() == PlayerRole::Dealer);
+    let player_count = players.iter().filter(|player| player.get_player_role() == PlayerRole::Player).count();
+    let Some(dealer) = dealer else {
+        state.phase = SlotsPhase::Settled;
+        return;
+    };
+    if player_count == 0 || player_count > SLOTS_MAX_PLAYERS {
+        state.phase = SlotsPhase::Settled;
+        return;
+    }
+    state.dealer_user = Some(dealer.get_user().get_id());
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家转动或不转，有免费转动时直接使用免费转动并不扣筹码；最后一位行动后转动
+fn player_action(
+    config: &SlotsGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SlotsState>(&context, SLOTS_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(action) = action.downcast_ref::<SlotsAction>().copied() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.pending_players(&players).contains(&user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+    let free_unit = state.free_spins.get(&user_id).map(|(_, unit)| *unit);
+    match (action, free_unit) {
+        (SlotsAction::SitOut, Some(_)) => return ActionOutcome::Rejected("free spins must be played".to_string()),
+        (SlotsAction::SitOut, None) => state.sitting_out.push(user_id),
+        (SlotsAction::Spin(_), Some(unit)) => {
+            state.spins.insert(user_id, (unit, 0));
+        }
+        (SlotsAction::Spin(unit), None) => {
+            if unit < config.min_unit || unit > config.max_unit {
+                return ActionOutcome::Rejected(format!("unit must be between {} and {}", config.min_unit, config.max_unit));
+            }
+            let cost = unit * config.machine.get_cost();
+            if cost > player.get_token() as u32 {
+                return ActionOutcome::Rejected("not enough tokens".to_string());
+            }
+            player.take_token(cost as u16);
+            state.spins.insert(user_id, (unit, cost));
+        }
+    }
+
+    let outcome = if state.pending_players(&players).is_empty() { ActionOutcome::GameComplete } else { ActionOutcome::Continue };
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 所有玩家行动后才开始本局的随机数，按座位顺序依次转动并结算，随即公开种子
+fn game_finish(
+    config: &SlotsGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<SlotsState>(&context, SLOTS_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.phase == SlotsPhase::Settled {
+        return;
+    }
+    state.phase = SlotsPhase::Settled;
+
+    let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    let mut fairness = lock_or_recover(&fairness);
+    let mut rng = fairness.begin_round(&seat_order);
+
+    let dealer = state.dealer_user.and_then(|user_id| find_player(&players, user_id));
+    let machine = &config.machine;
+    let mut records = Vec::new();
+    for player in players.iter() {
+        let user_id = player.get_user().get_id();
+        let Some((unit, stake)) = state.spins.get(&user_id).copied() else {
+            continue;
+        };
+        let free_spin = stake == 0;
+        let result = machine.spin(&mut rng);
+        let multiplier = if free_spin { machine.get_free_spin_multiplier() } else { 1 };
+        let paid = pay(&dealer, player, result.total_pay() * multiplier * unit);
+        collect(&dealer, stake);
+
+        // 免费转动中再次触发时累加次数，沿用最初的每单位注额
+        let remaining = state.free_spins.remove(&user_id).map(|(count, _)| count).unwrap_or(0);
+        let remaining = remaining.saturating_sub(free_spin as u32) + result.free_spins;
+        if remaining > 0 {
+            state.free_spins.insert(user_id, (remaining, unit));
+        }
+        records.push(SlotsSpinRecord{user_id, unit, free_spin, result, net: paid as i32 - stake as i32});
+    }
+    fairness.reveal();
+    state.records = records;
+    sync_action_players(&state, &players, &context);
+}
+
+// 转动前离开的玩家退回本局注额，未用完的免费转动作废；剩下的玩家都已行动时照常转动
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<SlotsState>(&context, SLOTS_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    for player in leave_players {
+        let user_id = player.get_user().get_id();
+        if state.phase == SlotsPhase::Betting {
+            if let Some((_, stake)) = state.spins.remove(&user_id) {
+                player.add_token(stake as u16);
+            }
+        }
+        state.free_spins.remove(&user_id);
+    }
+
+    // 还没开局时只退注，不转动
+    let outcome = if state.dealer_user.is_some() && state.phase == SlotsPhase::Betting
+        && state.pending_players(&current_players).is_empty() {
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::Game;
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{game, player};
+
+    fn table() -> (Game, Arc<Player>, Arc<Player>, Arc<Player>) {
+        let mut game = game(SlotsGameRules::new(SlotMachine::classic(), 1, 10).build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Slots, 10_000);
+        let first = player(1, PlayerRole::Player, GameProject::Slots, 1000);
+        let second = player(2, PlayerRole::Player, GameProject::Slots, 1000);
+        game.player_join(vec![dealer.clone(), first.clone(), second.clone()]);
+        (game, dealer, first, second)
+    }
+
+    #[test]
+    fn last_pending_player_leaving_spins_the_reels() {
+        let (mut game, dealer, first, second) = table();
+        game.game_start();
+        assert_eq!(game.player_action(first.clone(), Arc::new(SlotsAction::Spin(1))), ActionOutcome::Continue);
+
+        assert_eq!(game.player_leave(vec![second.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        let state = get_state::<SlotsState>(&game.get_game_context(), SLOTS_STATE_KEY).unwrap();
+        let state = lock_or_recover(&state);
+        assert_eq!(state.records.len(), 1);
+        assert_eq!(first.get_token() as u32 + dealer.get_token() as u32, 11_000);
+        assert_eq!(second.get_token(), 1000);
+    }
+
+    #[test]
+    fn free_spins_use_the_triggering_unit_and_retrigger() {
+        // 每次转动都是三个分散符号：赔 3 倍总花费并奖励 2 次免费转动
+        let machine: SlotMachine = "rows = 1\nscatter = S\nreel = S\nreel = S\nreel = S\nline = 0 0 0\nscatter_pay = 3:3\nfree_spins = 3:2\nfree_spin_multiplier = 2".parse().unwrap();
+        let mut game = game(SlotsGameRules::new(machine, 1, 10).build().unwrap());
+        let dealer = player(100, PlayerRole::Dealer, GameProject::Slots, 10_000);
+        let spinner = player(1, PlayerRole::Player, GameProject::Slots, 1000);
+        game.player_join(vec![dealer.clone(), spinner.clone()]);
+        game.game_start();
+        let state = get_state::<SlotsState>(&game.get_game_context(), SLOTS_STATE_KEY).unwrap();
+        assert_eq!(game.player_action(spinner.clone(), Arc::new(SlotsAction::Spin(10))), ActionOutcome::GameComplete);
+        assert_eq!(lock_or_recover(&state).free_spins[&1], (2, 10));
+        assert_eq!(spinner.get_token(), 1020);
+
+        game.game_wait_start();
+        game.game_start();
+        assert!(matches!(game.player_action(spinner.clone(), Arc::new(SlotsAction::SitOut)), ActionOutcome::Rejected(_)));
+        assert_eq!(game.player_action(spinner.clone(), Arc::new(SlotsAction::Spin(1))), ActionOutcome::GameComplete);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.free_spins[&1], (3, 10));
+        assert!(state.records[0].free_spin);
+        assert_eq!(state.records[0].net, 60);
+        assert_eq!((spinner.get_token(), dealer.get_token()), (1080, 9920));
+    }
+
+    #[test]
+    fn leaving_before_start_does_not_spin() {
+        let (mut game, _dealer, _first, second) = table();
+        assert_eq!(game.player_leave(vec![second]), ActionOutcome::Continue);
+        assert_eq!(game.get_game_state(), GameState::NotStarted);
+    }
+}
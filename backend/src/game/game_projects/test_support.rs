@@ -0,0 +1,25 @@
+use std::sync::{Arc, Mutex};
+use crate::game::game::Game;
+use crate::game::game_context::lock_or_recover;
+use crate::game::game_item::GameItem;
+use crate::game::game_projects::game_project::GameProject;
+use crate::game::game_rule::GameRule;
+use crate::game::player::{Player, PlayerRole};
+use crate::user::user::User;
+
+// 测试用玩家，用户和物品在整个测试进程内有效
+pub(crate) fn player(id: u32, role: PlayerRole, game_project: GameProject, token: u16) -> Arc<Player> {
+    let user: &'static User = Box::leak(Box::new(User::new(id, format!("user{}", id), 1000)));
+    let game_item: &'static Vec<&'static dyn GameItem> = Box::leak(Box::new(Vec::new()));
+    Arc::new(Player::new(game_project, role, user, game_item, token))
+}
+
+// 用规则创建一局空牌堆的对局
+pub(crate) fn game(rule: GameRule) -> Game {
+    Game::new(Arc::new(Mutex::new(Vec::new())), Box::leak(Box::new(rule)))
+}
+
+// 当前行动玩家的 user id
+pub(crate) fn acting(game: &Game) -> Vec<u32> {
+    lock_or_recover(&game.get_current_action_players()).iter().map(|player| player.get_user().get_id()).collect()
+}
@@ -0,0 +1,331 @@
+use std::sync::Arc;
+use crate::game::game_items::poker::poker::Card;
+use crate::game::game_projects::texas_holdem_poker::{
+    open_betting_round, preflop_first_seat, Street, TexasHoldemPokerGameRules, TexasHoldemState,
+};
+use crate::game::game_rule::ActionOutcome;
+use crate::game::player::Player;
+use crate::game::provably_fair::mental_poker::{CardOwner, MentalPokerAction, MentalPokerTable};
+
+// 一手牌的公共牌数量
+const BOARD_CARDS: usize = 5;
+
+/// 心理扑克模式下一手牌的发牌进度
+#[derive(Debug, Clone)]
+pub struct MentalPokerHand {
+    pub table: MentalPokerTable,
+    config: TexasHoldemPokerGameRules,
+    pub awaiting_showdown: bool, // 河牌圈结束，等待玩家公开密钥亮牌
+    pub voided: bool,            // 有玩家离桌或超时，剩余的牌无法解密，本手作废并退回投入
+    next_position: usize,        // 下一张要分配的牌在加密牌堆中的位置
+    board_positions: Vec<usize>, // 公共牌在加密牌堆中的位置，按发出顺序排列
+}
+
+// 开局：由座位顺序上的玩家依次加密洗牌
+pub(crate) fn start_hand(config: TexasHoldemPokerGameRules, state: &mut TexasHoldemState, seat_order: Vec<u32>) {
+    state.mental_poker = Some(MentalPokerHand {
+        table: MentalPokerTable::new(seat_order),
+        config,
+        awaiting_showdown: false,
+        voided: false,
+        next_position: 0,
+        board_positions: Vec::new(),
+    });
+}
+
+// 按普通发牌顺序分配底牌位置，第 r 轮第 s 个座位拿第 r * n + s 张
+pub(crate) fn assign_hole_cards(state: &mut TexasHoldemState, players: &[Arc<Player>], button: usize) {
+    let Some(hand) = state.mental_poker.as_mut() else {
+        return;
+    };
+    for _ in 0..state.variant.hole_card_count() {
+        for offset in 1..=players.len() {
+            let user_id = players[(button + offset) % players.len()].get_user().get_id();
+            hand.table.assign(hand.next_position, CardOwner::Player(user_id));
+            hand.next_position += 1;
+            // 底牌在摊牌公开密钥前对服务端不可见
+            state.hole_cards.entry(user_id).or_default();
+        }
+    }
+}
+
+// 进入下一条街：跳过烧牌位置，把公共牌位置交给所有玩家解密
+pub(crate) fn advance_street(state: &mut TexasHoldemState) {
+    let (next_street, board_cards) = match state.street {
+        Street::Preflop => (Street::Flop, 3),
+        Street::Flop => (Street::Turn, 1),
+        Street::Turn => (Street::River, 1),
+        Street::River | Street::Showdown => (Street::Showdown, 0),
+    };
+    state.street = next_street;
+    state.betting = None;
+
+    let Some(hand) = state.mental_poker.as_mut() else {
+        return;
+    };
+    if board_cards > 0 {
+        hand.next_position += 1;
+        for _ in 0..board_cards {
+            hand.table.assign(hand.next_position, CardOwner::Public);
+            hand.board_positions.push(hand.next_position);
+            hand.next_position += 1;
+        }
+    }
+}
+
+// 处理洗牌、解密份额和公开密钥
+pub(crate) fn player_action(
+    player: &Player,
+    action: MentalPokerAction,
+    players: &[Arc<Player>],
+    state: &mut TexasHoldemState,
+) -> ActionOutcome {
+    let user_id = player.get_user().get_id();
+    let Some(hand) = state.mental_poker.as_mut() else {
+        return ActionOutcome::Rejected("mental poker is not enabled for this hand".to_string());
+    };
+
+    let result = match action {
+        MentalPokerAction::Shuffle(deck) => hand.table.submit_shuffle(user_id, deck),
+        MentalPokerAction::Share{index, value} => hand.table.submit_share(user_id, index, value),
+        MentalPokerAction::RevealKey(key) => hand.table.reveal_key(user_id, key).map(|cards| {
+            state.hole_cards.insert(user_id, cards);
+        }),
+    };
+    if let Err(error) = result {
+        return ActionOutcome::Rejected(error.to_string());
+    }
+    progress(state, players)
+}
+
+// 份额收齐后亮出公共牌并开启下注；无需下注时通知 Game 进入下一条街或结算
+pub(crate) fn progress(state: &mut TexasHoldemState, players: &[Arc<Player>]) -> ActionOutcome {
+    let Some(hand) = state.mental_poker.as_ref() else {
+        return ActionOutcome::Continue;
+    };
+    if !hand.table.is_settled() {
+        return ActionOutcome::Continue;
+    }
+    let board: Vec<Card> = hand.board_positions.iter().filter_map(|index| hand.table.public_card(*index)).collect();
+    let awaiting_showdown = hand.awaiting_showdown;
+    let config = hand.config;
+    state.board = board;
+
+    let live_players = state.live_players(players);
+    if live_players.len() <= 1 {
+        return ActionOutcome::GameComplete;
+    }
+    if awaiting_showdown {
+        let all_revealed = live_players.iter()
+            .all(|player| state.hole_cards.get(&player.get_user().get_id()).is_some_and(|cards| cards.len() == state.variant.hole_card_count()));
+        return if all_revealed { ActionOutcome::GameComplete } else { ActionOutcome::Continue };
+    }
+    if state.betting.is_some() {
+        return ActionOutcome::Continue;
+    }
+
+    let button = state.button_seat(players);
+    let first_seat = if state.street == Street::Preflop { preflop_first_seat(config, button, players.len()) } else { button + 1 };
+    open_betting_round(config, state, players, first_seat);
+    if state.betting.as_ref().is_some_and(|betting| !betting.is_complete()) {
+        return ActionOutcome::Continue;
+    }
+    betting_outcome(state, ActionOutcome::RoundComplete)
+}
+
+// 下注轮结束后还需解密剩余公共牌或等待摊牌，不能像普通模式那样直接结算
+pub(crate) fn betting_outcome(state: &mut TexasHoldemState, outcome: ActionOutcome) -> ActionOutcome {
+    let remaining = state.betting.as_ref().map(|betting| betting.remaining_players().len()).unwrap_or(0);
+    if outcome == ActionOutcome::Continue || outcome == ActionOutcome::GameComplete && remaining <= 1 {
+        return outcome;
+    }
+    if state.street != Street::River {
+        return ActionOutcome::RoundComplete;
+    }
+    if let Some(hand) = state.mental_poker.as_mut() {
+        hand.awaiting_showdown = true;
+    }
+    ActionOutcome::Continue
+}
+
+// 玩家之后还要提交洗牌或份额时，缺少他那一层密钥就解不开剩余的牌
+pub(crate) fn needs_player(state: &TexasHoldemState, user_id: u32) -> bool {
+    let Some(hand) = state.mental_poker.as_ref() else {
+        return false;
+    };
+    !hand.table.is_shuffled() || !hand.table.required_shares(user_id).is_empty() || hand.board_positions.len() < BOARD_CARDS
+}
+
+// 作废本手：退回所有玩家的投入，game_finish 不再摊牌
+pub(crate) fn void_hand(state: &mut TexasHoldemState, players: &[Arc<Player>]) -> ActionOutcome {
+    for player in players {
+        if let Some(amount) = state.contributions.remove(&player.get_user().get_id()) {
+            player.add_token(amount.min(u16::MAX as u32) as u16);
+        }
+    }
+    state.betting = None;
+    if let Some(hand) = state.mental_poker.as_mut() {
+        hand.voided = true;
+    }
+    ActionOutcome::GameComplete
+}
+
+// 等待协议消息超时：洗牌或份额没有收齐时作废本手；摊牌阶段未公开密钥的玩家视为弃牌，没有人亮牌时作废
+pub(crate) fn timeout(state: &mut TexasHoldemState, players: &[Arc<Player>]) -> ActionOutcome {
+    let Some(hand) = state.mental_poker.as_ref() else {
+        return ActionOutcome::Continue;
+    };
+    if !hand.table.is_settled() {
+        return void_hand(state, players);
+    }
+    if !hand.awaiting_showdown {
+        return ActionOutcome::Continue;
+    }
+    let any_revealed = state.live_players(players).iter().any(|player| hand.table.has_revealed(player.get_user().get_id()));
+    if any_revealed { ActionOutcome::GameComplete } else { void_hand(state, players) }
+}
+
+// 当前需要提交消息的玩家：洗牌者、欠份额的玩家或需要亮牌的玩家
+pub(crate) fn waiting_for(state: &TexasHoldemState, players: &[Arc<Player>]) -> Vec<u32> {
+    let Some(hand) = state.mental_poker.as_ref() else {
+        return Vec::new();
+    };
+    if let Some(shuffler) = hand.table.next_shuffler() {
+        return vec![shuffler];
+    }
+    let owing = hand.table.players_owing_shares();
+    if !owing.is_empty() || !hand.awaiting_showdown {
+        return owing;
+    }
+    state.live_players(players).iter()
+        .map(|player| player.get_user().get_id())
+        .filter(|user_id| state.hole_cards.get(user_id).is_none_or(|cards| cards.len() < state.variant.hole_card_count()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::any::Any;
+    use std::collections::HashMap;
+    use std::thread;
+    use std::time::Duration;
+    use num_bigint::BigUint;
+    use crate::game::betting::betting_round::{BettingAction, BettingStructure};
+    use crate::game::game::{Game, GameState};
+    use crate::game::game_context::{get_state, lock_or_recover};
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{acting, game, player};
+    use crate::game::game_projects::texas_holdem_poker::TEXAS_HOLDEM_STATE_KEY;
+    use crate::game::player::PlayerRole;
+    use crate::game::provably_fair::mental_poker::SraKey;
+
+    fn table(count: u32, action_timeout: Duration) -> (Game, Vec<Arc<Player>>, HashMap<u32, SraKey>) {
+        let mut config = TexasHoldemPokerGameRules::new(5, 10, BettingStructure::NoLimit);
+        config.mental_poker = true;
+        config.action_timeout = action_timeout;
+        let mut game = game(config.build().unwrap());
+        let players: Vec<Arc<Player>> = (1..=count)
+            .map(|id| player(id, PlayerRole::Player, GameProject::TexasHoldemPoker, 100))
+            .collect();
+        // 测试用小加密指数，加密快，解密指数仍是完整长度
+        let keys = (1..=count).map(|id| (id, SraKey::from_exponent(BigUint::from(2 * id + 1)).unwrap())).collect();
+        game.player_join(players.clone());
+        game.game_start();
+        (game, players, keys)
+    }
+
+    fn hand_state(game: &Game) -> Arc<std::sync::Mutex<TexasHoldemState>> {
+        get_state::<TexasHoldemState>(&game.get_game_context(), TEXAS_HOLDEM_STATE_KEY).unwrap()
+    }
+
+    // 替第一位需要行动的玩家在本地计算并提交消息，下注时只过牌或跟注
+    fn step(game: &mut Game, players: &[Arc<Player>], keys: &HashMap<u32, SraKey>) {
+        let user_id = acting(game)[0];
+        let player = players.iter().find(|player| player.get_user().get_id() == user_id).unwrap().clone();
+        let key = &keys[&user_id];
+        let action: Arc<dyn Any + Send + Sync> = {
+            let state = hand_state(game);
+            let state = lock_or_recover(&state);
+            let table = &state.mental_poker.as_ref().unwrap().table;
+            match state.betting.as_ref().filter(|betting| betting.next_to_act() == Some(user_id)) {
+                Some(betting) if betting.to_call(user_id) == 0 => Arc::new(BettingAction::Check),
+                Some(_) => Arc::new(BettingAction::Call),
+                None if table.next_shuffler() == Some(user_id) => {
+                    Arc::new(MentalPokerAction::Shuffle(key.encrypt_and_shuffle(table.get_deck())))
+                }
+                None => match table.required_shares(user_id).first() {
+                    Some(index) => Arc::new(MentalPokerAction::Share{index: *index, value: key.decrypt(table.pending_value(*index).unwrap())}),
+                    None => Arc::new(MentalPokerAction::RevealKey(key.clone())),
+                },
+            }
+        };
+        let outcome = game.player_action(player, action);
+        assert!(!matches!(outcome, ActionOutcome::Rejected(_)), "{:?}", outcome);
+    }
+
+    #[test]
+    fn full_hand_is_dealt_and_settled_by_the_players() {
+        let (mut game, players, keys) = table(2, Duration::from_secs(30));
+        for _ in 0..200 {
+            if game.get_game_state() != GameState::InProgress {
+                break;
+            }
+            // 亮牌之前服务端看不到任何底牌
+            {
+                let state = hand_state(&game);
+                let state = lock_or_recover(&state);
+                if !state.mental_poker.as_ref().unwrap().awaiting_showdown {
+                    assert!(state.hole_cards.values().all(|cards| cards.is_empty()));
+                }
+            }
+            step(&mut game, &players, &keys);
+        }
+
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(players.iter().map(|player| player.get_token() as u32).sum::<u32>(), 200);
+        let state = hand_state(&game);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.board.len(), 5);
+        assert_eq!(state.showdown.len(), 2);
+        let mut cards: Vec<Card> = state.board.clone();
+        for cards_of_player in state.hole_cards.values() {
+            assert_eq!(cards_of_player.len(), 2);
+            cards.extend_from_slice(cards_of_player);
+        }
+        cards.sort_by_key(|card| card.to_string());
+        cards.dedup();
+        assert_eq!(cards.len(), 9);
+    }
+
+    #[test]
+    fn leaving_while_still_needed_for_decryption_voids_the_hand() {
+        let (mut game, players, keys) = table(3, Duration::from_secs(30));
+        while !lock_or_recover(&hand_state(&game)).mental_poker.as_ref().unwrap().table.is_shuffled() {
+            step(&mut game, &players, &keys);
+        }
+
+        assert_eq!(game.player_leave(vec![players[0].clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        // 盲注全部退回
+        assert!(players.iter().all(|player| player.get_token() == 100));
+        assert!(lock_or_recover(&hand_state(&game)).showdown.is_empty());
+    }
+
+    #[test]
+    fn stalled_protocol_message_voids_the_hand_on_timeout() {
+        let (mut game, players, keys) = table(2, Duration::from_millis(20));
+        step(&mut game, &players, &keys);
+
+        for _ in 0..1000 {
+            if game.get_game_state() == GameState::Finished {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+            game.update_timers();
+        }
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert!(lock_or_recover(&hand_state(&game)).mental_poker.as_ref().unwrap().voided);
+        assert!(players.iter().all(|player| player.get_token() == 100));
+    }
+}
@@ -1,4 +1,988 @@
-use std::fmt::{Debug};
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::game::betting::betting_round::{BettingAction, BettingRound, BettingSeat, BettingStructure};
+use crate::game::betting::pot::{build_pots, distribute_hi_lo_pots, distribute_pots, Pot};
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::poker::hand_evaluator::{
+    compare_hands, compare_omaha_hands, compare_short_deck_hands, evaluate, evaluate_omaha, evaluate_short_deck, HandValue,
+};
+use crate::game::game_items::poker::lowball_evaluator::{evaluate_omaha_eight_or_better, LowHandValue};
+use crate::game::game_items::poker::poker::{get_all_cards, get_short_deck_cards, Card};
+use crate::game::game_rule::{ActionOutcome, CompareCB, GameRule, GameRuleError};
+use crate::game::player::Player;
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+#[cfg(feature = "mental-poker")]
+use crate::game::game_projects::texas_holdem_mental_poker::{self, MentalPokerHand};
+#[cfg(feature = "mental-poker")]
+use crate::game::provably_fair::mental_poker::MentalPokerAction;
+use crate::game::game_projects::rule_helpers::{ActingPlayers, commit_seed_on_join, sync_action_players};
+use crate::timer::timer::CBTimesMethod;
 
-#[derive(Debug)]
-pub struct TexasHoldemPokerGameRules {}
\ No newline at end of file
+/// 牌局状态在 game_context 中的 key
+pub const TEXAS_HOLDEM_STATE_KEY: &str = "texas_holdem_state";
+
+/// 德州扑克每位玩家的底牌数量
+pub const HOLE_CARD_COUNT: usize = 2;
+
+// 公共牌和烧牌一共要用掉的牌数
+const BOARD_AND_BURN_CARDS: usize = 8;
+
+/// 共用同一套发牌、下注和结算流程的公共牌玩法
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum HoldemVariant {
+    #[default]
+    TexasHoldem,                     // 任选 5 张
+    Omaha { hole_cards: usize },     // 必须用两张底牌加三张公共牌
+    OmahaHiLo { hole_cards: usize }, // 奥马哈高低，8-or-better 的低牌平分底池
+    ShortDeck,                       // 短牌(6+)，36 张牌，同花大于葫芦
+}
+
+impl HoldemVariant {
+    pub fn hole_card_count(&self) -> usize {
+        match self {
+            HoldemVariant::TexasHoldem | HoldemVariant::ShortDeck => HOLE_CARD_COUNT,
+            HoldemVariant::Omaha{hole_cards} | HoldemVariant::OmahaHiLo{hole_cards} => *hole_cards,
+        }
+    }
+
+    /// 是否高低平分底池
+    pub fn is_hi_lo(&self) -> bool {
+        matches!(self, HoldemVariant::OmahaHiLo{..})
+    }
+
+    /// 洗牌前的初始牌组
+    pub fn deck(&self) -> Vec<Card> {
+        match self {
+            HoldemVariant::ShortDeck => get_short_deck_cards(),
+            _ => get_all_cards(),
+        }
+    }
+
+    /// 一副牌最多能同时发给的玩家数
+    pub fn max_players(&self) -> usize {
+        let deck_size = match self {
+            HoldemVariant::ShortDeck => 36,
+            _ => 52,
+        };
+        (deck_size - BOARD_AND_BURN_CARDS) / self.hole_card_count().max(1)
+    }
+
+    /// 按玩法规则计算底牌加公共牌的牌力
+    pub fn evaluate(&self, hole_cards: &[Card], board: &[Card]) -> Option<HandValue> {
+        match self {
+            HoldemVariant::TexasHoldem => {
+                let mut cards = hole_cards.to_vec();
+                cards.extend_from_slice(board);
+                evaluate(&cards)
+            }
+            HoldemVariant::Omaha{..} | HoldemVariant::OmahaHiLo{..} => evaluate_omaha(hole_cards, board),
+            HoldemVariant::ShortDeck => {
+                let mut cards = hole_cards.to_vec();
+                cards.extend_from_slice(board);
+                evaluate_short_deck(&cards)
+            }
+        }
+    }
+
+    /// 高低玩法中合格的低牌，其余玩法或不合格时为 None
+    pub fn evaluate_low(&self, hole_cards: &[Card], board: &[Card]) -> Option<LowHandValue> {
+        match self {
+            HoldemVariant::OmahaHiLo{..} => evaluate_omaha_eight_or_better(hole_cards, board),
+            _ => None,
+        }
+    }
+}
+
+/// 强制下注方式
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum ForcedBets {
+    #[default]
+    Blinds,      // 庄位左手边依次下小盲、大盲
+    AnteOnly,    // 只有前注，翻牌前从庄位左手边开始行动
+    ButtonBlind, // 前注之外由庄位下一个 big_blind 的活注，翻牌前从庄位左手边开始行动
+}
+
+/// 下注轮
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum Street {
+    #[default]
+    Preflop, // 翻牌前
+    Flop,    // 翻牌
+    Turn,    // 转牌
+    River,   // 河牌
+    Showdown,// 摊牌
+}
+
+/// 摊牌结果
+#[derive(Debug, Clone)]
+pub struct ShowdownResult {
+    pub user_id: u32,
+    pub hand_value: Option<HandValue>,        // 未摊牌直接赢下底池时为 None
+    pub low_hand_value: Option<LowHandValue>, // 高低玩法中合格的低牌
+    pub won: u32,
+}
+
+/// 德州扑克牌局状态，保存在 game_context 中
+#[derive(Debug, Default)]
+pub struct TexasHoldemState {
+    pub variant: HoldemVariant,
+    pub street: Street,
+    pub button_user: Option<u32>, // 庄位玩家的 user id
+    pub button_index: usize,      // 庄位在 current_players 中的下标，庄位玩家离桌后用于顺延
+    pub board: Vec<Card>,
+    pub hole_cards: HashMap<u32, Vec<Card>>,
+    pub folded: HashSet<u32>,
+    pub contributions: HashMap<u32, u32>, // 本手牌每位玩家投入底池的筹码
+    pub antes: HashMap<u32, u32>,         // 其中的前注部分，不计入翻牌前的下注额
+    pub showdown: Vec<ShowdownResult>,
+    pub betting: Option<BettingRound>, // 当前街的下注轮
+    pub pots: Vec<Pot>, // 最近一次摊牌时的主池和边池
+    #[cfg(feature = "mental-poker")]
+    pub mental_poker: Option<MentalPokerHand>, // 心理扑克模式下的发牌进度
+}
+
+impl TexasHoldemState {
+    /// 底池总额
+    pub fn pot(&self) -> u32 {
+        self.contributions.values().sum()
+    }
+
+    /// 持有底牌且未弃牌的玩家，按座位顺序排列
+    pub fn live_players(&self, players: &[Arc<Player>]) -> Vec<Arc<Player>> {
+        players.iter()
+            .filter(|player| {
+                let user_id = player.get_user().get_id();
+                self.hole_cards.contains_key(&user_id) && !self.folded.contains(&user_id)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 翻牌前已下的盲注，不含前注
+    pub fn preflop_bet(&self, user_id: u32) -> u32 {
+        let contributed = self.contributions.get(&user_id).copied().unwrap_or(0);
+        contributed - self.antes.get(&user_id).copied().unwrap_or(0)
+    }
+
+    /// 庄位在当前座位中的下标
+    pub fn button_seat(&self, players: &[Arc<Player>]) -> usize {
+        if players.is_empty() {
+            return 0;
+        }
+        self.button_user
+            .and_then(|button_user| players.iter().position(|player| player.get_user().get_id() == button_user))
+            .unwrap_or(self.button_index % players.len())
+    }
+
+    // 开始新的一手牌前清空上一手的数据
+    fn reset_hand(&mut self) {
+        self.street = Street::Preflop;
+        self.board.clear();
+        self.hole_cards.clear();
+        self.folded.clear();
+        self.contributions.clear();
+        self.antes.clear();
+        self.showdown.clear();
+        self.betting = None;
+        self.pots.clear();
+        #[cfg(feature = "mental-poker")]
+        {
+            self.mental_poker = None;
+        }
+    }
+
+    // 玩家投入筹码到底池，返回实际投入的数额(筹码不足时全下)
+    fn commit(&mut self, player: &Player, amount: u32) -> u32 {
+        let taken = player.take_token(amount.min(u16::MAX as u32) as u16) as u32;
+        *self.contributions.entry(player.get_user().get_id()).or_insert(0) += taken;
+        taken
+    }
+}
+
+impl ActingPlayers for TexasHoldemState {
+    #[cfg_attr(not(feature = "mental-poker"), allow(unused_variables))]
+    fn acting_players(&self, players: &[Arc<Player>]) -> Vec<u32> {
+        let acting: Vec<u32> = self.betting.as_ref().and_then(|betting| betting.next_to_act()).into_iter().collect();
+        // 心理扑克模式下没有人需要下注时，等待的是提交协议消息的玩家
+        #[cfg(feature = "mental-poker")]
+        if acting.is_empty() {
+            return texas_holdem_mental_poker::waiting_for(self, players);
+        }
+        acting
+    }
+}
+
+/// 德州扑克规则配置
+///
+/// 玩家定时器从最近一次有效行动起计时，action_timeout 内轮到的玩家没有行动时，无需跟注则自动过牌，否则自动弃牌
+#[derive(Debug, Clone, Copy)]
+pub struct TexasHoldemPokerGameRules {
+    pub small_blind: u16,
+    pub big_blind: u16, // 也是无限注和底池限注的最小下注额，ButtonBlind 模式下为庄位盲注
+    pub ante: u16,      // 每位玩家的前注，0 表示没有前注
+    pub forced_bets: ForcedBets,
+    pub betting_structure: BettingStructure,
+    pub variant: HoldemVariant,
+    pub action_timeout: Duration,
+    #[cfg(feature = "mental-poker")]
+    pub mental_poker: bool, // 由玩家交换加密洗牌发牌，服务端看不到底牌
+}
+
+impl TexasHoldemPokerGameRules {
+    pub fn new(small_blind: u16, big_blind: u16, betting_structure: BettingStructure) -> Self {
+        TexasHoldemPokerGameRules {
+            small_blind,
+            big_blind,
+            ante: 0,
+            forced_bets: ForcedBets::Blinds,
+            betting_structure,
+            variant: HoldemVariant::TexasHoldem,
+            action_timeout: Duration::from_secs(30),
+            #[cfg(feature = "mental-poker")]
+            mental_poker: false,
+        }
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.action_timeout.is_zero() {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+        // 心理扑克按 52 张牌的编码加密，不支持短牌
+        #[cfg(feature = "mental-poker")]
+        if config.mental_poker && config.variant == HoldemVariant::ShortDeck {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+        let compare: CompareCB = match config.variant {
+            HoldemVariant::TexasHoldem => Arc::new(compare_hands),
+            HoldemVariant::ShortDeck => Arc::new(compare_short_deck_hands),
+            HoldemVariant::Omaha{hole_cards} | HoldemVariant::OmahaHiLo{hole_cards} => {
+                Arc::new(move |left, right, _| compare_omaha_hands(left, right, hole_cards))
+            }
+        };
+
+        GameRule::new(
+            compare,
+            Arc::new(move |players, game_items, context| allocate(config, players, game_items, context)),
+            Arc::new(move |players, game_items, context| game_start(config, players, game_items, context)),
+            Arc::new(move |players, game_items, context| game_progress(config, players, game_items, context)),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(game_finish),
+            Arc::new(game_wait_start),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(players_leave),
+            Arc::new(player_action),
+            Arc::new(players_timeout),
+            Some(config.action_timeout),
+            Some(CBTimesMethod::ONCE),
+        )
+    }
+}
+
+// 重置牌局状态，洗牌并下前注和盲注
+fn game_start(
+    config: TexasHoldemPokerGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<TexasHoldemState>(&context, TEXAS_HOLDEM_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_hand();
+    state.variant = config.variant;
+
+    if players.len() < 2 || players.len() > config.variant.max_players() {
+        return;
+    }
+    let button = state.button_seat(&players);
+    state.button_index = button;
+    state.button_user = Some(players[button].get_user().get_id());
+
+    if config.ante > 0 {
+        for player in &players {
+            let ante = state.commit(player, config.ante as u32);
+            state.antes.insert(player.get_user().get_id(), ante);
+        }
+    }
+    match config.forced_bets {
+        ForcedBets::Blinds => {
+            let (small_blind_seat, big_blind_seat) = blind_seats(button, players.len());
+            state.commit(&players[small_blind_seat], config.small_blind as u32);
+            state.commit(&players[big_blind_seat], config.big_blind as u32);
+        }
+        ForcedBets::ButtonBlind => {
+            state.commit(&players[button], config.big_blind as u32);
+        }
+        ForcedBets::AnteOnly => {}
+    }
+
+    // 心理扑克模式下由玩家依次加密洗牌，服务端不持有明文牌堆
+    #[cfg(feature = "mental-poker")]
+    if config.mental_poker {
+        lock_or_recover(&game_items).clear();
+        texas_holdem_mental_poker::start_hand(config, &mut state, seat_order(&players, button));
+        sync_action_players(&state, &players, &context);
+        return;
+    }
+
+    // 用已承诺的服务端种子和按座位顺序排列的客户端种子洗牌
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    let mut rng = lock_or_recover(&fairness).begin_round(&seat_order(&players, button));
+    let mut deck = config.variant.deck();
+    rng.shuffle(&mut deck);
+    *lock_or_recover(&game_items) = deck
+        .into_iter()
+        .map(|card| Arc::new(card) as Arc<dyn GameItem>)
+        .collect();
+}
+
+// 从庄位左手边开始，每人轮流发一张，直到发满底牌，然后开始翻牌前下注
+fn allocate(
+    config: TexasHoldemPokerGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<TexasHoldemState>(&context, TEXAS_HOLDEM_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+
+    if players.len() < 2 || players.len() > config.variant.max_players() {
+        return;
+    }
+    let button = state.button_seat(&players);
+
+    // 心理扑克模式下只分配位置，玩家收齐份额后才能看到底牌
+    #[cfg(feature = "mental-poker")]
+    if config.mental_poker {
+        texas_holdem_mental_poker::assign_hole_cards(&mut state, &players, button);
+        sync_action_players(&state, &players, &context);
+        return;
+    }
+
+    for _ in 0..config.variant.hole_card_count() {
+        for offset in 1..=players.len() {
+            let player = &players[(button + offset) % players.len()];
+            if let Some(card) = draw_card(&mut deck) {
+                state.hole_cards.entry(player.get_user().get_id()).or_default().push(card);
+            }
+        }
+    }
+
+    // 盲注计入本轮已下注
+    open_betting_round(config, &mut state, &players, preflop_first_seat(config, button, players.len()));
+    sync_action_players(&state, &players, &context);
+}
+
+// 进入下一条街，发出对应的公共牌并开始新一轮下注
+fn game_progress(
+    config: TexasHoldemPokerGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<TexasHoldemState>(&context, TEXAS_HOLDEM_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+
+    // 只剩一位玩家时无需再发牌
+    if state.live_players(&players).len() <= 1 {
+        state.street = Street::Showdown;
+        return;
+    }
+
+    // 心理扑克模式下公共牌要等所有玩家提交份额后才亮出，之后再开始下注
+    #[cfg(feature = "mental-poker")]
+    if config.mental_poker {
+        texas_holdem_mental_poker::advance_street(&mut state);
+        sync_action_players(&state, &players, &context);
+        return;
+    }
+
+    advance_street(&mut state, &mut deck);
+
+    if state.street != Street::Showdown {
+        // 翻牌后从庄位左手边第一个未弃牌的玩家开始行动
+        let button = state.button_seat(&players);
+        open_betting_round(config, &mut state, &players, button + 1);
+    }
+    sync_action_players(&state, &players, &context);
+}
+
+// 玩家下注行动，本轮结束时通知 Game 进入下一条街或摊牌
+fn player_action(
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<TexasHoldemState>(&context, TEXAS_HOLDEM_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    #[cfg(feature = "mental-poker")]
+    if let Some(action) = action.downcast_ref::<MentalPokerAction>() {
+        let outcome = texas_holdem_mental_poker::player_action(&player, action.clone(), &players, &mut state);
+        sync_action_players(&state, &players, &context);
+        return outcome;
+    }
+
+    let Some(action) = action.downcast_ref::<BettingAction>().copied() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let outcome = apply_betting_action(&mut state, &player, action);
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 轮到的玩家超时未行动：无需跟注时自动过牌，否则自动弃牌
+fn players_timeout(
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _current_action_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<TexasHoldemState>(&context, TEXAS_HOLDEM_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(user_id) = state.betting.as_ref().and_then(|betting| betting.next_to_act()) else {
+        // 心理扑克模式下没有人需要下注时，超时的是提交协议消息的玩家
+        #[cfg(feature = "mental-poker")]
+        if state.mental_poker.is_some() {
+            let outcome = texas_holdem_mental_poker::timeout(&mut state, &players);
+            sync_action_players(&state, &players, &context);
+            return outcome;
+        }
+        return ActionOutcome::Continue;
+    };
+    let no_call = state.betting.as_ref().is_some_and(|betting| betting.to_call(user_id) == 0);
+    let action = if no_call { BettingAction::Check } else { BettingAction::Fold };
+    let Some(player) = players.iter().find(|player| player.get_user().get_id() == user_id).cloned() else {
+        return ActionOutcome::Continue;
+    };
+
+    let outcome = apply_betting_action(&mut state, &player, action);
+    sync_action_players(&state, &players, &context);
+    match outcome {
+        ActionOutcome::Rejected(_) => ActionOutcome::Continue,
+        outcome => outcome,
+    }
+}
+
+// 在当前下注轮中执行玩家的下注行动并投入筹码
+fn apply_betting_action(state: &mut TexasHoldemState, player: &Player, action: BettingAction) -> ActionOutcome {
+    let user_id = player.get_user().get_id();
+    let Some(betting) = state.betting.as_mut() else {
+        return ActionOutcome::Rejected("no betting round in progress".to_string());
+    };
+    let committed = match betting.apply(user_id, action) {
+        Ok(committed) => committed,
+        Err(error) => return ActionOutcome::Rejected(error.to_string()),
+    };
+
+    if action == BettingAction::Fold {
+        state.folded.insert(user_id);
+    }
+    state.commit(player, committed);
+    betting_outcome(state)
+}
+
+// 根据当前下注轮决定继续等待行动、进入下一条街还是直接摊牌
+fn betting_outcome(state: &mut TexasHoldemState) -> ActionOutcome {
+    let Some(betting) = state.betting.as_ref() else {
+        return ActionOutcome::Continue;
+    };
+    let outcome = if !betting.is_complete() {
+        ActionOutcome::Continue
+    } else if state.street == Street::River || betting.remaining_players().len() <= 1 || betting.active_players().len() <= 1 {
+        // 河牌圈结束、只剩一位玩家或其余玩家都已全下时直接摊牌
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::RoundComplete
+    };
+
+    #[cfg(feature = "mental-poker")]
+    let outcome = if state.mental_poker.is_some() { texas_holdem_mental_poker::betting_outcome(state, outcome) } else { outcome };
+
+    outcome
+}
+
+// 摊牌，按主池和边池分别结算给牌力最大的有资格玩家
+fn game_finish(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<TexasHoldemState>(&context, TEXAS_HOLDEM_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+
+    // 作废的一手牌已退回投入，不再摊牌
+    #[cfg(feature = "mental-poker")]
+    if state.mental_poker.as_ref().is_some_and(|hand| hand.voided) {
+        state.contributions.clear();
+        state.betting = None;
+        sync_action_players(&state, &players, &context);
+        return;
+    }
+
+    let mut live_players = state.live_players(&players);
+    // 多人摊牌时未亮出全部底牌的玩家视为弃牌
+    if live_players.len() > 1 {
+        let (revealed, mucked): (Vec<Arc<Player>>, Vec<Arc<Player>>) = live_players.iter()
+            .cloned()
+            .partition(|player| state.hole_cards[&player.get_user().get_id()].len() == state.variant.hole_card_count());
+        if !revealed.is_empty() {
+            for player in mucked {
+                state.folded.insert(player.get_user().get_id());
+            }
+            live_players = revealed;
+        }
+    }
+    // 仍有多位玩家时把剩余公共牌发完
+    if live_players.len() > 1 {
+        while state.street != Street::Showdown {
+            advance_street(&mut state, &mut deck);
+        }
+    }
+    state.street = Street::Showdown;
+
+    let hand_values: HashMap<u32, Option<HandValue>> = live_players.iter()
+        .map(|player| {
+            let user_id = player.get_user().get_id();
+            let hand_value = if live_players.len() > 1 {
+                state.variant.evaluate(&state.hole_cards[&user_id], &state.board)
+            } else {
+                None
+            };
+            (user_id, hand_value)
+        })
+        .collect();
+    let low_hand_values: HashMap<u32, LowHandValue> = live_players.iter()
+        .filter(|_| live_players.len() > 1)
+        .filter_map(|player| {
+            let user_id = player.get_user().get_id();
+            state.variant.evaluate_low(&state.hole_cards[&user_id], &state.board).map(|low| (user_id, low))
+        })
+        .collect();
+
+    // 除不尽的筹码按庄位左手边开始的座位顺序分配
+    let seat_order = seat_order(&players, state.button_seat(&players));
+    let mut contributions: Vec<(u32, u32)> = seat_order.iter()
+        .map(|user_id| (*user_id, state.contributions.get(user_id).copied().unwrap_or(0)))
+        .collect();
+    // 已离桌玩家的投入留在池中，视为弃牌
+    let mut folded = state.folded.clone();
+    for (user_id, amount) in &state.contributions {
+        if !seat_order.contains(user_id) {
+            contributions.push((*user_id, *amount));
+            folded.insert(*user_id);
+        }
+    }
+
+    let pots = build_pots(&contributions, &folded);
+    let payouts = if state.variant.is_hi_lo() {
+        distribute_hi_lo_pots(&pots, &hand_values, &low_hand_values, &seat_order)
+    } else {
+        distribute_pots(&pots, &hand_values, &seat_order)
+    };
+    let showdown: Vec<ShowdownResult> = live_players.iter()
+        .map(|player| {
+            let user_id = player.get_user().get_id();
+            let won = payouts.get(&user_id).copied().unwrap_or(0);
+            player.add_token(won.min(u16::MAX as u32) as u16);
+            ShowdownResult{user_id, hand_value: hand_values[&user_id], low_hand_value: low_hand_values.get(&user_id).copied(), won}
+        })
+        .collect();
+    state.pots = pots;
+
+    // 结算后公开本手牌的服务端种子
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    lock_or_recover(&fairness).reveal();
+    state.showdown = showdown;
+    state.contributions.clear();
+    state.betting = None;
+    sync_action_players(&state, &players, &context);
+}
+
+// 一手牌结束后庄位顺时针移动一位
+fn game_wait_start(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<TexasHoldemState>(&context, TEXAS_HOLDEM_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    // 为下一手牌承诺新的服务端种子
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    lock_or_recover(&fairness).ensure_committed();
+
+    if players.is_empty() {
+        state.button_user = None;
+        return;
+    }
+    let next_button = match state.button_user {
+        Some(button_user) if players.iter().any(|player| player.get_user().get_id() == button_user) => {
+            (state.button_seat(&players) + 1) % players.len()
+        }
+        // 庄位玩家已离桌，顺延到坐进该位置的玩家
+        _ => state.button_index % players.len(),
+    };
+    state.button_index = next_button;
+    state.button_user = Some(players[next_button].get_user().get_id());
+}
+
+// 离桌玩家视为弃牌，已投入的筹码留在底池中；离桌导致本轮结束或只剩一位玩家时通知 Game 推进
+fn players_leave(
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<TexasHoldemState>(&context, TEXAS_HOLDEM_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let in_hand = !state.hole_cards.is_empty();
+    let betting_open = state.betting.as_ref().is_some_and(|betting| !betting.is_complete());
+
+    // 心理扑克模式下离桌玩家之后还要解密时，剩余的牌无法亮出，作废本手
+    #[cfg(feature = "mental-poker")]
+    if in_hand
+        && state.live_players(&current_players).len() > 1
+        && leave_players.iter().any(|player| texas_holdem_mental_poker::needs_player(&state, player.get_user().get_id()))
+    {
+        let players: Vec<Arc<Player>> = current_players.iter().chain(leave_players.iter()).cloned().collect();
+        let outcome = texas_holdem_mental_poker::void_hand(&mut state, &players);
+        sync_action_players(&state, &current_players, &context);
+        return outcome;
+    }
+
+    for player in &leave_players {
+        let user_id = player.get_user().get_id();
+        if state.hole_cards.remove(&user_id).is_some() {
+            state.folded.insert(user_id);
+        }
+        if let Some(betting) = state.betting.as_mut() {
+            betting.fold_out(user_id);
+        }
+    }
+
+    let outcome = if in_hand && state.live_players(&current_players).len() <= 1 {
+        // 只剩一位玩家时底池直接归他
+        ActionOutcome::GameComplete
+    } else if betting_open {
+        betting_outcome(&mut state)
+    } else {
+        ActionOutcome::Continue
+    };
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+// 从庄位左手边开始的座位顺序
+fn seat_order(players: &[Arc<Player>], button: usize) -> Vec<u32> {
+    (1..=players.len())
+        .map(|offset| players[(button + offset) % players.len()].get_user().get_id())
+        .collect()
+}
+
+// 单挑时庄位下小盲，否则庄位左手边依次为小盲、大盲
+pub(crate) fn blind_seats(button: usize, player_count: usize) -> (usize, usize) {
+    let small_blind_seat = if player_count == 2 { button } else { (button + 1) % player_count };
+    (small_blind_seat, (small_blind_seat + 1) % player_count)
+}
+
+// 翻牌前第一个行动的座位：有大盲时为大盲左手边，否则为庄位左手边
+pub(crate) fn preflop_first_seat(config: TexasHoldemPokerGameRules, button: usize, player_count: usize) -> usize {
+    match config.forced_bets {
+        ForcedBets::Blinds => blind_seats(button, player_count).1 + 1,
+        ForcedBets::AnteOnly | ForcedBets::ButtonBlind => button + 1,
+    }
+}
+
+// 从 first_seat 开始按座位顺序为未弃牌的玩家开启新一轮下注
+pub(crate) fn open_betting_round(config: TexasHoldemPokerGameRules, state: &mut TexasHoldemState, players: &[Arc<Player>], first_seat: usize) {
+    let is_preflop = state.street == Street::Preflop;
+    let is_big_street = matches!(state.street, Street::Turn | Street::River);
+    let live_players = state.live_players(players);
+
+    let seats: Vec<BettingSeat> = (0..players.len())
+        .map(|offset| &players[(first_seat + offset) % players.len()])
+        .filter(|player| live_players.iter().any(|live_player| Arc::ptr_eq(live_player, player)))
+        .map(|player| {
+            let user_id = player.get_user().get_id();
+            BettingSeat {
+                user_id,
+                stack: player.get_token() as u32,
+                street_bet: if is_preflop { state.preflop_bet(user_id) } else { 0 },
+            }
+        })
+        .collect();
+    // 翻牌前的盲注已计入 street_bet，只有前注算作之前的底池
+    let pot = if is_preflop { state.antes.values().sum() } else { state.pot() };
+    let bet_unit = config.betting_structure.bet_unit(config.big_blind as u32, is_big_street);
+    state.betting = Some(BettingRound::new(config.betting_structure, bet_unit, pot, seats));
+}
+
+// 按街推进并发公共牌，每条街发牌前先烧一张
+fn advance_street(state: &mut TexasHoldemState, deck: &mut Vec<Arc<dyn GameItem>>) {
+    let (next_street, board_cards) = match state.street {
+        Street::Preflop => (Street::Flop, 3),
+        Street::Flop => (Street::Turn, 1),
+        Street::Turn => (Street::River, 1),
+        Street::River | Street::Showdown => (Street::Showdown, 0),
+    };
+
+    if board_cards > 0 {
+        draw_card(deck);
+        for _ in 0..board_cards {
+            if let Some(card) = draw_card(deck) {
+                state.board.push(card);
+            }
+        }
+    }
+    state.street = next_street;
+}
+
+// 从牌堆顶摸一张牌
+fn draw_card(deck: &mut Vec<Arc<dyn GameItem>>) -> Option<Card> {
+    if deck.is_empty() {
+        return None;
+    }
+    Card::from_item(deck.remove(0).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_context::get_state;
+    use crate::game::game_items::poker::poker::{get_shuffled_cards, verify_deck};
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{acting, game, player};
+    use crate::game::player::PlayerRole;
+    use std::thread;
+
+    fn table(count: u32) -> (crate::game::game::Game, Vec<Arc<Player>>) {
+        let rule = TexasHoldemPokerGameRules::new(5, 10, BettingStructure::NoLimit).build().unwrap();
+        let mut game = game(rule);
+        let players: Vec<Arc<Player>> = (1..=count)
+            .map(|id| player(id, PlayerRole::Player, GameProject::TexasHoldemPoker, 100))
+            .collect();
+        game.player_join(players.clone());
+        game.game_start();
+        (game, players)
+    }
+
+    fn find(players: &[Arc<Player>], user_id: u32) -> Arc<Player> {
+        players.iter().find(|player| player.get_user().get_id() == user_id).unwrap().clone()
+    }
+
+    #[test]
+    fn waiting_player_leaving_heads_up_awards_pot() {
+        let (mut game, players) = table(2);
+        let actor = acting(&game)[0];
+        let leaver = players.iter().find(|player| player.get_user().get_id() != actor).unwrap().clone();
+
+        assert_eq!(game.player_leave(vec![leaver.clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(find(&players, actor).get_token() + leaver.get_token(), 200);
+        assert_eq!(leaver.get_token(), 90);
+    }
+
+    #[test]
+    fn acting_player_leaving_closes_betting_round() {
+        let (mut game, players) = table(3);
+        for _ in 0..2 {
+            let actor = find(&players, acting(&game)[0]);
+            assert_eq!(game.player_action(actor, Arc::new(BettingAction::Call)), ActionOutcome::Continue);
+        }
+        let big_blind = find(&players, acting(&game)[0]);
+
+        assert_eq!(game.player_leave(vec![big_blind]), ActionOutcome::RoundComplete);
+        assert_eq!(game.get_game_state(), GameState::InProgress);
+        let state = get_state::<TexasHoldemState>(&game.get_game_context(), TEXAS_HOLDEM_STATE_KEY).unwrap();
+        assert_eq!(lock_or_recover(&state).street, Street::Flop);
+        assert_eq!(acting(&game).len(), 1);
+    }
+
+    #[test]
+    fn dealt_cards_follow_revealed_seed() {
+        let rule = TexasHoldemPokerGameRules::new(5, 10, BettingStructure::NoLimit).build().unwrap();
+        let mut game = game(rule);
+        let players: Vec<Arc<Player>> = (1..=3)
+            .map(|id| player(id, PlayerRole::Player, GameProject::TexasHoldemPoker, 100))
+            .collect();
+        game.player_join(players.clone());
+        let fairness = get_state::<FairnessState>(&game.get_game_context(), PROVABLY_FAIR_KEY).unwrap();
+        let commitment = lock_or_recover(&fairness).get_commitment().unwrap();
+        lock_or_recover(&fairness).set_client_seed(2, "client".to_string());
+        game.game_start();
+        game.game_finish();
+
+        let reveal = lock_or_recover(&fairness).get_last_reveal().unwrap().clone();
+        assert_eq!(reveal.commitment, commitment);
+        assert_eq!(reveal.client_seeds, vec!["client".to_string(), String::new(), String::new()]);
+        let deck = get_shuffled_cards(&mut reveal.rng());
+        assert!(verify_deck(&reveal, &deck));
+        let state = get_state::<TexasHoldemState>(&game.get_game_context(), TEXAS_HOLDEM_STATE_KEY).unwrap();
+        let state = lock_or_recover(&state);
+        // 庄位为 1 号，从 2 号开始逐张发底牌，翻牌、转牌、河牌前各烧一张
+        assert_eq!(state.hole_cards[&2], vec![deck[0], deck[3]]);
+        assert_eq!(state.board, vec![deck[7], deck[8], deck[9], deck[11], deck[13]]);
+    }
+
+    #[test]
+    fn short_stack_all_in_wins_at_most_the_main_pot() {
+        let rule = TexasHoldemPokerGameRules::new(5, 10, BettingStructure::NoLimit).build().unwrap();
+        let mut game = game(rule);
+        let players = vec![
+            player(1, PlayerRole::Player, GameProject::TexasHoldemPoker, 30),
+            player(2, PlayerRole::Player, GameProject::TexasHoldemPoker, 100),
+            player(3, PlayerRole::Player, GameProject::TexasHoldemPoker, 200),
+        ];
+        game.player_join(players.clone());
+        game.game_start();
+        while game.get_game_state() == GameState::InProgress {
+            match acting(&game).first() {
+                Some(user_id) => {
+                    game.player_action(find(&players, *user_id), Arc::new(BettingAction::AllIn));
+                }
+                None => game.game_progress(),
+            }
+        }
+
+        assert_eq!(players.iter().map(|player| player.get_token() as u32).sum::<u32>(), 330);
+        let state = get_state::<TexasHoldemState>(&game.get_game_context(), TEXAS_HOLDEM_STATE_KEY).unwrap();
+        let state = lock_or_recover(&state);
+        assert_eq!(state.pots, vec![
+            Pot{amount: 90, eligible: vec![2, 3, 1]},
+            Pot{amount: 140, eligible: vec![2, 3]},
+            // 3 号超出 2 号的 100 无人跟注，只有 3 号有资格赢回
+            Pot{amount: 100, eligible: vec![3]},
+        ]);
+        assert!(players[0].get_token() <= 90);
+        assert!(players[1].get_token() <= 230);
+        assert!(players[2].get_token() >= 100);
+    }
+
+    #[test]
+    fn timed_out_player_folds_facing_a_bet_and_checks_otherwise() {
+        let mut config = TexasHoldemPokerGameRules::new(5, 10, BettingStructure::NoLimit);
+        config.action_timeout = Duration::from_millis(20);
+        let mut game = game(config.build().unwrap());
+        let players: Vec<Arc<Player>> = (1..=3)
+            .map(|id| player(id, PlayerRole::Player, GameProject::TexasHoldemPoker, 100))
+            .collect();
+        game.player_join(players.clone());
+        game.game_start();
+        let state = get_state::<TexasHoldemState>(&game.get_game_context(), TEXAS_HOLDEM_STATE_KEY).unwrap();
+
+        // 庄位 1 号面对大盲超时，自动弃牌
+        assert_eq!(acting(&game), vec![1]);
+        for _ in 0..1000 {
+            if acting(&game) != vec![1] {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+            game.update_timers();
+        }
+        assert!(lock_or_recover(&state).folded.contains(&1));
+        assert_eq!(players[0].get_token(), 100);
+
+        // 小盲跟注后大盲无需跟注，超时自动过牌并进入翻牌
+        assert_eq!(game.player_action(players[1].clone(), Arc::new(BettingAction::Call)), ActionOutcome::Continue);
+        assert_eq!(acting(&game), vec![3]);
+        for _ in 0..1000 {
+            if lock_or_recover(&state).street != Street::Preflop {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+            game.update_timers();
+        }
+        let state = lock_or_recover(&state);
+        assert_eq!(state.street, Street::Flop);
+        assert!(!state.folded.contains(&3));
+        assert_eq!(state.pot(), 20);
+        assert_eq!(game.get_game_state(), GameState::InProgress);
+    }
+
+    #[test]
+    fn omaha_deals_the_configured_hole_cards() {
+        let mut config = TexasHoldemPokerGameRules::new(5, 10, BettingStructure::PotLimit);
+        config.variant = HoldemVariant::Omaha{hole_cards: 5};
+        assert_eq!(config.variant.max_players(), 8);
+        let mut game = game(config.build().unwrap());
+        let players: Vec<Arc<Player>> = (1..=3)
+            .map(|id| player(id, PlayerRole::Player, GameProject::TexasHoldemPoker, 100))
+            .collect();
+        game.player_join(players.clone());
+        game.game_start();
+        game.game_finish();
+
+        let state = get_state::<TexasHoldemState>(&game.get_game_context(), TEXAS_HOLDEM_STATE_KEY).unwrap();
+        let state = lock_or_recover(&state);
+        assert!(state.hole_cards.values().all(|cards| cards.len() == 5));
+        let expected: Vec<Option<HandValue>> = state.showdown.iter()
+            .map(|result| evaluate_omaha(&state.hole_cards[&result.user_id], &state.board))
+            .collect();
+        assert_eq!(state.showdown.iter().map(|result| result.hand_value).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn short_deck_deals_from_thirty_six_cards_with_antes() {
+        let mut config = TexasHoldemPokerGameRules::new(0, 10, BettingStructure::NoLimit);
+        config.variant = HoldemVariant::ShortDeck;
+        config.forced_bets = ForcedBets::ButtonBlind;
+        config.ante = 5;
+        assert_eq!(config.variant.deck().len(), 36);
+        let mut game = game(config.build().unwrap());
+        let players: Vec<Arc<Player>> = (1..=3)
+            .map(|id| player(id, PlayerRole::Player, GameProject::TexasHoldemPoker, 100))
+            .collect();
+        game.player_join(players.clone());
+        game.game_start();
+
+        // 每人下前注，庄位 1 号另下一个大盲，从庄位左手边开始行动
+        assert_eq!(players.iter().map(|player| player.get_token()).collect::<Vec<_>>(), vec![85, 95, 95]);
+        assert_eq!(acting(&game), vec![2]);
+
+        game.game_finish();
+        let state = get_state::<TexasHoldemState>(&game.get_game_context(), TEXAS_HOLDEM_STATE_KEY).unwrap();
+        let state = lock_or_recover(&state);
+        assert_eq!(state.board.len(), 5);
+        assert!(state.hole_cards.values().flatten().chain(state.board.iter()).all(|card| card.rank.value() >= 6));
+    }
+
+    #[test]
+    fn leaving_before_start_does_not_advance() {
+        let rule = TexasHoldemPokerGameRules::new(5, 10, BettingStructure::NoLimit).build().unwrap();
+        let mut game = game(rule);
+        let players: Vec<Arc<Player>> = (1..=2)
+            .map(|id| player(id, PlayerRole::Player, GameProject::TexasHoldemPoker, 100))
+            .collect();
+        game.player_join(players.clone());
+        game.player_leave(vec![players[0].clone()]);
+        assert_eq!(game.get_game_state(), GameState::NotStarted);
+    }
+}
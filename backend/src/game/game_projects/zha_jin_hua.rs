@@ -0,0 +1,655 @@
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use crate::game::betting::pot::split_among;
+use crate::game::game::GameState;
+use crate::game::game_context::{get_or_insert_state, lock_or_recover};
+use crate::game::game_item::GameItem;
+use crate::game::game_items::poker::poker::{get_all_cards, Card};
+use crate::game::game_items::poker::three_card_evaluator::{beats_three_card, evaluate_three_card, ThreeCardHandValue};
+use crate::game::game_rule::{ActionOutcome, CompareCB, GameRule, GameRuleError};
+use crate::game::player::Player;
+use crate::game::provably_fair::commit_reveal::{FairnessState, PROVABLY_FAIR_KEY};
+use crate::game::game_projects::rule_helpers::{ActingPlayers, commit_next_seed, commit_seed_on_join, sync_action_players};
+
+/// 牌局状态在 game_context 中的 key
+pub const ZHA_JIN_HUA_STATE_KEY: &str = "zha_jin_hua_state";
+
+/// 每位玩家的手牌数
+pub const ZHA_JIN_HUA_HAND_SIZE: usize = 3;
+
+/// 一桌最多的玩家数
+pub const ZHA_JIN_HUA_MAX_PLAYERS: usize = 6;
+
+/// 炸金花玩家行动
+///
+/// 单注以闷牌玩家的注额计，看过牌的玩家每次需要付出双倍
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZhaJinHuaAction {
+    Look,          // 看牌，不消耗行动机会，不轮到自己时也可以看
+    Call,          // 跟注，按当前单注下注
+    Raise(u32),    // 加注，把单注提高到给定数额
+    Compare(u32),  // 比牌，付出一次跟注后与指定玩家比牌，输的一方出局；筹码不够跟注时用剩余的全部筹码比牌
+    Fold,          // 弃牌
+}
+
+/// 一次比牌的记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompareRecord {
+    pub challenger: u32,
+    pub target: u32,
+    pub winner: u32,
+}
+
+/// 一手牌结束时每位玩家的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZhaJinHuaResult {
+    pub user_id: u32,
+    pub hand_value: Option<ThreeCardHandValue>, // 只有最后亮牌的玩家才有
+    pub won: u32,
+}
+
+/// 炸金花牌局状态，保存在 game_context 中
+#[derive(Debug, Default)]
+pub struct ZhaJinHuaState {
+    pub hands: HashMap<u32, Vec<Card>>,
+    pub seen: HashSet<u32>,   // 已看牌的玩家
+    pub folded: HashSet<u32>, // 弃牌或比牌输掉的玩家
+    pub contributions: HashMap<u32, u32>,
+    pub dealer_user: Option<u32>,
+    pub order: Vec<u32>,        // 本手牌的行动顺序，从庄家左手边开始
+    pub acting: Option<usize>,  // 当前行动玩家在 order 中的位置
+    pub stake: u32,             // 当前单注(闷牌注额)
+    pub round: u32,             // 当前下注圈数，从 1 开始
+    pub compares: Vec<CompareRecord>,
+    pub results: Vec<ZhaJinHuaResult>,
+}
+
+/// 某位观察者看到的一个座位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZhaJinHuaSeatView {
+    pub user_id: u32,
+    pub cards: Option<Vec<Card>>, // 本人看过牌后或最后亮牌时可见
+    pub seen: bool,
+    pub folded: bool,
+    pub contributed: u32,
+}
+
+/// 某位观察者看到的牌桌
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZhaJinHuaTableView {
+    pub seats: Vec<ZhaJinHuaSeatView>,
+    pub pot: u32,
+    pub stake: u32,
+    pub round: u32,
+    pub dealer_user: Option<u32>,
+    pub next_to_act: Option<u32>,
+}
+
+impl ZhaJinHuaState {
+    /// 底池总额
+    pub fn pot(&self) -> u32 {
+        self.contributions.values().sum()
+    }
+
+    /// 下一位行动的玩家
+    pub fn next_to_act(&self) -> Option<u32> {
+        self.acting.map(|index| self.order[index])
+    }
+
+    /// 仍在牌局中的玩家，按行动顺序排列
+    pub fn live_players(&self) -> Vec<u32> {
+        self.order.iter().copied().filter(|user_id| !self.folded.contains(user_id)).collect()
+    }
+
+    /// 玩家跟注一次需要付出的筹码，看过牌的玩家加倍
+    pub fn call_cost(&self, user_id: u32) -> u32 {
+        if self.seen.contains(&user_id) { self.stake * 2 } else { self.stake }
+    }
+
+    /// viewer 看到的牌桌，viewer 为 None 时是旁观者视角
+    pub fn view_for(&self, viewer: Option<u32>) -> ZhaJinHuaTableView {
+        let shown: HashSet<u32> = self.results.iter()
+            .filter(|result| result.hand_value.is_some())
+            .map(|result| result.user_id)
+            .collect();
+        let seats: Vec<ZhaJinHuaSeatView> = self.order.iter()
+            .map(|user_id| {
+                let visible = (viewer == Some(*user_id) && self.seen.contains(user_id)) || shown.contains(user_id);
+                ZhaJinHuaSeatView {
+                    user_id: *user_id,
+                    cards: if visible { self.hands.get(user_id).cloned() } else { None },
+                    seen: self.seen.contains(user_id),
+                    folded: self.folded.contains(user_id),
+                    contributed: self.contributions.get(user_id).copied().unwrap_or(0),
+                }
+            })
+            .collect();
+        ZhaJinHuaTableView {
+            seats,
+            pot: self.pot(),
+            stake: self.stake,
+            round: self.round,
+            dealer_user: self.dealer_user,
+            next_to_act: self.next_to_act(),
+        }
+    }
+
+    // 开始新的一手牌前清空上一手的数据，庄家位保留
+    fn reset_hand(&mut self) {
+        self.hands.clear();
+        self.seen.clear();
+        self.folded.clear();
+        self.contributions.clear();
+        self.order.clear();
+        self.acting = None;
+        self.stake = 0;
+        self.round = 0;
+        self.compares.clear();
+        self.results.clear();
+    }
+
+    // 玩家投入筹码到底池，返回实际投入的数额
+    fn commit(&mut self, player: &Player, amount: u32) -> u32 {
+        let taken = player.take_token(amount.min(u16::MAX as u32) as u16) as u32;
+        *self.contributions.entry(player.get_user().get_id()).or_insert(0) += taken;
+        taken
+    }
+
+    // 轮到 order 中下一位未出局的玩家，绕回第一位时返回 true 表示进入新的一圈
+    fn advance(&mut self) -> bool {
+        let Some(current) = self.acting else {
+            return false;
+        };
+        let count = self.order.len();
+        let next = (1..=count)
+            .map(|offset| (current + offset) % count)
+            .find(|index| !self.folded.contains(&self.order[*index]));
+        self.acting = next;
+        matches!(next, Some(next) if next <= current)
+    }
+}
+
+impl ActingPlayers for ZhaJinHuaState {
+    fn acting_players(&self, _players: &[Arc<Player>]) -> Vec<u32> {
+        self.next_to_act().into_iter().collect()
+    }
+}
+
+/// 炸金花规则配置
+#[derive(Debug, Clone, Copy)]
+pub struct ZhaJinHuaGameRules {
+    pub ante: u16,        // 底注，开局每人下一次，也是初始单注
+    pub max_stake: u16,   // 单注封顶(闷牌注额)
+    pub max_rounds: u32,  // 下注圈数上限，到达后所有未出局玩家亮牌
+    pub special_235: bool, // 杂色 235 吃豹子
+}
+
+impl ZhaJinHuaGameRules {
+    pub fn new(ante: u16, max_stake: u16) -> Self {
+        ZhaJinHuaGameRules{ante, max_stake, max_rounds: 20, special_235: true}
+    }
+
+    /// 生成可交给 Game 驱动的完整 GameRule
+    pub fn build(&self) -> Result<GameRule, GameRuleError> {
+        let config = *self;
+        if config.ante == 0 || config.max_stake < config.ante || config.max_rounds == 0 {
+            return Err(GameRuleError::UnsupportedOption);
+        }
+
+        GameRule::new(
+            config.compare(),
+            Arc::new(allocate),
+            Arc::new(move |players, game_items, context| game_start(config, players, game_items, context)),
+            Arc::new(game_progress),
+            Arc::new(|_, _, _| {}),
+            Arc::new(|_, _, _| {}),
+            Arc::new(move |players, game_items, context| game_finish(config, players, game_items, context)),
+            Arc::new(commit_next_seed),
+            Arc::new(|_, _, _| ActionOutcome::Continue),
+            None,
+            None,
+            Arc::new(commit_seed_on_join),
+            Arc::new(move |leave_players, current_players, game_items, game_state, context| {
+                players_leave(config, leave_players, current_players, game_items, game_state, context)
+            }),
+            Arc::new(move |player, action, players, game_items, game_state, context| {
+                player_action(config, player, action, players, game_items, game_state, context)
+            }),
+            Arc::new(|_, _, _, _, _| ActionOutcome::Continue),
+            None,
+            None,
+        )
+    }
+
+    /// 比牌回调，GameRule.compare、行动中的比牌和最后亮牌都用它判定：left 严格大于 right 时返回 true
+    pub fn compare(&self) -> CompareCB {
+        let special_235 = self.special_235;
+        Arc::new(move |left, right, _| {
+            let cards = |items: &Vec<&dyn GameItem>| -> Vec<Card> {
+                items.iter().filter_map(|item| Card::from_item(*item)).collect()
+            };
+            beats_three_card(&cards(left), &cards(right), special_235)
+        })
+    }
+}
+
+// 轮换庄家，下底注并洗牌
+fn game_start(
+    config: ZhaJinHuaGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<ZhaJinHuaState>(&context, ZHA_JIN_HUA_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    state.reset_hand();
+
+    if players.len() < 2 || players.len() > ZHA_JIN_HUA_MAX_PLAYERS {
+        return;
+    }
+    let seat_order: Vec<u32> = players.iter().map(|player| player.get_user().get_id()).collect();
+    // 庄家按座位顺序轮换，上一手的庄家离桌时从第一个座位开始
+    let dealer_seat = state.dealer_user
+        .and_then(|dealer_user| seat_order.iter().position(|user_id| *user_id == dealer_user))
+        .map(|seat| (seat + 1) % seat_order.len())
+        .unwrap_or(0);
+    state.dealer_user = Some(seat_order[dealer_seat]);
+    state.order = (1..=seat_order.len())
+        .map(|offset| seat_order[(dealer_seat + offset) % seat_order.len()])
+        .collect();
+    state.stake = config.ante as u32;
+    for player in &players {
+        state.commit(player, config.ante as u32);
+    }
+
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    let mut rng = lock_or_recover(&fairness).begin_round(&seat_order);
+    let mut deck = get_all_cards();
+    rng.shuffle(&mut deck);
+    *lock_or_recover(&game_items) = deck
+        .into_iter()
+        .map(|card| Arc::new(card) as Arc<dyn GameItem>)
+        .collect();
+}
+
+// 从庄家左手边开始每人轮流发一张，发满三张后开始第一圈下注
+fn allocate(
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let state = get_or_insert_state::<ZhaJinHuaState>(&context, ZHA_JIN_HUA_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    let mut deck = lock_or_recover(&game_items);
+
+    if state.order.is_empty() {
+        return;
+    }
+    for _ in 0..ZHA_JIN_HUA_HAND_SIZE {
+        for user_id in state.order.clone() {
+            let Some(card) = draw_card(&mut deck) else {
+                return;
+            };
+            state.hands.entry(user_id).or_default().push(card);
+        }
+    }
+    state.round = 1;
+    state.acting = Some(0);
+    sync_action_players(&state, &players, &context);
+}
+
+// 行动绕回第一位玩家时进入下一圈
+fn game_progress(
+    _players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let state = get_or_insert_state::<ZhaJinHuaState>(&context, ZHA_JIN_HUA_STATE_KEY);
+    lock_or_recover(&state).round += 1;
+}
+
+// 玩家行动：看牌、跟注、加注、比牌或弃牌
+fn player_action(
+    config: ZhaJinHuaGameRules,
+    player: Arc<Player>,
+    action: Arc<dyn Any + Send + Sync>,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let players = lock_or_recover(&players).clone();
+    let context_snapshot = Arc::new(lock_or_recover(&context).clone());
+    let state = get_or_insert_state::<ZhaJinHuaState>(&context, ZHA_JIN_HUA_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let Some(action) = action.downcast_ref::<ZhaJinHuaAction>().copied() else {
+        return ActionOutcome::Rejected("unsupported action".to_string());
+    };
+    let user_id = player.get_user().get_id();
+    if !state.hands.contains_key(&user_id) || state.folded.contains(&user_id) {
+        return ActionOutcome::Rejected("player is not in the hand".to_string());
+    }
+    // 看牌不需要轮到自己
+    if action == ZhaJinHuaAction::Look {
+        state.seen.insert(user_id);
+        return ActionOutcome::Continue;
+    }
+    if state.next_to_act() != Some(user_id) {
+        return ActionOutcome::Rejected("not this player's turn".to_string());
+    }
+
+    match action {
+        ZhaJinHuaAction::Look => unreachable!(),
+        ZhaJinHuaAction::Call => {
+            let cost = state.call_cost(user_id);
+            if (player.get_token() as u32) < cost {
+                return ActionOutcome::Rejected(format!("not enough tokens to call {}, compare instead", cost));
+            }
+            state.commit(&player, cost);
+        }
+        ZhaJinHuaAction::Raise(stake) => {
+            if stake <= state.stake || stake > config.max_stake as u32 {
+                return ActionOutcome::Rejected(format!("stake must be above {} and at most {}", state.stake, config.max_stake));
+            }
+            let cost = if state.seen.contains(&user_id) { stake * 2 } else { stake };
+            if (player.get_token() as u32) < cost {
+                return ActionOutcome::Rejected(format!("not enough tokens to raise to {}", stake));
+            }
+            state.stake = stake;
+            state.commit(&player, cost);
+        }
+        ZhaJinHuaAction::Compare(target) => {
+            if target == user_id || !state.hands.contains_key(&target) || state.folded.contains(&target) {
+                return ActionOutcome::Rejected("target is not in the hand".to_string());
+            }
+            // 筹码不够跟注时用剩余的全部筹码比牌
+            let cost = state.call_cost(user_id);
+            state.commit(&player, cost);
+            // 牌力相同时发起比牌的一方输
+            let challenger_wins = (config.compare())(&as_items(&state.hands[&user_id]), &as_items(&state.hands[&target]), context_snapshot);
+            let (winner, loser) = if challenger_wins { (user_id, target) } else { (target, user_id) };
+            state.folded.insert(loser);
+            state.compares.push(CompareRecord{challenger: user_id, target, winner});
+        }
+        ZhaJinHuaAction::Fold => {
+            state.folded.insert(user_id);
+        }
+    }
+
+    let wrapped = state.advance();
+    let outcome = turn_outcome(config, &mut state, wrapped);
+    sync_action_players(&state, &players, &context);
+    outcome
+}
+
+// 只剩一人或达到圈数上限时结算，行动绕回第一位(wrapped)时进入下一圈
+fn turn_outcome(config: ZhaJinHuaGameRules, state: &mut ZhaJinHuaState, wrapped: bool) -> ActionOutcome {
+    if state.live_players().len() <= 1 {
+        state.acting = None;
+        ActionOutcome::GameComplete
+    } else if !wrapped {
+        ActionOutcome::Continue
+    } else if state.round >= config.max_rounds {
+        state.acting = None;
+        ActionOutcome::GameComplete
+    } else {
+        ActionOutcome::RoundComplete
+    }
+}
+
+// 结算：只剩一人时直接赢得底池，否则所有未出局玩家亮牌比大小
+fn game_finish(
+    config: ZhaJinHuaGameRules,
+    players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) {
+    let players = lock_or_recover(&players).clone();
+    let context_snapshot = Arc::new(lock_or_recover(&context).clone());
+    let state = get_or_insert_state::<ZhaJinHuaState>(&context, ZHA_JIN_HUA_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+
+    let live_players = state.live_players();
+    if live_players.is_empty() {
+        return;
+    }
+    let is_showdown = live_players.len() > 1;
+    let items: HashMap<u32, Vec<Card>> = live_players.iter()
+        .map(|user_id| (*user_id, state.hands.get(user_id).cloned().unwrap_or_default()))
+        .collect();
+    let compare = config.compare();
+    let beats = |left: u32, right: u32| compare(&as_items(&items[&left]), &as_items(&items[&right]), context_snapshot.clone());
+    // 没有被任何人比下去的玩家获胜，235 吃豹子可能形成循环，此时退回按牌型大小比较
+    let mut winners: Vec<u32> = live_players.iter()
+        .copied()
+        .filter(|user_id| live_players.iter().all(|other| !beats(*other, *user_id)))
+        .collect();
+    if winners.is_empty() {
+        let best = live_players.iter().map(|user_id| evaluate_three_card(&items[user_id])).max().flatten();
+        winners = live_players.iter()
+            .copied()
+            .filter(|user_id| evaluate_three_card(&items[user_id]) == best)
+            .collect();
+    }
+
+    let mut payouts: HashMap<u32, u32> = HashMap::new();
+    split_among(state.pot(), &winners, &state.order, &mut payouts);
+    let results: Vec<ZhaJinHuaResult> = live_players.iter()
+        .map(|user_id| ZhaJinHuaResult {
+            user_id: *user_id,
+            hand_value: if is_showdown { evaluate_three_card(&items[user_id]) } else { None },
+            won: payouts.get(user_id).copied().unwrap_or(0),
+        })
+        .collect();
+    for player in &players {
+        if let Some(won) = payouts.get(&player.get_user().get_id()) {
+            player.add_token((*won).min(u16::MAX as u32) as u16);
+        }
+    }
+
+    let fairness = get_or_insert_state::<FairnessState>(&context, PROVABLY_FAIR_KEY);
+    lock_or_recover(&fairness).reveal();
+    state.results = results;
+    state.contributions.clear();
+    state.acting = None;
+    sync_action_players(&state, &players, &context);
+}
+
+// 离桌玩家视为弃牌，轮到他行动时交给下一位；只剩一人时结算
+fn players_leave(
+    config: ZhaJinHuaGameRules,
+    leave_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    current_players: Arc<Mutex<Vec<Arc<Player>>>>,
+    _game_items: Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    _game_state: Arc<Mutex<GameState>>,
+    context: Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>,
+) -> ActionOutcome {
+    let leave_players = lock_or_recover(&leave_players).clone();
+    let current_players = lock_or_recover(&current_players).clone();
+    let state = get_or_insert_state::<ZhaJinHuaState>(&context, ZHA_JIN_HUA_STATE_KEY);
+    let mut state = lock_or_recover(&state);
+    if state.acting.is_none() {
+        return ActionOutcome::Continue;
+    }
+
+    let mut wrapped = false;
+    for player in leave_players {
+        let user_id = player.get_user().get_id();
+        if !state.hands.contains_key(&user_id) {
+            continue;
+        }
+        state.folded.insert(user_id);
+        if state.next_to_act() == Some(user_id) {
+            wrapped |= state.advance();
+        }
+    }
+    let outcome = turn_outcome(config, &mut state, wrapped);
+    sync_action_players(&state, &current_players, &context);
+    outcome
+}
+
+// 手牌转成 compare 回调需要的 GameItem 列表
+fn as_items(cards: &[Card]) -> Vec<&dyn GameItem> {
+    cards.iter().map(|card| card as &dyn GameItem).collect()
+}
+
+// 从牌堆顶摸一张牌
+fn draw_card(deck: &mut Vec<Arc<dyn GameItem>>) -> Option<Card> {
+    if deck.is_empty() {
+        return None;
+    }
+    Card::from_item(deck.remove(0).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game::{Game, GameState};
+    use crate::game::game_context::get_state;
+    use crate::game::game_projects::game_project::GameProject;
+    use crate::game::game_projects::test_support::{acting, game, player};
+    use crate::game::player::PlayerRole;
+
+    fn table() -> (Game, Vec<Arc<Player>>) {
+        let mut game = game(ZhaJinHuaGameRules::new(1, 10).build().unwrap());
+        let players: Vec<Arc<Player>> = (1..=3).map(|id| player(id, PlayerRole::Player, GameProject::ZhaJinHua, 100)).collect();
+        game.player_join(players.clone());
+        game.game_start();
+        (game, players)
+    }
+
+    fn seat(players: &[Arc<Player>], user_id: u32) -> Arc<Player> {
+        players.iter().find(|player| player.get_user().get_id() == user_id).unwrap().clone()
+    }
+
+    #[test]
+    fn last_player_standing_after_leaves_wins_pot() {
+        let (mut game, players) = table();
+        let actor = acting(&game)[0];
+        let others: Vec<Arc<Player>> = players.iter().filter(|player| player.get_user().get_id() != actor).cloned().collect();
+
+        assert_eq!(game.player_leave(vec![others[0].clone()]), ActionOutcome::Continue);
+        assert_eq!(game.player_leave(vec![others[1].clone()]), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!(seat(&players, actor).get_token(), 102);
+    }
+
+    #[test]
+    fn acting_player_leaving_at_end_of_round_starts_next_round() {
+        let (mut game, players) = table();
+        let first = acting(&game)[0];
+        for _ in 0..2 {
+            let actor = seat(&players, acting(&game)[0]);
+            assert_eq!(game.player_action(actor, Arc::new(ZhaJinHuaAction::Call)), ActionOutcome::Continue);
+        }
+        let last = seat(&players, acting(&game)[0]);
+
+        assert_eq!(game.player_leave(vec![last]), ActionOutcome::RoundComplete);
+        let state = get_state::<ZhaJinHuaState>(&game.get_game_context(), ZHA_JIN_HUA_STATE_KEY).unwrap();
+        assert_eq!(lock_or_recover(&state).round, 2);
+        assert_eq!(acting(&game), vec![first]);
+    }
+
+    fn cards(text: &str) -> Vec<Card> {
+        text.split_whitespace().map(|card| card.parse().unwrap()).collect()
+    }
+
+    // 按行动顺序替换各玩家的手牌
+    fn deal(game: &Game, hands: &[&str]) -> Arc<Mutex<ZhaJinHuaState>> {
+        let state = get_state::<ZhaJinHuaState>(&game.get_game_context(), ZHA_JIN_HUA_STATE_KEY).unwrap();
+        {
+            let mut state = lock_or_recover(&state);
+            let order = state.order.clone();
+            for (user_id, hand) in order.iter().zip(hands) {
+                state.hands.insert(*user_id, cards(hand));
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn compare_tie_goes_against_the_challenger() {
+        let (mut game, players) = table();
+        let state = deal(&game, &["Ks Qd 9c", "Kh Qc 9d", "2s 4d 7c"]);
+        let (challenger, target) = {
+            let state = lock_or_recover(&state);
+            (state.order[0], state.order[1])
+        };
+
+        let outcome = game.player_action(seat(&players, challenger), Arc::new(ZhaJinHuaAction::Compare(target)));
+        assert_eq!(outcome, ActionOutcome::Continue);
+        let state = lock_or_recover(&state);
+        assert_eq!(state.compares, vec![CompareRecord{challenger, target, winner: target}]);
+        assert!(state.folded.contains(&challenger));
+        assert_eq!(seat(&players, challenger).get_token(), 98);
+    }
+
+    #[test]
+    fn seen_player_pays_double() {
+        let (mut game, players) = table();
+        let actor = seat(&players, acting(&game)[0]);
+        assert_eq!(game.player_action(actor.clone(), Arc::new(ZhaJinHuaAction::Look)), ActionOutcome::Continue);
+        let state = get_state::<ZhaJinHuaState>(&game.get_game_context(), ZHA_JIN_HUA_STATE_KEY).unwrap();
+        assert_eq!(lock_or_recover(&state).call_cost(actor.get_user().get_id()), 2);
+
+        assert_eq!(game.player_action(actor.clone(), Arc::new(ZhaJinHuaAction::Call)), ActionOutcome::Continue);
+        assert_eq!(actor.get_token(), 97);
+        // 闷牌玩家加注到 3 只付 3
+        let blind = seat(&players, acting(&game)[0]);
+        assert_eq!(game.player_action(blind.clone(), Arc::new(ZhaJinHuaAction::Raise(3))), ActionOutcome::Continue);
+        assert_eq!(blind.get_token(), 96);
+        assert_eq!(lock_or_recover(&state).call_cost(actor.get_user().get_id()), 6);
+    }
+
+    #[test]
+    fn short_stack_may_compare_with_what_it_has_left() {
+        let mut game = game(ZhaJinHuaGameRules::new(1, 10).build().unwrap());
+        let players = vec![
+            player(1, PlayerRole::Player, GameProject::ZhaJinHua, 100),
+            player(2, PlayerRole::Player, GameProject::ZhaJinHua, 1),
+        ];
+        game.player_join(players.clone());
+        game.game_start();
+        // 庄家为 1 号，2 号先行动且下完底注后没有筹码
+        deal(&game, &["2s 4d 7c", "As Ad Kc"]);
+        let short = seat(&players, 2);
+        assert_eq!(acting(&game), vec![2]);
+        assert!(matches!(game.player_action(short.clone(), Arc::new(ZhaJinHuaAction::Call)), ActionOutcome::Rejected(_)));
+        assert_eq!(game.player_action(short.clone(), Arc::new(ZhaJinHuaAction::Compare(1))), ActionOutcome::GameComplete);
+        assert_eq!(game.get_game_state(), GameState::Finished);
+        assert_eq!((players[0].get_token(), short.get_token()), (101, 0));
+    }
+
+    #[test]
+    fn offsuit_235_beats_a_leopard_at_showdown() {
+        let mut game = game(ZhaJinHuaGameRules::new(1, 10).build().unwrap());
+        let players: Vec<Arc<Player>> = (1..=2).map(|id| player(id, PlayerRole::Player, GameProject::ZhaJinHua, 100)).collect();
+        game.player_join(players.clone());
+        game.game_start();
+        let state = deal(&game, &["As Ad Ac", "2s 3d 5c"]);
+        let special = lock_or_recover(&state).order[1];
+        game.game_finish();
+
+        assert_eq!(seat(&players, special).get_token(), 101);
+        let state = lock_or_recover(&state);
+        assert!(state.results.iter().all(|result| result.hand_value.is_some()));
+    }
+
+    #[test]
+    fn showdown_cycle_falls_back_to_hand_strength() {
+        // 235 吃豹子，豹子大于对子，对子大于 235，形成循环时按牌型大小由豹子赢
+        let (mut game, players) = table();
+        let state = deal(&game, &["2s 3d 5c", "9s 9d 9c", "Ks Kd 4c"]);
+        let leopard = lock_or_recover(&state).order[1];
+        game.game_finish();
+
+        assert_eq!(seat(&players, leopard).get_token(), 102);
+        let state = lock_or_recover(&state);
+        let won: Vec<u32> = state.results.iter().map(|result| result.won).collect();
+        assert_eq!(won.iter().sum::<u32>(), 3);
+    }
+}
@@ -19,17 +19,42 @@ pub type GameCB = Arc<dyn Fn(
     Arc<Mutex<Vec<Arc<Player>>>>,
     Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
     Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>
-) -> ()>;
+)>;
+
+/// 定时器回调，返回值交给 Game 决定是否进入下一轮或结算
+pub type TimeoutCB = Arc<dyn Fn(
+    Arc<Mutex<Vec<Arc<Player>>>>,
+    Arc<Mutex<Vec<Arc<dyn GameItem>>>>,
+    Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>
+) -> ActionOutcome>;
 
 pub type PlayersCB = Arc<dyn Fn(
     Arc<Mutex<Vec<Arc<Player>>>>,
     Arc<Mutex<Vec<Arc<Player>>>>,
     Arc<Mutex<Vec<Arc<dyn GameItem>>>>, Arc<Mutex<GameState>>, Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>
-)->()>;
+) -> ActionOutcome>;
+
+pub type ActionCB = Arc<dyn Fn(
+    Arc<Player>,
+    Arc<dyn Any + Send + Sync>,
+    Arc<Mutex<Vec<Arc<Player>>>>,
+    Arc<Mutex<Vec<Arc<dyn GameItem>>>>, Arc<Mutex<GameState>>, Arc<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>>
+) -> ActionOutcome>;
+
+/// 玩家行动、入座或离桌后牌局的推进方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionOutcome {
+    Rejected(String), // 行动不合法，附带原因
+    Continue,         // 等待下一位玩家行动
+    RoundComplete,    // 本轮结束，进入下一轮
+    GameComplete,     // 对局结束，进入结算
+}
 
 /// 游戏规则错误类型
+#[derive(Debug)]
 pub enum GameRuleError {
     TimerConfigMismatch,
+    UnsupportedOption, // 规则配置中的选项组合不受支持
 }
 
 /// 游戏规则
@@ -42,17 +67,20 @@ pub struct GameRule {
     pub game_resume: GameCB,
     pub game_finish: GameCB,
     pub game_wait_start: GameCB,
-    pub game_timeout: GameCB,
+    pub game_timeout: TimeoutCB,
     pub game_timer_duration: Option<Duration>,
     pub game_timer_times_method: Option<CBTimesMethod>,
     pub players_join: PlayersCB,
     pub players_leave: PlayersCB,
+    pub players_action: ActionCB,
     pub players_timeout: PlayersCB,
     pub players_timer_duration: Option<Duration>,
     pub players_timer_times_method: Option<CBTimesMethod>,
 }
 
 impl GameRule {
+    // 按字段顺序逐个传入回调，由各玩法的配置在 build 中组装
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         compare: CompareCB,
         allocate: GameCB,
@@ -62,11 +90,12 @@ impl GameRule {
         game_resume: GameCB,
         game_finish: GameCB,
         game_wait_start: GameCB,
-        game_timeout: GameCB,
+        game_timeout: TimeoutCB,
         game_timer_duration: Option<Duration>,
         game_timer_times_method: Option<CBTimesMethod>,
         players_join: PlayersCB,
         players_leave: PlayersCB,
+        players_action: ActionCB,
         players_timeout: PlayersCB,
         players_timer_duration: Option<Duration>,
         players_timer_times_method: Option<CBTimesMethod>,
@@ -94,6 +123,7 @@ impl GameRule {
             game_timer_times_method,
             players_join,
             players_leave,
+            players_action,
             players_timeout,
             players_timer_duration,
             players_timer_times_method,
@@ -118,6 +148,7 @@ impl fmt::Debug for GameRule {
             .field("game_timer_times_method", &self.game_timer_times_method)
             .field("players_join", &"Arc[Fn players_join]")
             .field("players_leave", &"Arc[Fn players_leave]")
+            .field("players_action", &"Arc[Fn players_action]")
             .field("players_timeout", &"Arc[Fn players_timeout]")
             .field("players_timer_duration", &self.players_timer_duration)
             .field("players_timer_times_method", &self.players_timer_times_method)
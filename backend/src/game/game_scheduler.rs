@@ -9,8 +9,11 @@ pub struct GamesScheduler {
     participant_set: HashSet<User>
 }
 
+// Game 按参与者哈希，调度器中的对局不应再增减参与者
+#[allow(clippy::mutable_key_type)]
 impl GamesScheduler {
-    //控制为单例模式
+    //控制为单例模式，单例尚未接入
+    #[allow(dead_code)]
     pub(self) fn new(game_set: HashSet<Game>, participant_set: HashSet<User>) -> GamesScheduler {
         GamesScheduler{game_set, participant_set}
     }
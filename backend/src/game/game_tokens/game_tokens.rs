@@ -8,6 +8,14 @@ pub struct GameTokens {
 
 impl GameTokens {
     pub fn new(target_game: GameProject, price_from_balance: u8) -> Self {
-        GameTokens(target_game, price_from_balance)
+        GameTokens{target_game, price_from_balance}
+    }
+
+    pub fn get_target_game(&self) -> GameProject {
+        self.target_game
+    }
+
+    pub fn get_price_from_balance(&self) -> u8 {
+        self.price_from_balance
     }
 }
\ No newline at end of file
@@ -5,5 +5,7 @@ pub mod game_rule;
 pub mod game_item;
 pub mod game_scheduler;
 pub mod game_items;
-mod game_stoke;
-mod game_tokens;
\ No newline at end of file
+pub mod game_context;
+pub mod betting;
+pub mod provably_fair;
+pub mod game_tokens;
\ No newline at end of file
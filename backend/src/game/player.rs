@@ -1,4 +1,6 @@
 use std::hash::Hash;
+use std::sync::Mutex;
+use crate::game::game_context::lock_or_recover;
 use crate::game::game_item::GameItem;
 use crate::game::game_projects::game_project::GameProject;
 use crate::user::user::User;
@@ -13,19 +15,49 @@ pub enum PlayerRole{
 #[derive(Debug)]
 pub struct Player {
     target_game: GameProject,
-    player_role: PlayerRole,
+    player_role: Mutex<PlayerRole>,// 庄家等角色在对局中会变化
     user: &'static User,
     game_item: &'static Vec<&'static dyn GameItem>,
-    token: u16,// 质押筹码数量
+    token: Mutex<u16>,// 质押筹码数量
 }
 
 impl Player {
     pub fn new(target_game: GameProject, player_role: PlayerRole, user: &'static User, game_item: &'static Vec<&'static dyn GameItem>, token: u16) -> Self{
-        Player {target_game, player_role, user, game_item, token}
+        Player {target_game, player_role: Mutex::new(player_role), user, game_item, token: Mutex::new(token)}
     }
 
-    pub fn update_player_role(&mut self, new_role: PlayerRole){
-        self.player_role = new_role;
+    pub fn get_target_game(&self) -> GameProject {
+        self.target_game
+    }
+
+    pub fn get_player_role(&self) -> PlayerRole {
+        *lock_or_recover(&self.player_role)
+    }
+
+    pub fn get_user(&self) -> &'static User {
+        self.user
+    }
+
+    pub fn get_token(&self) -> u16 {
+        *lock_or_recover(&self.token)
+    }
+
+    /// 增加筹码，超出上限的部分截断
+    pub fn add_token(&self, amount: u16) {
+        let mut token_guard = lock_or_recover(&self.token);
+        *token_guard = token_guard.saturating_add(amount);
+    }
+
+    /// 扣除筹码，筹码不足时扣光，返回实际扣除的数量
+    pub fn take_token(&self, amount: u16) -> u16 {
+        let mut token_guard = lock_or_recover(&self.token);
+        let taken = amount.min(*token_guard);
+        *token_guard -= taken;
+        taken
+    }
+
+    pub fn update_player_role(&self, new_role: PlayerRole){
+        *lock_or_recover(&self.player_role) = new_role;
     }
 
     pub fn update_game_item(&mut self, new_game_item: &'static Vec<&'static dyn GameItem>){
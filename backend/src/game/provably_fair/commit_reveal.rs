@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::fmt;
+use sha2::{Digest, Sha256};
+
+/// 可验证公平状态在 game_context 中的 key
+pub const PROVABLY_FAIR_KEY: &str = "provably_fair";
+
+/// 计算 SHA-256 并以小写十六进制返回
+pub fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+/// 字节转小写十六进制
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 校验公开的种子是否与事先公布的承诺值一致
+pub fn verify_commitment(server_seed: &str, commitment: &str) -> bool {
+    sha256_hex(server_seed.as_bytes()) == commitment.to_lowercase()
+}
+
+/// 服务端种子，开局前只公布其哈希(承诺值)，结束后才公开原文
+#[derive(Clone)]
+pub struct ServerSeed {
+    seed: String,
+}
+
+impl ServerSeed {
+    pub fn new(seed: String) -> Self {
+        ServerSeed{seed}
+    }
+
+    /// 用操作系统随机源生成 32 字节的种子
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes).expect("operating system random source is unavailable");
+        ServerSeed{seed: to_hex(&bytes)}
+    }
+
+    /// 承诺值 = SHA-256(seed)
+    pub fn commitment(&self) -> String {
+        sha256_hex(self.seed.as_bytes())
+    }
+
+    pub fn reveal(&self) -> &str {
+        &self.seed
+    }
+}
+
+// 种子公开前不能出现在日志中
+impl fmt::Debug for ServerSeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerSeed")
+            .field("commitment", &self.commitment())
+            .finish()
+    }
+}
+
+/// 可复现的随机数流
+///
+/// 第 counter 个 32 字节块为 SHA-256("{server_seed}:{client_seed}:{nonce}:{counter}")，
+/// client_seed 为各玩家客户端种子按座位顺序用 "," 连接，每个块依次切成 8 个大端 u32 使用
+#[derive(Debug, Clone)]
+pub struct FairRng {
+    server_seed: String,
+    client_seed: String,
+    nonce: u64,
+    counter: u64,
+    block: [u8; 32],
+    offset: usize,
+}
+
+impl FairRng {
+    pub fn new(server_seed: &str, client_seeds: &[String], nonce: u64) -> Self {
+        FairRng {
+            server_seed: server_seed.to_string(),
+            client_seed: client_seeds.join(","),
+            nonce,
+            counter: 0,
+            block: [0u8; 32],
+            offset: 32,
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        if self.offset >= self.block.len() {
+            let message = format!("{}:{}:{}:{}", self.server_seed, self.client_seed, self.nonce, self.counter);
+            self.block.copy_from_slice(&Sha256::digest(message.as_bytes()));
+            self.counter += 1;
+            self.offset = 0;
+        }
+        let bytes = [self.block[self.offset], self.block[self.offset + 1], self.block[self.offset + 2], self.block[self.offset + 3]];
+        self.offset += 4;
+        u32::from_be_bytes(bytes)
+    }
+
+    /// [0, bound) 内的均匀随机数，用拒绝采样消除取模偏差
+    pub fn next_below(&mut self, bound: u32) -> u32 {
+        assert!(bound > 0, "bound must be positive");
+        let zone = u32::MAX - (u32::MAX % bound + 1) % bound;
+        loop {
+            let value = self.next_u32();
+            if value <= zone {
+                return value % bound;
+            }
+        }
+    }
+
+    /// [0, 1) 内的随机小数，取 52 位精度
+    pub fn next_f64(&mut self) -> f64 {
+        let high = (self.next_u32() as u64) << 20;
+        let low = (self.next_u32() >> 12) as u64;
+        (high | low) as f64 / (1u64 << 52) as f64
+    }
+
+    /// Fisher-Yates 洗牌：i 从末尾往前，与 [0, i] 中随机位置交换
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.next_below(i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// 玩家提交客户端种子的行动消息，任何阶段都可以发送，从下一次洗牌起生效
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSeed(pub String);
+
+/// 一局结束后公开的全部参数，任何人都可以据此重算结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedReveal {
+    pub server_seed: String,
+    pub commitment: String,
+    pub client_seeds: Vec<String>,
+    pub nonce: u64,
+}
+
+impl SeedReveal {
+    pub fn verify_commitment(&self) -> bool {
+        verify_commitment(&self.server_seed, &self.commitment)
+    }
+
+    /// 用公开的参数重建本局的随机数流
+    pub fn rng(&self) -> FairRng {
+        FairRng::new(&self.server_seed, &self.client_seeds, self.nonce)
+    }
+}
+
+// 正在使用的牌靴的种子及洗牌参数，换靴时才公开
+#[derive(Debug)]
+struct ShoeSeed {
+    server_seed: ServerSeed,
+    client_seeds: Vec<String>,
+    nonce: u64,
+}
+
+/// 一张桌子的承诺-公开状态，保存在 game_context 中
+#[derive(Debug, Default)]
+pub struct FairnessState {
+    server_seed: Option<ServerSeed>,     // 已承诺、尚未公开的种子
+    client_seeds: HashMap<u32, String>, // 玩家提交的客户端种子
+    round_client_seeds: Vec<String>,     // 本局实际使用的客户端种子
+    nonce: u64,                          // 局号，每公开一次加一
+    last_reveal: Option<SeedReveal>,
+    shoe_seed: Option<ShoeSeed>,         // 洗当前牌靴用的种子
+}
+
+impl FairnessState {
+    /// 承诺下一局的服务端种子，返回需要公布的承诺值
+    pub fn commit(&mut self, server_seed: ServerSeed) -> String {
+        let commitment = server_seed.commitment();
+        self.server_seed = Some(server_seed);
+        commitment
+    }
+
+    /// 尚未承诺时生成新种子并承诺
+    pub fn ensure_committed(&mut self) -> String {
+        match &self.server_seed {
+            Some(server_seed) => server_seed.commitment(),
+            None => self.commit(ServerSeed::generate()),
+        }
+    }
+
+    pub fn get_commitment(&self) -> Option<String> {
+        self.server_seed.as_ref().map(|server_seed| server_seed.commitment())
+    }
+
+    pub fn get_nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    pub fn set_client_seed(&mut self, user_id: u32, client_seed: String) {
+        self.client_seeds.insert(user_id, client_seed);
+    }
+
+    pub fn get_last_reveal(&self) -> Option<&SeedReveal> {
+        self.last_reveal.as_ref()
+    }
+
+    /// 开局：按座位顺序收集客户端种子(未提交的玩家视为空串)，返回本局的随机数流
+    pub fn begin_round(&mut self, seat_order: &[u32]) -> FairRng {
+        self.ensure_committed();
+        self.round_client_seeds = seat_order.iter()
+            .map(|user_id| self.client_seeds.get(user_id).cloned().unwrap_or_default())
+            .collect();
+        let server_seed = self.server_seed.as_ref().map(|server_seed| server_seed.reveal()).unwrap_or_default();
+        FairRng::new(server_seed, &self.round_client_seeds, self.nonce)
+    }
+
+    /// 结束：公开本局的服务端种子并推进局号，下一局需要重新承诺
+    pub fn reveal(&mut self) -> Option<SeedReveal> {
+        let server_seed = self.server_seed.take()?;
+        let reveal = SeedReveal {
+            server_seed: server_seed.reveal().to_string(),
+            commitment: server_seed.commitment(),
+            client_seeds: std::mem::take(&mut self.round_client_seeds),
+            nonce: self.nonce,
+        };
+        self.nonce += 1;
+        self.last_reveal = Some(reveal.clone());
+        Some(reveal)
+    }
+
+    /// 换靴：公开上一靴的种子，用事先承诺的种子洗新靴，并在发牌前承诺下一靴的种子
+    ///
+    /// 一副牌靴要用很多局，洗牌用的种子要等这副牌靴用完才能公开
+    pub fn reshuffle_shoe(&mut self, seat_order: &[u32]) -> FairRng {
+        if let Some(shoe_seed) = self.shoe_seed.take() {
+            self.last_reveal = Some(SeedReveal {
+                server_seed: shoe_seed.server_seed.reveal().to_string(),
+                commitment: shoe_seed.server_seed.commitment(),
+                client_seeds: shoe_seed.client_seeds,
+                nonce: shoe_seed.nonce,
+            });
+        }
+        let rng = self.begin_round(seat_order);
+        if let Some(server_seed) = self.server_seed.take() {
+            self.shoe_seed = Some(ShoeSeed {
+                server_seed,
+                client_seeds: std::mem::take(&mut self.round_client_seeds),
+                nonce: self.nonce,
+            });
+            self.nonce += 1;
+        }
+        self.commit(ServerSeed::generate());
+        rng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveal_matches_commitment_and_replays_rng() {
+        let mut fairness = FairnessState::default();
+        let commitment = fairness.commit(ServerSeed::new("server".to_string()));
+        fairness.set_client_seed(2, "alice".to_string());
+        let mut rng = fairness.begin_round(&[1, 2]);
+        let values: Vec<u32> = (0..20).map(|_| rng.next_u32()).collect();
+
+        let reveal = fairness.reveal().unwrap();
+        assert_eq!(reveal.commitment, commitment);
+        assert_eq!(reveal.client_seeds, vec![String::new(), "alice".to_string()]);
+        assert_eq!(reveal.nonce, 0);
+        assert!(reveal.verify_commitment());
+        let mut replay = reveal.rng();
+        assert_eq!((0..20).map(|_| replay.next_u32()).collect::<Vec<u32>>(), values);
+
+        assert!(!verify_commitment("other", &commitment));
+        assert!(verify_commitment("server", &commitment.to_uppercase()));
+        assert_eq!(fairness.get_nonce(), 1);
+        assert_eq!(fairness.get_commitment(), None);
+        assert_eq!(fairness.reveal(), None);
+    }
+
+    #[test]
+    fn client_seeds_and_nonce_change_the_stream() {
+        let seeds = vec!["a".to_string(), "b".to_string()];
+        let first = FairRng::new("server", &seeds, 0).next_u32();
+        assert_eq!(FairRng::new("server", &seeds, 0).next_u32(), first);
+        assert_ne!(FairRng::new("server", &seeds, 1).next_u32(), first);
+        assert_ne!(FairRng::new("server", &["b".to_string(), "a".to_string()], 0).next_u32(), first);
+        assert_ne!(FairRng::new("other", &seeds, 0).next_u32(), first);
+    }
+
+    #[test]
+    fn bounded_values_stay_in_range_and_shuffle_is_a_permutation() {
+        let mut rng = FairRng::new("server", &[], 0);
+        let mut counts = [0u32; 3];
+        for _ in 0..3000 {
+            counts[rng.next_below(3) as usize] += 1;
+        }
+        assert!(counts.iter().all(|count| *count > 900));
+        assert_eq!(rng.next_below(1), 0);
+        assert!((0..100).map(|_| rng.next_f64()).all(|value| (0.0..1.0).contains(&value)));
+
+        let mut items: Vec<u32> = (0..52).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..52).collect::<Vec<u32>>());
+        items.sort();
+        assert_eq!(items, (0..52).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn shoe_seed_is_committed_before_use_and_revealed_at_next_shoe() {
+        let mut fairness = FairnessState::default();
+        let first_commitment = fairness.commit(ServerSeed::new("shoe one".to_string()));
+        let first_value = fairness.reshuffle_shoe(&[1]).next_u32();
+        // 第一靴仍在使用，种子不能公开，下一靴的种子已承诺
+        assert_eq!(fairness.get_last_reveal(), None);
+        let second_commitment = fairness.get_commitment().unwrap();
+        assert_ne!(second_commitment, first_commitment);
+
+        let second_value = fairness.reshuffle_shoe(&[1]).next_u32();
+        let reveal = fairness.get_last_reveal().unwrap().clone();
+        assert_eq!(reveal.server_seed, "shoe one");
+        assert_eq!(reveal.commitment, first_commitment);
+        assert_eq!(reveal.nonce, 0);
+        assert!(reveal.verify_commitment());
+        assert_eq!(reveal.rng().next_u32(), first_value);
+        assert_ne!(second_value, first_value);
+        assert_eq!(fairness.get_nonce(), 2);
+        assert!(fairness.get_commitment().is_some());
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+use crate::game::provably_fair::commit_reveal::{sha256_hex, ServerSeed};
+
+/// 预先生成的哈希链，默认为空链
+///
+/// 从秘密种子出发反复做 SHA-256 得到整条链，按生成的逆序逐局使用：
+/// 每一局的哈希再做一次 SHA-256 就是上一局的哈希，第一局的哈希做一次 SHA-256 就是事先公布的终点哈希。
+/// 服务端在第一局前就无法再改动任何一局的结果，玩家拿到某局的哈希后可以一路哈希回终点来验证
+#[derive(Clone, Default)]
+pub struct HashChain {
+    hashes: Vec<String>, // 按使用顺序排列
+    next: usize,
+    terminal_hash: String,
+}
+
+impl HashChain {
+    /// 由种子生成长度为 length 的哈希链
+    pub fn from_seed(seed: &str, length: usize) -> Self {
+        let mut hashes = Vec::with_capacity(length);
+        let mut hash = sha256_hex(seed.as_bytes());
+        for _ in 0..length {
+            hashes.push(hash.clone());
+            hash = sha256_hex(hash.as_bytes());
+        }
+        hashes.reverse();
+        HashChain{hashes, next: 0, terminal_hash: hash}
+    }
+
+    /// 用操作系统随机源生成种子并建链
+    pub fn generate(length: usize) -> Self {
+        HashChain::from_seed(ServerSeed::generate().reveal(), length)
+    }
+
+    /// 事先公布的终点哈希
+    pub fn get_terminal_hash(&self) -> &str {
+        &self.terminal_hash
+    }
+
+    /// 取出下一局的哈希，整条链用完时返回 None
+    pub fn next_hash(&mut self) -> Option<String> {
+        let hash = self.hashes.get(self.next).cloned()?;
+        self.next += 1;
+        Some(hash)
+    }
+
+    /// 已经使用(公开)过的哈希
+    pub fn get_used_hashes(&self) -> &[String] {
+        &self.hashes[..self.next]
+    }
+
+    /// 剩余可用的局数
+    pub fn remaining(&self) -> usize {
+        self.hashes.len() - self.next
+    }
+}
+
+// 未使用的哈希不能出现在日志中
+impl fmt::Debug for HashChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashChain")
+            .field("terminal_hash", &self.terminal_hash)
+            .field("next", &self.next)
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+/// 校验某局的哈希属于以 terminal_hash 为终点的链，返回它是第几局(从 0 开始)，最多回溯 max_steps 次
+pub fn verify_chain_link(terminal_hash: &str, game_hash: &str, max_steps: usize) -> Option<usize> {
+    let terminal_hash = terminal_hash.to_lowercase();
+    let mut hash = game_hash.to_lowercase();
+    for index in 0..max_steps {
+        hash = sha256_hex(hash.as_bytes());
+        if hash == terminal_hash {
+            return Some(index);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_hash_links_back_to_terminal_hash() {
+        let mut chain = HashChain::from_seed("seed", 20);
+        let terminal_hash = chain.get_terminal_hash().to_string();
+        for index in 0..20 {
+            let hash = chain.next_hash().unwrap();
+            assert_eq!(verify_chain_link(&terminal_hash, &hash, 20), Some(index));
+            assert_eq!(verify_chain_link(&terminal_hash, &hash.to_uppercase(), 20), Some(index));
+        }
+        assert_eq!(chain.next_hash(), None);
+        assert_eq!(chain.remaining(), 0);
+        assert_eq!(chain.get_used_hashes().len(), 20);
+    }
+
+    #[test]
+    fn foreign_or_too_deep_hash_is_rejected() {
+        let mut chain = HashChain::from_seed("seed", 10);
+        let terminal_hash = chain.get_terminal_hash().to_string();
+        let mut last = String::new();
+        while let Some(hash) = chain.next_hash() {
+            last = hash;
+        }
+        assert_eq!(verify_chain_link(&terminal_hash, &last, 9), None);
+        assert_eq!(verify_chain_link(&terminal_hash, &last, 10), Some(9));
+        assert_eq!(verify_chain_link(&terminal_hash, &sha256_hex(b"other"), 100), None);
+        // 终点哈希本身不是任何一局的哈希
+        assert_eq!(verify_chain_link(&terminal_hash, &terminal_hash, 100), None);
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::OnceLock;
+use num_bigint::BigUint;
+use crate::game::game_items::poker::poker::{get_all_cards, Card};
+use crate::game::provably_fair::commit_reveal::{to_hex, FairRng};
+
+/// RFC 3526 中 2048 位 MODP 群的安全素数 p = 2q + 1，所有玩家共用
+const MODP_2048_PRIME_HEX: &str = concat!(
+    "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+    "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+    "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+    "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05",
+    "98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB",
+    "9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+    "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718",
+    "3995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF",
+);
+
+/// 共用的素数模数
+pub fn shared_prime() -> &'static BigUint {
+    static PRIME: OnceLock<BigUint> = OnceLock::new();
+    PRIME.get_or_init(|| BigUint::parse_bytes(MODP_2048_PRIME_HEX.as_bytes(), 16).expect("invalid prime constant"))
+}
+
+/// 把牌编码为明文：第 i 张牌(按 get_all_cards 的顺序)编码为 (i + 2)^2 mod p，
+/// 取二次剩余可以避免 SRA 泄露明文的二次剩余性
+pub fn encode_card(card: &Card) -> Option<BigUint> {
+    card_encodings().iter().find(|(deck_card, _)| deck_card == card).map(|(_, plaintext)| plaintext.clone())
+}
+
+/// 把明文还原为牌，不是合法编码时返回 None
+pub fn decode_card(plaintext: &BigUint) -> Option<Card> {
+    card_encodings().iter().find(|(_, encoded)| encoded == plaintext).map(|(card, _)| *card)
+}
+
+// 52 张牌的明文编码表
+fn card_encodings() -> &'static Vec<(Card, BigUint)> {
+    static ENCODINGS: OnceLock<Vec<(Card, BigUint)>> = OnceLock::new();
+    ENCODINGS.get_or_init(|| {
+        get_all_cards().into_iter()
+            .enumerate()
+            .map(|(index, card)| (card, BigUint::from(index as u32 + 2).modpow(&BigUint::from(2u32), shared_prime())))
+            .collect()
+    })
+}
+
+/// 整副牌的明文，作为第一位玩家加密洗牌的输入
+pub fn initial_deck() -> Vec<BigUint> {
+    get_all_cards().iter().filter_map(encode_card).collect()
+}
+
+/// SRA 交换加密密钥：E(m) = m^e mod p，D(c) = c^d mod p，e * d ≡ 1 (mod p - 1)，
+/// 多位玩家的加密可以按任意顺序叠加和解除
+#[derive(Clone, PartialEq, Eq)]
+pub struct SraKey {
+    encrypt_exponent: BigUint,
+    decrypt_exponent: BigUint,
+}
+
+impl SraKey {
+    /// 用操作系统随机源生成密钥
+    pub fn generate() -> Self {
+        let order = shared_prime() - 1u32;
+        loop {
+            let mut bytes = [0u8; 256];
+            getrandom::getrandom(&mut bytes).expect("operating system random source is unavailable");
+            let exponent = BigUint::from_bytes_be(&bytes) % &order;
+            if let Some(key) = SraKey::from_exponent(exponent) {
+                return key;
+            }
+        }
+    }
+
+    /// 由加密指数构造密钥，指数与 p - 1 不互素时返回 None
+    pub fn from_exponent(encrypt_exponent: BigUint) -> Option<Self> {
+        if encrypt_exponent <= BigUint::from(1u32) {
+            return None;
+        }
+        let order = shared_prime() - 1u32;
+        let decrypt_exponent = encrypt_exponent.modinv(&order)?;
+        Some(SraKey{encrypt_exponent, decrypt_exponent})
+    }
+
+    /// 校验加密、解密指数是否互逆
+    pub fn is_valid(&self) -> bool {
+        let order = shared_prime() - 1u32;
+        (&self.encrypt_exponent * &self.decrypt_exponent) % &order == BigUint::from(1u32)
+    }
+
+    pub fn encrypt(&self, value: &BigUint) -> BigUint {
+        value.modpow(&self.encrypt_exponent, shared_prime())
+    }
+
+    pub fn decrypt(&self, value: &BigUint) -> BigUint {
+        value.modpow(&self.decrypt_exponent, shared_prime())
+    }
+
+    /// 玩家本地执行：给每张牌加一层密并打乱顺序
+    pub fn encrypt_and_shuffle(&self, deck: &[BigUint]) -> Vec<BigUint> {
+        let mut seed = [0u8; 32];
+        getrandom::getrandom(&mut seed).expect("operating system random source is unavailable");
+        let mut encrypted: Vec<BigUint> = deck.iter().map(|value| self.encrypt(value)).collect();
+        FairRng::new(&to_hex(&seed), &[], 0).shuffle(&mut encrypted);
+        encrypted
+    }
+}
+
+// 密钥不能出现在日志中
+impl fmt::Debug for SraKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SraKey").finish_non_exhaustive()
+    }
+}
+
+/// 牌的归属：私有牌只有持有者能看到，公共牌所有人都能看到
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardOwner {
+    Player(u32),
+    Public,
+}
+
+/// 玩家提交给牌桌的协议消息
+#[derive(Debug, Clone)]
+pub enum MentalPokerAction {
+    Shuffle(Vec<BigUint>),                    // 加密并洗好的整副牌
+    Share { index: usize, value: BigUint },   // 解除自己对第 index 张牌的一层加密
+    RevealKey(SraKey),                        // 摊牌时公开密钥，用于亮牌和事后校验
+}
+
+/// 协议错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MentalPokerError {
+    NotYourTurn,
+    AlreadyShuffled,
+    NotShuffled,
+    WrongDeckSize,
+    DuplicateCard,
+    OutOfRange,
+    ShareNotRequested,
+    InvalidKey,
+    InvalidShare,
+    UndecodableCard,
+}
+
+impl fmt::Display for MentalPokerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MentalPokerError::NotYourTurn => write!(f, "it is not this player's turn to shuffle"),
+            MentalPokerError::AlreadyShuffled => write!(f, "deck is already shuffled"),
+            MentalPokerError::NotShuffled => write!(f, "deck is not shuffled yet"),
+            MentalPokerError::WrongDeckSize => write!(f, "submitted deck has the wrong size"),
+            MentalPokerError::DuplicateCard => write!(f, "submitted deck contains duplicates"),
+            MentalPokerError::OutOfRange => write!(f, "value is outside the group"),
+            MentalPokerError::ShareNotRequested => write!(f, "decryption share was not requested"),
+            MentalPokerError::InvalidKey => write!(f, "key is not a valid SRA key"),
+            MentalPokerError::InvalidShare => write!(f, "a previously submitted share does not match the key"),
+            MentalPokerError::UndecodableCard => write!(f, "decrypted value is not a card"),
+        }
+    }
+}
+
+/// 一手牌的心理扑克牌桌：玩家按座位顺序依次加密洗牌，
+/// 发牌时除持有者外的每位玩家提交解密份额，服务端始终看不到私有牌
+#[derive(Debug, Clone)]
+pub struct MentalPokerTable {
+    seat_order: Vec<u32>,
+    shuffled_count: usize,
+    deck: Vec<BigUint>,
+    owners: HashMap<usize, CardOwner>,
+    current: HashMap<usize, BigUint>,                     // 已解除部分加密层后的值
+    shares: HashMap<usize, Vec<(u32, BigUint, BigUint)>>, // 每张牌的份额记录 (玩家, 解除前, 解除后)
+    revealed_keys: HashMap<u32, SraKey>,
+}
+
+impl MentalPokerTable {
+    pub fn new(seat_order: Vec<u32>) -> Self {
+        MentalPokerTable {
+            seat_order,
+            shuffled_count: 0,
+            deck: initial_deck(),
+            owners: HashMap::new(),
+            current: HashMap::new(),
+            shares: HashMap::new(),
+            revealed_keys: HashMap::new(),
+        }
+    }
+
+    /// 下一位需要加密洗牌的玩家
+    pub fn next_shuffler(&self) -> Option<u32> {
+        self.seat_order.get(self.shuffled_count).copied()
+    }
+
+    pub fn is_shuffled(&self) -> bool {
+        self.shuffled_count >= self.seat_order.len()
+    }
+
+    /// 当前的牌堆，洗牌阶段为下一位玩家的输入
+    pub fn get_deck(&self) -> &[BigUint] {
+        &self.deck
+    }
+
+    pub fn submit_shuffle(&mut self, user_id: u32, deck: Vec<BigUint>) -> Result<(), MentalPokerError> {
+        if self.is_shuffled() {
+            return Err(MentalPokerError::AlreadyShuffled);
+        }
+        if self.next_shuffler() != Some(user_id) {
+            return Err(MentalPokerError::NotYourTurn);
+        }
+        if deck.len() != self.deck.len() {
+            return Err(MentalPokerError::WrongDeckSize);
+        }
+        if deck.iter().any(|value| value >= shared_prime() || *value <= BigUint::from(1u32)) {
+            return Err(MentalPokerError::OutOfRange);
+        }
+        if deck.iter().collect::<HashSet<_>>().len() != deck.len() {
+            return Err(MentalPokerError::DuplicateCard);
+        }
+        self.deck = deck;
+        self.shuffled_count += 1;
+        Ok(())
+    }
+
+    /// 指定第 index 张牌的归属，之后相关玩家需要提交解密份额
+    pub fn assign(&mut self, index: usize, owner: CardOwner) {
+        if index < self.deck.len() {
+            self.owners.insert(index, owner);
+        }
+    }
+
+    /// 玩家还需要提交份额的牌
+    pub fn required_shares(&self, user_id: u32) -> Vec<usize> {
+        if !self.is_shuffled() || !self.seat_order.contains(&user_id) {
+            return Vec::new();
+        }
+        let mut indexes: Vec<usize> = self.owners.iter()
+            .filter(|(_, owner)| **owner != CardOwner::Player(user_id))
+            .map(|(index, _)| *index)
+            .filter(|index| !self.has_shared(*index, user_id))
+            .collect();
+        indexes.sort_unstable();
+        indexes
+    }
+
+    /// 还需要提交份额的玩家
+    pub fn players_owing_shares(&self) -> Vec<u32> {
+        self.seat_order.iter().copied().filter(|user_id| !self.required_shares(*user_id).is_empty()).collect()
+    }
+
+    /// 所有已分配的牌是否都已收齐份额
+    pub fn is_settled(&self) -> bool {
+        self.is_shuffled() && self.players_owing_shares().is_empty()
+    }
+
+    /// 第 index 张牌当前待解密的值，玩家据此计算自己的份额
+    pub fn pending_value(&self, index: usize) -> Option<&BigUint> {
+        if !self.is_shuffled() {
+            return None;
+        }
+        self.current.get(&index).or_else(|| self.deck.get(index))
+    }
+
+    pub fn submit_share(&mut self, user_id: u32, index: usize, value: BigUint) -> Result<(), MentalPokerError> {
+        if !self.is_shuffled() {
+            return Err(MentalPokerError::NotShuffled);
+        }
+        if !self.required_shares(user_id).contains(&index) {
+            return Err(MentalPokerError::ShareNotRequested);
+        }
+        if value >= *shared_prime() || value <= BigUint::from(1u32) {
+            return Err(MentalPokerError::OutOfRange);
+        }
+        let before = self.pending_value(index).cloned().unwrap_or_default();
+        self.shares.entry(index).or_default().push((user_id, before, value.clone()));
+        self.current.insert(index, value);
+        Ok(())
+    }
+
+    /// 收齐份额后仍由持有者加密的私有牌，持有者在本地用自己的密钥解密
+    pub fn owner_value(&self, index: usize) -> Option<&BigUint> {
+        match self.owners.get(&index) {
+            Some(CardOwner::Player(owner)) if self.required_by_others(index, *owner) => self.pending_value(index),
+            _ => None,
+        }
+    }
+
+    /// 收齐所有玩家份额的公共牌
+    pub fn public_card(&self, index: usize) -> Option<Card> {
+        match self.owners.get(&index) {
+            Some(CardOwner::Public) if self.seat_order.iter().all(|user_id| self.has_shared(index, *user_id)) => {
+                self.pending_value(index).and_then(decode_card)
+            }
+            _ => None,
+        }
+    }
+
+    /// 玩家公开密钥：校验其提交过的全部份额，并解出其私有牌(按 index 升序)
+    pub fn reveal_key(&mut self, user_id: u32, key: SraKey) -> Result<Vec<Card>, MentalPokerError> {
+        if !key.is_valid() {
+            return Err(MentalPokerError::InvalidKey);
+        }
+        let shares_valid = self.shares.values()
+            .flatten()
+            .filter(|(sharer, _, _)| *sharer == user_id)
+            .all(|(_, before, after)| key.decrypt(before) == *after);
+        if !shares_valid {
+            return Err(MentalPokerError::InvalidShare);
+        }
+
+        let mut indexes: Vec<usize> = self.owners.iter()
+            .filter(|(_, owner)| **owner == CardOwner::Player(user_id))
+            .map(|(index, _)| *index)
+            .collect();
+        indexes.sort_unstable();
+        let mut cards = Vec::with_capacity(indexes.len());
+        for index in indexes {
+            let value = self.owner_value(index).ok_or(MentalPokerError::NotShuffled)?;
+            cards.push(decode_card(&key.decrypt(value)).ok_or(MentalPokerError::UndecodableCard)?);
+        }
+        self.revealed_keys.insert(user_id, key);
+        Ok(cards)
+    }
+
+    pub fn has_revealed(&self, user_id: u32) -> bool {
+        self.revealed_keys.contains_key(&user_id)
+    }
+
+    fn has_shared(&self, index: usize, user_id: u32) -> bool {
+        self.shares.get(&index).is_some_and(|shares| shares.iter().any(|(sharer, _, _)| *sharer == user_id))
+    }
+
+    // 除持有者外的其他玩家是否都已提交份额
+    fn required_by_others(&self, index: usize, owner: u32) -> bool {
+        self.seat_order.iter().filter(|user_id| **user_id != owner).all(|user_id| self.has_shared(index, *user_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试用小加密指数，加密快，解密指数仍是完整长度
+    fn key(exponent: u32) -> SraKey {
+        SraKey::from_exponent(BigUint::from(exponent)).unwrap()
+    }
+
+    #[test]
+    fn encryption_layers_commute() {
+        let (first, second) = (key(3), key(5));
+        assert!(first.is_valid() && second.is_valid());
+        let plaintext = initial_deck()[7].clone();
+        let encrypted = second.encrypt(&first.encrypt(&plaintext));
+        assert_eq!(second.decrypt(&first.decrypt(&encrypted)), plaintext);
+        assert_eq!(decode_card(&plaintext), Some(get_all_cards()[7]));
+        assert_eq!(SraKey::from_exponent(BigUint::from(2u32)), None);
+    }
+
+    #[test]
+    fn shares_reveal_private_and_public_cards() {
+        let keys = [(1, key(3)), (2, key(5))];
+        let mut table = MentalPokerTable::new(vec![1, 2]);
+        assert_eq!(table.submit_shuffle(2, initial_deck()), Err(MentalPokerError::NotYourTurn));
+        for (user_id, key) in &keys {
+            let deck = key.encrypt_and_shuffle(table.get_deck());
+            table.submit_shuffle(*user_id, deck).unwrap();
+        }
+        table.assign(0, CardOwner::Player(1));
+        table.assign(1, CardOwner::Public);
+        assert_eq!(table.players_owing_shares(), vec![1, 2]);
+        assert_eq!(table.required_shares(1), vec![1]);
+        assert_eq!(table.submit_share(1, 0, BigUint::from(7u32)), Err(MentalPokerError::ShareNotRequested));
+
+        for (user_id, key) in &keys {
+            for index in table.required_shares(*user_id) {
+                let share = key.decrypt(table.pending_value(index).unwrap());
+                table.submit_share(*user_id, index, share).unwrap();
+            }
+        }
+        assert!(table.is_settled());
+        let public_card = table.public_card(1).unwrap();
+        let private_card = decode_card(&keys[0].1.decrypt(table.owner_value(0).unwrap())).unwrap();
+        assert_ne!(public_card, private_card);
+
+        // 公开的密钥必须与提交过的份额一致
+        assert_eq!(table.reveal_key(2, key(7)), Err(MentalPokerError::InvalidShare));
+        assert_eq!(table.reveal_key(1, keys[0].1.clone()), Ok(vec![private_card]));
+        assert!(table.has_revealed(1));
+    }
+}
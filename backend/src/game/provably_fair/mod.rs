@@ -0,0 +1,4 @@
+pub mod commit_reveal;
+pub mod hash_chain;
+#[cfg(feature = "mental-poker")]
+pub mod mental_poker;
\ No newline at end of file
@@ -0,0 +1,7 @@
+// 模块按 xxx/xxx.rs 组织，例如 game::game、timer::timer
+#![allow(clippy::module_inception)]
+
+pub mod user;
+pub mod game;
+pub mod timer;
+pub mod event;
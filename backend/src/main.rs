@@ -1,10 +1,16 @@
-mod user;
-use game::game_items::poker::poker::get_all_cards;
-mod game;
-mod timer;
-mod event;
+use gambling::game::game_items::poker::poker::get_all_cards;
+use gambling::game::game_items::poker::lookup_evaluator;
 
 fn main() {
+    // cargo run --release -- bench-evaluator [手数] [每手张数]
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("bench-evaluator") {
+        let hands = args.get(2).and_then(|arg| arg.parse().ok()).unwrap_or(1_000_000);
+        let cards_per_hand = args.get(3).and_then(|arg| arg.parse().ok()).unwrap_or(7);
+        println!("{}", lookup_evaluator::run_benchmark(hands, cards_per_hand, "bench-evaluator"));
+        return;
+    }
+
     println!("{:?} ", get_all_cards());
     // println!("{:?} ", *game::game::GLOBAL_GAMES_SCHEDULER);
 }
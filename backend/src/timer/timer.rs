@@ -16,46 +16,43 @@ pub struct Timer<T : Any> {
     cb_last_step_time: SystemTime,
     cb_duration: Option<Duration>,
     cb_params: Option<Arc<T>>,
-    cb: Box<dyn FnMut(Option<Arc<T>>)->()>,
+    cb: Box<dyn FnMut(Option<Arc<T>>)>,
     cb_times_method: CBTimesMethod,
 }
 
 impl<T> Timer<T> where T : Any {
-    pub fn new(cb_duration:Option<Duration>, cb_params: Option<Arc<T>>, cb: Box<dyn FnMut(Option<Arc<T>>)->()>, cb_times_method: CBTimesMethod)-> Self {
+    pub fn new(cb_duration:Option<Duration>, cb_params: Option<Arc<T>>, cb: Box<dyn FnMut(Option<Arc<T>>)>, cb_times_method: CBTimesMethod)-> Self {
         // SystemTime 实现了 Copy, Timer 的 cb_last_time 和 now是两个独立的副本
         let now:SystemTime = SystemTime::now();
         Timer{now, is_running: false, cb_last_step_time: now, cb_duration, cb_params, cb, cb_times_method}
     }
 
     // 区块链上无法设置 定时触发器，需要用户请求触发 或 时间预言机触发
-    pub fn update_timer(&mut self) ->() {
+    pub fn update_timer(&mut self) {
         if !self.is_running {return;}
 
         if let Some(cb_duration) = self.cb_duration {
             let now:SystemTime = SystemTime::now();
-            let duration:Duration;
-
-            match now.duration_since(self.now){
-                Ok(res) => {
-                    duration = res;
-                }
+            // 从上一个触发时间步开始计算，避免频繁调用时永远达不到一个周期
+            let duration:Duration = match now.duration_since(self.cb_last_step_time){
+                Ok(res) => res,
                 Err(error) => {
                     eprintln!("无法计算持续时间，时钟错误: {:?}", error);
                     return;
                 }
-            }
+            };
 
-            // 理应触发次数
-            let cb_times:u8= (duration.as_secs() / cb_duration.as_secs()) as u8;
+            // 理应触发次数，按纳秒计算以支持不足一秒的周期
+            let cb_times:u32 = (duration.as_nanos() / cb_duration.as_nanos().max(1)).min(u32::MAX as u128) as u32;
 
-            if(duration > cb_duration){
+            if cb_times > 0 {
                 //如果cb_times_method为ONCE，则只触发一次，否则触发 cb_times 次
                 match self.cb_times_method{
                     CBTimesMethod::ONCE => {
                         (self.cb)(self.cb_params.clone());
                     }
                     CBTimesMethod::Fixed(fixed_times)=>{
-                        let actual_times = if(fixed_times>cb_times){cb_times}else{cb_times};
+                        let actual_times = cb_times.min(fixed_times as u32);
                         for _ in 0..actual_times{
                             (self.cb)(self.cb_params.clone());
                         }
@@ -68,7 +65,7 @@ impl<T> Timer<T> where T : Any {
                 }
 
                 //更新最新的触发时间步
-                self.cb_last_step_time += cb_duration * cb_times as u32;
+                self.cb_last_step_time += cb_duration * cb_times;
             }
 
             // 更新当前时间
@@ -84,7 +81,20 @@ impl<T> Timer<T> where T : Any {
         self.is_running
     }
 
+    /// 从现在起重新计时，不改变运行状态
+    pub fn restart(&mut self) {
+        let now:SystemTime = SystemTime::now();
+        self.now = now;
+        self.cb_last_step_time = now;
+    }
+
     pub fn set_is_running(&mut self, is_running: bool) {
+        // 重新开始计时，暂停期间的时间不计入触发次数
+        if is_running && !self.is_running {
+            let now:SystemTime = SystemTime::now();
+            self.now = now;
+            self.cb_last_step_time = now;
+        }
         self.is_running = is_running;
     }
 }
@@ -16,6 +16,26 @@ impl User{
     pub fn new(id: u32, name:String, balance: u32) -> User{
         User{id, name, balance, cur_player_map: HashMap::new(), token_count_map: HashMap::new()}
     }
+
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_balance(&self) -> u32 {
+        self.balance
+    }
+
+    pub fn get_cur_player_map(&self) -> &HashMap<GameProject, Player> {
+        &self.cur_player_map
+    }
+
+    pub fn get_token_count_map(&self) -> &HashMap<u32, GameProject> {
+        &self.token_count_map
+    }
 }
 
 impl Hash for User {